@@ -0,0 +1,51 @@
+//! Adds a zigzag steering behavior through the [`SteeringRegistry`] hook and hands it to every
+//! other red enemy, next to the built-in chase.
+
+use bevy::prelude::*;
+use gorbulet::{
+    BehaviorId, Enemy, EnemyType, GorbuletPlugin, SteerContext, SteeringBehavior, SteeringRegistry,
+};
+
+const ZIGZAG: BehaviorId = BehaviorId("zigzag");
+
+const ZIGZAG_WAVELENGTH: f32 = 120.0;
+const ZIGZAG_WEIGHT: f32 = 0.8;
+
+struct Zigzag;
+
+impl SteeringBehavior for Zigzag {
+    fn desired_velocity(&self, ctx: &SteerContext) -> Vec3 {
+        let offset = ctx.predicted_player_position() - ctx.position;
+        let forward = offset.normalize_or_zero();
+        let side = Vec3::new(-forward.y, forward.x, 0.0);
+        let phase = (offset.length() / ZIGZAG_WAVELENGTH * std::f32::consts::TAU).sin();
+
+        let direction = (forward + side * phase * ZIGZAG_WEIGHT).normalize_or_zero();
+        ctx.steer_toward(direction)
+    }
+}
+
+fn register_zigzag(mut registry: ResMut<SteeringRegistry>) {
+    registry.register(ZIGZAG, Zigzag);
+}
+
+fn assign_zigzag(mut red_spawns: Local<u32>, mut enemies: Query<&mut Enemy, Added<Enemy>>) {
+    for mut enemy in enemies.iter_mut() {
+        if enemy.enemy_type != EnemyType::Red {
+            continue;
+        }
+
+        *red_spawns += 1;
+        if red_spawns.is_multiple_of(2) {
+            enemy.behavior = ZIGZAG;
+        }
+    }
+}
+
+fn main() {
+    App::new()
+        .add_plugins((DefaultPlugins, GorbuletPlugin))
+        .add_systems(Startup, register_zigzag)
+        .add_systems(Update, assign_zigzag)
+        .run();
+}
@@ -0,0 +1,105 @@
+use bevy::prelude::*;
+
+use crate::{coin::Coin, config::*, game::GameInfo, util::lerp};
+
+#[derive(Resource)]
+pub struct PlayfieldAccent {
+    coin_material: Handle<ColorMaterial>,
+    base_background: Color,
+    stage: usize,
+    coin_from: Color,
+    background_from: Color,
+    timer: Timer,
+}
+
+impl PlayfieldAccent {
+    fn coin_target(&self) -> Color {
+        ACCENT_PALETTE[self.stage]
+    }
+
+    fn background_target(&self) -> Color {
+        lerp_color(
+            self.base_background,
+            ACCENT_PALETTE[self.stage],
+            if self.stage == 0 {
+                0.0
+            } else {
+                ACCENT_BACKGROUND_TINT
+            },
+        )
+    }
+}
+
+fn lerp_color(from: Color, to: Color, float: f32) -> Color {
+    let [r1, g1, b1, a1] = from.as_rgba_f32();
+    let [r2, g2, b2, a2] = to.as_rgba_f32();
+    Color::rgba(
+        lerp(r1, r2, float),
+        lerp(g1, g2, float),
+        lerp(b1, b2, float),
+        lerp(a1, a2, float),
+    )
+}
+
+pub fn setup_playfield_accent(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    clear_color: Res<ClearColor>,
+) {
+    let mut timer = Timer::from_seconds(ACCENT_FADE_TIME, TimerMode::Once);
+    timer.tick(timer.duration());
+
+    commands.insert_resource(PlayfieldAccent {
+        coin_material: materials.add(ColorMaterial::from(ACCENT_PALETTE[0])),
+        base_background: clear_color.0,
+        stage: 0,
+        coin_from: ACCENT_PALETTE[0],
+        background_from: clear_color.0,
+        timer,
+    });
+}
+
+pub fn cleanup_playfield_accent(
+    mut commands: Commands,
+    mut clear_color: ResMut<ClearColor>,
+    accent: Res<PlayfieldAccent>,
+) {
+    clear_color.0 = accent.base_background;
+    commands.remove_resource::<PlayfieldAccent>();
+}
+
+// Health coins keep their own material: the accent only ever replaces a plain coin's.
+pub fn update_playfield_accent(
+    mut accent: ResMut<PlayfieldAccent>,
+    mut clear_color: ResMut<ClearColor>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut coin_query: Query<(&Coin, &mut Handle<ColorMaterial>)>,
+    game_info: Res<GameInfo>,
+    time: Res<Time>,
+) {
+    let stage = (game_info.points() / ACCENT_MILESTONE) as usize % ACCENT_PALETTE.len();
+    if stage != accent.stage {
+        accent.coin_from = materials
+            .get(&accent.coin_material)
+            .map_or(accent.coin_target(), |material| material.color);
+        accent.background_from = clear_color.0;
+        accent.stage = stage;
+        accent.timer.reset();
+    }
+
+    if !accent.timer.finished() {
+        accent.timer.tick(time.delta());
+        let float = accent.timer.percent();
+
+        if let Some(material) = materials.get_mut(&accent.coin_material) {
+            material.color = lerp_color(accent.coin_from, accent.coin_target(), float);
+        }
+        clear_color.0 = lerp_color(accent.background_from, accent.background_target(), float);
+    }
+
+    for (coin, mut material) in coin_query.iter_mut() {
+        if !coin.health && *material != accent.coin_material {
+            *material = accent.coin_material.clone();
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use bevy::prelude::*;
+
+use crate::{
+    config::*,
+    game::{GainHealth, GameInfo},
+    game_time::GameTime,
+};
+
+// Game time of the player's last hit, or of the run's start before the first one.
+#[derive(Resource, Default)]
+pub struct LastHitTime(pub f32);
+
+// Scales every enemy's acceleration in move_enemy.
+#[derive(Resource)]
+pub struct AggressionModifier {
+    pub accel_multiplier: f32,
+}
+
+impl Default for AggressionModifier {
+    fn default() -> Self {
+        Self {
+            accel_multiplier: 1.0,
+        }
+    }
+}
+
+// A comeback window: a player hanging on at 1 HP without being hit for a while sees the enemies
+// ease off, gradually, down to a floor.
+pub fn mercy_accel_multiplier(health: i8, since_hit: f32) -> f32 {
+    if health != 1 {
+        return 1.0;
+    }
+
+    let ramp = ((since_hit - MERCY_DELAY) / MERCY_RAMP_TIME).clamp(0.0, 1.0);
+    1.0 - MERCY_ACCEL_REDUCTION * ramp
+}
+
+// hit_player resets the clock on hits; picking up health resets it here.
+pub fn update_aggression(
+    mut modifier: ResMut<AggressionModifier>,
+    mut last_hit: ResMut<LastHitTime>,
+    mut gain_health: EventReader<GainHealth>,
+    game_info: Res<GameInfo>,
+    game_rules: Res<GameRules>,
+    game_time: Res<GameTime>,
+) {
+    let now = game_time.elapsed_seconds();
+    if gain_health.read().count() > 0 {
+        last_hit.0 = now;
+    }
+
+    let accel_multiplier = if game_rules.mercy {
+        mercy_accel_multiplier(game_info.health, now - last_hit.0)
+    } else {
+        1.0
+    };
+
+    if modifier.accel_multiplier != accel_multiplier {
+        modifier.accel_multiplier = accel_multiplier;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aggression_app(health: i8, difficulty: Difficulty) -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_event::<GainHealth>()
+            .init_resource::<AggressionModifier>()
+            .init_resource::<GameTime>()
+            .insert_resource(GameInfo {
+                points: 0,
+                score: 0,
+                carried: 0,
+                health,
+            })
+            .insert_resource(GameRules::for_difficulty(difficulty))
+            // Game time stands at zero, so this is 25 seconds since the last hit.
+            .insert_resource(LastHitTime(-25.0))
+            .add_systems(Update, update_aggression);
+        app
+    }
+
+    fn multiplier(app: &App) -> f32 {
+        app.world.resource::<AggressionModifier>().accel_multiplier
+    }
+
+    #[test]
+    fn mercy_waits_then_ramps_down_to_the_floor() {
+        assert_eq!(mercy_accel_multiplier(1, MERCY_DELAY - 1.0), 1.0);
+        assert_eq!(mercy_accel_multiplier(1, MERCY_DELAY), 1.0);
+        let halfway = mercy_accel_multiplier(1, MERCY_DELAY + MERCY_RAMP_TIME / 2.0);
+        assert!((halfway - (1.0 - MERCY_ACCEL_REDUCTION / 2.0)).abs() < 1e-6);
+        assert_eq!(
+            mercy_accel_multiplier(1, MERCY_DELAY + MERCY_RAMP_TIME * 3.0),
+            1.0 - MERCY_ACCEL_REDUCTION
+        );
+    }
+
+    #[test]
+    fn mercy_needs_the_player_at_one_health() {
+        assert_eq!(mercy_accel_multiplier(2, 1000.0), 1.0);
+        assert_eq!(mercy_accel_multiplier(3, 1000.0), 1.0);
+    }
+
+    #[test]
+    fn health_pickup_resets_the_ramp() {
+        let mut app = aggression_app(1, Difficulty::Normal);
+        app.update();
+        assert!(multiplier(&app) < 1.0);
+
+        app.world.send_event(GainHealth);
+        app.update();
+
+        assert_eq!(app.world.resource::<LastHitTime>().0, 0.0);
+        assert_eq!(multiplier(&app), 1.0);
+    }
+
+    #[test]
+    fn hit_resets_the_ramp() {
+        let mut app = aggression_app(1, Difficulty::Normal);
+        app.update();
+        assert!(multiplier(&app) < 1.0);
+
+        // What hit_player does on a hit.
+        app.world.resource_mut::<LastHitTime>().0 = 0.0;
+        app.update();
+
+        assert_eq!(multiplier(&app), 1.0);
+    }
+
+    #[test]
+    fn hard_has_no_mercy() {
+        let mut app = aggression_app(1, Difficulty::Hard);
+
+        app.update();
+
+        assert_eq!(multiplier(&app), 1.0);
+    }
+}
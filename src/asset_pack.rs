@@ -0,0 +1,82 @@
+use std::{env, fmt, path::PathBuf};
+
+use bevy::prelude::*;
+
+use crate::assets::expected_asset_files;
+
+const ASSETS_FLAG: &str = "--assets";
+const ASSETS_ENV: &str = "GORBULET_ASSETS";
+
+// An asset folder to load from instead of the default one, for trying out other sounds. Chosen
+// with --assets PATH or, failing that, GORBULET_ASSETS, and has to be picked before the
+// AssetPlugin is built.
+#[derive(Resource, Debug, Clone)]
+pub struct AssetPack {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+#[derive(Debug)]
+pub struct AssetPackError {
+    pub path: PathBuf,
+    pub missing: Vec<String>,
+}
+
+impl fmt::Display for AssetPackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "asset pack {} is missing {} file(s): {}",
+            self.path.display(),
+            self.missing.len(),
+            self.missing.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for AssetPackError {}
+
+impl AssetPack {
+    pub fn from_env() -> Option<Self> {
+        let args: Vec<String> = env::args().collect();
+        let path = args
+            .iter()
+            .position(|arg| arg == ASSETS_FLAG)
+            .and_then(|index| args.get(index + 1))
+            .map(PathBuf::from)
+            .or_else(|| env::var_os(ASSETS_ENV).map(PathBuf::from))?;
+
+        // Bevy resolves a relative folder against its own base, not the working directory.
+        let path = env::current_dir()
+            .map(|dir| dir.join(&path))
+            .unwrap_or(path);
+        let name = path.file_name().map_or_else(
+            || path.display().to_string(),
+            |name| name.to_string_lossy().into(),
+        );
+
+        Some(Self { name, path })
+    }
+
+    pub fn validate(&self) -> Result<(), AssetPackError> {
+        let missing: Vec<String> = expected_asset_files()
+            .filter(|file| !self.path.join(file).is_file())
+            .collect();
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(AssetPackError {
+                path: self.path.clone(),
+                missing,
+            })
+        }
+    }
+
+    pub fn asset_plugin(&self) -> AssetPlugin {
+        AssetPlugin {
+            file_path: self.path.to_string_lossy().into(),
+            ..default()
+        }
+    }
+}
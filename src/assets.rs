@@ -0,0 +1,222 @@
+use bevy::prelude::*;
+
+use crate::{config::*, enemy::EnemyType, game::HitSource};
+
+#[derive(Resource)]
+pub struct AssetHandles {
+    pub font: Handle<Font>,
+    pub player_mesh: Handle<Mesh>,
+    pub player_material: Handle<ColorMaterial>,
+    pub ghost_material: Handle<ColorMaterial>,
+    pub speed_ribbon_material: Handle<ColorMaterial>,
+    pub red_hit_sound: Handle<AudioSource>,
+    pub purple_hit_sound: Handle<AudioSource>,
+    pub projectile_hit_sound: Handle<AudioSource>,
+    pub enemy_mesh: Handle<Mesh>,
+    pub enemy_material_red: Handle<ColorMaterial>,
+    pub enemy_material_purple: Handle<ColorMaterial>,
+    pub enemy_material_silhouette: Handle<ColorMaterial>,
+    pub wrap_hint_material: Handle<ColorMaterial>,
+    pub lunge_outline_material: Handle<ColorMaterial>,
+    pub leader_outline_material: Handle<ColorMaterial>,
+    pub pack_aura_material: Handle<ColorMaterial>,
+    pub whoosh_sound: Handle<AudioSource>,
+    pub pop_sound: Handle<AudioSource>,
+    pub fanfare_sound: Handle<AudioSource>,
+    pub sting_sound: Handle<AudioSource>,
+    pub coin_mesh: Handle<Mesh>,
+    pub coin_material: Handle<ColorMaterial>,
+    pub coin_ring_material: Handle<ColorMaterial>,
+    pub home_zone_mesh: Handle<Mesh>,
+    pub home_zone_material: Handle<ColorMaterial>,
+    pub coin_trail_materials: Vec<Handle<ColorMaterial>>,
+    pub arrow_mesh: Handle<Mesh>,
+    pub arrow_material: Handle<ColorMaterial>,
+    pub coin_sound: Handle<AudioSource>,
+    pub bar_mesh: Handle<Mesh>,
+    pub health_material: Handle<ColorMaterial>,
+    pub empty_health_material: Handle<ColorMaterial>,
+    pub shrink_material: Handle<ColorMaterial>,
+    pub safe_area_material: Handle<ColorMaterial>,
+    pub companion_material: Handle<ColorMaterial>,
+    pub score_decay_material: Handle<ColorMaterial>,
+    pub dash_ready_material: Handle<ColorMaterial>,
+    pub dash_cooldown_material: Handle<ColorMaterial>,
+    pub health_sound: Handle<AudioSource>,
+    pub music_5: Handle<AudioSource>,
+    pub music_4: Handle<AudioSource>,
+    pub music_3: Handle<AudioSource>,
+    pub music_2: Handle<AudioSource>,
+    pub music_1: Handle<AudioSource>,
+    pub music_layers: Vec<Handle<AudioSource>>,
+}
+
+impl AssetHandles {
+    pub fn new(
+        asset_server: Res<AssetServer>,
+        mut meshes: ResMut<Assets<Mesh>>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
+    ) -> Self {
+        let mut asset_handles = Self {
+            font: asset_server.load("lato.ttf"),
+            player_mesh: meshes.add(shape::Circle::new(PLAYER_RADIUS).into()),
+            player_material: materials.add(ColorMaterial::from(PLAYER_COLOR)),
+            ghost_material: materials.add(ColorMaterial::from(GHOST_COLOR)),
+            // The ribbon's color comes from its vertices.
+            speed_ribbon_material: materials.add(ColorMaterial::from(Color::WHITE)),
+            red_hit_sound: asset_server.load("hit.ogg"),
+            purple_hit_sound: asset_server.load("purple_hit.wav"),
+            projectile_hit_sound: asset_server.load("projectile_hit.wav"),
+            enemy_mesh: meshes.add(shape::Circle::new(ENEMY_RADIUS).into()),
+            enemy_material_red: materials.add(ColorMaterial::from(ENEMY_COLOR_RED)),
+            enemy_material_purple: materials.add(ColorMaterial::from(ENEMY_COLOR_PURPLE)),
+            enemy_material_silhouette: materials.add(ColorMaterial::from(ENEMY_SILHOUETTE_COLOR)),
+            wrap_hint_material: materials.add(ColorMaterial::from(WRAP_HINT_COLOR)),
+            lunge_outline_material: materials.add(ColorMaterial::from(LUNGE_OUTLINE_COLOR)),
+            leader_outline_material: materials.add(ColorMaterial::from(LEADER_OUTLINE_COLOR)),
+            pack_aura_material: materials.add(ColorMaterial::from(PACK_AURA_COLOR)),
+            whoosh_sound: asset_server.load("whoosh.wav"),
+            pop_sound: asset_server.load("pop.wav"),
+            fanfare_sound: asset_server.load("fanfare.wav"),
+            sting_sound: asset_server.load("sting.wav"),
+            coin_mesh: meshes.add(shape::Circle::new(COIN_RADIUS).into()),
+            coin_material: materials.add(ColorMaterial::from(COIN_COLOR)),
+            coin_ring_material: materials.add(ColorMaterial::from(COIN_RING_COLOR)),
+            home_zone_mesh: meshes.add(shape::Circle::new(HOME_ZONE_RADIUS).into()),
+            home_zone_material: materials.add(ColorMaterial::from(HOME_ZONE_COLOR)),
+            coin_trail_materials: (0..COIN_TRAIL_LENGTH)
+                .map(|age| {
+                    materials.add(ColorMaterial::from(
+                        COIN_TRAIL_COLOR.with_a(trail_alpha(age)),
+                    ))
+                })
+                .collect(),
+            arrow_mesh: meshes.add(shape::RegularPolygon::new(COIN_ARROW_SIZE, 3).into()),
+            arrow_material: materials.add(ColorMaterial::from(COIN_ARROW_COLOR)),
+            coin_sound: asset_server.load("coin.ogg"),
+            bar_mesh: meshes.add(shape::Quad::new(Vec2::ONE).into()),
+            health_material: materials.add(ColorMaterial::from(HEALTH_COLOR)),
+            empty_health_material: materials.add(ColorMaterial::from(HEALTH_PIP_EMPTY_COLOR)),
+            shrink_material: materials.add(ColorMaterial::from(SHRINK_PICKUP_COLOR)),
+            safe_area_material: materials.add(ColorMaterial::from(SAFE_AREA_OVERLAY_COLOR)),
+            companion_material: materials.add(ColorMaterial::from(COMPANION_COLOR)),
+            score_decay_material: materials.add(ColorMaterial::from(SCORE_DECAY_COLOR)),
+            dash_ready_material: materials.add(ColorMaterial::from(DASH_READY_COLOR)),
+            dash_cooldown_material: materials.add(ColorMaterial::from(DASH_COOLDOWN_COLOR)),
+            health_sound: asset_server.load("health.ogg"),
+            music_5: Handle::default(),
+            music_4: Handle::default(),
+            music_3: Handle::default(),
+            music_2: Handle::default(),
+            music_1: Handle::default(),
+            music_layers: Vec::new(),
+        };
+        asset_handles.load_music(&asset_server, None);
+        asset_handles
+    }
+
+    // Swaps in an arena's music set. The previous handles drop here, which unloads whatever the
+    // new set doesn't share with them.
+    pub fn load_music(&mut self, asset_server: &AssetServer, dir: Option<&str>) {
+        let load = |file: String| asset_server.load(themed_asset_path(dir, file));
+
+        self.music_5 = load("5.ogg".to_string());
+        self.music_4 = load("4.ogg".to_string());
+        self.music_3 = load("3.ogg".to_string());
+        self.music_2 = load("2.ogg".to_string());
+        self.music_1 = load("1.ogg".to_string());
+        self.music_layers = MUSIC_LAYER_MILESTONES
+            .iter()
+            .map(|milestone| load(music_layer_file(*milestone)))
+            .collect();
+    }
+
+    pub fn enemy_material(&self, enemy_type: EnemyType) -> Handle<ColorMaterial> {
+        match enemy_type {
+            EnemyType::Red => self.enemy_material_red.clone(),
+            EnemyType::Purple => self.enemy_material_purple.clone(),
+        }
+    }
+
+    pub fn hit_sound(&self, source: HitSource) -> Handle<AudioSource> {
+        match source {
+            HitSource::Enemy(EnemyType::Red) => self.red_hit_sound.clone(),
+            HitSource::Enemy(EnemyType::Purple) => self.purple_hit_sound.clone(),
+            HitSource::Projectile => self.projectile_hit_sound.clone(),
+        }
+    }
+}
+
+fn music_layer_file(milestone: i32) -> String {
+    format!("music_layer_{}.wav", milestone)
+}
+
+// Every file the default set loads from. An asset pack has to provide all of them; arena folders
+// are optional, since any file missing there falls back to the default one.
+pub fn expected_asset_files() -> impl Iterator<Item = String> {
+    [
+        "lato.ttf",
+        "hit.ogg",
+        "purple_hit.wav",
+        "projectile_hit.wav",
+        "whoosh.wav",
+        "pop.wav",
+        "fanfare.wav",
+        "sting.wav",
+        "coin.ogg",
+        "health.ogg",
+        "5.ogg",
+        "4.ogg",
+        "3.ogg",
+        "2.ogg",
+        "1.ogg",
+    ]
+    .into_iter()
+    .map(String::from)
+    .chain(MUSIC_LAYER_MILESTONES.into_iter().map(music_layer_file))
+}
+
+// The themed copy of a file when the theme folder has one, otherwise the default one. Themes ship
+// only the tracks they change.
+#[cfg(not(target_arch = "wasm32"))]
+fn themed_asset_path(dir: Option<&str>, file: String) -> String {
+    match dir {
+        Some(dir) if asset_root().join(dir).join(&file).exists() => format!("{}/{}", dir, file),
+        _ => file,
+    }
+}
+
+// Where Bevy's file reader looks for the asset folder: a chosen asset pack, an explicit root, the
+// crate when run through cargo, otherwise next to the executable.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn asset_root() -> std::path::PathBuf {
+    use std::{env, path::PathBuf};
+
+    use crate::asset_pack::AssetPack;
+
+    if let Some(pack) = AssetPack::from_env() {
+        return pack.path;
+    }
+
+    let base = env::var_os("BEVY_ASSET_ROOT")
+        .or_else(|| env::var_os("CARGO_MANIFEST_DIR"))
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::current_exe()
+                .ok()
+                .and_then(|exe| exe.parent().map(PathBuf::from))
+        })
+        .unwrap_or_default();
+
+    base.join(ASSET_FOLDER)
+}
+
+// There is no asset folder to look in on the web, so it always gets the default set.
+#[cfg(target_arch = "wasm32")]
+fn themed_asset_path(_dir: Option<&str>, file: String) -> String {
+    file
+}
+
+pub fn trail_alpha(age: usize) -> f32 {
+    1.0 - age as f32 / COIN_TRAIL_LENGTH as f32
+}
@@ -0,0 +1,82 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    assets::AssetHandles, coin::Coin, config::*, player::Player, render_z, storage::SaveData,
+    util::wraparound_tracking_position,
+};
+
+#[derive(Component)]
+pub struct CoinArrow;
+
+pub fn spawn_coin_arrow(
+    commands: &mut Commands,
+    asset_handles: &AssetHandles,
+    save_data: &SaveData,
+    player: Entity,
+) {
+    if !save_data.settings.coin_arrow {
+        return;
+    }
+
+    commands.entity(player).with_children(|parent| {
+        parent.spawn((
+            CoinArrow,
+            ColorMesh2dBundle {
+                mesh: asset_handles.arrow_mesh.clone().into(),
+                material: asset_handles.arrow_material.clone(),
+                transform: Transform::from_translation(Vec3::new(
+                    0.0,
+                    COIN_ARROW_DISTANCE,
+                    render_z::CHILD_ABOVE,
+                )),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+        ));
+    });
+}
+
+pub fn update_coin_arrow(
+    mut arrow_query: Query<(&mut Transform, &mut Visibility), With<CoinArrow>>,
+    player_query: Query<&Transform, (With<Player>, Without<CoinArrow>)>,
+    coin_query: Query<&Transform, (With<Coin>, Without<CoinArrow>)>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    time: Res<Time>,
+) {
+    let Ok((mut transform, mut visibility)) = arrow_query.get_single_mut() else {
+        return;
+    };
+    let (Ok(player_transform), Ok(coin_transform)) =
+        (player_query.get_single(), coin_query.get_single())
+    else {
+        return;
+    };
+
+    let window = window.single();
+    let target = wraparound_tracking_position(
+        player_transform.translation,
+        coin_transform.translation,
+        window.width(),
+        window.height(),
+    );
+    let offset = (target - player_transform.translation).truncate();
+
+    if offset.length_squared() < COIN_ARROW_HIDE_DISTANCE.powf(2.0) {
+        *visibility = Visibility::Hidden;
+        return;
+    }
+
+    let target_rotation = Quat::from_rotation_arc_2d(Vec2::Y, offset.normalize());
+    transform.rotation = if *visibility == Visibility::Hidden {
+        target_rotation
+    } else {
+        transform.rotation.slerp(
+            target_rotation,
+            (COIN_ARROW_TURN_RATE * time.delta_seconds()).min(1.0),
+        )
+    };
+    transform.translation = (transform.rotation * Vec3::Y * COIN_ARROW_DISTANCE)
+        .truncate()
+        .extend(render_z::CHILD_ABOVE);
+    *visibility = Visibility::Inherited;
+}
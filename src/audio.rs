@@ -0,0 +1,225 @@
+use bevy::{
+    asset::LoadState,
+    audio::{PlaybackMode, Volume},
+    prelude::*,
+};
+
+use crate::{
+    config::*,
+    player::{Action, InputBindings},
+    storage::SaveData,
+};
+
+#[derive(Resource, Default)]
+pub struct Muted(pub bool);
+
+// False once there turns out to be no output device, or no audio plugin at all. Nothing spawns
+// sounds from then on, and the HUD shows health as pips since the music can't.
+#[derive(Resource)]
+pub struct AudioAvailable(pub bool);
+
+impl Default for AudioAvailable {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct SfxBudget {
+    elapsed: f32,
+    played: u32,
+    // Mirrors AudioAvailable, so the many callers of play don't each need it.
+    unavailable: bool,
+}
+
+impl SfxBudget {
+    pub fn play(
+        &mut self,
+        commands: &mut Commands,
+        source: Handle<AudioSource>,
+        settings: PlaybackSettings,
+    ) {
+        if self.unavailable || self.played >= SFX_BUDGET_MAX {
+            return;
+        }
+
+        self.played += 1;
+        commands.spawn(AudioBundle { source, settings });
+    }
+
+    // Pans the sound toward where it happens, relative to the camera's listener.
+    pub fn play_at(
+        &mut self,
+        commands: &mut Commands,
+        source: Handle<AudioSource>,
+        settings: PlaybackSettings,
+        position: Vec3,
+    ) {
+        if self.unavailable || self.played >= SFX_BUDGET_MAX {
+            return;
+        }
+
+        self.played += 1;
+        commands.spawn((
+            AudioBundle {
+                source,
+                settings: settings.with_spatial(true),
+            },
+            SpatialBundle::from_transform(Transform::from_translation(position)),
+        ));
+    }
+}
+
+// On a one-shot sound that has no sink yet. Bevy despawns such a sound once its sink runs dry, but
+// one that never gets a sink, with no output device or a file that failed to load, would stay
+// forever and outlive every state change.
+#[derive(Component)]
+pub struct UnplayedSound(f32);
+
+pub fn sweep_unplayed_sounds(
+    mut commands: Commands,
+    mut query: Query<
+        (Entity, &PlaybackSettings, Option<&mut UnplayedSound>),
+        (
+            With<Handle<AudioSource>>,
+            Without<AudioSink>,
+            Without<SpatialAudioSink>,
+            Without<AudioProbe>,
+        ),
+    >,
+    time: Res<Time<Real>>,
+) {
+    for (entity, settings, unplayed) in query.iter_mut() {
+        if !matches!(settings.mode, PlaybackMode::Despawn) {
+            continue;
+        }
+
+        match unplayed {
+            Some(mut unplayed) => {
+                unplayed.0 += time.delta_seconds();
+                if unplayed.0 >= SFX_UNPLAYED_TIMEOUT {
+                    commands.entity(entity).despawn();
+                }
+            }
+            None => {
+                commands.entity(entity).insert(UnplayedSound(0.0));
+            }
+        }
+    }
+}
+
+pub fn refill_sfx_budget(mut budget: ResMut<SfxBudget>, time: Res<Time<Real>>) {
+    budget.elapsed += time.delta_seconds();
+    if budget.elapsed >= SFX_BUDGET_WINDOW {
+        budget.elapsed = 0.0;
+        budget.played = 0;
+    }
+}
+
+// A silent sound played at startup. Bevy gives every sound a sink once its file has loaded, unless
+// there is no output device to open one on.
+#[derive(Component)]
+pub struct AudioProbe;
+
+pub fn probe_audio_output(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    available: Res<AudioAvailable>,
+    mut budget: ResMut<SfxBudget>,
+) {
+    // Only false this early without the audio plugin.
+    if !available.0 {
+        info!("No audio plugin, running without sound");
+        budget.unavailable = true;
+        return;
+    }
+
+    commands.spawn((
+        AudioProbe,
+        AudioBundle {
+            source: asset_server.load("pop.wav"),
+            settings: PlaybackSettings::DESPAWN.with_volume(Volume::new_relative(0.0)),
+        },
+    ));
+}
+
+// Waits, from when the probe's file has loaded, for its sink. Without one by AUDIO_PROBE_TIMEOUT
+// there is no output, and every sound already spawned is dropped.
+pub fn detect_audio_output(
+    mut commands: Commands,
+    mut waited: Local<f32>,
+    probe: Query<
+        (
+            Entity,
+            &Handle<AudioSource>,
+            Option<&AudioSink>,
+            Option<&SpatialAudioSink>,
+        ),
+        With<AudioProbe>,
+    >,
+    sounds: Query<Entity, With<Handle<AudioSource>>>,
+    asset_server: Res<AssetServer>,
+    mut available: ResMut<AudioAvailable>,
+    mut budget: ResMut<SfxBudget>,
+    time: Res<Time<Real>>,
+) {
+    let Ok((entity, source, sink, spatial_sink)) = probe.get_single() else {
+        return;
+    };
+
+    if sink.is_some() || spatial_sink.is_some() {
+        commands.entity(entity).remove::<AudioProbe>();
+        return;
+    }
+    match asset_server.get_load_state(source) {
+        Some(LoadState::Loaded) => {}
+        // Without the file, the probe can't tell anything.
+        Some(LoadState::Failed) | None => {
+            commands.entity(entity).despawn();
+            return;
+        }
+        _ => return,
+    }
+
+    *waited += time.delta_seconds();
+    if *waited < AUDIO_PROBE_TIMEOUT {
+        return;
+    }
+
+    warn!("No audio output device, running without sound");
+    available.0 = false;
+    budget.unavailable = true;
+    sounds.iter().for_each(|entity| {
+        commands.entity(entity).despawn();
+    });
+}
+
+pub fn toggle_mute(
+    bindings: Res<InputBindings>,
+    input: Res<Input<KeyCode>>,
+    mut muted: ResMut<Muted>,
+) {
+    if bindings.just_pressed(Action::Mute, &input) {
+        muted.0 = !muted.0;
+    }
+}
+
+pub fn sync_volume(
+    muted: Res<Muted>,
+    save_data: Res<SaveData>,
+    mut global_volume: ResMut<GlobalVolume>,
+    sinks: Query<&AudioSink>,
+) {
+    if !muted.is_changed() && !save_data.is_changed() {
+        return;
+    }
+
+    let volume = if muted.0 {
+        0.0
+    } else {
+        save_data.settings.volume
+    };
+
+    *global_volume = GlobalVolume::new(volume);
+    sinks.iter().for_each(|sink| sink.set_volume(volume));
+}
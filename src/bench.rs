@@ -0,0 +1,328 @@
+use std::{
+    env,
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    app::AppExit,
+    diagnostic::{Diagnostic, DiagnosticId, Diagnostics, DiagnosticsStore, RegisterDiagnostic},
+    prelude::*,
+    time::TimeUpdateStrategy,
+    window::PrimaryWindow,
+};
+use rand::Rng;
+
+use crate::{
+    assets::AssetHandles,
+    config::*,
+    descriptor::QueuedSeed,
+    enemy::{spawn_enemy, EnemySpawned, EnemyType},
+    game::GameMode,
+    player::{sample_player_input, Player, PlayerInput},
+    quality::EffectsQuality,
+    render_z,
+    rng::GameRng,
+    storage::SaveData,
+    AppState,
+};
+
+const SCENARIO_FLAG: &str = "--bench-scenario";
+const JSON_FLAG: &str = "--bench-json";
+
+pub const BENCH_FRAME_TIME_DIAGNOSTIC: DiagnosticId =
+    DiagnosticId::from_u128(0x7b2e_90c4_1f3a_4d58_b6e1_53a8_c0d9_e401);
+pub const BENCH_MOVE_ENEMY_DIAGNOSTIC: DiagnosticId =
+    DiagnosticId::from_u128(0x7b2e_90c4_1f3a_4d58_b6e1_53a8_c0d9_e402);
+pub const BENCH_COLLISION_DIAGNOSTIC: DiagnosticId =
+    DiagnosticId::from_u128(0x7b2e_90c4_1f3a_4d58_b6e1_53a8_c0d9_e403);
+pub const BENCH_GRID_REBUILD_DIAGNOSTIC: DiagnosticId =
+    DiagnosticId::from_u128(0x7b2e_90c4_1f3a_4d58_b6e1_53a8_c0d9_e404);
+
+// Reported in this order, under these names.
+const REPORTED: [(DiagnosticId, &str); 4] = [
+    (BENCH_FRAME_TIME_DIAGNOSTIC, "frame"),
+    (BENCH_MOVE_ENEMY_DIAGNOSTIC, "move_enemy"),
+    (BENCH_COLLISION_DIAGNOSTIC, "collision"),
+    (BENCH_GRID_REBUILD_DIAGNOSTIC, "grid_rebuild"),
+];
+
+// A fixed heavy scenario for comparing performance work across commits and machines:
+// --bench-scenario starts a seeded Zen run, fills it with BENCH_ENEMY_COUNT enemies, circles the
+// player and, after exactly BENCH_TICKS fixed ticks, prints timings and exits. Add --bench-json
+// for a single JSON object on stdout instead of the table, and --headless to leave rendering out.
+pub struct BenchPlugin;
+
+impl Plugin for BenchPlugin {
+    fn build(&self, app: &mut App) {
+        let args: Vec<String> = env::args().collect();
+        if !args.iter().any(|arg| arg == SCENARIO_FLAG) {
+            return;
+        }
+
+        for (id, name) in REPORTED {
+            app.register_diagnostic(Diagnostic::new(
+                id,
+                format!("bench_{}", name),
+                BENCH_TICKS as usize,
+            ));
+        }
+
+        // One fixed tick per frame, so the tick count is also the frame count.
+        app.insert_resource(BenchScenario {
+            json: args.iter().any(|arg| arg == JSON_FLAG),
+            populated: false,
+            ticks: 0,
+            frame_start: None,
+        })
+        .init_resource::<BenchSpans>()
+        .insert_resource(TimeUpdateStrategy::ManualDuration(BENCH_TICK))
+        .add_systems(PostStartup, start_bench)
+        .add_systems(First, begin_bench_frame)
+        .add_systems(PreUpdate, drive_bench_input.after(sample_player_input))
+        .add_systems(
+            Update,
+            populate_bench
+                .run_if(in_state(AppState::Game))
+                .run_if(|scenario: Res<BenchScenario>| !scenario.populated),
+        )
+        .add_systems(FixedUpdate, count_bench_tick)
+        .add_systems(Last, (end_bench_frame, finish_bench).chain());
+    }
+}
+
+pub const BENCH_TICK: Duration = Duration::from_micros(15_625);
+
+#[derive(Resource)]
+pub struct BenchScenario {
+    json: bool,
+    populated: bool,
+    ticks: u32,
+    frame_start: Option<Instant>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BenchSpan {
+    MoveEnemy,
+    Collision,
+    GridRebuild,
+}
+
+impl BenchSpan {
+    const ALL: [BenchSpan; 3] = [
+        BenchSpan::MoveEnemy,
+        BenchSpan::Collision,
+        BenchSpan::GridRebuild,
+    ];
+
+    fn diagnostic(self) -> DiagnosticId {
+        match self {
+            BenchSpan::MoveEnemy => BENCH_MOVE_ENEMY_DIAGNOSTIC,
+            BenchSpan::Collision => BENCH_COLLISION_DIAGNOSTIC,
+            BenchSpan::GridRebuild => BENCH_GRID_REBUILD_DIAGNOSTIC,
+        }
+    }
+}
+
+// Wall time spent in each span this frame. A span can open and close several times a frame, as
+// collision does around enemy and coin collision; the times add up.
+#[derive(Resource, Default)]
+pub struct BenchSpans {
+    started: [Option<Instant>; BenchSpan::ALL.len()],
+    frame: [Duration; BenchSpan::ALL.len()],
+}
+
+impl BenchSpans {
+    pub fn begin(&mut self, span: BenchSpan) {
+        self.started[span as usize] = Some(Instant::now());
+    }
+
+    pub fn end(&mut self, span: BenchSpan) {
+        if let Some(started) = self.started[span as usize].take() {
+            self.add(span, started.elapsed());
+        }
+    }
+
+    pub fn add(&mut self, span: BenchSpan, duration: Duration) {
+        self.frame[span as usize] += duration;
+    }
+}
+
+// Span bracketing systems, for placing right before and after the measured ones.
+pub fn begin_span(span: BenchSpan) -> impl FnMut(ResMut<BenchSpans>) {
+    move |mut spans| spans.begin(span)
+}
+
+pub fn end_span(span: BenchSpan) -> impl FnMut(ResMut<BenchSpans>) {
+    move |mut spans| spans.end(span)
+}
+
+// A bench run never touches the real save, and runs everything at full effects.
+fn start_bench(
+    mut save_data: ResMut<SaveData>,
+    mut game_mode: ResMut<GameMode>,
+    mut queued_seed: ResMut<QueuedSeed>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    save_data.read_only = true;
+    save_data.settings.effects_quality = EffectsQuality::High;
+    save_data.settings.pause_on_unfocus = false;
+
+    // Zen, so the player survives every tick of the scenario.
+    *game_mode = GameMode::Zen;
+    queued_seed.0 = Some(BENCH_SEED);
+    next_state.set(AppState::Game);
+}
+
+// Flocking is switched on so the neighbor grid is part of what's measured.
+fn populate_bench(
+    mut commands: Commands,
+    mut scenario: ResMut<BenchScenario>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut rng: ResMut<GameRng>,
+    mut game_rules: ResMut<GameRules>,
+    mut enemy_spawned: EventWriter<EnemySpawned>,
+    asset_handles: Res<AssetHandles>,
+    player_query: Query<(), With<Player>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+) {
+    if player_query.is_empty() {
+        return;
+    }
+
+    let window = window.single();
+    let half = Vec2::new(window.width(), window.height()) / 2.0;
+    for index in 0..BENCH_ENEMY_COUNT {
+        let enemy_type = if index % 3 == 0 {
+            EnemyType::Purple
+        } else {
+            EnemyType::Red
+        };
+        let position = Vec3::new(
+            rng.gen_range(-half.x..half.x),
+            rng.gen_range(-half.y..half.y),
+            render_z::ENEMY,
+        );
+
+        enemy_spawned.send(spawn_enemy(
+            &mut commands,
+            &mut materials,
+            &mut rng,
+            BENCH_ENEMY_POINTS,
+            &asset_handles,
+            enemy_type,
+            position,
+        ));
+    }
+
+    game_rules.flocking = true;
+    scenario.populated = true;
+}
+
+fn drive_bench_input(scenario: Res<BenchScenario>, mut player_input: ResMut<PlayerInput>) {
+    let angle = scenario.ticks as f32 * BENCH_TICK.as_secs_f32() / BENCH_CIRCLE_PERIOD
+        * std::f32::consts::TAU;
+    player_input.direction = Vec2::from_angle(angle).extend(0.0);
+    player_input.dash = false;
+}
+
+fn count_bench_tick(mut scenario: ResMut<BenchScenario>) {
+    if scenario.populated {
+        scenario.ticks += 1;
+    }
+}
+
+fn begin_bench_frame(mut scenario: ResMut<BenchScenario>) {
+    scenario.frame_start = Some(Instant::now());
+}
+
+fn end_bench_frame(
+    mut scenario: ResMut<BenchScenario>,
+    mut spans: ResMut<BenchSpans>,
+    mut diagnostics: Diagnostics,
+) {
+    let frame_start = scenario.frame_start.take();
+    if !scenario.populated || scenario.ticks == 0 {
+        *spans = BenchSpans::default();
+        return;
+    }
+
+    if let Some(frame_start) = frame_start {
+        let elapsed = frame_start.elapsed();
+        diagnostics.add_measurement(BENCH_FRAME_TIME_DIAGNOSTIC, || as_millis(elapsed));
+    }
+    for span in BenchSpan::ALL {
+        let spent = spans.frame[span as usize];
+        diagnostics.add_measurement(span.diagnostic(), || as_millis(spent));
+    }
+    *spans = BenchSpans::default();
+}
+
+fn finish_bench(
+    scenario: Res<BenchScenario>,
+    store: Res<DiagnosticsStore>,
+    mut app_exit: EventWriter<AppExit>,
+) {
+    if scenario.ticks < BENCH_TICKS {
+        return;
+    }
+
+    let rows: Vec<_> = REPORTED
+        .iter()
+        .map(|(id, name)| {
+            let mut values: Vec<f64> = store.get(*id).map_or(Vec::new(), |diagnostic| {
+                diagnostic.values().copied().collect()
+            });
+            values.sort_by(f64::total_cmp);
+            (*name, average(&values), percentile(&values, 0.95))
+        })
+        .collect();
+
+    if scenario.json {
+        let timings: Vec<_> = rows
+            .iter()
+            .map(|(name, average, p95)| {
+                format!(
+                    "\"{}\":{{\"avg_ms\":{:.4},\"p95_ms\":{:.4}}}",
+                    name, average, p95
+                )
+            })
+            .collect();
+        println!(
+            "{{\"ticks\":{},\"enemies\":{},\"timings\":{{{}}}}}",
+            scenario.ticks,
+            BENCH_ENEMY_COUNT,
+            timings.join(",")
+        );
+    } else {
+        println!(
+            "Bench: {} ticks, {} enemies",
+            scenario.ticks, BENCH_ENEMY_COUNT
+        );
+        println!("{:<14}{:>10}{:>10}", "", "avg ms", "p95 ms");
+        for (name, average, p95) in rows {
+            println!("{:<14}{:>10.3}{:>10.3}", name, average, p95);
+        }
+    }
+
+    app_exit.send(AppExit);
+}
+
+fn as_millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn average(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+// Nearest rank over already sorted values.
+fn percentile(sorted: &[f64], fraction: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (fraction * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
@@ -0,0 +1,186 @@
+//! Boots the whole game without a window or GPU and plays one scripted Classic run: start from
+//! the menu, move, collect a coin, get hit until the run ends and return to the menu. Run it
+//! before tagging a release with `cargo run --bin smoke --features smoke`.
+
+use std::{env, fs, process, time::Duration};
+
+use bevy::{
+    app::PluginsState,
+    input::{keyboard::KeyboardInput, ButtonState},
+    prelude::*,
+    render::{settings::WgpuSettings, RenderPlugin},
+    time::TimeUpdateStrategy,
+    window::{ExitCondition, PrimaryWindow},
+    winit::WinitPlugin,
+};
+use gorbulet::{AppState, Coin, Enemy, GameInfo, GorbuletPlugin, LastScore, PauseState, Player};
+
+const FRAME_TIME: f32 = 1.0 / 30.0;
+const MAX_FRAMES: u32 = 600;
+const MENU_FRAMES: u32 = 10;
+const MOVE_FRAMES: u32 = (5.0 / FRAME_TIME) as u32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Menu,
+    Moving,
+    Collecting,
+    TakingHits,
+    Spectating,
+    Done,
+}
+
+fn main() {
+    // Keeps the run from reading or writing the real save.
+    let save_root = env::temp_dir().join("gorbulet-smoke");
+    let _ = fs::remove_dir_all(&save_root);
+    for var in ["XDG_DATA_HOME", "APPDATA", "HOME"] {
+        env::set_var(var, &save_root);
+    }
+
+    let mut app = App::new();
+    app.add_plugins((
+        DefaultPlugins
+            .set(WindowPlugin {
+                primary_window: Some(Window::default()),
+                exit_condition: ExitCondition::DontExit,
+                close_when_requested: false,
+            })
+            .set(RenderPlugin {
+                render_creation: WgpuSettings {
+                    backends: None,
+                    ..default()
+                }
+                .into(),
+            })
+            .disable::<WinitPlugin>(),
+        GorbuletPlugin,
+    ))
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+        FRAME_TIME,
+    )));
+
+    while app.plugins_state() == PluginsState::Adding {
+        bevy::tasks::tick_global_task_pools_on_main_thread();
+    }
+    app.finish();
+    app.cleanup();
+
+    let window = app
+        .world
+        .query_filtered::<Entity, With<PrimaryWindow>>()
+        .single(&app.world);
+
+    let mut step = Step::Menu;
+    let mut step_frames = 0;
+    let mut points = 0;
+    let mut visited = vec![AppState::Menu];
+
+    for _ in 0..MAX_FRAMES {
+        app.update();
+        step_frames += 1;
+
+        let app_state = *app.world.resource::<State<AppState>>().get();
+        let pause_state = *app.world.resource::<State<PauseState>>().get();
+        if visited.last() != Some(&app_state) {
+            visited.push(app_state);
+        }
+
+        let next = match step {
+            Step::Menu if step_frames >= MENU_FRAMES => {
+                tap(&mut app, window, KeyCode::Space);
+                // Held until the run ends, which also keeps the idle pause away.
+                send_key(&mut app, window, KeyCode::D, ButtonState::Pressed);
+                Some(Step::Moving)
+            }
+            Step::Moving if step_frames >= MOVE_FRAMES => Some(Step::Collecting),
+            Step::Collecting => {
+                points = app.world.resource::<GameInfo>().points();
+                if points > 0 {
+                    Some(Step::TakingHits)
+                } else {
+                    let player = player_position(&mut app);
+                    for mut transform in app
+                        .world
+                        .query_filtered::<&mut Transform, With<Coin>>()
+                        .iter_mut(&mut app.world)
+                    {
+                        transform.translation = player.extend(transform.translation.z);
+                    }
+                    None
+                }
+            }
+            Step::TakingHits if pause_state == PauseState::GameOver => {
+                send_key(&mut app, window, KeyCode::D, ButtonState::Released);
+                Some(Step::Spectating)
+            }
+            Step::TakingHits => {
+                let player = player_position(&mut app);
+                for mut transform in app
+                    .world
+                    .query_filtered::<&mut Transform, With<Enemy>>()
+                    .iter_mut(&mut app.world)
+                {
+                    transform.translation = player.extend(transform.translation.z);
+                }
+                None
+            }
+            Step::Spectating if step_frames >= MENU_FRAMES => {
+                tap(&mut app, window, KeyCode::Space);
+                Some(Step::Done)
+            }
+            Step::Done if app_state == AppState::Menu => break,
+            _ => None,
+        };
+
+        if let Some(next) = next {
+            info!("Smoke test: {:?} -> {:?}", step, next);
+            step = next;
+            step_frames = 0;
+        }
+    }
+
+    let last_score = app.world.resource::<LastScore>().0;
+    let mut failures = Vec::new();
+    if step != Step::Done {
+        failures.push(format!("stopped at {:?}", step));
+    }
+    if visited != [AppState::Menu, AppState::Game, AppState::Menu] {
+        failures.push(format!("unexpected state sequence {:?}", visited));
+    }
+    if points == 0 {
+        failures.push("no coin was collected".to_string());
+    }
+    if last_score.map_or(true, |score| score <= 0) {
+        failures.push(format!("LastScore is {:?}", last_score));
+    }
+
+    let _ = fs::remove_dir_all(&save_root);
+    if !failures.is_empty() {
+        eprintln!("Smoke test failed: {}", failures.join("; "));
+        process::exit(1);
+    }
+    println!("Smoke test passed (last score {:?})", last_score);
+}
+
+fn player_position(app: &mut App) -> Vec2 {
+    app.world
+        .query_filtered::<&Transform, With<Player>>()
+        .get_single(&app.world)
+        .map_or(Vec2::ZERO, |transform| transform.translation.truncate())
+}
+
+fn send_key(app: &mut App, window: Entity, key_code: KeyCode, state: ButtonState) {
+    app.world.send_event(KeyboardInput {
+        scan_code: 0,
+        key_code: Some(key_code),
+        state,
+        window,
+    });
+}
+
+// Pressed and released within one update, which still registers as just pressed.
+fn tap(app: &mut App, window: Entity, key_code: KeyCode) {
+    send_key(app, window, key_code, ButtonState::Pressed);
+    send_key(app, window, key_code, ButtonState::Released);
+}
@@ -0,0 +1,121 @@
+use bevy::prelude::*;
+
+use crate::effects::ScreenShake;
+
+// Screen shake moves the camera itself, so a plain viewport conversion lands off by the shake
+// offset and anything steered by the cursor jitters after every hit. Cursor and world positions
+// are converted here, with the shake taken out, rather than through the camera directly.
+
+pub fn screen_to_world(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    screen_shake: &ScreenShake,
+    screen_position: Vec2,
+) -> Option<Vec2> {
+    camera
+        .viewport_to_world_2d(camera_transform, screen_position)
+        .map(|world_position| world_position - screen_shake.offset())
+}
+
+pub fn world_to_screen(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    screen_shake: &ScreenShake,
+    world_position: Vec2,
+) -> Option<Vec2> {
+    camera.world_to_viewport(
+        camera_transform,
+        (world_position + screen_shake.offset()).extend(0.0),
+    )
+}
+
+// The cursor's world position on the primary window, if the cursor is over it.
+pub fn cursor_to_world(
+    window: &Window,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    screen_shake: &ScreenShake,
+) -> Option<Vec2> {
+    window
+        .cursor_position()
+        .and_then(|cursor| screen_to_world(camera, camera_transform, screen_shake, cursor))
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        render::camera::{camera_system, ManualTextureViews},
+        window::{PrimaryWindow, WindowCreated, WindowResized},
+    };
+
+    use super::*;
+
+    const SHAKE: Vec2 = Vec2::new(12.5, -7.25);
+
+    // An 800x600 window's camera, with its viewport computed the way the render plugin does.
+    fn camera() -> Camera {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Image>()
+            .init_resource::<ManualTextureViews>()
+            .add_event::<WindowCreated>()
+            .add_event::<WindowResized>()
+            .add_systems(Update, camera_system::<OrthographicProjection>);
+        app.world.spawn((
+            Window {
+                resolution: (800.0, 600.0).into(),
+                ..default()
+            },
+            PrimaryWindow,
+        ));
+        let camera = app.world.spawn(Camera2dBundle::default()).id();
+        app.update();
+
+        app.world.get::<Camera>(camera).unwrap().clone()
+    }
+
+    // Where the shake has moved the camera to.
+    fn shaken_transform() -> GlobalTransform {
+        Transform::from_translation(SHAKE.extend(999.9)).into()
+    }
+
+    #[test]
+    fn conversions_round_trip_through_a_shake() {
+        let camera = camera();
+        let screen_shake = ScreenShake::with_offset(SHAKE);
+
+        for world_position in [
+            Vec2::ZERO,
+            Vec2::new(123.4, -56.7),
+            Vec2::new(-399.0, 299.0),
+        ] {
+            let screen_position =
+                world_to_screen(&camera, &shaken_transform(), &screen_shake, world_position)
+                    .unwrap();
+            let back =
+                screen_to_world(&camera, &shaken_transform(), &screen_shake, screen_position)
+                    .unwrap();
+
+            assert!(back.distance(world_position) < 0.01, "{:?}", back);
+        }
+    }
+
+    #[test]
+    fn shake_does_not_move_the_cursor_in_the_world() {
+        let camera = camera();
+        let still = GlobalTransform::from(Transform::from_xyz(0.0, 0.0, 999.9));
+        let cursor = Vec2::new(250.0, 420.0);
+
+        let resting = screen_to_world(&camera, &still, &ScreenShake::default(), cursor).unwrap();
+        let shaking = screen_to_world(
+            &camera,
+            &shaken_transform(),
+            &ScreenShake::with_offset(SHAKE),
+            cursor,
+        )
+        .unwrap();
+
+        assert!(shaking.distance(resting) < 0.01);
+        assert!((resting - Vec2::new(-150.0, -120.0)).length() < 0.01);
+    }
+}
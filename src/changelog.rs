@@ -0,0 +1,186 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    assets::AssetHandles,
+    config::*,
+    effects::text_label,
+    menu::cleanup_menu,
+    menu_list::{spawn_menu_list, EntryKind, MenuEntry, MenuFocus, MenuListEvent, MenuListWindow},
+    player::{Action, InputBindings},
+    render_z,
+    storage::SaveData,
+    AppState,
+};
+
+const CHANGELOG: &str = include_str!("changelog.ron");
+const WHATS_NEW_LIST: &str = "whats_new";
+
+pub struct ChangelogPlugin;
+
+impl Plugin for ChangelogPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Changelog::parse(CHANGELOG))
+            .add_systems(OnEnter(AppState::Menu), open_whats_new)
+            .add_systems(OnEnter(AppState::WhatsNew), setup_whats_new)
+            .add_systems(
+                Update,
+                handle_whats_new.run_if(in_state(AppState::WhatsNew)),
+            )
+            .add_systems(OnExit(AppState::WhatsNew), cleanup_menu);
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChangelogEntry {
+    pub version: String,
+    pub notes: Vec<String>,
+}
+
+// Newest first, as written in changelog.ron.
+#[derive(Resource, Debug, Default)]
+pub struct Changelog(pub Vec<ChangelogEntry>);
+
+impl Changelog {
+    // A malformed entry is skipped rather than losing the whole changelog, and a file that doesn't
+    // parse at all just means there is nothing to show.
+    pub fn parse(source: &str) -> Self {
+        let values: Vec<ron::Value> = match ron::from_str(source) {
+            Ok(values) => values,
+            Err(error) => {
+                warn!("Could not parse the changelog: {}", error);
+                return Self::default();
+            }
+        };
+
+        let entries = values
+            .into_iter()
+            .filter_map(|value| match value.into_rust::<ChangelogEntry>() {
+                Ok(entry) if parse_version(&entry.version).is_some() => Some(entry),
+                Ok(entry) => {
+                    warn!(
+                        "Skipping changelog entry with bad version {}",
+                        entry.version
+                    );
+                    None
+                }
+                Err(error) => {
+                    warn!("Skipping malformed changelog entry: {}", error);
+                    None
+                }
+            })
+            .collect();
+        Self(entries)
+    }
+
+    // Entries after `last_version`, up to and including the running build.
+    pub fn unread(&self, last_version: Option<&str>) -> Vec<&ChangelogEntry> {
+        let current = parse_version(env!("CARGO_PKG_VERSION"));
+        let last = last_version.and_then(parse_version);
+        self.0
+            .iter()
+            .filter(|entry| {
+                let version = parse_version(&entry.version);
+                version <= current && last.is_none_or(|last| version > Some(last))
+            })
+            .collect()
+    }
+}
+
+fn parse_version(version: &str) -> Option<(u32, u32, u32)> {
+    let mut parts = version.trim().split('.').map(|part| part.parse().ok());
+    let version = (parts.next()??, parts.next()??, parts.next()??);
+    parts.next().is_none().then_some(version)
+}
+
+// Nothing to catch up on for someone who has never played; they just start from this build.
+fn returning_player(save_data: &SaveData) -> bool {
+    save_data.best_score.is_some()
+        || save_data.zen_best.is_some()
+        || save_data.tutorial_done
+        || !save_data.records.is_empty()
+}
+
+fn open_whats_new(
+    mut save_data: ResMut<SaveData>,
+    mut next_state: ResMut<NextState<AppState>>,
+    changelog: Res<Changelog>,
+) {
+    let last_version = save_data.last_version.as_deref();
+    if last_version == Some(env!("CARGO_PKG_VERSION")) {
+        return;
+    }
+
+    let unread = !changelog.unread(last_version).is_empty();
+    if unread && (last_version.is_some() || returning_player(&save_data)) {
+        next_state.set(AppState::WhatsNew);
+    } else {
+        save_data.last_version = Some(env!("CARGO_PKG_VERSION").to_string());
+    }
+}
+
+fn setup_whats_new(
+    mut commands: Commands,
+    asset_handles: Res<AssetHandles>,
+    save_data: Res<SaveData>,
+    changelog: Res<Changelog>,
+    bindings: Res<InputBindings>,
+    focus: Res<MenuFocus>,
+) {
+    commands.spawn(text_label(
+        &asset_handles,
+        "What's new",
+        80.0,
+        Color::DARK_GRAY,
+        Vec3::new(0.0, 260.0, render_z::BACKDROP),
+    ));
+
+    let entries = changelog
+        .unread(save_data.last_version.as_deref())
+        .into_iter()
+        .flat_map(|entry| {
+            std::iter::once(format!("Version {}", entry.version))
+                .chain(entry.notes.iter().map(|note| format!("- {}", note)))
+        })
+        .map(|line| MenuEntry::new("line", line, EntryKind::Action))
+        .collect();
+    let list = spawn_menu_list(
+        &mut commands,
+        &asset_handles,
+        &focus,
+        WHATS_NEW_LIST,
+        entries,
+        Vec3::new(0.0, 180.0, render_z::BACKDROP),
+    );
+    commands
+        .entity(list)
+        .insert(MenuListWindow(WHATS_NEW_VISIBLE_LINES));
+
+    commands.spawn(text_label(
+        &asset_handles,
+        format!(
+            "Up/Down to scroll, {} to close",
+            bindings.label(Action::Pause)
+        ),
+        28.0,
+        Color::DARK_GRAY,
+        Vec3::new(0.0, -260.0, render_z::BACKDROP),
+    ));
+}
+
+// Closing the panel is what marks the notes as read.
+fn handle_whats_new(
+    mut events: EventReader<MenuListEvent>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut save_data: ResMut<SaveData>,
+) {
+    for event in events.read() {
+        if let MenuListEvent::Back {
+            list: WHATS_NEW_LIST,
+        } = event
+        {
+            save_data.last_version = Some(env!("CARGO_PKG_VERSION").to_string());
+            next_state.set(AppState::Menu);
+        }
+    }
+}
@@ -0,0 +1,106 @@
+// The system clipboard, reached through each platform's command-line tools so no windowing
+// integration is needed. Without the feature, or on other platforms, copying and pasting always
+// fail and callers fall back to the log.
+
+#[cfg(all(
+    feature = "clipboard",
+    any(target_os = "windows", target_os = "macos", target_os = "linux")
+))]
+pub fn copy(text: &str) -> bool {
+    native::copy(text)
+}
+
+#[cfg(all(
+    feature = "clipboard",
+    any(target_os = "windows", target_os = "macos", target_os = "linux")
+))]
+pub fn paste() -> Option<String> {
+    native::paste()
+}
+
+#[cfg(not(all(
+    feature = "clipboard",
+    any(target_os = "windows", target_os = "macos", target_os = "linux")
+)))]
+pub fn copy(_text: &str) -> bool {
+    false
+}
+
+#[cfg(not(all(
+    feature = "clipboard",
+    any(target_os = "windows", target_os = "macos", target_os = "linux")
+)))]
+pub fn paste() -> Option<String> {
+    None
+}
+
+#[cfg(all(
+    feature = "clipboard",
+    any(target_os = "windows", target_os = "macos", target_os = "linux")
+))]
+mod native {
+    use std::{
+        io::Write,
+        process::{Command, Stdio},
+    };
+
+    type ClipboardCommand = (&'static str, &'static [&'static str]);
+
+    #[cfg(target_os = "windows")]
+    const COPY_COMMANDS: &[ClipboardCommand] = &[("clip", &[])];
+    #[cfg(target_os = "windows")]
+    const PASTE_COMMANDS: &[ClipboardCommand] =
+        &[("powershell", &["-NoProfile", "-Command", "Get-Clipboard"])];
+
+    #[cfg(target_os = "macos")]
+    const COPY_COMMANDS: &[ClipboardCommand] = &[("pbcopy", &[])];
+    #[cfg(target_os = "macos")]
+    const PASTE_COMMANDS: &[ClipboardCommand] = &[("pbpaste", &[])];
+
+    // Wayland first; under X11 wl-copy fails to start and xclip takes over.
+    #[cfg(target_os = "linux")]
+    const COPY_COMMANDS: &[ClipboardCommand] =
+        &[("wl-copy", &[]), ("xclip", &["-selection", "clipboard"])];
+    #[cfg(target_os = "linux")]
+    const PASTE_COMMANDS: &[ClipboardCommand] = &[
+        ("wl-paste", &["--no-newline"]),
+        ("xclip", &["-selection", "clipboard", "-o"]),
+    ];
+
+    pub fn copy(text: &str) -> bool {
+        COPY_COMMANDS.iter().any(|(program, args)| {
+            let Ok(mut child) = Command::new(program)
+                .args(*args)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+            else {
+                return false;
+            };
+
+            // Dropping stdin closes it, which is what tells the tool the text is complete.
+            let written = child
+                .stdin
+                .take()
+                .is_some_and(|mut stdin| stdin.write_all(text.as_bytes()).is_ok());
+            child.wait().is_ok_and(|status| status.success()) && written
+        })
+    }
+
+    pub fn paste() -> Option<String> {
+        PASTE_COMMANDS.iter().find_map(|(program, args)| {
+            let output = Command::new(program)
+                .args(*args)
+                .stdin(Stdio::null())
+                .stderr(Stdio::null())
+                .output()
+                .ok()?;
+
+            output
+                .status
+                .success()
+                .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        })
+    }
+}
@@ -0,0 +1,216 @@
+use bevy::{prelude::*, sprite::Anchor};
+
+use crate::{
+    assets::AssetHandles,
+    config::*,
+    effects::BannerQueue,
+    enemy::{EnemyRemoved, EnemySpawned, EnemyType},
+    game::RunStats,
+    menu::cleanup_menu,
+    player::{Action, InputBindings},
+    render_z,
+    storage::SaveData,
+    AppState,
+};
+
+pub struct CodexPlugin;
+
+impl Plugin for CodexPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, codex_navigation.run_if(in_state(AppState::Codex)))
+            .add_systems(
+                Update,
+                (record_enemy_spawns, record_enemy_removals).run_if(in_state(AppState::Game)),
+            )
+            .add_systems(OnEnter(AppState::Codex), setup_codex)
+            .add_systems(OnExit(AppState::Codex), cleanup_menu);
+    }
+}
+
+#[derive(Resource)]
+struct CodexSelection(usize);
+
+#[derive(Component)]
+struct CodexEntry(usize);
+
+#[derive(Component)]
+struct CodexDetail;
+
+fn setup_codex(
+    mut commands: Commands,
+    asset_handles: Res<AssetHandles>,
+    save_data: Res<SaveData>,
+    bindings: Res<InputBindings>,
+) {
+    commands.insert_resource(CodexSelection(0));
+
+    commands.spawn(Text2dBundle {
+        text: Text::from_section(
+            "Codex",
+            TextStyle {
+                font: asset_handles.font.clone(),
+                font_size: 80.0,
+                color: Color::DARK_GRAY,
+            },
+        )
+        .with_alignment(TextAlignment::Center),
+        transform: Transform::from_translation(Vec3::new(0.0, 260.0, render_z::BACKDROP)),
+        ..default()
+    });
+
+    for (index, enemy_type) in EnemyType::ALL.iter().enumerate() {
+        let y = 140.0 - index as f32 * CODEX_ROW_SPACING;
+        let encountered = save_data.codex.get(enemy_type).copied().unwrap_or(0);
+        let removed = save_data
+            .codex_removed
+            .get(enemy_type)
+            .copied()
+            .unwrap_or(0);
+
+        let (material, label) = if encountered > 0 {
+            (
+                asset_handles.enemy_material(*enemy_type),
+                format!(
+                    "{}  -  {} encountered, {} removed",
+                    enemy_type.name(),
+                    encountered,
+                    removed
+                ),
+            )
+        } else {
+            (
+                asset_handles.enemy_material_silhouette.clone(),
+                "???".to_string(),
+            )
+        };
+
+        commands.spawn(ColorMesh2dBundle {
+            mesh: asset_handles.enemy_mesh.clone().into(),
+            material,
+            transform: Transform::from_translation(Vec3::new(-260.0, y, render_z::ENEMY)),
+            ..default()
+        });
+
+        commands.spawn((
+            CodexEntry(index),
+            Text2dBundle {
+                text: Text::from_section(
+                    label,
+                    TextStyle {
+                        font: asset_handles.font.clone(),
+                        font_size: 40.0,
+                        color: Color::DARK_GRAY,
+                    },
+                ),
+                text_anchor: Anchor::CenterLeft,
+                transform: Transform::from_translation(Vec3::new(-220.0, y, render_z::BACKDROP)),
+                ..default()
+            },
+        ));
+    }
+
+    commands.spawn((
+        CodexDetail,
+        Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: asset_handles.font.clone(),
+                    font_size: 32.0,
+                    color: Color::GRAY,
+                },
+            )
+            .with_alignment(TextAlignment::Center),
+            transform: Transform::from_translation(Vec3::new(0.0, -160.0, render_z::BACKDROP)),
+            ..default()
+        },
+    ));
+
+    commands.spawn(Text2dBundle {
+        text: Text::from_section(
+            format!(
+                "Up/Down to browse, {} to return",
+                bindings.label(Action::Pause)
+            ),
+            TextStyle {
+                font: asset_handles.font.clone(),
+                font_size: 28.0,
+                color: Color::DARK_GRAY,
+            },
+        )
+        .with_alignment(TextAlignment::Center),
+        transform: Transform::from_translation(Vec3::new(0.0, -260.0, render_z::BACKDROP)),
+        ..default()
+    });
+}
+
+fn codex_navigation(
+    bindings: Res<InputBindings>,
+    input: Res<Input<KeyCode>>,
+    save_data: Res<SaveData>,
+    mut selection: ResMut<CodexSelection>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut entries: Query<(&CodexEntry, &mut Text), Without<CodexDetail>>,
+    mut detail: Query<&mut Text, With<CodexDetail>>,
+) {
+    if bindings.just_pressed(Action::Pause, &input) {
+        next_state.set(AppState::Menu);
+        return;
+    }
+
+    let count = EnemyType::ALL.len();
+    if input.any_just_pressed([KeyCode::Up, KeyCode::W]) {
+        selection.0 = (selection.0 + count - 1) % count;
+    }
+    if input.any_just_pressed([KeyCode::Down, KeyCode::S]) {
+        selection.0 = (selection.0 + 1) % count;
+    }
+
+    if !selection.is_changed() {
+        return;
+    }
+
+    for (entry, mut text) in entries.iter_mut() {
+        text.sections[0].style.color = if entry.0 == selection.0 {
+            Color::WHITE
+        } else {
+            Color::DARK_GRAY
+        };
+    }
+
+    let enemy_type = EnemyType::ALL[selection.0];
+    let mut detail = detail.single_mut();
+    detail.sections[0].value = if save_data.codex.contains_key(&enemy_type) {
+        enemy_type.description().to_string()
+    } else {
+        "Not yet encountered".to_string()
+    };
+}
+
+fn record_enemy_spawns(
+    mut enemy_spawned: EventReader<EnemySpawned>,
+    mut run_stats: ResMut<RunStats>,
+    mut save_data: ResMut<SaveData>,
+    mut banners: ResMut<BannerQueue>,
+) {
+    for event in enemy_spawned.read() {
+        *run_stats.encountered.entry(event.enemy_type).or_default() += 1;
+
+        let encountered = save_data.codex.entry(event.enemy_type).or_default();
+        if *encountered == 0 {
+            banners.push(format!("New codex entry: {}", event.enemy_type.name()));
+        }
+        *encountered += 1;
+    }
+}
+
+fn record_enemy_removals(
+    mut enemy_removed: EventReader<EnemyRemoved>,
+    mut run_stats: ResMut<RunStats>,
+    mut save_data: ResMut<SaveData>,
+) {
+    for event in enemy_removed.read() {
+        *run_stats.removed.entry(event.enemy_type).or_default() += 1;
+        *save_data.codex_removed.entry(event.enemy_type).or_default() += 1;
+    }
+}
@@ -0,0 +1,597 @@
+use bevy::{audio::Volume, prelude::*};
+use rand::Rng;
+
+use crate::{
+    assets::AssetHandles,
+    audio::SfxBudget,
+    config::*,
+    effects::{text_label, Fade, ScaleAnimation},
+    game::GameMode,
+    game_time::GameTime,
+    quality::EffectsLevel,
+    render_z,
+    rng::GameRng,
+    util::safe_half_extent,
+};
+
+#[derive(Component, Default)]
+pub struct Coin {
+    pub health: bool,
+}
+
+#[derive(Component, Default)]
+pub struct CoinCharge {
+    pub age: f32,
+    pub bonus: i32,
+}
+
+#[derive(Component)]
+pub struct CoinRing;
+
+#[derive(Component)]
+pub struct CoinValueLabel;
+
+// Sent when a collected coin moves to its next spot. The first coin of a run is placed, not
+// relocated, so it never sends one.
+#[derive(Event)]
+pub struct CoinRelocated {
+    pub position: Vec3,
+    pub player_position: Vec3,
+}
+
+#[derive(Component)]
+pub struct CoinBeacon(Timer);
+
+// Keeps enemies from racing the player to a coin that just moved: their pull toward it is off
+// for GameRules::coin_contest_time after each relocation, then eases back in.
+#[derive(Resource, Default)]
+pub struct CoinContestTimer {
+    window: f32,
+    elapsed: f32,
+}
+
+impl CoinContestTimer {
+    pub fn start(&mut self, window: f32) {
+        self.window = window;
+        self.elapsed = 0.0;
+    }
+
+    pub fn tick(&mut self, delta: f32) {
+        self.elapsed += delta;
+    }
+
+    // Scales every enemy's coin_pull.
+    pub fn pull_scale(&self) -> f32 {
+        if self.window <= 0.0 {
+            return 1.0;
+        }
+        ((self.elapsed - self.window) / COIN_CONTEST_RAMP_TIME).clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct CoinSpawnHistory {
+    quadrants: [Option<u8>; 2],
+    previous: Option<Vec3>,
+}
+
+impl CoinSpawnHistory {
+    fn accepts(&self, candidate: Vec3, min_distance: f32) -> bool {
+        let quadrant = Some(quadrant(candidate));
+        if self.quadrants.iter().all(|previous| *previous == quadrant) {
+            return false;
+        }
+
+        self.previous
+            .is_none_or(|previous| previous.distance(candidate) >= min_distance)
+    }
+
+    fn record(&mut self, position: Vec3) {
+        self.quadrants = [self.quadrants[1], Some(quadrant(position))];
+        self.previous = Some(position);
+    }
+}
+
+fn quadrant(position: Vec3) -> u8 {
+    (position.x >= 0.0) as u8 | ((position.y >= 0.0) as u8) << 1
+}
+
+pub fn charge_bonus(age: f32) -> i32 {
+    if age < COIN_CHARGE_DELAY {
+        return 0;
+    }
+
+    (((age - COIN_CHARGE_DELAY) / COIN_CHARGE_INTERVAL) as i32).min(COIN_MAX_BONUS)
+}
+
+// Health coins never charge: their reward is the health itself.
+pub fn charge_coin(
+    mut commands: Commands,
+    mut coin_query: Query<(Entity, &Coin, &mut CoinCharge)>,
+    asset_handles: Res<AssetHandles>,
+    game_time: Res<GameTime>,
+) {
+    for (entity, coin, mut charge) in coin_query.iter_mut() {
+        if coin.health {
+            continue;
+        }
+
+        charge.age += game_time.delta_seconds();
+
+        let bonus = charge_bonus(charge.age);
+        if bonus == charge.bonus {
+            continue;
+        }
+        charge.bonus = bonus;
+
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn((
+                CoinRing,
+                ColorMesh2dBundle {
+                    mesh: asset_handles.coin_mesh.clone().into(),
+                    material: asset_handles.coin_ring_material.clone(),
+                    transform: Transform::from_translation(Vec3::new(
+                        0.0,
+                        0.0,
+                        render_z::CHILD_BELOW,
+                    ))
+                    .with_scale(Vec3::splat(1.0 + COIN_RING_SCALE_STEP * bonus as f32)),
+                    ..default()
+                },
+            ));
+        });
+    }
+}
+
+// Everything a coin placement depends on besides the RNG and the spawn history, so placement can
+// be sampled away from the ECS.
+#[derive(Debug, Clone, Copy)]
+pub struct CoinSpawnConstraints {
+    pub width: f32,
+    pub height: f32,
+    pub player: Vec2,
+    pub rerolls: u32,
+    pub min_distance_fraction: f32,
+    // From the player, as a fraction of the window diagonal; see CoinReachCurve.
+    pub reach_fraction: f32,
+    pub opposite_half: bool,
+    pub safe_area: f32,
+}
+
+impl CoinSpawnConstraints {
+    pub fn new(width: f32, height: f32, player: Vec2, points: i32, rules: &GameRules) -> Self {
+        Self {
+            width,
+            height,
+            player,
+            rerolls: rules.coin_spawn_rerolls,
+            min_distance_fraction: rules.coin_min_distance_fraction,
+            reach_fraction: rules.coin_reach.fraction(points),
+            opposite_half: false,
+            safe_area: rules.safe_area,
+        }
+    }
+
+    // Rules in priority order: clearance, the cornered half, quadrant fairness and spacing from
+    // the last coin always hold; the reach requirement only holds while `strict`.
+    fn accepts(&self, history: &CoinSpawnHistory, candidate: Vec3, strict: bool) -> bool {
+        let diagonal = Vec2::new(self.width, self.height).length();
+        let player_distance = candidate.truncate().distance(self.player);
+
+        player_distance >= COIN_PLAYER_CLEARANCE
+            && (!self.opposite_half || candidate.x * self.player.x <= 0.0)
+            && history.accepts(candidate, diagonal * self.min_distance_fraction)
+            && (!strict || player_distance >= diagonal * self.reach_fraction)
+    }
+}
+
+pub fn get_coin_spawn_position(
+    rng: &mut GameRng,
+    history: &mut CoinSpawnHistory,
+    constraints: &CoinSpawnConstraints,
+) -> Vec3 {
+    // Only where the coin lands shrinks; the spacing from earlier coins still follows the window.
+    let safe_extent = safe_half_extent(
+        Vec2::new(constraints.width, constraints.height),
+        constraints.safe_area,
+    );
+    let (width, height) = (safe_extent.x, safe_extent.y);
+
+    let mut candidate = random_coin_position(width, height, rng);
+    for attempt in 0..constraints.rerolls {
+        if constraints.accepts(history, candidate, attempt < COIN_REACH_STRICT_REROLLS) {
+            break;
+        }
+        candidate = random_coin_position(width, height, rng);
+    }
+
+    history.record(candidate);
+    candidate
+}
+
+fn random_coin_position(width: f32, height: f32, rng: &mut GameRng) -> Vec3 {
+    let x_float: f32 = rng.gen();
+    let y_float: f32 = rng.gen();
+
+    Vec3::new(
+        width * (x_float - 0.5),
+        height * (y_float - 0.5),
+        render_z::PICKUP,
+    )
+}
+
+// A coin that lands far from the player marks its new spot with an expanding ring and a soft ping
+// panned toward it. Nearby coins are easy enough to spot on their own.
+pub fn announce_relocated_coin(
+    mut commands: Commands,
+    mut events: EventReader<CoinRelocated>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut budget: ResMut<SfxBudget>,
+    asset_handles: Res<AssetHandles>,
+    effects_level: Res<EffectsLevel>,
+) {
+    for event in events.read() {
+        if event
+            .position
+            .truncate()
+            .distance(event.player_position.truncate())
+            < COIN_BEACON_MIN_DISTANCE
+        {
+            continue;
+        }
+
+        budget.play_at(
+            &mut commands,
+            asset_handles.coin_sound.clone(),
+            PlaybackSettings::DESPAWN
+                .with_volume(Volume::new_relative(COIN_BEACON_VOLUME))
+                .with_speed(COIN_BEACON_PITCH),
+            event.position,
+        );
+
+        if !effects_level.0.coin_beacon() {
+            continue;
+        }
+
+        commands.spawn((
+            CoinBeacon(Timer::from_seconds(COIN_BEACON_TIME, TimerMode::Once)),
+            ScaleAnimation::new(COIN_BEACON_TIME, 1.0, COIN_BEACON_SCALE),
+            Fade::out(COIN_BEACON_TIME),
+            ColorMesh2dBundle {
+                mesh: asset_handles.coin_mesh.clone().into(),
+                material: materials.add(ColorMaterial::from(COIN_BEACON_COLOR)),
+                transform: Transform::from_translation(
+                    event.position.truncate().extend(render_z::TRAIL),
+                ),
+                ..default()
+            },
+        ));
+    }
+}
+
+pub fn contest_relocated_coin(
+    mut events: EventReader<CoinRelocated>,
+    mut contest: ResMut<CoinContestTimer>,
+    game_rules: Res<GameRules>,
+    time: Res<Time>,
+) {
+    contest.tick(time.delta_seconds());
+    if events.read().count() > 0 {
+        contest.start(game_rules.coin_contest_time);
+    }
+}
+
+pub fn expire_coin_beacons(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut CoinBeacon)>,
+    time: Res<Time>,
+) {
+    for (entity, mut beacon) in query.iter_mut() {
+        if beacon.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+// What collecting the coin is worth right now. hit_coin awards exactly this and the label on the
+// coin shows it, so the two can't disagree. Deposit runs bank unmultiplied coins.
+pub fn coin_value(charge: &CoinCharge, game_mode: GameMode, rules: &GameRules) -> i32 {
+    let coins = 1 + charge.bonus;
+    if game_mode == GameMode::Deposit {
+        return coins;
+    }
+
+    (coins as f32 * rules.score_multiplier).round().max(1.0) as i32
+}
+
+pub fn coin_value_label(asset_handles: &AssetHandles) -> (CoinValueLabel, Text2dBundle) {
+    let mut label = text_label(
+        asset_handles,
+        "",
+        COIN_VALUE_FONT_SIZE,
+        Color::WHITE,
+        COIN_VALUE_OFFSET.extend(render_z::CHILD_ABOVE),
+    );
+    label.visibility = Visibility::Hidden;
+
+    (CoinValueLabel, label)
+}
+
+// Plain one-point coins stay unlabeled.
+pub fn update_coin_value_labels(
+    coin_query: Query<&CoinCharge>,
+    mut label_query: Query<(&Parent, &mut Text, &mut Visibility), With<CoinValueLabel>>,
+    game_mode: Res<GameMode>,
+    game_rules: Res<GameRules>,
+) {
+    for (parent, mut text, mut visibility) in label_query.iter_mut() {
+        let Ok(charge) = coin_query.get(parent.get()) else {
+            continue;
+        };
+
+        let value = coin_value(charge, *game_mode, &game_rules);
+        let next = if value > 1 {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if *visibility != next {
+            *visibility = next;
+        }
+
+        let label = value.to_string();
+        if text.sections[0].value != label {
+            text.sections[0].value = label;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIDTH: f32 = 800.0;
+    const HEIGHT: f32 = 600.0;
+
+    fn history(positions: &[Vec2]) -> CoinSpawnHistory {
+        let mut history = CoinSpawnHistory::default();
+        for position in positions {
+            history.record(position.extend(render_z::PICKUP));
+        }
+        history
+    }
+
+    fn constraints(player: Vec2) -> CoinSpawnConstraints {
+        CoinSpawnConstraints::new(
+            WIDTH,
+            HEIGHT,
+            player,
+            0,
+            &GameRules::for_difficulty(Difficulty::Normal),
+        )
+    }
+
+    #[test]
+    fn third_coin_in_the_same_quadrant_is_rejected() {
+        let history = history(&[Vec2::new(300.0, 250.0), Vec2::new(30.0, 20.0)]);
+
+        assert!(!history.accepts(Vec3::new(350.0, 280.0, 0.0), 0.0));
+        assert!(history.accepts(Vec3::new(-350.0, 280.0, 0.0), 0.0));
+    }
+
+    #[test]
+    fn two_coins_in_a_quadrant_are_allowed() {
+        let history = history(&[Vec2::new(-300.0, 250.0), Vec2::new(30.0, 20.0)]);
+
+        assert!(history.accepts(Vec3::new(350.0, 280.0, 0.0), 0.0));
+    }
+
+    #[test]
+    fn coin_too_close_to_the_previous_one_is_rejected() {
+        let history = history(&[Vec2::new(-300.0, 250.0)]);
+
+        assert!(!history.accepts(Vec3::new(-250.0, 0.0, 0.0), 300.0));
+        assert!(history.accepts(Vec3::new(100.0, -50.0, 0.0), 300.0));
+    }
+
+    #[test]
+    fn seeded_placements_spread_out() {
+        let mut rng = GameRng::from_seed(434);
+        let mut history = CoinSpawnHistory::default();
+        let constraints = constraints(Vec2::ZERO);
+        let min_distance = Vec2::new(WIDTH, HEIGHT).length() * COIN_MIN_DISTANCE_FRACTION;
+
+        let coins: Vec<Vec3> = (0..500)
+            .map(|_| get_coin_spawn_position(&mut rng, &mut history, &constraints))
+            .collect();
+
+        for window in coins.windows(3) {
+            let quadrants = window.iter().map(|coin| quadrant(*coin));
+            assert!(quadrants.clone().any(|q| q != quadrant(window[0])));
+            assert!(window[2].distance(window[1]) >= min_distance);
+            assert!(window[2].truncate().length() >= COIN_PLAYER_CLEARANCE);
+        }
+    }
+
+    #[test]
+    fn placement_gives_up_after_the_rerolls() {
+        let mut rules = GameRules::for_difficulty(Difficulty::Normal);
+        rules.coin_spawn_rerolls = 0;
+        rules.coin_min_distance_fraction = 10.0;
+        let constraints = CoinSpawnConstraints::new(WIDTH, HEIGHT, Vec2::ZERO, 0, &rules);
+        let mut history = history(&[Vec2::new(-300.0, 250.0)]);
+
+        let coin = get_coin_spawn_position(&mut GameRng::from_seed(1), &mut history, &constraints);
+
+        assert_eq!(history.previous, Some(coin));
+    }
+
+    // Cells are compared with their share of the area outside the player's clearance, so only bias
+    // from the rejection rules shows up, not the hole the clearance cuts around the player.
+    #[test]
+    fn placement_covers_the_playfield_evenly() {
+        const COLUMNS: usize = 8;
+        const ROWS: usize = 6;
+        const SAMPLES: usize = 100_000;
+        const SUBSTEPS: usize = 20;
+
+        let cell = Vec2::new(WIDTH / COLUMNS as f32, HEIGHT / ROWS as f32);
+        let cell_index = |position: Vec2| {
+            let column = ((position.x + WIDTH / 2.0) / cell.x) as usize;
+            let row = ((position.y + HEIGHT / 2.0) / cell.y) as usize;
+            row.min(ROWS - 1) * COLUMNS + column.min(COLUMNS - 1)
+        };
+
+        let mut open_area = [0.0; COLUMNS * ROWS];
+        for x in 0..COLUMNS * SUBSTEPS {
+            for y in 0..ROWS * SUBSTEPS {
+                let point = Vec2::new(
+                    (x as f32 + 0.5) * cell.x / SUBSTEPS as f32 - WIDTH / 2.0,
+                    (y as f32 + 0.5) * cell.y / SUBSTEPS as f32 - HEIGHT / 2.0,
+                );
+                if point.length() >= COIN_PLAYER_CLEARANCE {
+                    open_area[cell_index(point)] += 1.0;
+                }
+            }
+        }
+        let total_area: f32 = open_area.iter().sum();
+
+        let mut rng = GameRng::from_seed(457);
+        let mut history = CoinSpawnHistory::default();
+        let constraints = constraints(Vec2::ZERO);
+        let mut counts = [0; COLUMNS * ROWS];
+        for _ in 0..SAMPLES {
+            let coin = get_coin_spawn_position(&mut rng, &mut history, &constraints);
+            counts[cell_index(coin.truncate())] += 1;
+        }
+
+        for (index, count) in counts.iter().enumerate() {
+            if open_area[index] == 0.0 {
+                assert_eq!(*count, 0);
+                continue;
+            }
+            let share = *count as f32 / (SAMPLES as f32 * open_area[index] / total_area);
+            assert!(
+                (0.5..=2.0).contains(&share),
+                "cell {} got {:.2} of its share",
+                index,
+                share
+            );
+        }
+    }
+
+    #[test]
+    fn contest_suppresses_the_pull_then_ramps_it_back() {
+        let mut contest = CoinContestTimer::default();
+        assert_eq!(contest.pull_scale(), 1.0);
+
+        contest.start(COIN_CONTEST_TIME);
+        assert_eq!(contest.pull_scale(), 0.0);
+        contest.tick(COIN_CONTEST_TIME - 0.01);
+        assert_eq!(contest.pull_scale(), 0.0);
+
+        contest.tick(0.01 + COIN_CONTEST_RAMP_TIME / 2.0);
+        assert!((contest.pull_scale() - 0.5).abs() < 1e-4);
+
+        contest.tick(COIN_CONTEST_RAMP_TIME / 2.0);
+        assert_eq!(contest.pull_scale(), 1.0);
+        contest.tick(10.0);
+        assert_eq!(contest.pull_scale(), 1.0);
+    }
+
+    #[test]
+    fn relocating_again_restarts_the_contest() {
+        let mut contest = CoinContestTimer::default();
+        contest.start(COIN_CONTEST_TIME);
+        contest.tick(COIN_CONTEST_TIME + COIN_CONTEST_RAMP_TIME);
+        assert_eq!(contest.pull_scale(), 1.0);
+
+        contest.start(COIN_CONTEST_TIME);
+
+        assert_eq!(contest.pull_scale(), 0.0);
+    }
+
+    #[test]
+    fn hard_has_no_contest() {
+        let mut contest = CoinContestTimer::default();
+
+        contest.start(GameRules::for_difficulty(Difficulty::Hard).coin_contest_time);
+
+        assert_eq!(contest.pull_scale(), 1.0);
+    }
+
+    // Player to coin distances over many seeded placements, each against an empty history.
+    fn reach_distances(player: Vec2, points: i32) -> Vec<f32> {
+        let mut rng = GameRng::from_seed(points as u64);
+        let constraints = CoinSpawnConstraints::new(
+            WIDTH,
+            HEIGHT,
+            player,
+            points,
+            &GameRules::for_difficulty(Difficulty::Normal),
+        );
+
+        (0..2000)
+            .map(|_| {
+                get_coin_spawn_position(&mut rng, &mut CoinSpawnHistory::default(), &constraints)
+                    .truncate()
+                    .distance(player)
+            })
+            .collect()
+    }
+
+    fn mean(distances: &[f32]) -> f32 {
+        distances.iter().sum::<f32>() / distances.len() as f32
+    }
+
+    #[test]
+    fn reach_ramps_with_points_and_then_holds() {
+        let reach = CoinReachCurve::for_difficulty(Difficulty::Normal);
+
+        assert_eq!(reach.fraction(0), 0.1);
+        assert!((reach.fraction(20) - 0.275).abs() < 1e-6);
+        assert_eq!(reach.fraction(40), 0.45);
+        assert_eq!(reach.fraction(400), 0.45);
+        assert_eq!(
+            CoinReachCurve::for_difficulty(Difficulty::Easy).fraction(400),
+            0.3
+        );
+    }
+
+    #[test]
+    fn coins_land_farther_as_points_rise() {
+        // Off center, so even the full reach leaves room to land in.
+        let player = Vec2::new(-200.0, -150.0);
+        let means: Vec<f32> = [0, 20, 40]
+            .into_iter()
+            .map(|points| mean(&reach_distances(player, points)))
+            .collect();
+
+        assert!(
+            means[0] + 20.0 < means[1] && means[1] + 20.0 < means[2],
+            "{:?}",
+            means
+        );
+    }
+
+    #[test]
+    fn late_coins_keep_the_full_reach_when_there_is_room() {
+        // From near a corner, most of the playfield is past 45% of the diagonal.
+        let player = Vec2::new(-350.0, -250.0);
+        let reach = Vec2::new(WIDTH, HEIGHT).length() * 0.45;
+        let share_beyond = |points| {
+            let distances = reach_distances(player, points);
+            distances
+                .iter()
+                .filter(|&&distance| distance >= reach)
+                .count() as f32
+                / distances.len() as f32
+        };
+
+        let early = share_beyond(0);
+        let late = share_beyond(40);
+
+        assert!(late > 0.99, "{}", late);
+        assert!(early < late - 0.2, "{} {}", early, late);
+    }
+}
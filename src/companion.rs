@@ -0,0 +1,246 @@
+use std::collections::VecDeque;
+
+use bevy::{prelude::*, sprite::Mesh2dHandle, window::PrimaryWindow};
+
+use crate::{
+    assets::AssetHandles,
+    coin::{Coin, CoinRelocated},
+    config::*,
+    game::{hit_coin, run_in_progress, GameMode},
+    game_time::GameTime,
+    pause::PauseState,
+    pickup::{pickup_collision, HitPickup, PickupKind},
+    player::Player,
+    quality::EffectsLevel,
+    render_z,
+    shrink::{player_radius, SizeModifier},
+    speed_ribbon::{ribbon_mesh, RibbonPoint},
+    storage::SaveData,
+    window::not_suspended,
+    AppState,
+};
+
+pub struct CompanionPlugin;
+
+impl Plugin for CompanionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Game), spawn_companion)
+            .add_systems(
+                Update,
+                (
+                    update_companion.run_if(run_in_progress),
+                    update_companion_trail,
+                )
+                    .chain()
+                    .after(pickup_collision)
+                    .before(hit_coin)
+                    .run_if(in_state(AppState::Game))
+                    .run_if(in_state(PauseState::Running))
+                    .run_if(not_suspended),
+            );
+    }
+}
+
+pub fn companion_unlocked(save_data: &SaveData) -> bool {
+    save_data
+        .best_score
+        .is_some_and(|best| best >= COMPANION_UNLOCK_SCORE)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CompanionState {
+    Orbiting,
+    Dashing,
+    Returning,
+}
+
+// A small orb circling the player that now and then darts out for a nearby coin. It has no
+// collision of its own, so enemies pass straight through it.
+#[derive(Component)]
+pub struct Companion {
+    state: CompanionState,
+    angle: f32,
+    cooldown: Timer,
+    // Recent positions, newest first, for the dash trail.
+    trail: VecDeque<Vec2>,
+}
+
+#[derive(Component)]
+struct CompanionTrail;
+
+fn spawn_companion(
+    mut commands: Commands,
+    save_data: Res<SaveData>,
+    game_mode: Res<GameMode>,
+    asset_handles: Res<AssetHandles>,
+) {
+    if !save_data.settings.companion
+        || !companion_unlocked(&save_data)
+        || *game_mode == GameMode::Tutorial
+    {
+        return;
+    }
+
+    commands.spawn((
+        Companion {
+            state: CompanionState::Orbiting,
+            angle: 0.0,
+            cooldown: Timer::from_seconds(COMPANION_COOLDOWN, TimerMode::Once),
+            trail: VecDeque::with_capacity(COMPANION_TRAIL_LENGTH),
+        },
+        ColorMesh2dBundle {
+            mesh: asset_handles.coin_mesh.clone().into(),
+            material: asset_handles.companion_material.clone(),
+            transform: Transform::from_xyz(COMPANION_ORBIT_RADIUS, 0.0, render_z::PLAYER)
+                .with_scale(Vec3::splat(COMPANION_RADIUS / COIN_RADIUS)),
+            ..default()
+        },
+    ));
+}
+
+// Orbits until the cooldown is up and a coin comes within reach, then dashes for it and comes
+// back. Health coins are left for the player, and a coin that moves mid-dash calls the dash off.
+fn update_companion(
+    mut companion_query: Query<(Entity, &mut Companion, &mut Transform)>,
+    player_query: Query<(&Transform, Option<&SizeModifier>), (With<Player>, Without<Companion>)>,
+    coin_query: Query<(Entity, &Coin, &Transform), (Without<Player>, Without<Companion>)>,
+    mut relocated: EventReader<CoinRelocated>,
+    mut hit_pickup: EventWriter<HitPickup>,
+    run_config: Res<RunConfig>,
+    game_time: Res<GameTime>,
+) {
+    let relocated = relocated.read().count() > 0;
+    let Ok((entity, mut companion, mut transform)) = companion_query.get_single_mut() else {
+        return;
+    };
+    let (Ok((player_transform, size)), Ok((coin_entity, coin, coin_transform))) =
+        (player_query.get_single(), coin_query.get_single())
+    else {
+        return;
+    };
+
+    let dt = game_time.delta_seconds();
+    let player = player_transform.translation.truncate();
+    let coin_position = coin_transform.translation.truncate();
+    // The player's own touch this frame already counts; the orb mustn't collect the same coin.
+    let player_touching =
+        player.distance(coin_position) < player_radius(&run_config, size) + run_config.coin_radius;
+
+    companion.angle = (companion.angle + COMPANION_ORBIT_SPEED * dt) % std::f32::consts::TAU;
+    let orbit = player + Vec2::from_angle(companion.angle) * COMPANION_ORBIT_RADIUS;
+    let mut position = transform.translation.truncate();
+    let step = COMPANION_DASH_SPEED * dt;
+
+    match companion.state {
+        CompanionState::Orbiting => {
+            position = orbit;
+            companion.cooldown.tick(game_time.delta());
+            if companion.cooldown.finished()
+                && !coin.health
+                && !player_touching
+                && position.distance(coin_position) <= COMPANION_REACH
+            {
+                companion.state = CompanionState::Dashing;
+            }
+        }
+        CompanionState::Dashing => {
+            if relocated || coin.health {
+                companion.state = CompanionState::Returning;
+            } else {
+                position += (coin_position - position).clamp_length_max(step);
+                if position.distance(coin_position) <= COMPANION_RADIUS + run_config.coin_radius {
+                    if !player_touching {
+                        hit_pickup.send(HitPickup {
+                            kind: PickupKind::Coin,
+                            pickup: coin_entity,
+                            collector: entity,
+                        });
+                        companion.cooldown.reset();
+                    }
+                    companion.state = CompanionState::Returning;
+                }
+            }
+        }
+        CompanionState::Returning => {
+            position += (orbit - position).clamp_length_max(step);
+            if position.distance(orbit) <= step {
+                companion.state = CompanionState::Orbiting;
+            }
+        }
+    }
+
+    // The trail only grows while the orb is off its orbit, and drains away once it is back.
+    if companion.state == CompanionState::Orbiting {
+        companion.trail.pop_back();
+    } else {
+        companion.trail.push_front(position);
+        companion.trail.truncate(COMPANION_TRAIL_LENGTH);
+    }
+
+    transform.translation = position.extend(render_z::PLAYER);
+}
+
+// Built the same way as the speed ribbon, and shown at the same effects levels.
+fn update_companion_trail(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    companion_query: Query<&Companion>,
+    mut trail_query: Query<(&Mesh2dHandle, &mut Visibility), With<CompanionTrail>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    effects_level: Res<EffectsLevel>,
+    asset_handles: Res<AssetHandles>,
+) {
+    let Ok(companion) = companion_query.get_single() else {
+        return;
+    };
+
+    let mesh = if effects_level.0.speed_ribbon() {
+        let count = companion.trail.len();
+        let points: Vec<RibbonPoint> = companion
+            .trail
+            .iter()
+            .enumerate()
+            .map(|(age, position)| {
+                let fade = 1.0 - age as f32 / count as f32;
+                RibbonPoint {
+                    position: *position,
+                    width: COMPANION_TRAIL_WIDTH * fade,
+                    color: COMPANION_COLOR.with_a(fade),
+                }
+            })
+            .collect();
+
+        let window = window.single();
+        ribbon_mesh(&points, window.width().min(window.height()) / 2.0)
+    } else {
+        None
+    };
+
+    match (trail_query.get_single_mut(), mesh) {
+        (Ok((handle, mut visibility)), Some(mesh)) => {
+            if let Some(existing) = meshes.get_mut(&handle.0) {
+                *existing = mesh;
+            }
+            if *visibility != Visibility::Inherited {
+                *visibility = Visibility::Inherited;
+            }
+        }
+        (Ok((_, mut visibility)), None) => {
+            if *visibility != Visibility::Hidden {
+                *visibility = Visibility::Hidden;
+            }
+        }
+        (Err(_), Some(mesh)) => {
+            commands.spawn((
+                CompanionTrail,
+                ColorMesh2dBundle {
+                    mesh: meshes.add(mesh).into(),
+                    material: asset_handles.speed_ribbon_material.clone(),
+                    transform: Transform::from_xyz(0.0, 0.0, render_z::TRAIL),
+                    ..default()
+                },
+            ));
+        }
+        (Err(_), None) => {}
+    }
+}
@@ -0,0 +1,620 @@
+use bevy::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{enemy::EnemyType, game::HitSource};
+
+pub const PLAYER_RADIUS: f32 = 16.0;
+pub const PLAYER_COLOR: Color = Color::BLUE;
+pub const PLAYER_HEALTH: i8 = 3;
+pub const PLAYER_INVINCIBILITY_TIME: f32 = 2.0;
+pub const PLAYER_PICKUP_INVINCIBILITY_TIME: f32 = 0.5;
+pub const PLAYER_SPAWN_INVINCIBILITY_TIME: f32 = 1.5;
+pub const PLAYER_ACCEL: f32 = 900.0;
+pub const PLAYER_MAX_SPEED: f32 = 300.0;
+// A dash starts at this speed and eases back to PLAYER_MAX_SPEED over PLAYER_DASH_TIME.
+pub const PLAYER_DASH_SPEED: f32 = PLAYER_MAX_SPEED * 3.0;
+pub const PLAYER_DASH_TIME: f32 = 0.25;
+// Counted from the start of the dash.
+pub const PLAYER_DASH_COOLDOWN: f32 = 2.0;
+pub const PLAYER_RESPONSIVE_ACCEL: f32 = 2500.0;
+pub const PLAYER_RESPONSIVE_DAMPING: f32 = 8.0;
+pub const PLAYER_SPAWN_EDGE_FRACTION: f32 = 0.35;
+pub const PLAYER_SPAWN_COIN_DISTANCE: f32 = 150.0;
+pub const PLAYER_SPAWN_TRIES: u32 = 16;
+pub const SPEED_RIBBON_LENGTH: usize = 20;
+pub const SPEED_RIBBON_SAMPLE_RATE: f32 = 30.0;
+pub const SPEED_RIBBON_WIDTH: f32 = 10.0;
+pub const SPEED_RIBBON_COLOR: Color = Color::CYAN;
+pub const SPEED_RIBBON_MIN_SPEED: f32 = 100.0;
+
+pub const POST_HIT_GRACE_TIME: f32 = 5.0;
+
+pub const HIT_KNOCKBACK: f32 = 700.0;
+pub const HIT_DECAY_RATE: f32 = -0.002;
+pub const HIT_TRAUMA: f32 = 70.0;
+pub const PURPLE_HIT_KNOCKBACK: f32 = 800.0;
+pub const PURPLE_HIT_TRAUMA: f32 = 85.0;
+// Projectiles sting rather than slam: less shake and a lighter shove than any body hit.
+pub const PROJECTILE_HIT_KNOCKBACK: f32 = 450.0;
+pub const PROJECTILE_HIT_TRAUMA: f32 = 40.0;
+pub const HIT_INDICATOR_COLOR: Color = Color::RED;
+pub const HIT_INDICATOR_TIME: f32 = 0.5;
+pub const HIT_INDICATOR_LENGTH: f32 = 20.0;
+pub const HIT_INDICATOR_THICKNESS: f32 = 4.0;
+pub const HIT_EDGE_THICKNESS: f32 = 8.0;
+
+pub const ENEMY_RADIUS: f32 = 14.0;
+pub const ENEMY_COLOR_RED: Color = Color::RED;
+pub const ENEMY_COLOR_PURPLE: Color = Color::PURPLE;
+pub const ENEMY_PURPLE_COIN_SPAWN: i32 = 16;
+pub const ENEMY_PURPLE_ACCEL_MUTLIPLIER: f32 = 0.75;
+pub const ENEMY_THREAT_COLOR: Color = Color::rgb(1.0, 0.9, 0.6);
+pub const ENEMY_THREAT_TINT: f32 = 0.6;
+pub const ENEMY_SILHOUETTE_COLOR: Color = Color::rgb(0.15, 0.15, 0.15);
+pub const WRAP_HINT_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.15);
+pub const WRAP_HINT_EDGE_DISTANCE: f32 = 80.0;
+pub const WRAP_HINT_MAX: usize = 6;
+
+pub const ENEMY_MIN_ACCEL: f32 = 300.0;
+pub const ENEMY_MAX_ACCEL: f32 = 600.0;
+pub const ENEMY_MIN_SPEED: f32 = 200.0;
+pub const ENEMY_MAX_SPEED: f32 = 500.0;
+pub const ENEMY_COIN_PULL: f32 = 0.1;
+pub const ENEMY_SPAWN_SIDE_MIN_WEIGHT: f32 = 0.1;
+pub const MAX_ENEMIES: usize = 40;
+pub const FLOCK_RADIUS: f32 = 120.0;
+pub const FLOCK_ALIGNMENT_WEIGHT: f32 = 0.6;
+pub const FLOCK_COHESION_WEIGHT: f32 = 0.4;
+pub const ENEMY_REMOVAL_TIME: f32 = 0.3;
+pub const ENEMY_REMOVAL_POP_VOLUME: f32 = 0.5;
+
+pub const LUNGE_MIN_POINTS: i32 = 25;
+pub const LUNGE_MIN_WAIT: f32 = 3.0;
+pub const LUNGE_MAX_WAIT: f32 = 5.0;
+pub const LUNGE_TELEGRAPH_TIME: f32 = 0.6;
+pub const LUNGE_TIME: f32 = 0.5;
+pub const LUNGE_ACCEL_MULTIPLIER: f32 = 2.5;
+pub const LUNGE_TELEGRAPH_SPEED_MULTIPLIER: f32 = 0.8;
+pub const LUNGE_OUTLINE_COLOR: Color = Color::WHITE;
+pub const LUNGE_OUTLINE_SCALE: f32 = 1.35;
+pub const LUNGE_FLASH_INTERVAL: f32 = 0.1;
+pub const LUNGE_WHOOSH_VOLUME: f32 = 0.6;
+
+pub const LEADER_MIN_POINTS: i32 = 35;
+pub const LEADER_CHANCE: f64 = 0.08;
+pub const LEADER_SPEED_SCALE: f32 = 0.8;
+pub const LEADER_BUFF_RADIUS: f32 = 180.0;
+pub const LEADER_SPEED_BUFF: f32 = 0.15;
+pub const LEADER_OUTLINE_COLOR: Color = Color::rgb(1.0, 0.8, 0.2);
+pub const LEADER_OUTLINE_SCALE: f32 = 1.3;
+pub const PACK_AURA_COLOR: Color = Color::rgba(1.0, 0.8, 0.2, 0.15);
+pub const PACK_AURA_SCALE: f32 = 1.8;
+
+pub const SPEED_GROWTH_RATE: f32 = 0.15;
+pub const SPEED_MIDPOINT: f32 = 20.0;
+pub const SPEED_MAX_DEVIATION: f32 = 50.0;
+pub const ACCEL_GROWTH_RATE: f32 = 0.16;
+pub const ACCEL_MIDPOINT: f32 = 20.0;
+pub const ACCEL_MAX_DEVIATION: f32 = 25.0;
+pub const ENEMY_STAT_FLOOR_SCALE: f32 = 0.9;
+pub const ENEMY_STAT_CEILING_SCALE: f32 = 1.1;
+
+pub const COIN_RADIUS: f32 = 8.0;
+pub const COIN_COLOR: Color = Color::YELLOW;
+pub const COIN_RING_COLOR: Color = Color::rgba(1.0, 1.0, 0.0, 0.2);
+pub const COIN_RING_SCALE_STEP: f32 = 0.6;
+pub const COIN_CHARGE_DELAY: f32 = 6.0;
+pub const COIN_CHARGE_INTERVAL: f32 = 3.0;
+pub const COIN_MAX_BONUS: i32 = 3;
+pub const COIN_TRAIL_LENGTH: usize = 5;
+pub const COIN_TRAIL_COLOR: Color = Color::GRAY;
+pub const ACCENT_MILESTONE: i32 = 10;
+pub const ACCENT_FADE_TIME: f32 = 1.0;
+pub const ACCENT_BACKGROUND_TINT: f32 = 0.08;
+pub const ACCENT_PALETTE: [Color; 6] = [
+    Color::YELLOW,
+    Color::ORANGE,
+    Color::PINK,
+    Color::CYAN,
+    Color::rgb(0.6, 0.6, 1.0),
+    Color::WHITE,
+];
+
+pub const HOME_ZONE_RADIUS: f32 = 40.0;
+pub const HOME_ZONE_COLOR: Color = Color::rgba(1.0, 1.0, 0.0, 0.12);
+pub const CARRIED_TEXT_OFFSET: f32 = 32.0;
+pub const HUD_CORNER_MARGIN: f32 = 60.0;
+pub const HUD_CORNER_SCORE_SCALE: f32 = 0.25;
+pub const HUD_MINIMAL_MARGIN: f32 = 40.0;
+pub const HUD_MINIMAL_SCORE_SCALE: f32 = 0.2;
+pub const HUD_PIE_SEGMENTS: usize = 32;
+pub const DASH_INDICATOR_RADIUS: f32 = 10.0;
+pub const DASH_READY_COLOR: Color = Color::rgba(0.3, 0.8, 1.0, 0.9);
+pub const DASH_COOLDOWN_COLOR: Color = Color::rgba(0.5, 0.5, 0.5, 0.6);
+pub const SAFE_AREA_MAX: f32 = 10.0;
+pub const SAFE_AREA_STEP: f32 = 1.0;
+pub const SAFE_AREA_OVERLAY_COLOR: Color = Color::rgba(1.0, 0.5, 0.0, 0.6);
+pub const SAFE_AREA_OVERLAY_THICKNESS: f32 = 3.0;
+pub const INPUT_DISPLAY_MARGIN: f32 = 24.0;
+pub const INPUT_DISPLAY_KEY_SIZE: f32 = 36.0;
+pub const INPUT_DISPLAY_KEY_GAP: f32 = 4.0;
+pub const INPUT_DISPLAY_FONT_SIZE: f32 = 16.0;
+pub const INPUT_DISPLAY_MIN_OPACITY: f32 = 0.2;
+pub const INPUT_DISPLAY_IDLE_COLOR: Color = Color::rgba(0.5, 0.5, 0.5, 0.5);
+pub const INPUT_DISPLAY_PRESSED_COLOR: Color = Color::WHITE;
+pub const COMPANION_UNLOCK_SCORE: i32 = 30;
+pub const COMPANION_ORBIT_RADIUS: f32 = 60.0;
+// Radians per second.
+pub const COMPANION_ORBIT_SPEED: f32 = 2.5;
+pub const COMPANION_COOLDOWN: f32 = 12.0;
+pub const COMPANION_REACH: f32 = 150.0;
+pub const COMPANION_DASH_SPEED: f32 = 900.0;
+pub const COMPANION_RADIUS: f32 = 8.0;
+pub const COMPANION_COLOR: Color = Color::rgb(0.6, 1.0, 0.8);
+pub const COMPANION_TRAIL_LENGTH: usize = 12;
+pub const COMPANION_TRAIL_WIDTH: f32 = 6.0;
+pub const POST_FX_VIGNETTE_COLOR: Color = Color::rgba(0.8, 0.0, 0.0, 0.7);
+pub const POST_FX_VIGNETTE_TIME: f32 = 0.4;
+// Peak vignette strength with "Reduce flashing" on.
+pub const POST_FX_REDUCED_VIGNETTE: f32 = 0.3;
+pub const POST_FX_DESATURATION_TIME: f32 = 0.8;
+pub const POST_FX_HIGHLIGHT_COLOR: Color = Color::rgba(1.0, 1.0, 0.4, 0.35);
+pub const POST_FX_HIGHLIGHT_SCALE: f32 = 2.5;
+pub const POST_FX_HIGHLIGHT_FADE_TIME: f32 = 0.3;
+// The overlay is a little larger than the window, so shake never uncovers an edge.
+pub const POST_FX_OVERSCAN: f32 = 1.2;
+pub const POST_FX_FALLBACK_GRAY: Color = Color::rgba(0.35, 0.35, 0.35, 0.6);
+// A flat tint has no clear center, so it stays fainter than the shader's vignette edge.
+pub const POST_FX_FALLBACK_VIGNETTE_ALPHA: f32 = 0.25;
+pub const DROPPED_COIN_LIFETIME: f32 = 8.0;
+pub const DROPPED_COIN_SCALE: f32 = 0.7;
+pub const DROPPED_COIN_MIN_SPREAD: f32 = 60.0;
+pub const DROPPED_COIN_MAX_SPREAD: f32 = 140.0;
+pub const DROPPED_COIN_BLINK_TIME: f32 = 2.0;
+pub const DROPPED_COIN_BLINK_INTERVAL: f32 = 0.15;
+
+pub const COIN_SPAWN_REROLLS: u32 = 8;
+pub const COIN_MIN_DISTANCE_FRACTION: f32 = 0.3;
+pub const COIN_PLAYER_CLEARANCE: f32 = 120.0;
+// Placement tries the reach requirement this many times before letting it go.
+pub const COIN_REACH_STRICT_REROLLS: u32 = 5;
+
+pub const COIN_ARROW_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.35);
+pub const COIN_ARROW_SIZE: f32 = 6.0;
+pub const COIN_ARROW_DISTANCE: f32 = 28.0;
+pub const COIN_ARROW_HIDE_DISTANCE: f32 = 200.0;
+pub const COIN_ARROW_TURN_RATE: f32 = 10.0;
+
+pub const HEALTH_COLOR: Color = Color::LIME_GREEN;
+pub const HEALTH_MULTIPLE: i32 = 8;
+pub const HEALTH_PIP_RADIUS: f32 = 8.0;
+pub const HEALTH_PIP_SPACING: f32 = 24.0;
+pub const HEALTH_PIP_EMPTY_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.2);
+
+pub const SCREEN_SHAKE_X_FREQUENCY: f32 = 10.0;
+pub const SCREEN_SHAKE_Y_FREQUENCY: f32 = 1.0;
+pub const SCREEN_SHAKE_LERP: f32 = 0.15;
+
+pub const IDLE_PAUSE_TIME: f32 = 10.0;
+pub const IDLE_DANGER_RADIUS: f32 = 250.0;
+
+pub const BANNER_TIME: f32 = 2.5;
+pub const BANNER_FADE_TIME: f32 = 0.5;
+
+pub const SFX_BUDGET_WINDOW: f32 = 0.25;
+pub const SFX_BUDGET_MAX: u32 = 4;
+pub const AUDIO_RECOVERY_DEBOUNCE: f32 = 2.0;
+pub const SFX_UNPLAYED_TIMEOUT: f32 = 5.0;
+pub const AUDIO_PROBE_TIMEOUT: f32 = 1.0;
+pub const SOAK_WINDOW: usize = 10;
+pub const SOAK_SETTLE_FRAMES: u32 = 2;
+pub const DETERMINISTIC_QUANTUM: f32 = 1.0 / 256.0;
+
+pub const MUSIC_LAYER_MILESTONES: [i32; 3] = [25, 50, 75];
+pub const MUSIC_LAYER_VOLUME: f32 = 0.6;
+
+pub const FLOATING_TEXT_TIME: f32 = 0.8;
+pub const FLOATING_TEXT_RISE_SPEED: f32 = 60.0;
+
+pub const TIP_RUNS: u32 = 3;
+
+// Where the first step's spot is, as a fraction of the half extent.
+pub const TUTORIAL_TARGET: Vec2 = Vec2::new(0.5, 0.4);
+pub const TUTORIAL_COINS: i32 = 3;
+pub const TUTORIAL_SURVIVE_TIME: f32 = 8.0;
+pub const TUTORIAL_FREE_PLAY_TIME: f32 = 15.0;
+pub const TUTORIAL_ENEMY_SCALE: f32 = 0.5;
+
+pub const VICTORY_POINTS: i32 = 100;
+pub const VICTORY_SEQUENCE_TIME: f32 = 2.0;
+
+pub const PATTERN_EVERY: i32 = 15;
+pub const PATTERN_RING_COUNT: usize = 6;
+pub const PATTERN_RING_RADIUS: f32 = 90.0;
+pub const PATTERN_LINE_COUNT: usize = 5;
+pub const PATTERN_COIN_SCALE: f32 = 0.7;
+pub const PATTERN_LIFETIME: f32 = 20.0;
+pub const PATTERN_MIN_PLAYER_DISTANCE: f32 = 150.0;
+pub const PATTERN_PLACEMENT_TRIES: u32 = 8;
+
+pub const PICKUP_SPAWN_EVERY: i32 = 25;
+// Spawned pickups only; the coin doesn't count.
+pub const PICKUP_MAX_ON_FIELD: usize = 3;
+pub const PICKUP_MIN_PLAYER_DISTANCE: f32 = 200.0;
+pub const PICKUP_PLACEMENT_TRIES: u32 = 8;
+
+pub const SHRINK_PICKUP_SCALE: f32 = 0.8;
+pub const SHRINK_PICKUP_COLOR: Color = Color::WHITE;
+pub const SHRINK_PICKUP_LIFETIME: f32 = 10.0;
+pub const SHRINK_PICKUP_COOLDOWN: f32 = 15.0;
+pub const SHRINK_PICKUP_PITCH: f32 = 0.7;
+pub const SHRINK_SCALE: f32 = 0.6;
+pub const SHRINK_TIME: f32 = 8.0;
+pub const SHRINK_WARNING_TIME: f32 = 2.0;
+pub const SHRINK_FLASH_INTERVAL: f32 = 0.15;
+
+pub const SPECTATE_TIME: f32 = 5.0;
+pub const SPECTATE_PAN_SPEED: f32 = 500.0;
+
+pub const CODEX_ROW_SPACING: f32 = 80.0;
+
+pub const RECORDS_PER_MODE: usize = 10;
+pub const RECORDS_ROW_SPACING: f32 = 36.0;
+
+pub const PACING_BUCKET_SECONDS: f32 = 10.0;
+pub const PACING_MAX_BARS: usize = 30;
+pub const PACING_GRAPH_WIDTH: f32 = 300.0;
+pub const PACING_GRAPH_HEIGHT: f32 = 60.0;
+pub const PACING_GRAPH_Y: f32 = 90.0;
+pub const PACING_BAR_GAP: f32 = 2.0;
+
+// Seconds of music per envelope level, for the bar under the last score.
+#[cfg(feature = "music-envelope")]
+pub const MUSIC_ENVELOPE_WINDOW: f32 = 0.05;
+#[cfg(feature = "music-envelope")]
+pub const MUSIC_ENVELOPE_BAR_WIDTH: f32 = 240.0;
+#[cfg(feature = "music-envelope")]
+pub const MUSIC_ENVELOPE_BAR_HEIGHT: f32 = 4.0;
+#[cfg(feature = "music-envelope")]
+pub const MUSIC_ENVELOPE_BAR_Y: f32 = -66.0;
+
+pub const THUMBNAIL_WIDTH: u32 = 240;
+pub const THUMBNAIL_POSITION: Vec2 = Vec2::new(480.0, 160.0);
+pub const THUMBNAIL_PLACEHOLDER_SIZE: Vec2 = Vec2::new(240.0, 135.0);
+
+pub const MAX_FRAME_DELTA: f32 = 0.05;
+// How often a minimized window still wakes the app when no window event does.
+pub const SUSPENDED_WAKE_INTERVAL: f32 = 1.0;
+
+pub const COIN_VALUE_FONT_SIZE: f32 = 20.0;
+pub const COIN_VALUE_OFFSET: Vec2 = Vec2::new(COIN_RADIUS + 8.0, COIN_RADIUS + 8.0);
+
+pub const COIN_BEACON_MIN_DISTANCE: f32 = 300.0;
+pub const COIN_BEACON_TIME: f32 = 0.5;
+pub const COIN_BEACON_SCALE: f32 = 4.0;
+pub const COIN_BEACON_COLOR: Color = Color::rgb(1.0, 0.9, 0.5);
+pub const COIN_BEACON_VOLUME: f32 = 0.3;
+pub const COIN_BEACON_PITCH: f32 = 1.5;
+pub const COIN_CONTEST_TIME: f32 = 1.5;
+pub const COIN_CONTEST_RAMP_TIME: f32 = 0.5;
+pub const SPATIAL_AUDIO_SCALE: f32 = 1.0 / 1000.0;
+pub const SPATIAL_EAR_GAP: f32 = 600.0;
+
+pub const REPRO_DURATION: f32 = 30.0;
+
+pub const BENCH_TICKS: u32 = 1800;
+pub const BENCH_ENEMY_COUNT: usize = 300;
+pub const BENCH_ENEMY_POINTS: i32 = 40;
+pub const BENCH_SEED: u64 = 0x0062_656e_6368;
+pub const BENCH_CIRCLE_PERIOD: f32 = 4.0;
+
+pub const HAPTICS_HIT_DURATION: f32 = 0.25;
+pub const HAPTICS_HIT_MIN_STRENGTH: f32 = 0.6;
+pub const HAPTICS_COIN_DURATION: f32 = 0.08;
+pub const HAPTICS_COIN_STRENGTH: f32 = 0.2;
+pub const HAPTICS_HEALTH_DURATION: f32 = 0.15;
+pub const HAPTICS_HEALTH_STRENGTH: f32 = 0.45;
+
+pub const MERCY_DELAY: f32 = 20.0;
+pub const MERCY_RAMP_TIME: f32 = 10.0;
+pub const MERCY_ACCEL_REDUCTION: f32 = 0.2;
+
+pub const CORNERED_CORNER_SCALE: f32 = 1.5;
+pub const CORNERED_ENEMY_RADIUS: f32 = 150.0;
+pub const CORNERED_RELEASE_CORNER_SCALE: f32 = 2.5;
+pub const CORNERED_RELEASE_ENEMY_RADIUS: f32 = 190.0;
+pub const CORNERED_MIN_ENEMIES: usize = 3;
+pub const CORNERED_SPAWN_DELAY: f32 = 3.0;
+
+pub const RUN_STAMP_FONT_SIZE: f32 = 20.0;
+pub const RUN_STAMP_MARGIN: f32 = 40.0;
+pub const EXPORT_GHOST_KEY: KeyCode = KeyCode::E;
+pub const IMPORT_GHOST_KEY: KeyCode = KeyCode::I;
+pub const GHOST_SAMPLE_INTERVAL: f32 = 0.1;
+pub const GHOST_COLOR: Color = Color::rgba(0.6, 0.6, 1.0, 0.35);
+pub const GHOST_RACE_FONT_SIZE: f32 = 24.0;
+pub const GHOST_RACE_MARGIN: f32 = 70.0;
+pub const COPY_RUN_KEY: KeyCode = KeyCode::C;
+pub const PASTE_RUN_KEY: KeyCode = KeyCode::V;
+
+// Dumps the live playfield to a JSON snapshot.
+#[cfg(feature = "devtools")]
+pub const INSPECT_KEY: KeyCode = KeyCode::F2;
+
+#[cfg(feature = "online")]
+pub const ONLINE_PORT: u16 = 7777;
+// Peer state broadcasts, 10 a second.
+#[cfg(feature = "online")]
+pub const ONLINE_STATE_INTERVAL: f32 = 0.1;
+// A peer silent for this long is treated as gone.
+#[cfg(feature = "online")]
+pub const ONLINE_TIMEOUT: f32 = 5.0;
+#[cfg(feature = "online")]
+pub const ONLINE_POLL_INTERVAL: f32 = 0.05;
+
+pub const PREVIEW_POINTS: [i32; 3] = [0, 20, 40];
+pub const PREVIEW_ORIGIN: Vec2 = Vec2::new(420.0, -80.0);
+pub const PREVIEW_SPACING: f32 = 70.0;
+pub const PREVIEW_BAR_WIDTH: f32 = 14.0;
+pub const PREVIEW_BAR_HEIGHT: f32 = 80.0;
+
+pub const MENU_DECOR_ALPHA: f32 = 0.3;
+
+pub const ASSET_FOLDER: &str = "assets";
+pub const ASSET_PACK_LABEL_MARGIN: f32 = 16.0;
+pub const NIGHT_CLEAR_COLOR: Color = Color::rgb(0.08, 0.09, 0.16);
+pub const NIGHT_DECOR_COLOR: Color = Color::rgb(0.5, 0.6, 0.9);
+pub const NIGHT_VIGNETTE_COLOR: Color = Color::rgb(0.02, 0.02, 0.06);
+pub const NIGHT_VIGNETTE_LAYER_ALPHA: f32 = 0.25;
+pub const NIGHT_VIGNETTE_RADII: [f32; 3] = [220.0, 280.0, 360.0];
+pub const NIGHT_VIGNETTE_OUTER_RADIUS: f32 = 2400.0;
+pub const NIGHT_VIGNETTE_SEGMENTS: u32 = 64;
+pub const INFERNO_CLEAR_COLOR: Color = Color::rgb(0.25, 0.08, 0.04);
+pub const INFERNO_DECOR_COLOR: Color = Color::rgb(1.0, 0.45, 0.1);
+pub const INFERNO_ENEMY_SPEED_SCALE: f32 = 1.1;
+pub const MENU_DECOR_PACE: f32 = 0.5;
+pub const MENU_DECOR_TARGET_SPEED: f32 = 0.25;
+
+pub const QUALITY_WINDOW: f32 = 2.0;
+pub const QUALITY_STEP_DOWN_FRAME_TIME: f32 = 0.020;
+pub const QUALITY_STEP_UP_FRAME_TIME: f32 = 0.012;
+pub const QUALITY_MAX_FRAME_TIME: f32 = 0.25;
+
+pub const MENU_LIST_FONT_SIZE: f32 = 28.0;
+pub const MENU_LIST_SPACING: f32 = 30.0;
+pub const MENU_LIST_FOCUS_SCALE: f32 = 1.15;
+pub const WHATS_NEW_VISIBLE_LINES: usize = 12;
+
+// Physical keys each action can be bound to.
+pub const BINDING_SLOTS: usize = 2;
+// Left stick tilt below the dead zone reads as centered; above it, speed scales up from zero.
+pub const GAMEPAD_STICK_DEADZONE: f32 = 0.2;
+pub const GAMEPAD_STICK_DEADZONE_MAX: f32 = 0.5;
+pub const GAMEPAD_STICK_DEADZONE_STEP: f32 = 0.05;
+// Tilt past the dead zone is raised to this power; above 1 gives finer control near the center.
+pub const GAMEPAD_STICK_CURVE: f32 = 1.0;
+pub const GAMEPAD_STICK_CURVE_MIN: f32 = 0.5;
+pub const GAMEPAD_STICK_CURVE_MAX: f32 = 3.0;
+pub const GAMEPAD_STICK_CURVE_STEP: f32 = 0.25;
+
+pub const MUTATOR_ROW_SPACING: f32 = 42.0;
+pub const MUTATOR_TINY_PLAYER_RADIUS: f32 = 10.0;
+pub const MUTATOR_GIANT_COIN_SCALE: f32 = 2.0;
+pub const MUTATOR_SLIPPERY_ACCEL_SCALE: f32 = 0.4;
+pub const SCORE_DECAY_INTERVAL: f32 = 8.0;
+// The score never decays below the highest multiple of this it has reached.
+pub const SCORE_DECAY_FLOOR_STEP: i32 = 10;
+pub const SCORE_DECAY_PIE_RADIUS: f32 = 14.0;
+pub const SCORE_DECAY_COLOR: Color = Color::rgba(1.0, 0.4, 0.3, 0.8);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct HitFeedback {
+    pub trauma: f32,
+    pub knockback: f32,
+    pub sound_speed: f32,
+}
+
+#[derive(Resource, Clone, Serialize, Deserialize)]
+pub struct GameConfig {
+    pub red_hit: HitFeedback,
+    pub purple_hit: HitFeedback,
+    // Repro files from before projectiles existed don't carry it.
+    #[serde(default = "default_projectile_hit")]
+    pub projectile_hit: HitFeedback,
+    pub player_radius: f32,
+    pub player_accel: f32,
+    pub coin_radius: f32,
+    pub enemy_speed_multiplier: f32,
+    pub flock_alignment_weight: f32,
+    pub flock_cohesion_weight: f32,
+}
+
+impl GameConfig {
+    pub fn hit_feedback(&self, source: HitSource) -> HitFeedback {
+        match source {
+            HitSource::Enemy(EnemyType::Red) => self.red_hit,
+            HitSource::Enemy(EnemyType::Purple) => self.purple_hit,
+            HitSource::Projectile => self.projectile_hit,
+        }
+    }
+}
+
+fn default_projectile_hit() -> HitFeedback {
+    HitFeedback {
+        trauma: PROJECTILE_HIT_TRAUMA,
+        knockback: PROJECTILE_HIT_KNOCKBACK,
+        sound_speed: 1.0,
+    }
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            red_hit: HitFeedback {
+                trauma: HIT_TRAUMA,
+                knockback: HIT_KNOCKBACK,
+                sound_speed: 1.0,
+            },
+            purple_hit: HitFeedback {
+                trauma: PURPLE_HIT_TRAUMA,
+                knockback: PURPLE_HIT_KNOCKBACK,
+                sound_speed: 1.0,
+            },
+            projectile_hit: default_projectile_hit(),
+            player_radius: PLAYER_RADIUS,
+            player_accel: PLAYER_ACCEL,
+            coin_radius: COIN_RADIUS,
+            enemy_speed_multiplier: 1.0,
+            flock_alignment_weight: FLOCK_ALIGNMENT_WEIGHT,
+            flock_cohesion_weight: FLOCK_COHESION_WEIGHT,
+        }
+    }
+}
+
+/// The [`GameConfig`] in effect for the current run, after mutators have been applied.
+#[derive(Resource, Deref)]
+pub struct RunConfig(pub GameConfig);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+}
+
+// Where the player starts a run.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum PlayerSpawn {
+    #[default]
+    Center,
+    // Away from the edges and the first coin; see random_safe_spawn_position.
+    RandomSafe,
+    // From the center, kept inside the playfield.
+    Offset(Vec2),
+}
+
+impl PlayerSpawn {
+    // The position when it doesn't depend on the first coin, so the coin can avoid it.
+    pub fn fixed_position(self, half_extent: Vec2) -> Option<Vec2> {
+        match self {
+            PlayerSpawn::Center => Some(Vec2::ZERO),
+            PlayerSpawn::RandomSafe => None,
+            PlayerSpawn::Offset(offset) => Some(offset.clamp(-half_extent, half_extent)),
+        }
+    }
+}
+
+// How far from the player a new coin has to land, as a fraction of the window diagonal. Ramps
+// linearly from `start` at 0 points to `end` at `end_points` and stays there.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoinReachCurve {
+    pub start: f32,
+    pub end: f32,
+    pub end_points: i32,
+}
+
+impl CoinReachCurve {
+    pub fn for_difficulty(difficulty: Difficulty) -> Self {
+        let end = match difficulty {
+            Difficulty::Easy => 0.3,
+            Difficulty::Normal | Difficulty::Hard => 0.45,
+        };
+        Self {
+            start: 0.1,
+            end,
+            end_points: 40,
+        }
+    }
+
+    pub fn fraction(&self, points: i32) -> f32 {
+        let t = (points as f32 / self.end_points.max(1) as f32).clamp(0.0, 1.0);
+        self.start + (self.end - self.start) * t
+    }
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct GameRules {
+    pub post_hit_grace: bool,
+    pub invincibility_frames: bool,
+    pub health_coins: bool,
+    pub score_multiplier: f32,
+    pub coin_spawn_rerolls: u32,
+    pub coin_min_distance_fraction: f32,
+    pub coin_reach: CoinReachCurve,
+    pub flocking: bool,
+    pub spawn_side_bag: bool,
+    pub victory: bool,
+    pub start_grace: bool,
+    pub cornered_relief: bool,
+    pub mercy: bool,
+    // The score drains without pickups; see decay.rs.
+    pub score_decay: bool,
+    // How long enemies ignore a coin that just moved; 0 turns it off.
+    pub coin_contest_time: f32,
+    pub player_spawn: PlayerSpawn,
+    // Percent inset for coin placement, from Settings::safe_area. Wraparound ignores it.
+    pub safe_area: f32,
+    // Seeded runs with Settings::deterministic on; see quantize_motion.
+    pub deterministic: bool,
+}
+
+impl GameRules {
+    pub fn for_difficulty(difficulty: Difficulty) -> Self {
+        Self {
+            post_hit_grace: difficulty != Difficulty::Hard,
+            invincibility_frames: true,
+            health_coins: true,
+            score_multiplier: 1.0,
+            coin_spawn_rerolls: COIN_SPAWN_REROLLS,
+            coin_min_distance_fraction: COIN_MIN_DISTANCE_FRACTION,
+            coin_reach: CoinReachCurve::for_difficulty(difficulty),
+            flocking: false,
+            spawn_side_bag: true,
+            victory: false,
+            start_grace: true,
+            cornered_relief: difficulty != Difficulty::Hard,
+            mercy: difficulty != Difficulty::Hard,
+            score_decay: false,
+            coin_contest_time: if difficulty == Difficulty::Hard {
+                0.0
+            } else {
+                COIN_CONTEST_TIME
+            },
+            player_spawn: PlayerSpawn::default(),
+            safe_area: 0.0,
+            deterministic: false,
+        }
+    }
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        Self::for_difficulty(Difficulty::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hard_turns_off_the_cornered_relief() {
+        assert!(!GameRules::for_difficulty(Difficulty::Hard).cornered_relief);
+        assert!(GameRules::for_difficulty(Difficulty::Normal).cornered_relief);
+        assert!(GameRules::for_difficulty(Difficulty::Easy).cornered_relief);
+    }
+}
@@ -0,0 +1,219 @@
+use bevy::prelude::*;
+
+use crate::{
+    assets::AssetHandles,
+    config::*,
+    effects::text_label,
+    menu::cleanup_menu,
+    menu_list::{spawn_menu_list, EntryKind, MenuEntry, MenuFocus, MenuList, MenuListEvent},
+    player::{Action, InputBindings},
+    render_z, AppState,
+};
+
+const CONTROLS_LIST: &str = "controls";
+
+// MenuEntry ids have to be static, so every action/slot pair gets one here, in Action::ALL order.
+const SLOT_IDS: [[&str; BINDING_SLOTS]; Action::ALL.len()] = [
+    ["up_1", "up_2"],
+    ["down_1", "down_2"],
+    ["left_1", "left_2"],
+    ["right_1", "right_2"],
+    ["start_1", "start_2"],
+    ["pause_1", "pause_2"],
+    ["restart_1", "restart_2"],
+    ["mute_1", "mute_2"],
+    ["dash_1", "dash_2"],
+];
+
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Capture>()
+            .add_systems(OnEnter(AppState::Controls), setup_controls)
+            .add_systems(
+                Update,
+                (capture_binding, handle_controls)
+                    .chain()
+                    .run_if(in_state(AppState::Controls)),
+            )
+            .add_systems(OnExit(AppState::Controls), cleanup_menu);
+    }
+}
+
+// The action and slot waiting for a key press, if any.
+#[derive(Resource, Default)]
+struct Capture(Option<(Action, usize)>);
+
+#[derive(Component)]
+struct ControlsPrompt;
+
+fn setup_controls(
+    mut commands: Commands,
+    asset_handles: Res<AssetHandles>,
+    bindings: Res<InputBindings>,
+    focus: Res<MenuFocus>,
+) {
+    commands.insert_resource(Capture::default());
+
+    commands.spawn(text_label(
+        &asset_handles,
+        "Controls",
+        60.0,
+        Color::DARK_GRAY,
+        Vec3::new(0.0, 320.0, render_z::BACKDROP),
+    ));
+
+    spawn_controls_list(&mut commands, &asset_handles, &bindings, &focus);
+
+    commands.spawn((
+        ControlsPrompt,
+        text_label(
+            &asset_handles,
+            idle_prompt(&bindings),
+            28.0,
+            Color::DARK_GRAY,
+            Vec3::new(0.0, -330.0, render_z::BACKDROP),
+        ),
+    ));
+}
+
+fn spawn_controls_list(
+    commands: &mut Commands,
+    asset_handles: &AssetHandles,
+    bindings: &InputBindings,
+    focus: &MenuFocus,
+) {
+    let mut entries = Vec::new();
+    for (action, ids) in Action::ALL.iter().zip(SLOT_IDS) {
+        let slots = bindings.slots(*action);
+        for (slot, id) in ids.into_iter().enumerate() {
+            let key = slots[slot].map_or("-".to_string(), |key| format!("{:?}", key));
+            entries.push(MenuEntry::new(
+                id,
+                format!("{} {}: {}", action.name(), slot + 1, key),
+                EntryKind::Action,
+            ));
+        }
+    }
+    entries.push(MenuEntry::new(
+        "reset",
+        "Reset to defaults",
+        EntryKind::Action,
+    ));
+
+    spawn_menu_list(
+        commands,
+        asset_handles,
+        focus,
+        CONTROLS_LIST,
+        entries,
+        Vec3::new(0.0, 260.0, render_z::BACKDROP),
+    );
+}
+
+fn idle_prompt(bindings: &InputBindings) -> String {
+    match bindings.conflicts().first() {
+        Some((first, second)) => format!("{} and {} share a key", first, second),
+        None => format!(
+            "Enter to rebind, {} to return",
+            bindings.label(Action::Pause)
+        ),
+    }
+}
+
+// Runs before handle_controls, so the Enter press that starts a capture isn't captured itself.
+fn capture_binding(
+    mut commands: Commands,
+    asset_handles: Res<AssetHandles>,
+    input: Res<Input<KeyCode>>,
+    focus: Res<MenuFocus>,
+    mut capture: ResMut<Capture>,
+    mut bindings: ResMut<InputBindings>,
+    mut prompt: Query<&mut Text, With<ControlsPrompt>>,
+) {
+    let Some((action, slot)) = capture.0 else {
+        return;
+    };
+    let Some(key) = input.get_just_pressed().next().copied() else {
+        return;
+    };
+
+    let message = match key {
+        KeyCode::Escape => idle_prompt(&bindings),
+        KeyCode::Back | KeyCode::Delete => {
+            let _ = bindings.bind(action, slot, None);
+            idle_prompt(&bindings)
+        }
+        key => match bindings.bind(action, slot, Some(key)) {
+            Ok(Some(other)) => format!("Swapped keys with {}", other.name()),
+            Ok(None) => idle_prompt(&bindings),
+            Err(error) => {
+                prompt.single_mut().sections[0].value =
+                    format!("{}, press another key for {}", error, action.name());
+                return;
+            }
+        },
+    };
+
+    capture.0 = None;
+    prompt.single_mut().sections[0].value = message;
+    spawn_controls_list(&mut commands, &asset_handles, &bindings, &focus);
+}
+
+fn handle_controls(
+    mut commands: Commands,
+    asset_handles: Res<AssetHandles>,
+    focus: Res<MenuFocus>,
+    mut events: EventReader<MenuListEvent>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut capture: ResMut<Capture>,
+    mut bindings: ResMut<InputBindings>,
+    lists: Query<Entity, With<MenuList>>,
+    mut prompt: Query<&mut Text, With<ControlsPrompt>>,
+) {
+    for event in events.read() {
+        match event {
+            MenuListEvent::Back {
+                list: CONTROLS_LIST,
+            } => next_state.set(AppState::Settings),
+            MenuListEvent::Activated {
+                list: CONTROLS_LIST,
+                entry: "reset",
+            } => {
+                *bindings = InputBindings::default();
+                prompt.single_mut().sections[0].value = idle_prompt(&bindings);
+                lists.iter().for_each(|entity| {
+                    commands.entity(entity).despawn_recursive();
+                });
+                spawn_controls_list(&mut commands, &asset_handles, &bindings, &focus);
+            }
+            MenuListEvent::Activated {
+                list: CONTROLS_LIST,
+                entry,
+            } => {
+                let Some((action, slot)) =
+                    Action::ALL.iter().zip(SLOT_IDS).find_map(|(action, ids)| {
+                        ids.iter()
+                            .position(|id| id == entry)
+                            .map(|slot| (*action, slot))
+                    })
+                else {
+                    continue;
+                };
+
+                // The list goes away while waiting, so the captured key can't also navigate it.
+                lists.iter().for_each(|entity| {
+                    commands.entity(entity).despawn_recursive();
+                });
+                capture.0 = Some((action, slot));
+                prompt.single_mut().sections[0].value = format!(
+                    "Press a key for {} {}, Backspace to clear, Escape to cancel",
+                    action.name(),
+                    slot + 1
+                );
+            }
+            _ => {}
+        }
+    }
+}
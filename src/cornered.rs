@@ -0,0 +1,149 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{config::*, enemy::Enemy, player::Player};
+
+// Set while the player is pinned in a corner with a crowd on them. New enemies hold back and the
+// next coin lands on the other half of the screen, to pull the action out of the corner.
+#[derive(Resource, Default)]
+pub struct Cornered {
+    pub active: bool,
+}
+
+// Enemy spawns held back while the player was cornered, with the points they were earned at.
+#[derive(Resource, Default)]
+pub struct DelayedSpawns(pub Vec<(Timer, i32)>);
+
+impl DelayedSpawns {
+    pub fn push(&mut self, points: i32) {
+        self.0.push((
+            Timer::from_seconds(CORNERED_SPAWN_DELAY, TimerMode::Once),
+            points,
+        ));
+    }
+}
+
+// Distance to the nearest playfield corner, from anywhere on (or past) the playfield.
+pub fn corner_distance(position: Vec2, playfield: Vec2) -> f32 {
+    (playfield / 2.0 - position.abs()).length()
+}
+
+// Once cornered, the player has to get clearly away before the flag drops again, so standing on
+// the edge of either threshold doesn't make it flicker.
+pub fn is_cornered(
+    player: Vec2,
+    enemies: impl IntoIterator<Item = Vec2>,
+    playfield: Vec2,
+    currently: bool,
+) -> bool {
+    let (corner_scale, enemy_radius) = if currently {
+        (CORNERED_RELEASE_CORNER_SCALE, CORNERED_RELEASE_ENEMY_RADIUS)
+    } else {
+        (CORNERED_CORNER_SCALE, CORNERED_ENEMY_RADIUS)
+    };
+
+    corner_distance(player, playfield) <= PLAYER_RADIUS * corner_scale
+        && enemies
+            .into_iter()
+            .filter(|enemy| enemy.distance(player) <= enemy_radius)
+            .count()
+            >= CORNERED_MIN_ENEMIES
+}
+
+pub fn detect_cornered(
+    mut cornered: ResMut<Cornered>,
+    game_rules: Res<GameRules>,
+    player_query: Query<&Transform, (With<Player>, Without<Enemy>)>,
+    enemy_query: Query<&Transform, (With<Enemy>, Without<Player>)>,
+    window: Query<&Window, With<PrimaryWindow>>,
+) {
+    let (Ok(player_transform), Ok(window)) = (player_query.get_single(), window.get_single())
+    else {
+        return;
+    };
+
+    let active = game_rules.cornered_relief
+        && is_cornered(
+            player_transform.translation.truncate(),
+            enemy_query
+                .iter()
+                .map(|transform| transform.translation.truncate()),
+            Vec2::new(window.width(), window.height()),
+            cornered.active,
+        );
+
+    if cornered.active != active {
+        cornered.active = active;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAYFIELD: Vec2 = Vec2::new(800.0, 600.0);
+
+    // Three enemies spread around the player, each `distance` away.
+    fn crowd(player: Vec2, distance: f32) -> Vec<Vec2> {
+        [Vec2::NEG_X, Vec2::NEG_Y, Vec2::new(-1.0, -1.0).normalize()]
+            .into_iter()
+            .map(|direction| player + direction * distance)
+            .collect()
+    }
+
+    #[test]
+    fn corner_distance_is_the_same_in_every_corner() {
+        for sign in [
+            Vec2::ONE,
+            Vec2::NEG_ONE,
+            Vec2::new(1.0, -1.0),
+            Vec2::new(-1.0, 1.0),
+        ] {
+            let distance = corner_distance(Vec2::new(390.0, 290.0) * sign, PLAYFIELD);
+
+            assert!((distance - 200f32.sqrt()).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn crowded_corner_is_cornered() {
+        let player = Vec2::new(390.0, 290.0);
+
+        assert!(is_cornered(player, crowd(player, 100.0), PLAYFIELD, false));
+    }
+
+    #[test]
+    fn needs_enough_enemies_close_enough() {
+        let player = Vec2::new(390.0, 290.0);
+        let mut two = crowd(player, 100.0);
+        two.pop();
+
+        assert!(!is_cornered(player, two, PLAYFIELD, false));
+        assert!(!is_cornered(player, crowd(player, 160.0), PLAYFIELD, false));
+    }
+
+    #[test]
+    fn crowd_away_from_a_corner_is_not_cornered() {
+        for player in [Vec2::ZERO, Vec2::new(390.0, 0.0), Vec2::new(0.0, 290.0)] {
+            assert!(!is_cornered(player, crowd(player, 50.0), PLAYFIELD, false));
+        }
+    }
+
+    #[test]
+    fn release_needs_a_clear_escape() {
+        // Past the entry thresholds but inside the release ones.
+        let player = PLAYFIELD / 2.0 - Vec2::splat(PLAYER_RADIUS * 2.0 / 2f32.sqrt());
+        let enemies = crowd(player, 170.0);
+
+        assert!(!is_cornered(player, enemies.clone(), PLAYFIELD, false));
+        assert!(is_cornered(player, enemies, PLAYFIELD, true));
+
+        let escaped = PLAYFIELD / 2.0 - Vec2::splat(PLAYER_RADIUS * 3.0 / 2f32.sqrt());
+        assert!(!is_cornered(
+            escaped,
+            crowd(escaped, 100.0),
+            PLAYFIELD,
+            true
+        ));
+        assert!(!is_cornered(player, crowd(player, 200.0), PLAYFIELD, true));
+    }
+}
@@ -0,0 +1,268 @@
+use bevy::{prelude::*, sprite::Mesh2dHandle};
+
+use crate::{
+    assets::AssetHandles,
+    config::*,
+    game::{run_in_progress, GameInfo},
+    game_time::GameTime,
+    hud::{HudElement, HudKey},
+    pause::PauseState,
+    score::{apply_score_events, PenaltyReason, ScoreEvent},
+    util::pie_mesh,
+    window::not_suspended,
+    AppState,
+};
+
+pub struct DecayPlugin;
+
+impl Plugin for DecayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Game), setup_score_decay)
+            .add_systems(
+                Update,
+                decay_score
+                    .before(apply_score_events)
+                    .run_if(score_decay_active)
+                    .run_if(run_in_progress)
+                    .run_if(in_state(AppState::Game))
+                    .run_if(in_state(PauseState::Running))
+                    .run_if(not_suspended),
+            )
+            .add_systems(
+                Update,
+                update_decay_pie
+                    .after(apply_score_events)
+                    .run_if(in_state(AppState::Game)),
+            );
+    }
+}
+
+fn score_decay_active(game_rules: Option<Res<GameRules>>) -> bool {
+    game_rules.is_some_and(|game_rules| game_rules.score_decay)
+}
+
+// Only the score drains. Points, which health coins and spawns follow, only ever go up, so
+// decaying back across a multiple can't grant health twice.
+#[derive(Resource, Default)]
+pub struct ScoreDecay {
+    since_pickup: f32,
+    floor: i32,
+    // Points as last seen; any rise means a pickup landed.
+    points: i32,
+}
+
+impl ScoreDecay {
+    // The highest multiple of SCORE_DECAY_FLOOR_STEP the score has reached.
+    pub fn floor(&self) -> i32 {
+        self.floor
+    }
+
+    fn raise_floor(&mut self, score: i32) {
+        self.floor = self
+            .floor
+            .max(score - score.rem_euclid(SCORE_DECAY_FLOOR_STEP));
+    }
+
+    // How much of the current interval is left, from 1 right after a pickup down to 0.
+    fn remaining(&self) -> f32 {
+        1.0 - (self.since_pickup / SCORE_DECAY_INTERVAL).clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Component)]
+struct DecayPie;
+
+fn setup_score_decay(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    asset_handles: Res<AssetHandles>,
+) {
+    commands.insert_resource(ScoreDecay::default());
+    commands.spawn((
+        DecayPie,
+        HudElement::new(HudKey::Decay),
+        ColorMesh2dBundle {
+            mesh: meshes.add(pie_mesh(1.0, SCORE_DECAY_PIE_RADIUS)).into(),
+            material: asset_handles.score_decay_material.clone(),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+    ));
+}
+
+// One point per SCORE_DECAY_INTERVAL without a pickup, never below the floor. At the floor the
+// timer waits full, so the next point above it still gets a whole interval.
+fn decay_score(
+    mut decay: ResMut<ScoreDecay>,
+    mut score_events: EventWriter<ScoreEvent>,
+    game_info: Res<GameInfo>,
+    game_time: Res<GameTime>,
+) {
+    decay.raise_floor(game_info.score());
+    if game_info.points() != decay.points {
+        decay.points = game_info.points();
+        decay.since_pickup = 0.0;
+        return;
+    }
+
+    decay.since_pickup += game_time.delta_seconds();
+    if decay.since_pickup < SCORE_DECAY_INTERVAL {
+        return;
+    }
+
+    if game_info.score() > decay.floor {
+        decay.since_pickup -= SCORE_DECAY_INTERVAL;
+        score_events.send(ScoreEvent::Penalty {
+            reason: PenaltyReason::Decayed,
+            value: 1,
+        });
+    } else {
+        decay.since_pickup = SCORE_DECAY_INTERVAL;
+    }
+}
+
+// Hidden unless the mutator is on and there is something above the floor to lose.
+fn update_decay_pie(
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut pie_query: Query<(&HudElement, &Mesh2dHandle, &mut Visibility), With<DecayPie>>,
+    decay: Option<Res<ScoreDecay>>,
+    game_info: Res<GameInfo>,
+    game_rules: Option<Res<GameRules>>,
+) {
+    let (Some(decay), Ok((element, handle, mut visibility))) = (decay, pie_query.get_single_mut())
+    else {
+        return;
+    };
+
+    let shown = element.visible
+        && game_rules.is_some_and(|game_rules| game_rules.score_decay)
+        && game_info.score() > decay.floor;
+    let target = if shown {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    if *visibility != target {
+        *visibility = target;
+    }
+
+    if shown {
+        if let Some(mesh) = meshes.get_mut(&handle.0) {
+            *mesh = pie_mesh(decay.remaining(), SCORE_DECAY_PIE_RADIUS);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+    use crate::{game::GameMode, game_time::register_game_time, victory::RunWon};
+
+    // Matches MAX_FRAME_DELTA, so no frame is capped.
+    const FRAME: f32 = 0.05;
+
+    fn decay_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+                FRAME,
+            )))
+            .add_state::<AppState>()
+            .add_state::<PauseState>()
+            .add_event::<ScoreEvent>()
+            .add_event::<RunWon>()
+            .init_resource::<GameInfo>()
+            .init_resource::<ScoreDecay>()
+            .insert_resource(GameMode::Classic)
+            .insert_resource(GameRules {
+                score_decay: true,
+                ..default()
+            })
+            .add_systems(Update, (decay_score, apply_score_events).chain());
+        register_game_time(&mut app);
+
+        app.world
+            .resource_mut::<NextState<AppState>>()
+            .set(AppState::Game);
+        app.update();
+        app
+    }
+
+    fn collect(app: &mut App, coins: usize) {
+        for _ in 0..coins {
+            app.world.send_event(ScoreEvent::CoinCollected {
+                points: 1,
+                value: 1,
+            });
+            app.update();
+        }
+    }
+
+    fn wait(app: &mut App, seconds: f32) {
+        for _ in 0..(seconds / FRAME).round() as u32 {
+            app.update();
+        }
+    }
+
+    fn totals(app: &App) -> (i32, i32) {
+        let game_info = app.world.resource::<GameInfo>();
+        (game_info.points(), game_info.score())
+    }
+
+    #[test]
+    fn score_drains_one_point_per_interval() {
+        let mut app = decay_app();
+        collect(&mut app, 14);
+
+        wait(&mut app, SCORE_DECAY_INTERVAL - 1.0);
+        assert_eq!(totals(&app), (14, 14));
+        wait(&mut app, 2.0);
+        assert_eq!(totals(&app), (14, 13));
+        wait(&mut app, SCORE_DECAY_INTERVAL);
+        assert_eq!(totals(&app), (14, 12));
+    }
+
+    #[test]
+    fn score_never_drains_below_the_highest_multiple_reached() {
+        let mut app = decay_app();
+        collect(&mut app, 13);
+
+        wait(&mut app, SCORE_DECAY_INTERVAL * 10.0);
+        assert_eq!(totals(&app), (13, 10));
+        assert_eq!(app.world.resource::<ScoreDecay>().floor(), 10);
+
+        collect(&mut app, 11);
+        wait(&mut app, SCORE_DECAY_INTERVAL * 10.0);
+        assert_eq!(totals(&app), (24, 20));
+    }
+
+    #[test]
+    fn pickup_restarts_the_interval() {
+        let mut app = decay_app();
+        collect(&mut app, 14);
+
+        wait(&mut app, SCORE_DECAY_INTERVAL - 1.0);
+        collect(&mut app, 1);
+        wait(&mut app, SCORE_DECAY_INTERVAL - 1.0);
+
+        assert_eq!(totals(&app), (15, 15));
+    }
+
+    // Health coins follow points, so decaying the score back down and collecting it back up
+    // again can't grant the same health twice.
+    #[test]
+    fn decay_leaves_the_points_alone() {
+        let mut app = decay_app();
+        collect(&mut app, 12);
+
+        wait(&mut app, SCORE_DECAY_INTERVAL * 5.0);
+        assert_eq!(totals(&app), (12, 10));
+
+        collect(&mut app, 1);
+        assert_eq!(totals(&app), (13, 11));
+    }
+}
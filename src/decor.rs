@@ -0,0 +1,119 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    assets::AssetHandles,
+    config::*,
+    enemy::EnemyType,
+    movement::{Velocity, Wraparound},
+    render_z,
+    steering::{SteerContext, SteeringRegistry},
+    theme::Theme,
+};
+
+#[derive(Component)]
+pub struct MenuDecor {
+    enemy_type: EnemyType,
+    speed: f32,
+    accel: f32,
+}
+
+pub fn spawn_menu_decor(
+    commands: &mut Commands,
+    materials: &mut Assets<ColorMaterial>,
+    asset_handles: &AssetHandles,
+    theme: &Theme,
+) {
+    let enemies = [
+        (
+            EnemyType::Red,
+            Vec3::new(-300.0, 200.0, render_z::BACKGROUND),
+            0.9,
+        ),
+        (
+            EnemyType::Purple,
+            Vec3::new(300.0, -200.0, render_z::BACKGROUND),
+            0.7,
+        ),
+        (
+            EnemyType::Red,
+            Vec3::new(0.0, -300.0, render_z::BACKGROUND),
+            0.6,
+        ),
+    ];
+
+    for (enemy_type, position, pace) in enemies {
+        let color = theme.decor_color.or_else(|| {
+            materials
+                .get(asset_handles.enemy_material(enemy_type))
+                .map(|material| material.color)
+        });
+        let material = color.map_or(ColorMaterial::default(), |color| {
+            ColorMaterial::from(color.with_a(MENU_DECOR_ALPHA))
+        });
+
+        commands.spawn((
+            MenuDecor {
+                enemy_type,
+                speed: ENEMY_MIN_SPEED * MENU_DECOR_PACE * pace,
+                accel: ENEMY_MIN_ACCEL * MENU_DECOR_PACE * pace,
+            },
+            Velocity(Vec3::ZERO),
+            Wraparound {
+                radius: ENEMY_RADIUS,
+            },
+            ColorMesh2dBundle {
+                mesh: asset_handles.enemy_mesh.clone().into(),
+                material: materials.add(material),
+                transform: Transform::from_translation(position),
+                ..default()
+            },
+        ));
+    }
+}
+
+// The target traces a slow figure eight so the chasers keep curving across the screen.
+fn decor_target(elapsed: f32, playfield: Vec2) -> Vec3 {
+    let t = elapsed * MENU_DECOR_TARGET_SPEED;
+    Vec3::new(
+        playfield.x * 0.35 * t.sin(),
+        playfield.y * 0.3 * (2.0 * t).sin(),
+        render_z::BACKGROUND,
+    )
+}
+
+pub fn move_menu_decor(
+    mut query: Query<(&mut Transform, &mut Velocity, &MenuDecor)>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    registry: Res<SteeringRegistry>,
+    time: Res<Time>,
+) {
+    let Ok(window) = window.get_single() else {
+        return;
+    };
+
+    let playfield = Vec2::new(window.width(), window.height());
+    let target = decor_target(time.elapsed_seconds(), playfield);
+
+    for (mut transform, mut velocity, decor) in query.iter_mut() {
+        let Some(behavior) = registry.get(decor.enemy_type.behavior()) else {
+            continue;
+        };
+
+        velocity.0 = behavior.desired_velocity(&SteerContext {
+            position: transform.translation,
+            velocity: velocity.0,
+            speed: decor.speed,
+            accel: decor.accel,
+            future_prediction: 0.0,
+            coin_pull: 0.0,
+            player_position: target,
+            player_velocity: Vec3::ZERO,
+            coin_position: Vec3::ZERO,
+            playfield,
+            dt: time.delta_seconds(),
+        });
+
+        velocity.0.z = 0.0;
+        transform.translation += velocity.0 * time.delta_seconds();
+    }
+}
@@ -0,0 +1,212 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+use rand::Rng;
+
+use crate::{
+    assets::AssetHandles,
+    audio::AudioAvailable,
+    config::*,
+    effects::{spawn_floating_text, text_label},
+    game::{GameInfo, HitPlayer},
+    game_time::GameTime,
+    hud::{HudElement, HudKey},
+    player::Player,
+    render_z,
+    rng::GameRng,
+    score::{BonusReason, PenaltyReason, ScoreEvent},
+    shrink::{player_radius, SizeModifier},
+};
+
+#[derive(Component)]
+pub struct HomeZone;
+
+#[derive(Component)]
+pub struct CarriedText;
+
+#[derive(Component)]
+pub struct DroppedCoin(Timer);
+
+pub fn banked_value(carried: i32) -> i32 {
+    carried * (carried + 1) / 2
+}
+
+pub fn spawn_home_zone(commands: &mut Commands, asset_handles: &AssetHandles, player: Entity) {
+    commands.spawn((
+        HomeZone,
+        ColorMesh2dBundle {
+            mesh: asset_handles.home_zone_mesh.clone().into(),
+            material: asset_handles.home_zone_material.clone(),
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, render_z::PLAYFIELD)),
+            ..default()
+        },
+    ));
+
+    commands.entity(player).with_children(|parent| {
+        parent.spawn((
+            CarriedText,
+            HudElement::new(HudKey::Carried),
+            text_label(
+                asset_handles,
+                "",
+                24.0,
+                COIN_COLOR,
+                Vec3::new(0.0, CARRIED_TEXT_OFFSET, render_z::HUD),
+            ),
+        ));
+    });
+}
+
+pub fn spill_carried(
+    mut hit_event: EventReader<HitPlayer>,
+    mut commands: Commands,
+    mut score_events: EventWriter<ScoreEvent>,
+    game_info: Res<GameInfo>,
+    mut rng: ResMut<GameRng>,
+    player_query: Query<&Transform, With<Player>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    asset_handles: Res<AssetHandles>,
+) {
+    if hit_event.read().last().is_none() || game_info.carried == 0 {
+        return;
+    }
+
+    let player_position = player_query.single().translation;
+    let window = window.single();
+    let half_extent = Vec2::new(window.width(), window.height()) / 2.0 - COIN_RADIUS;
+
+    for _ in 0..game_info.carried {
+        let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+        let distance = rng.gen_range(DROPPED_COIN_MIN_SPREAD..DROPPED_COIN_MAX_SPREAD);
+        let position = (player_position.truncate() + Vec2::from_angle(angle) * distance)
+            .clamp(-half_extent, half_extent);
+
+        commands.spawn((
+            DroppedCoin(Timer::from_seconds(DROPPED_COIN_LIFETIME, TimerMode::Once)),
+            ColorMesh2dBundle {
+                mesh: asset_handles.coin_mesh.clone().into(),
+                material: asset_handles.coin_material.clone(),
+                transform: Transform::from_translation(position.extend(render_z::PICKUP))
+                    .with_scale(Vec3::splat(DROPPED_COIN_SCALE)),
+                ..default()
+            },
+        ));
+    }
+
+    score_events.send(ScoreEvent::Penalty {
+        reason: PenaltyReason::Spilled,
+        value: game_info.carried,
+    });
+}
+
+pub fn collect_dropped_coins(
+    mut commands: Commands,
+    mut score_events: EventWriter<ScoreEvent>,
+    mut dropped_query: Query<(Entity, &mut DroppedCoin, &Transform, &mut Visibility)>,
+    player_query: Query<(&Transform, Option<&SizeModifier>), (With<Player>, Without<DroppedCoin>)>,
+    run_config: Res<RunConfig>,
+    asset_handles: Res<AssetHandles>,
+    game_time: Res<GameTime>,
+    audio: Res<AudioAvailable>,
+) {
+    let Ok((player_transform, size)) = player_query.get_single() else {
+        return;
+    };
+    let pickup_distance = player_radius(&run_config, size) + COIN_RADIUS * DROPPED_COIN_SCALE;
+
+    for (entity, mut dropped, transform, mut visibility) in dropped_query.iter_mut() {
+        if transform
+            .translation
+            .truncate()
+            .distance(player_transform.translation.truncate())
+            < pickup_distance
+        {
+            // Picking a dropped coin back up doesn't count toward progress again.
+            score_events.send(ScoreEvent::CoinCollected {
+                points: 0,
+                value: 1,
+            });
+            commands.entity(entity).despawn();
+            if audio.0 {
+                commands.spawn(AudioBundle {
+                    source: asset_handles.coin_sound.clone(),
+                    settings: PlaybackSettings::DESPAWN,
+                });
+            }
+            continue;
+        }
+
+        if dropped.0.tick(game_time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let remaining = dropped.0.remaining_secs();
+        *visibility = if remaining < DROPPED_COIN_BLINK_TIME
+            && ((remaining / DROPPED_COIN_BLINK_INTERVAL) as u32).is_multiple_of(2)
+        {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+    }
+}
+
+pub fn bank_carried(
+    mut commands: Commands,
+    mut score_events: EventWriter<ScoreEvent>,
+    game_info: Res<GameInfo>,
+    player_query: Query<&Transform, With<Player>>,
+    game_rules: Res<GameRules>,
+    asset_handles: Res<AssetHandles>,
+    audio: Res<AudioAvailable>,
+) {
+    if game_info.carried == 0 {
+        return;
+    }
+
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    if player_transform.translation.truncate().length() > HOME_ZONE_RADIUS {
+        return;
+    }
+
+    let value =
+        (banked_value(game_info.carried) as f32 * game_rules.score_multiplier).round() as i32;
+    score_events.send(ScoreEvent::Bonus {
+        reason: BonusReason::Banked {
+            carried: game_info.carried,
+        },
+        value,
+    });
+
+    spawn_floating_text(
+        &mut commands,
+        &asset_handles,
+        format!("+{}", value),
+        player_transform.translation,
+        COIN_COLOR,
+    );
+    if audio.0 {
+        commands.spawn(AudioBundle {
+            source: asset_handles.health_sound.clone(),
+            settings: PlaybackSettings::DESPAWN,
+        });
+    }
+}
+
+pub fn update_carried_text(
+    game_info: Res<GameInfo>,
+    mut carried_text: Query<&mut Text, With<CarriedText>>,
+) {
+    if !game_info.is_changed() {
+        return;
+    }
+
+    for mut text in carried_text.iter_mut() {
+        text.sections[0].value = if game_info.carried > 0 {
+            game_info.carried.to_string()
+        } else {
+            String::new()
+        };
+    }
+}
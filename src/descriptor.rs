@@ -0,0 +1,418 @@
+use std::{fmt, str::FromStr};
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    assets::AssetHandles,
+    clipboard,
+    config::*,
+    effects::text_label,
+    game::GameMode,
+    mutator::{ActiveMutators, Mutator},
+    pause::PauseState,
+    render_z,
+    spectate::SpectateText,
+    storage::SaveData,
+    AppState,
+};
+
+const DESCRIPTOR_PREFIX: &str = "gorbulet";
+
+pub struct DescriptorPlugin;
+
+impl Plugin for DescriptorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<QueuedSeed>()
+            .add_systems(OnEnter(PauseState::Paused), spawn_pause_stamp)
+            .add_systems(OnExit(PauseState::Paused), despawn_run_stamp)
+            .add_systems(OnEnter(PauseState::GameOver), spawn_game_over_stamp)
+            .add_systems(
+                Update,
+                copy_run_descriptor
+                    .run_if(in_state(AppState::Game))
+                    .run_if(in_state(PauseState::Paused).or_else(in_state(PauseState::GameOver))),
+            )
+            .add_systems(
+                Update,
+                paste_run_descriptor.run_if(in_state(AppState::Menu)),
+            );
+    }
+}
+
+// Everything that decides how a run plays out, so a shared code sets up the same run again.
+// Mutators are a bitmask over Mutator::ALL, which is why that list only ever grows at the end.
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct RunDescriptor {
+    pub version: String,
+    pub seed: u64,
+    pub mode: GameMode,
+    pub difficulty: Difficulty,
+    pub mutators: Vec<Mutator>,
+}
+
+impl RunDescriptor {
+    pub fn new(
+        seed: u64,
+        mode: GameMode,
+        difficulty: Difficulty,
+        mutators: &ActiveMutators,
+    ) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            seed,
+            mode,
+            difficulty,
+            mutators: mutators.iter().collect(),
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        let mutators = if self.mutators.is_empty() {
+            "no mutators".to_string()
+        } else {
+            self.mutators
+                .iter()
+                .map(Mutator::name)
+                .collect::<Vec<_>>()
+                .join(" + ")
+        };
+
+        format!(
+            "{} - {} - seed {:x} - {} - v{}",
+            self.mode.name(),
+            self.difficulty.name(),
+            self.seed,
+            mutators,
+            self.version
+        )
+    }
+}
+
+impl fmt::Display for RunDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mask = Mutator::ALL
+            .iter()
+            .enumerate()
+            .filter(|(_, mutator)| self.mutators.contains(mutator))
+            .fold(0u32, |mask, (index, _)| mask | (1 << index));
+
+        write!(
+            f,
+            "{}/{}/{}/{}/{:x}/{:x}",
+            DESCRIPTOR_PREFIX,
+            self.version,
+            self.mode.name().to_lowercase(),
+            self.difficulty.name().to_lowercase(),
+            self.seed,
+            mask
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DescriptorError {
+    NotARunCode,
+    UnknownMode(String),
+    UnknownDifficulty(String),
+    InvalidSeed,
+    UnknownMutators,
+}
+
+impl fmt::Display for DescriptorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DescriptorError::NotARunCode => write!(f, "not a run code"),
+            DescriptorError::UnknownMode(mode) => write!(f, "unknown mode \"{}\"", mode),
+            DescriptorError::UnknownDifficulty(difficulty) => {
+                write!(f, "unknown difficulty \"{}\"", difficulty)
+            }
+            DescriptorError::InvalidSeed => write!(f, "invalid seed"),
+            DescriptorError::UnknownMutators => write!(f, "unknown mutators"),
+        }
+    }
+}
+
+impl FromStr for RunDescriptor {
+    type Err = DescriptorError;
+
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<_> = code.trim().split('/').collect();
+        let [DESCRIPTOR_PREFIX, version, mode, difficulty, seed, mask] = parts[..] else {
+            return Err(DescriptorError::NotARunCode);
+        };
+
+        let mode = GameMode::ALL
+            .into_iter()
+            .find(|candidate| candidate.name().eq_ignore_ascii_case(mode))
+            .ok_or_else(|| DescriptorError::UnknownMode(mode.to_string()))?;
+        let difficulty = Difficulty::ALL
+            .into_iter()
+            .find(|candidate| candidate.name().eq_ignore_ascii_case(difficulty))
+            .ok_or_else(|| DescriptorError::UnknownDifficulty(difficulty.to_string()))?;
+        let seed = u64::from_str_radix(seed, 16).map_err(|_| DescriptorError::InvalidSeed)?;
+        let mask = u32::from_str_radix(mask, 16).map_err(|_| DescriptorError::UnknownMutators)?;
+        if mask >> Mutator::ALL.len() != 0 {
+            return Err(DescriptorError::UnknownMutators);
+        }
+
+        Ok(Self {
+            version: version.to_string(),
+            seed,
+            mode,
+            difficulty,
+            mutators: Mutator::ALL
+                .into_iter()
+                .enumerate()
+                .filter(|(index, _)| mask & (1 << index) != 0)
+                .map(|(_, mutator)| mutator)
+                .collect(),
+        })
+    }
+}
+
+// The seed for the next run, when a pasted code asked for one.
+#[derive(Resource, Default)]
+pub struct QueuedSeed(pub Option<u64>);
+
+#[derive(Component)]
+struct RunStamp;
+
+#[derive(Component)]
+struct PasteMessage;
+
+fn run_stamp(
+    asset_handles: &AssetHandles,
+    descriptor: &RunDescriptor,
+    translation: Vec3,
+) -> (RunStamp, Text2dBundle) {
+    let mut bundle = text_label(
+        asset_handles,
+        descriptor.summary(),
+        RUN_STAMP_FONT_SIZE,
+        Color::GRAY,
+        translation,
+    );
+    bundle.text.sections.push(TextSection::new(
+        format!("\n{:?} to copy the run code", COPY_RUN_KEY),
+        TextStyle {
+            font: asset_handles.font.clone(),
+            font_size: RUN_STAMP_FONT_SIZE,
+            color: Color::GRAY,
+        },
+    ));
+
+    (RunStamp, bundle)
+}
+
+fn stamp_offset(window: &Window) -> Vec2 {
+    Vec2::new(0.0, -window.height() / 2.0 + RUN_STAMP_MARGIN)
+}
+
+fn spawn_pause_stamp(
+    mut commands: Commands,
+    asset_handles: Res<AssetHandles>,
+    descriptor: Res<RunDescriptor>,
+    window: Query<&Window, With<PrimaryWindow>>,
+) {
+    let offset = stamp_offset(window.single());
+    commands.spawn(run_stamp(
+        &asset_handles,
+        &descriptor,
+        offset.extend(render_z::HUD),
+    ));
+}
+
+// The game over screen pans with the camera, so the stamp rides along like the spectate text.
+fn spawn_game_over_stamp(
+    mut commands: Commands,
+    asset_handles: Res<AssetHandles>,
+    descriptor: Res<RunDescriptor>,
+    window: Query<&Window, With<PrimaryWindow>>,
+) {
+    let offset = stamp_offset(window.single());
+    commands.spawn((
+        SpectateText(offset),
+        run_stamp(&asset_handles, &descriptor, offset.extend(render_z::HUD)),
+    ));
+}
+
+fn despawn_run_stamp(mut commands: Commands, query: Query<Entity, With<RunStamp>>) {
+    query.iter().for_each(|entity| {
+        commands.entity(entity).despawn();
+    });
+}
+
+fn copy_run_descriptor(
+    input: Res<Input<KeyCode>>,
+    descriptor: Res<RunDescriptor>,
+    mut stamp: Query<&mut Text, With<RunStamp>>,
+) {
+    if !input.just_pressed(COPY_RUN_KEY) {
+        return;
+    }
+
+    let code = descriptor.to_string();
+    info!("Run code: {}", code);
+    let hint = if clipboard::copy(&code) {
+        "\nRun code copied".to_string()
+    } else {
+        format!("\nNo clipboard, run code written to the log: {}", code)
+    };
+
+    for mut text in stamp.iter_mut() {
+        text.sections[1].value = hint.clone();
+    }
+}
+
+// Pasting a code sets up its mode, difficulty, mutators and seed, then starts the run.
+fn paste_run_descriptor(
+    mut commands: Commands,
+    input: Res<Input<KeyCode>>,
+    asset_handles: Res<AssetHandles>,
+    mut game_mode: ResMut<GameMode>,
+    mut save_data: ResMut<SaveData>,
+    mut mutators: ResMut<ActiveMutators>,
+    mut queued_seed: ResMut<QueuedSeed>,
+    mut next_state: ResMut<NextState<AppState>>,
+    messages: Query<Entity, With<PasteMessage>>,
+) {
+    if !input.just_pressed(PASTE_RUN_KEY) {
+        return;
+    }
+
+    let descriptor = clipboard::paste()
+        .ok_or_else(|| "Nothing to paste".to_string())
+        .and_then(|code| {
+            code.parse::<RunDescriptor>()
+                .map_err(|error| format!("Could not paste the run: {}", error))
+        });
+
+    let descriptor = match descriptor {
+        Ok(descriptor) => descriptor,
+        Err(message) => {
+            messages.iter().for_each(|entity| {
+                commands.entity(entity).despawn();
+            });
+            commands.spawn((
+                PasteMessage,
+                text_label(
+                    &asset_handles,
+                    message,
+                    RUN_STAMP_FONT_SIZE,
+                    Color::DARK_GRAY,
+                    Vec3::new(0.0, 330.0, render_z::BACKDROP),
+                ),
+            ));
+            return;
+        }
+    };
+
+    if descriptor.version != env!("CARGO_PKG_VERSION") {
+        warn!(
+            "Run code is from version {}, this is {}; the run may play out differently",
+            descriptor.version,
+            env!("CARGO_PKG_VERSION")
+        );
+    }
+
+    *game_mode = descriptor.mode;
+    save_data.settings.difficulty = descriptor.difficulty;
+    *mutators = ActiveMutators::default();
+    for mutator in descriptor.mutators {
+        mutators.toggle(mutator);
+    }
+    queued_seed.0 = Some(descriptor.seed);
+    next_state.set(AppState::Game);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn descriptor(mutators: Vec<Mutator>) -> RunDescriptor {
+        RunDescriptor {
+            version: "1.2.3".to_string(),
+            seed: 0xdead_beef_0042,
+            mode: GameMode::Deposit,
+            difficulty: Difficulty::Hard,
+            mutators,
+        }
+    }
+
+    #[test]
+    fn codes_round_trip() {
+        for mutators in [
+            vec![],
+            vec![Mutator::TinyPlayer, Mutator::NoGrace],
+            Mutator::ALL.to_vec(),
+        ] {
+            let descriptor = descriptor(mutators);
+
+            assert_eq!(descriptor.to_string().parse(), Ok(descriptor));
+        }
+    }
+
+    #[test]
+    fn every_mode_and_difficulty_round_trips() {
+        for mode in GameMode::ALL {
+            for difficulty in Difficulty::ALL {
+                let descriptor = RunDescriptor {
+                    mode,
+                    difficulty,
+                    ..descriptor(vec![Mutator::ScoreDecay])
+                };
+
+                assert_eq!(descriptor.to_string().parse(), Ok(descriptor));
+            }
+        }
+    }
+
+    #[test]
+    fn code_format_is_stable() {
+        let code = descriptor(vec![Mutator::DoubleEnemySpeed, Mutator::GiantCoins]).to_string();
+
+        assert_eq!(code, "gorbulet/1.2.3/deposit/hard/deadbeef0042/9");
+    }
+
+    #[test]
+    fn surrounding_whitespace_is_ignored() {
+        let descriptor = descriptor(vec![]);
+
+        assert_eq!(format!("  {}\n", descriptor).parse(), Ok(descriptor));
+    }
+
+    #[test]
+    fn bad_codes_are_rejected() {
+        let cases = [
+            ("hello", DescriptorError::NotARunCode),
+            (
+                "other/1.0.0/classic/normal/1/0",
+                DescriptorError::NotARunCode,
+            ),
+            (
+                "gorbulet/1.0.0/classic/normal/1/0/extra",
+                DescriptorError::NotARunCode,
+            ),
+            (
+                "gorbulet/1.0.0/sprint/normal/1/0",
+                DescriptorError::UnknownMode("sprint".to_string()),
+            ),
+            (
+                "gorbulet/1.0.0/classic/brutal/1/0",
+                DescriptorError::UnknownDifficulty("brutal".to_string()),
+            ),
+            (
+                "gorbulet/1.0.0/classic/normal/xyz/0",
+                DescriptorError::InvalidSeed,
+            ),
+            (
+                "gorbulet/1.0.0/classic/normal/1/100",
+                DescriptorError::UnknownMutators,
+            ),
+        ];
+
+        for (code, error) in cases {
+            assert_eq!(code.parse::<RunDescriptor>(), Err(error), "{}", code);
+        }
+    }
+}
@@ -0,0 +1,383 @@
+use std::{
+    collections::VecDeque,
+    f32::consts::{FRAC_PI_2, PI},
+};
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    assets::AssetHandles, config::*, quality::EffectsLevel, render_z, spectate::CameraMode,
+    tint::apply_tints, util::lerp, window::not_suspended,
+};
+
+pub struct EffectsPlugin;
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BannerQueue>().add_systems(
+            Update,
+            (
+                screen_shake.run_if(resource_equals(CameraMode::Fixed)),
+                show_banner,
+                update_banner,
+                update_floating_text,
+                fade_hit_indicators,
+                animate_scale,
+                fade_materials,
+                apply_tints,
+            )
+                .run_if(not_suspended),
+        );
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct BannerQueue(VecDeque<String>);
+
+impl BannerQueue {
+    pub fn push(&mut self, text: impl Into<String>) {
+        self.0.push_back(text.into());
+    }
+}
+
+#[derive(Component, Default)]
+pub struct ScreenShake {
+    trauma: f32,
+    time: f32,
+    offset: Vec2,
+}
+
+impl ScreenShake {
+    pub fn add_trauma(&mut self, trauma: f32) {
+        self.trauma += trauma;
+    }
+
+    pub fn trauma(&self) -> f32 {
+        self.trauma
+    }
+
+    // How far the shake currently moves the camera from where it rests.
+    pub fn offset(&self) -> Vec2 {
+        self.offset
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_offset(offset: Vec2) -> Self {
+        Self {
+            offset,
+            ..default()
+        }
+    }
+}
+
+#[derive(Component)]
+struct Banner(Timer);
+
+#[derive(Component)]
+struct FloatingText(Timer);
+
+#[derive(Component)]
+struct HitIndicator(Timer);
+
+#[derive(Component)]
+pub struct ScaleAnimation {
+    timer: Timer,
+    from: f32,
+    to: f32,
+}
+
+impl ScaleAnimation {
+    pub fn new(duration: f32, from: f32, to: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(duration, TimerMode::Once),
+            from,
+            to,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Fade(Timer);
+
+impl Fade {
+    pub fn out(duration: f32) -> Self {
+        Self(Timer::from_seconds(duration, TimerMode::Once))
+    }
+}
+
+pub fn spawn_hit_indicator(
+    commands: &mut Commands,
+    materials: &mut Assets<ColorMaterial>,
+    asset_handles: &AssetHandles,
+    player: Entity,
+    direction: Vec3,
+) {
+    let direction = direction.truncate().normalize_or_zero();
+    if direction == Vec2::ZERO {
+        return;
+    }
+
+    let material = materials.add(ColorMaterial::from(HIT_INDICATOR_COLOR));
+
+    commands.entity(player).with_children(|parent| {
+        parent.spawn((
+            HitIndicator(Timer::from_seconds(HIT_INDICATOR_TIME, TimerMode::Once)),
+            ColorMesh2dBundle {
+                mesh: asset_handles.bar_mesh.clone().into(),
+                material,
+                transform: Transform::from_translation(
+                    (direction * (PLAYER_RADIUS + HIT_INDICATOR_THICKNESS))
+                        .extend(render_z::CHILD_ABOVE),
+                )
+                .with_rotation(Quat::from_rotation_z(
+                    direction.y.atan2(direction.x) + FRAC_PI_2,
+                ))
+                .with_scale(Vec3::new(
+                    HIT_INDICATOR_LENGTH,
+                    HIT_INDICATOR_THICKNESS,
+                    1.0,
+                )),
+                ..default()
+            },
+        ));
+    });
+}
+
+pub fn spawn_edge_flash(
+    commands: &mut Commands,
+    materials: &mut Assets<ColorMaterial>,
+    asset_handles: &AssetHandles,
+    attacker_position: Vec3,
+    width: f32,
+    height: f32,
+) {
+    let overshoot_x = attacker_position.x.abs() - width / 2.0;
+    let overshoot_y = attacker_position.y.abs() - height / 2.0;
+    if overshoot_x <= 0.0 && overshoot_y <= 0.0 {
+        return;
+    }
+
+    let (translation, scale) = if overshoot_x > overshoot_y {
+        (
+            Vec3::new(
+                (width - HIT_EDGE_THICKNESS) / 2.0 * attacker_position.x.signum(),
+                0.0,
+                render_z::EFFECTS,
+            ),
+            Vec3::new(HIT_EDGE_THICKNESS, height, 1.0),
+        )
+    } else {
+        (
+            Vec3::new(
+                0.0,
+                (height - HIT_EDGE_THICKNESS) / 2.0 * attacker_position.y.signum(),
+                render_z::EFFECTS,
+            ),
+            Vec3::new(width, HIT_EDGE_THICKNESS, 1.0),
+        )
+    };
+
+    commands.spawn((
+        HitIndicator(Timer::from_seconds(HIT_INDICATOR_TIME, TimerMode::Once)),
+        ColorMesh2dBundle {
+            mesh: asset_handles.bar_mesh.clone().into(),
+            material: materials.add(ColorMaterial::from(HIT_INDICATOR_COLOR)),
+            transform: Transform::from_translation(translation).with_scale(scale),
+            ..default()
+        },
+    ));
+}
+
+pub fn spawn_floating_text(
+    commands: &mut Commands,
+    asset_handles: &AssetHandles,
+    text: impl Into<String>,
+    position: Vec3,
+    color: Color,
+) {
+    commands.spawn((
+        FloatingText(Timer::from_seconds(FLOATING_TEXT_TIME, TimerMode::Once)),
+        text_label(
+            asset_handles,
+            text,
+            32.0,
+            color,
+            position.truncate().extend(render_z::EFFECTS),
+        ),
+    ));
+}
+
+pub fn text_label(
+    asset_handles: &AssetHandles,
+    text: impl Into<String>,
+    font_size: f32,
+    color: Color,
+    translation: Vec3,
+) -> Text2dBundle {
+    Text2dBundle {
+        text: Text::from_section(
+            text,
+            TextStyle {
+                font: asset_handles.font.clone(),
+                font_size,
+                color,
+            },
+        )
+        .with_alignment(TextAlignment::Center),
+        transform: Transform::from_translation(translation),
+        ..default()
+    }
+}
+
+fn screen_shake(
+    mut query: Query<(&mut Transform, &mut ScreenShake), With<Camera>>,
+    effects_level: Res<EffectsLevel>,
+    time: Res<Time>,
+) {
+    let (mut transform, mut screen_shake) = query.single_mut();
+
+    if !effects_level.0.screen_shake() {
+        screen_shake.trauma = 0.0;
+    }
+
+    if screen_shake.trauma <= 0.0 {
+        screen_shake.time = 0.0;
+        screen_shake.offset = Vec2::ZERO;
+        return;
+    }
+
+    screen_shake.trauma = lerp(screen_shake.trauma, 0.0, SCREEN_SHAKE_LERP);
+
+    screen_shake.time += time.delta_seconds();
+
+    screen_shake.offset = Vec2::new(
+        screen_shake.trauma * (2.0 * PI * SCREEN_SHAKE_X_FREQUENCY * screen_shake.time).sin(),
+        screen_shake.trauma * (2.0 * PI * SCREEN_SHAKE_Y_FREQUENCY * screen_shake.time).sin(),
+    );
+    transform.translation.x = screen_shake.offset.x;
+    transform.translation.y = screen_shake.offset.y;
+}
+
+fn show_banner(
+    mut commands: Commands,
+    mut banners: ResMut<BannerQueue>,
+    banner_query: Query<(), With<Banner>>,
+    asset_handles: Res<AssetHandles>,
+    window: Query<&Window, With<PrimaryWindow>>,
+) {
+    if !banner_query.is_empty() {
+        return;
+    }
+
+    let Some(text) = banners.0.pop_front() else {
+        return;
+    };
+
+    let window = window.single();
+
+    commands.spawn((
+        Banner(Timer::from_seconds(BANNER_TIME, TimerMode::Once)),
+        Text2dBundle {
+            text: Text::from_section(
+                text,
+                TextStyle {
+                    font: asset_handles.font.clone(),
+                    font_size: 48.0,
+                    color: Color::WHITE,
+                },
+            )
+            .with_alignment(TextAlignment::Center),
+            transform: Transform::from_translation(Vec3::new(
+                0.0,
+                window.height() / 2.0 - 60.0,
+                render_z::HUD,
+            )),
+            ..default()
+        },
+    ));
+}
+
+fn update_banner(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Banner, &mut Text)>,
+    time: Res<Time>,
+) {
+    for (entity, mut banner, mut text) in query.iter_mut() {
+        banner.0.tick(time.delta());
+
+        let alpha = (banner.0.remaining_secs() / BANNER_FADE_TIME).min(1.0);
+        text.sections[0].style.color.set_a(alpha);
+
+        if banner.0.finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn update_floating_text(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut FloatingText, &mut Transform, &mut Text)>,
+    effects_level: Res<EffectsLevel>,
+    time: Res<Time>,
+) {
+    for (entity, mut floating_text, mut transform, mut text) in query.iter_mut() {
+        if !effects_level.0.floating_text() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        floating_text.0.tick(time.delta());
+
+        transform.translation.y += FLOATING_TEXT_RISE_SPEED * time.delta_seconds();
+        text.sections[0]
+            .style
+            .color
+            .set_a(floating_text.0.percent_left());
+
+        if floating_text.0.finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn animate_scale(mut query: Query<(&mut ScaleAnimation, &mut Transform)>, time: Res<Time>) {
+    for (mut animation, mut transform) in query.iter_mut() {
+        animation.timer.tick(time.delta());
+
+        let scale = lerp(animation.from, animation.to, animation.timer.percent());
+        transform.scale = Vec3::splat(scale);
+    }
+}
+
+fn fade_materials(
+    mut query: Query<(&mut Fade, &Handle<ColorMaterial>)>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    time: Res<Time>,
+) {
+    for (mut fade, material) in query.iter_mut() {
+        fade.0.tick(time.delta());
+
+        if let Some(material) = materials.get_mut(material) {
+            material.color.set_a(fade.0.percent_left());
+        }
+    }
+}
+
+fn fade_hit_indicators(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut HitIndicator, &Handle<ColorMaterial>)>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    time: Res<Time>,
+) {
+    for (entity, mut indicator, material) in query.iter_mut() {
+        indicator.0.tick(time.delta());
+
+        if let Some(material) = materials.get_mut(material) {
+            material.color.set_a(indicator.0.percent_left());
+        }
+
+        if indicator.0.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,820 @@
+use std::time::Instant;
+
+use bevy::{
+    audio::Volume,
+    diagnostic::{Diagnostic, DiagnosticId, Diagnostics, RegisterDiagnostic},
+    prelude::*,
+    window::PrimaryWindow,
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    aggression::AggressionModifier,
+    assets::AssetHandles,
+    audio::SfxBudget,
+    bench::{BenchSpan, BenchSpans},
+    coin::{Coin, CoinContestTimer},
+    config::*,
+    effects::{Fade, ScaleAnimation},
+    flocking::NeighborGrid,
+    game_time::GameTime,
+    leader::{leader_grid, leader_outline, pack_aura, pack_speed_multiplier, Leader},
+    lunge::Lunge,
+    movement::{Velocity, Wraparound},
+    player::Player,
+    render_z,
+    rng::GameRng,
+    stats::{expected_accel, expected_speed, speed_curve, threat_score},
+    steering::{BehaviorId, SteerContext, SteeringRegistry},
+    tint::{instance_material, TintController},
+};
+
+#[derive(Component)]
+pub struct Enemy {
+    pub enemy_type: EnemyType,
+    pub speed: f32,
+    pub accel: f32,
+    pub future_prediction: f32,
+    pub coin_pull: f32,
+    pub threat: f32,
+    pub behavior: BehaviorId,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EnemyType {
+    Red,
+    Purple,
+}
+
+impl EnemyType {
+    pub const ALL: [EnemyType; 2] = [EnemyType::Red, EnemyType::Purple];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EnemyType::Red => "Red",
+            EnemyType::Purple => "Purple",
+        }
+    }
+
+    pub fn for_points(points: i32) -> Self {
+        if points >= ENEMY_PURPLE_COIN_SPAWN && points % 2 == 0 {
+            EnemyType::Purple
+        } else {
+            EnemyType::Red
+        }
+    }
+
+    pub fn behavior(&self) -> BehaviorId {
+        match self {
+            EnemyType::Red => BehaviorId::RED,
+            EnemyType::Purple => BehaviorId::PURPLE,
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            EnemyType::Red => "Chases you down, sometimes drifting toward the coin.",
+            EnemyType::Purple => "Turns slowly, but cuts across the edges to reach you.",
+        }
+    }
+}
+
+#[derive(Bundle)]
+struct EnemyBundle {
+    enemy: Enemy,
+    wraparound: Wraparound,
+    velocity: Velocity,
+    color_mesh_2d_bundle: ColorMesh2dBundle,
+}
+
+impl Default for EnemyBundle {
+    fn default() -> Self {
+        Self {
+            enemy: Enemy {
+                enemy_type: EnemyType::Red,
+                speed: ENEMY_MIN_SPEED,
+                accel: ENEMY_MIN_ACCEL,
+                future_prediction: 0.0,
+                coin_pull: 0.0,
+                threat: 0.0,
+                behavior: BehaviorId::RED,
+            },
+            wraparound: Wraparound::default(),
+            velocity: Velocity(Vec3::ZERO),
+            color_mesh_2d_bundle: ColorMesh2dBundle::default(),
+        }
+    }
+}
+
+// Enemies being removed keep their mesh for the shrink-and-fade but lose the Enemy marker, so
+// nothing collides with or steers them any more.
+#[derive(Component)]
+pub struct DyingEnemy(Timer);
+
+#[derive(Event)]
+pub struct EnemySpawned {
+    pub enemy_type: EnemyType,
+    pub threat: f32,
+    pub speed: f32,
+    pub accel: f32,
+}
+
+#[derive(Event)]
+pub struct EnemyRemoved {
+    pub enemy_type: EnemyType,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EnemyStats {
+    pub speed: f32,
+    pub accel: f32,
+    pub future_prediction: f32,
+    pub coin_pull: f32,
+}
+
+// The deviation can otherwise push early enemies far below the curve's floor, slow enough to be
+// irrelevant, and late ones past its ceiling.
+pub fn roll_enemy_stats(rng: &mut GameRng, points: i32, enemy_type: EnemyType) -> EnemyStats {
+    let speed_float = speed_curve(points);
+    let speed_deviation = SPEED_MAX_DEVIATION * (2.0 * rng.gen::<f32>() - 1.0);
+    let speed = (expected_speed(points) + speed_deviation).clamp(
+        ENEMY_MIN_SPEED * ENEMY_STAT_FLOOR_SCALE,
+        ENEMY_MAX_SPEED * ENEMY_STAT_CEILING_SCALE,
+    );
+
+    let accel_deviation = ACCEL_MAX_DEVIATION * (2.0 * rng.gen::<f32>() - 1.0);
+    let accel = (expected_accel(points) + accel_deviation).clamp(
+        ENEMY_MIN_ACCEL * ENEMY_STAT_FLOOR_SCALE,
+        ENEMY_MAX_ACCEL * ENEMY_STAT_CEILING_SCALE,
+    );
+
+    let future_prediction: f32 = rng.gen();
+
+    let coin_pull = match enemy_type {
+        EnemyType::Red => (2.0 * rng.gen::<f32>() - 1.0) * (speed_float * 0.8 + 0.2),
+        EnemyType::Purple => (2.0 * rng.gen::<f32>() - 1.0) * 0.2,
+    };
+
+    EnemyStats {
+        speed,
+        accel,
+        future_prediction,
+        coin_pull,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpawnSide {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl SpawnSide {
+    pub const ALL: [SpawnSide; 4] = [
+        SpawnSide::Top,
+        SpawnSide::Bottom,
+        SpawnSide::Left,
+        SpawnSide::Right,
+    ];
+
+    // Sides further from the player are more likely.
+    fn weight(&self, player_position: Vec3, width: f32, height: f32) -> f32 {
+        let half_width = width / 2.0;
+        let half_height = height / 2.0;
+
+        let weight = match self {
+            SpawnSide::Top => 1.0 - player_position.y / half_height,
+            SpawnSide::Bottom => 1.0 + player_position.y / half_height,
+            SpawnSide::Left => 1.0 + player_position.x / half_width,
+            SpawnSide::Right => 1.0 - player_position.x / half_width,
+        };
+        weight.clamp(ENEMY_SPAWN_SIDE_MIN_WEIGHT, 2.0)
+    }
+
+    pub fn sample_weighted(
+        player_position: Vec3,
+        width: f32,
+        height: f32,
+        rng: &mut GameRng,
+    ) -> Self {
+        Self::ALL[sample_side_index(&Self::ALL, player_position, width, height, rng)]
+    }
+}
+
+fn sample_side_index(
+    sides: &[SpawnSide],
+    player_position: Vec3,
+    width: f32,
+    height: f32,
+    rng: &mut GameRng,
+) -> usize {
+    let weights: Vec<f32> = sides
+        .iter()
+        .map(|side| side.weight(player_position, width, height))
+        .collect();
+
+    let total: f32 = weights.iter().sum();
+    let mut roll = rng.gen::<f32>() * total;
+
+    for (index, weight) in weights.iter().enumerate() {
+        if roll < *weight {
+            return index;
+        }
+        roll -= weight;
+    }
+
+    sides.len() - 1
+}
+
+// Deals spawn sides without replacement, so every four spawns cover each side exactly once. The
+// player-distance weighting still decides the order within each bag.
+#[derive(Resource, Default)]
+pub struct SpawnSideBag(Vec<SpawnSide>);
+
+impl SpawnSideBag {
+    pub fn draw(
+        &mut self,
+        player_position: Vec3,
+        width: f32,
+        height: f32,
+        rng: &mut GameRng,
+    ) -> SpawnSide {
+        if self.0.is_empty() {
+            self.0.extend(SpawnSide::ALL);
+        }
+
+        let index = sample_side_index(&self.0, player_position, width, height, rng);
+        self.0.remove(index)
+    }
+
+    // Falls back to an independent weighted draw when the rules turn the bag off.
+    pub fn next_side(
+        &mut self,
+        rules: &GameRules,
+        player_position: Vec3,
+        width: f32,
+        height: f32,
+        rng: &mut GameRng,
+    ) -> SpawnSide {
+        if rules.spawn_side_bag {
+            self.draw(player_position, width, height, rng)
+        } else {
+            SpawnSide::sample_weighted(player_position, width, height, rng)
+        }
+    }
+}
+
+pub fn spawn_enemy(
+    commands: &mut Commands,
+    materials: &mut Assets<ColorMaterial>,
+    rng: &mut GameRng,
+    points: i32,
+    asset_handles: &AssetHandles,
+    enemy_type: EnemyType,
+    spawn_position: Vec3,
+) -> EnemySpawned {
+    let mut stats = roll_enemy_stats(rng, points, enemy_type);
+
+    let leader = points >= LEADER_MIN_POINTS && rng.gen_bool(LEADER_CHANCE);
+    if leader {
+        stats.speed *= LEADER_SPEED_SCALE;
+        stats.coin_pull = 0.0;
+    }
+
+    spawn_enemy_with_stats(
+        commands,
+        materials,
+        rng,
+        points,
+        asset_handles,
+        enemy_type,
+        spawn_position,
+        stats,
+        leader,
+    )
+}
+
+// Spawns exactly the enemy described instead of rolling one, for scripted spawns. `points` still
+// decides whether a red one can lunge.
+pub fn spawn_enemy_with_stats(
+    commands: &mut Commands,
+    materials: &mut Assets<ColorMaterial>,
+    rng: &mut GameRng,
+    points: i32,
+    asset_handles: &AssetHandles,
+    enemy_type: EnemyType,
+    spawn_position: Vec3,
+    stats: EnemyStats,
+    leader: bool,
+) -> EnemySpawned {
+    let EnemyStats {
+        speed,
+        accel,
+        future_prediction,
+        coin_pull,
+    } = stats;
+
+    let accel_multiplier = match enemy_type {
+        EnemyType::Red => 1.0,
+        EnemyType::Purple => ENEMY_PURPLE_ACCEL_MUTLIPLIER,
+    };
+
+    let threat = threat_score(points, speed, accel, future_prediction);
+
+    let base_material = asset_handles.enemy_material(enemy_type);
+    let base_color = materials
+        .get(&base_material)
+        .map_or(Color::WHITE, |material| material.color);
+    let tint = TintController::new(base_color).with_threat(threat);
+    let material = instance_material(materials, &base_material, &tint);
+
+    let wraparound = match enemy_type {
+        EnemyType::Red => Wraparound {
+            radius: ENEMY_RADIUS * 8.0,
+        },
+        EnemyType::Purple => Wraparound {
+            radius: ENEMY_RADIUS,
+        },
+    };
+
+    let mut enemy = commands.spawn(EnemyBundle {
+        enemy: Enemy {
+            enemy_type,
+            speed,
+            accel: accel * accel_multiplier,
+            future_prediction,
+            coin_pull,
+            threat,
+            behavior: enemy_type.behavior(),
+        },
+        wraparound,
+        color_mesh_2d_bundle: ColorMesh2dBundle {
+            mesh: asset_handles.enemy_mesh.clone().into(),
+            material,
+            transform: Transform::from_translation(spawn_position),
+            ..default()
+        },
+        ..default()
+    });
+
+    enemy.insert(tint);
+
+    if enemy_type == EnemyType::Red && points >= LUNGE_MIN_POINTS {
+        enemy.insert(Lunge::new(rng));
+    }
+
+    if leader {
+        enemy.insert(Leader).with_children(|parent| {
+            parent.spawn(leader_outline(asset_handles));
+        });
+    } else {
+        enemy.with_children(|parent| {
+            parent.spawn(pack_aura(asset_handles));
+        });
+    }
+
+    EnemySpawned {
+        enemy_type,
+        threat,
+        speed,
+        accel,
+    }
+}
+
+pub fn despawn_enemy(commands: &mut Commands, entity: Entity, enemy_type: EnemyType) {
+    commands.add(move |world: &mut World| world.send_event(EnemyRemoved { enemy_type }));
+    commands
+        .entity(entity)
+        .remove::<(Enemy, Lunge, Leader, Velocity)>()
+        .insert((
+            DyingEnemy(Timer::from_seconds(ENEMY_REMOVAL_TIME, TimerMode::Once)),
+            ScaleAnimation::new(ENEMY_REMOVAL_TIME, 1.0, 0.0),
+            Fade::out(ENEMY_REMOVAL_TIME),
+        ));
+}
+
+// When the cap is exceeded, the least threatening enemies make room.
+pub fn enforce_enemy_cap(mut commands: Commands, query: Query<(Entity, &Enemy)>) {
+    let count = query.iter().len();
+    if count <= MAX_ENEMIES {
+        return;
+    }
+
+    let mut enemies: Vec<_> = query.iter().collect();
+    enemies.sort_by(|(_, a), (_, b)| a.threat.total_cmp(&b.threat));
+
+    for (entity, enemy) in enemies.into_iter().take(count - MAX_ENEMIES) {
+        despawn_enemy(&mut commands, entity, enemy.enemy_type);
+    }
+}
+
+pub fn tick_dying_enemies(
+    mut commands: Commands,
+    mut budget: ResMut<SfxBudget>,
+    mut query: Query<(Entity, &mut DyingEnemy)>,
+    asset_handles: Res<AssetHandles>,
+    game_time: Res<GameTime>,
+) {
+    for (entity, mut dying) in query.iter_mut() {
+        if dying.is_added() {
+            budget.play(
+                &mut commands,
+                asset_handles.pop_sound.clone(),
+                PlaybackSettings::DESPAWN
+                    .with_volume(Volume::new_relative(ENEMY_REMOVAL_POP_VOLUME)),
+            );
+        }
+
+        if dying.0.tick(game_time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+pub fn get_enemy_spawn_position(
+    width: f32,
+    height: f32,
+    spawn_side: SpawnSide,
+    rng: &mut GameRng,
+) -> Vec3 {
+    let vertical = height / 2.0 + PLAYER_RADIUS;
+    let horizontal = width / 2.0 + PLAYER_RADIUS;
+    let rand_float: f32 = rng.gen();
+
+    match spawn_side {
+        SpawnSide::Top => Vec3::new(
+            horizontal * (2.0 * rand_float - 1.0),
+            vertical,
+            render_z::ENEMY,
+        ),
+        SpawnSide::Bottom => Vec3::new(
+            horizontal * (2.0 * rand_float - 1.0),
+            -vertical,
+            render_z::ENEMY,
+        ),
+        SpawnSide::Left => Vec3::new(
+            -horizontal,
+            vertical * (2.0 * rand_float - 1.0),
+            render_z::ENEMY,
+        ),
+        SpawnSide::Right => Vec3::new(
+            horizontal,
+            vertical * (2.0 * rand_float - 1.0),
+            render_z::ENEMY,
+        ),
+    }
+}
+
+type EnemyMotion<'a> = (
+    Entity,
+    Mut<'a, Transform>,
+    Mut<'a, Velocity>,
+    &'a Enemy,
+    Option<&'a Lunge>,
+    Option<&'a Leader>,
+);
+
+pub fn move_enemy(
+    mut query: Query<(
+        Entity,
+        &mut Transform,
+        &mut Velocity,
+        &Enemy,
+        Option<&Lunge>,
+        Option<&Leader>,
+    )>,
+    player_query: Query<(&Transform, &Velocity), (With<Player>, Without<Enemy>)>,
+    coin_transform: Query<&Transform, (With<Coin>, Without<Player>, Without<Enemy>)>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    registry: Res<SteeringRegistry>,
+    run_config: Res<RunConfig>,
+    game_rules: Res<GameRules>,
+    aggression: Res<AggressionModifier>,
+    coin_contest: Res<CoinContestTimer>,
+    time: Res<Time>,
+    mut bench_spans: Option<ResMut<BenchSpans>>,
+) {
+    if query.is_empty() || player_query.is_empty() {
+        return;
+    }
+
+    let (player_transform, player_velocity) = player_query.single();
+    let coin_transform = coin_transform.single();
+    let window = window.single();
+
+    let alignment = run_config.flock_alignment_weight;
+    let cohesion = run_config.flock_cohesion_weight;
+    let grid_started = bench_spans.is_some().then(Instant::now);
+    let grid = (game_rules.flocking && (alignment != 0.0 || cohesion != 0.0)).then(|| {
+        let mut grid = NeighborGrid::new(FLOCK_RADIUS);
+        for (entity, transform, velocity, _, _, _) in query.iter() {
+            grid.insert(entity, transform.translation, velocity.0);
+        }
+        grid
+    });
+    let leaders = leader_grid(
+        query
+            .iter()
+            .filter(|(.., leader)| leader.is_some())
+            .map(|(entity, transform, ..)| (entity, transform.translation)),
+    );
+    if let (Some(spans), Some(started)) = (bench_spans.as_mut(), grid_started) {
+        spans.add(BenchSpan::GridRebuild, started.elapsed());
+    }
+
+    let step = |(entity, mut transform, mut velocity, enemy, lunge, leader): EnemyMotion<'_>| {
+        if let Some(behavior) = registry.get(enemy.behavior) {
+            velocity.0 = behavior.desired_velocity(&SteerContext {
+                position: transform.translation,
+                velocity: velocity.0,
+                speed: enemy.speed
+                    * run_config.enemy_speed_multiplier
+                    * lunge.map_or(1.0, Lunge::speed_multiplier)
+                    * pack_speed_multiplier(
+                        leaders.as_ref(),
+                        entity,
+                        transform.translation,
+                        leader.is_some(),
+                    ),
+                accel: enemy.accel
+                    * run_config.enemy_speed_multiplier
+                    * lunge.map_or(1.0, Lunge::accel_multiplier)
+                    * aggression.accel_multiplier,
+                future_prediction: enemy.future_prediction,
+                coin_pull: enemy.coin_pull * coin_contest.pull_scale(),
+                player_position: player_transform.translation,
+                player_velocity: player_velocity.0,
+                coin_position: coin_transform.translation,
+                playfield: Vec2::new(window.width(), window.height()),
+                dt: time.delta_seconds(),
+            });
+        }
+
+        if let Some(neighborhood) = grid
+            .as_ref()
+            .and_then(|grid| grid.neighborhood(entity, transform.translation))
+        {
+            let heading = neighborhood.heading * velocity.0.length() - velocity.0;
+            let centroid = neighborhood.centroid - transform.translation;
+            velocity.0 += (heading * alignment + centroid * cohesion) * time.delta_seconds();
+        }
+
+        // Steering targets live on other layers; enemies stay on their own.
+        velocity.0.z = 0.0;
+        transform.translation += velocity.0 * time.delta_seconds();
+    };
+
+    // Each enemy moves independently, but deterministic runs skip the thread pool so nothing
+    // about the result can depend on how the work was split.
+    if game_rules.deterministic {
+        query.iter_mut().for_each(step);
+    } else {
+        query.par_iter_mut().for_each(step);
+    }
+}
+
+pub const ENEMY_SPEED_MIN_DIAGNOSTIC: DiagnosticId =
+    DiagnosticId::from_u128(0x3f1c_52a0_8d4e_4b71_9a26_e0b4_7c15_d201);
+pub const ENEMY_SPEED_MAX_DIAGNOSTIC: DiagnosticId =
+    DiagnosticId::from_u128(0x3f1c_52a0_8d4e_4b71_9a26_e0b4_7c15_d202);
+pub const ENEMY_ACCEL_MIN_DIAGNOSTIC: DiagnosticId =
+    DiagnosticId::from_u128(0x3f1c_52a0_8d4e_4b71_9a26_e0b4_7c15_d203);
+pub const ENEMY_ACCEL_MAX_DIAGNOSTIC: DiagnosticId =
+    DiagnosticId::from_u128(0x3f1c_52a0_8d4e_4b71_9a26_e0b4_7c15_d204);
+
+pub fn register_enemy_stat_diagnostics(app: &mut App) {
+    for (id, name) in [
+        (ENEMY_SPEED_MIN_DIAGNOSTIC, "enemy_speed_min"),
+        (ENEMY_SPEED_MAX_DIAGNOSTIC, "enemy_speed_max"),
+        (ENEMY_ACCEL_MIN_DIAGNOSTIC, "enemy_accel_min"),
+        (ENEMY_ACCEL_MAX_DIAGNOSTIC, "enemy_accel_max"),
+    ] {
+        app.register_diagnostic(Diagnostic::new(id, name, 1));
+    }
+}
+
+pub struct EnemyStatRange {
+    speed: (f32, f32),
+    accel: (f32, f32),
+}
+
+impl Default for EnemyStatRange {
+    fn default() -> Self {
+        Self {
+            speed: (f32::INFINITY, f32::NEG_INFINITY),
+            accel: (f32::INFINITY, f32::NEG_INFINITY),
+        }
+    }
+}
+
+// The extremes actually rolled this session, visible wherever diagnostics are shown or logged.
+pub fn record_enemy_stat_range(
+    mut enemy_spawned: EventReader<EnemySpawned>,
+    mut range: Local<EnemyStatRange>,
+    mut diagnostics: Diagnostics,
+) {
+    if enemy_spawned.is_empty() {
+        return;
+    }
+
+    for event in enemy_spawned.read() {
+        range.speed = (
+            range.speed.0.min(event.speed),
+            range.speed.1.max(event.speed),
+        );
+        range.accel = (
+            range.accel.0.min(event.accel),
+            range.accel.1.max(event.accel),
+        );
+    }
+
+    diagnostics.add_measurement(ENEMY_SPEED_MIN_DIAGNOSTIC, || range.speed.0 as f64);
+    diagnostics.add_measurement(ENEMY_SPEED_MAX_DIAGNOSTIC, || range.speed.1 as f64);
+    diagnostics.add_measurement(ENEMY_ACCEL_MIN_DIAGNOSTIC, || range.accel.0 as f64);
+    diagnostics.add_measurement(ENEMY_ACCEL_MAX_DIAGNOSTIC, || range.accel.1 as f64);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+
+    const WIDTH: f32 = 800.0;
+    const HEIGHT: f32 = 600.0;
+
+    fn side_frequency(player_position: Vec3, side: SpawnSide) -> f32 {
+        let mut rng = GameRng::from_seed(415);
+        let draws = 10_000;
+        let hits = (0..draws)
+            .filter(|_| {
+                SpawnSide::sample_weighted(player_position, WIDTH, HEIGHT, &mut rng) == side
+            })
+            .count();
+        hits as f32 / draws as f32
+    }
+
+    #[test]
+    fn centered_player_gets_uniform_sides() {
+        for side in SpawnSide::ALL {
+            assert_eq!(side.weight(Vec3::ZERO, WIDTH, HEIGHT), 1.0);
+        }
+    }
+
+    #[test]
+    fn hugging_the_left_edge_rarely_spawns_on_the_left() {
+        let player_position = Vec3::new(-WIDTH / 2.0, 0.0, 0.0);
+
+        assert!(side_frequency(player_position, SpawnSide::Left) < 0.1);
+        assert!(side_frequency(player_position, SpawnSide::Right) > 0.4);
+    }
+
+    // A tight pack chasing the player for a second, returning where every enemy ended up.
+    fn run_pack(flocking: bool, alignment: f32, cohesion: f32) -> Vec<Vec3> {
+        let mut rules = GameRules::for_difficulty(Difficulty::Normal);
+        rules.flocking = flocking;
+        let config = GameConfig {
+            flock_alignment_weight: alignment,
+            flock_cohesion_weight: cohesion,
+            ..default()
+        };
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+                1.0 / 60.0,
+            )))
+            .init_resource::<SteeringRegistry>()
+            .init_resource::<AggressionModifier>()
+            .init_resource::<CoinContestTimer>()
+            .insert_resource(RunConfig(config))
+            .insert_resource(rules)
+            .add_systems(Update, move_enemy);
+
+        app.world.spawn((Window::default(), PrimaryWindow));
+        app.world.spawn((
+            Player,
+            Transform::from_xyz(0.0, 200.0, 0.0),
+            Velocity(Vec3::new(50.0, 0.0, 0.0)),
+        ));
+        app.world
+            .spawn((Coin::default(), Transform::from_xyz(-150.0, 0.0, 0.0)));
+
+        let enemies: Vec<Entity> = (0..6)
+            .map(|index| {
+                let offset = index as f32;
+                app.world
+                    .spawn((
+                        Transform::from_xyz(30.0 * offset, -100.0 + 7.0 * offset, 0.0),
+                        Velocity(Vec3::new(10.0 * offset, 20.0, 0.0)),
+                        Enemy {
+                            enemy_type: EnemyType::Red,
+                            speed: 150.0 + offset,
+                            accel: 400.0,
+                            future_prediction: 0.5,
+                            coin_pull: 0.2,
+                            threat: 0.5,
+                            behavior: BehaviorId::RED,
+                        },
+                    ))
+                    .id()
+            })
+            .collect();
+
+        for _ in 0..60 {
+            app.update();
+        }
+
+        enemies
+            .into_iter()
+            .map(|enemy| app.world.get::<Transform>(enemy).unwrap().translation)
+            .collect()
+    }
+
+    #[test]
+    fn zero_flocking_weights_change_nothing() {
+        let without = run_pack(false, 0.0, 0.0);
+
+        assert_eq!(run_pack(true, 0.0, 0.0), without);
+        assert_eq!(
+            run_pack(false, FLOCK_ALIGNMENT_WEIGHT, FLOCK_COHESION_WEIGHT),
+            without
+        );
+        assert_ne!(
+            run_pack(true, FLOCK_ALIGNMENT_WEIGHT, FLOCK_COHESION_WEIGHT),
+            without
+        );
+    }
+
+    #[test]
+    fn bag_deals_every_side_once_per_refill() {
+        let mut bag = SpawnSideBag::default();
+        let mut rng = GameRng::from_seed(444);
+
+        for _ in 0..5 {
+            let mut dealt: Vec<_> = (0..4)
+                .map(|_| bag.draw(Vec3::ZERO, WIDTH, HEIGHT, &mut rng))
+                .collect();
+            dealt.sort_by_key(|side| SpawnSide::ALL.iter().position(|other| other == side));
+
+            assert_eq!(dealt, SpawnSide::ALL);
+        }
+    }
+
+    #[test]
+    fn bag_deals_the_far_side_first_more_often() {
+        let player_position = Vec3::new(-WIDTH / 2.0, 0.0, 0.0);
+        let mut rng = GameRng::from_seed(444);
+        let bags = 1_000;
+
+        let (mut right_first, mut left_last) = (0, 0);
+        for _ in 0..bags {
+            let mut bag = SpawnSideBag::default();
+            let dealt: Vec<_> = (0..4)
+                .map(|_| bag.draw(player_position, WIDTH, HEIGHT, &mut rng))
+                .collect();
+            right_first += (dealt[0] == SpawnSide::Right) as u32;
+            left_last += (dealt[3] == SpawnSide::Left) as u32;
+        }
+
+        assert!(right_first > bags / 3);
+        assert!(left_last > bags / 2);
+    }
+
+    #[test]
+    fn purist_rules_skip_the_bag() {
+        let mut rules = GameRules::for_difficulty(Difficulty::Normal);
+        rules.spawn_side_bag = false;
+        let mut bag = SpawnSideBag::default();
+        let mut rng = GameRng::from_seed(444);
+
+        bag.next_side(&rules, Vec3::ZERO, WIDTH, HEIGHT, &mut rng);
+
+        assert!(bag.0.is_empty());
+    }
+
+    #[test]
+    fn rolled_stats_stay_within_the_floor_and_ceiling() {
+        let mut rng = GameRng::from_seed(458);
+
+        for points in [0, 20, 60] {
+            for enemy_type in EnemyType::ALL {
+                for _ in 0..10_000 {
+                    let stats = roll_enemy_stats(&mut rng, points, enemy_type);
+
+                    assert!(stats.speed >= ENEMY_MIN_SPEED * ENEMY_STAT_FLOOR_SCALE);
+                    assert!(stats.speed <= ENEMY_MAX_SPEED * ENEMY_STAT_CEILING_SCALE);
+                    assert!(stats.accel >= ENEMY_MIN_ACCEL * ENEMY_STAT_FLOOR_SCALE);
+                    assert!(stats.accel <= ENEMY_MAX_ACCEL * ENEMY_STAT_CEILING_SCALE);
+                    assert!((0.0..1.0).contains(&stats.future_prediction));
+                    assert!(stats.coin_pull.abs() <= 1.0);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+/// Snapshot of enemy positions and velocities bucketed by cell, built once per frame so the
+/// parallel steering loop can look up neighbors without touching the query.
+pub struct NeighborGrid {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<(Entity, Vec3, Vec3)>>,
+}
+
+pub struct Neighborhood {
+    pub heading: Vec3,
+    pub centroid: Vec3,
+}
+
+impl NeighborGrid {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+        }
+    }
+
+    fn cell(&self, position: Vec3) -> (i32, i32) {
+        (
+            (position.x / self.cell_size).floor() as i32,
+            (position.y / self.cell_size).floor() as i32,
+        )
+    }
+
+    pub fn insert(&mut self, entity: Entity, position: Vec3, velocity: Vec3) {
+        let cell = self.cell(position);
+        self.cells
+            .entry(cell)
+            .or_default()
+            .push((entity, position, velocity));
+    }
+
+    pub fn neighborhood(&self, entity: Entity, position: Vec3) -> Option<Neighborhood> {
+        let (cell_x, cell_y) = self.cell(position);
+        let mut heading = Vec3::ZERO;
+        let mut centroid = Vec3::ZERO;
+        let mut count = 0;
+
+        for x in cell_x - 1..=cell_x + 1 {
+            for y in cell_y - 1..=cell_y + 1 {
+                let Some(cell) = self.cells.get(&(x, y)) else {
+                    continue;
+                };
+
+                for (other, other_position, other_velocity) in cell {
+                    if *other == entity || other_position.distance(position) > self.cell_size {
+                        continue;
+                    }
+                    heading += other_velocity.normalize_or_zero();
+                    centroid += *other_position;
+                    count += 1;
+                }
+            }
+        }
+
+        (count > 0).then(|| Neighborhood {
+            heading: heading.normalize_or_zero(),
+            centroid: centroid / count as f32,
+        })
+    }
+
+    pub fn any_within(&self, entity: Entity, position: Vec3) -> bool {
+        let (cell_x, cell_y) = self.cell(position);
+
+        (cell_x - 1..=cell_x + 1).any(|x| {
+            (cell_y - 1..=cell_y + 1).any(|y| {
+                self.cells.get(&(x, y)).is_some_and(|cell| {
+                    cell.iter().any(|(other, other_position, _)| {
+                        *other != entity && other_position.distance(position) <= self.cell_size
+                    })
+                })
+            })
+        })
+    }
+}
@@ -0,0 +1,1580 @@
+use std::{collections::HashMap, f32::consts::E};
+
+use bevy::{audio::PlaybackMode, input::InputSystem, prelude::*, window::PrimaryWindow};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    accent::{cleanup_playfield_accent, setup_playfield_accent, update_playfield_accent},
+    aggression::{update_aggression, AggressionModifier, LastHitTime},
+    assets::AssetHandles,
+    assist::{spawn_coin_arrow, update_coin_arrow},
+    audio::{AudioAvailable, Muted, SfxBudget},
+    bench::{begin_span, end_span, BenchScenario, BenchSpan, BenchSpans},
+    coin::{
+        announce_relocated_coin, charge_coin, coin_value, coin_value_label, contest_relocated_coin,
+        expire_coin_beacons, get_coin_spawn_position, update_coin_value_labels, Coin, CoinCharge,
+        CoinContestTimer, CoinRelocated, CoinRing, CoinSpawnConstraints, CoinSpawnHistory,
+    },
+    config::*,
+    cornered::{detect_cornered, Cornered, DelayedSpawns},
+    deposit::{
+        bank_carried, collect_dropped_coins, spawn_home_zone, spill_carried, update_carried_text,
+    },
+    descriptor::{QueuedSeed, RunDescriptor},
+    effects::{
+        spawn_edge_flash, spawn_floating_text, spawn_hit_indicator, BannerQueue, ScreenShake,
+    },
+    enemy::{
+        enforce_enemy_cap, get_enemy_spawn_position, move_enemy, record_enemy_stat_range,
+        register_enemy_stat_diagnostics, spawn_enemy, tick_dying_enemies, Enemy, EnemyRemoved,
+        EnemySpawned, EnemyType, SpawnSideBag,
+    },
+    game_time::{register_game_time, GameTime},
+    grace::{spawn_grace_indicator, tick_post_hit_grace, PostHitGrace},
+    hud::{HudElement, HudKey},
+    leader::show_pack_auras,
+    lunge::{flash_lunge_outlines, update_lunges},
+    menu::LastScore,
+    menu_list::MenuListEvent,
+    movement::{quantize_motion, wraparound, Velocity, Wraparound},
+    music::{play_health_sting, spawn_music_layers, MusicLayer},
+    mutator::{ActiveMutators, Mutator},
+    pattern::{collect_pattern_coins, expire_pattern_coins, spawn_coin_patterns, CoinPatterns},
+    pause::PauseState,
+    pickup::{
+        expire_pickups, pickup_collision, spawn_pickups, HitPickup, Pickup, PickupKind,
+        PickupSpawner,
+    },
+    player::{
+        change_movement_feel, move_player, random_safe_spawn_position, sample_player_input,
+        tick_invincibility, Action, Dash, InputBindings, Invincibility, InvincibilitySource,
+        Player, PlayerInput,
+    },
+    render_z,
+    rng::GameRng,
+    score::{apply_score_events, update_score_text, ScoreEvent},
+    session::SessionStats,
+    shrink::{
+        apply_size_modifier, collect_shrink_pickups, player_radius, tick_size_modifier,
+        SizeModifier,
+    },
+    speed_ribbon::{update_speed_ribbon, SpeedRibbon},
+    storage::{current_day, insert_record, RunRecord, SaveData},
+    tint::{instance_material, TintController},
+    trail::{update_coin_trail, CoinTrail},
+    transition::{RequestedTransition, Transition},
+    util::{self, StateSnapshot},
+    victory::Victory,
+    window::not_suspended,
+    wrap_hint::update_wrap_hints,
+    AppState,
+};
+
+pub struct GamePlugin;
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameMode>()
+            .init_resource::<PlayerInput>()
+            .add_event::<HitPlayer>()
+            .add_event::<HitPickup>()
+            .add_event::<CoinRelocated>()
+            .add_event::<GainHealth>()
+            .add_event::<ScoreEvent>()
+            .add_event::<EnemySpawned>()
+            .add_event::<EnemyRemoved>()
+            .add_systems(
+                Update,
+                (
+                    restart,
+                    recover_music.run_if(not_suspended),
+                    record_pacing
+                        .run_if(in_state(PauseState::Running))
+                        .run_if(not_suspended),
+                )
+                    .run_if(in_state(AppState::Game)),
+            )
+            .add_systems(Update, (change_movement_feel, record_enemy_stat_range))
+            .add_systems(
+                PreUpdate,
+                sample_player_input
+                    .after(InputSystem)
+                    .run_if(in_state(AppState::Game)),
+            )
+            .add_systems(
+                FixedUpdate,
+                record_checksums.run_if(in_state(AppState::Game)),
+            )
+            .add_systems(
+                Update,
+                (
+                    move_player,
+                    update_lunges,
+                    flash_lunge_outlines,
+                    (update_aggression, move_enemy).chain(),
+                    show_pack_auras,
+                    wraparound,
+                    quantize_motion.run_if(deterministic_run),
+                    charge_coin,
+                    enemy_collision,
+                    tick_invincibility,
+                    tick_post_hit_grace,
+                    hit_player,
+                    pickup_collision.run_if(run_in_progress),
+                    (
+                        detect_cornered,
+                        hit_coin,
+                        release_delayed_spawns,
+                        announce_relocated_coin,
+                        contest_relocated_coin,
+                        expire_coin_beacons,
+                    )
+                        .chain()
+                        .run_if(run_in_progress),
+                    update_playfield_accent,
+                    // The bench scenario runs far more enemies than a real run allows.
+                    enforce_enemy_cap.run_if(not(resource_exists::<BenchScenario>())),
+                    tick_dying_enemies,
+                    update_coin_trail,
+                    update_coin_arrow,
+                    update_music,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Game))
+                    .run_if(in_state(PauseState::Running))
+                    .run_if(not_suspended),
+            )
+            .add_systems(
+                Update,
+                (
+                    spill_carried,
+                    collect_dropped_coins.run_if(run_in_progress),
+                    bank_carried.run_if(run_in_progress),
+                )
+                    .chain()
+                    .after(hit_coin)
+                    .run_if(in_state(AppState::Game))
+                    .run_if(in_state(PauseState::Running))
+                    .run_if(not_suspended)
+                    .run_if(resource_equals(GameMode::Deposit)),
+            )
+            .add_systems(
+                Update,
+                (
+                    spawn_coin_patterns,
+                    collect_pattern_coins,
+                    expire_pattern_coins,
+                    update_coin_value_labels,
+                )
+                    .chain()
+                    .after(hit_coin)
+                    .after(spill_carried)
+                    .run_if(in_state(AppState::Game))
+                    .run_if(in_state(PauseState::Running))
+                    .run_if(not_suspended)
+                    .run_if(run_in_progress),
+            )
+            .add_systems(
+                Update,
+                (
+                    spawn_pickups.run_if(run_in_progress),
+                    collect_shrink_pickups.run_if(run_in_progress),
+                    expire_pickups,
+                    tick_size_modifier,
+                    apply_size_modifier,
+                )
+                    .chain()
+                    .after(hit_coin)
+                    .after(collect_pattern_coins)
+                    .run_if(in_state(AppState::Game))
+                    .run_if(in_state(PauseState::Running))
+                    .run_if(not_suspended),
+            )
+            // Everything that sends ScoreEvents runs before they are applied, and the score and
+            // carried texts only read the applied totals.
+            .add_systems(
+                Update,
+                (
+                    apply_score_events,
+                    (
+                        update_score_text,
+                        update_carried_text.run_if(resource_equals(GameMode::Deposit)),
+                    ),
+                )
+                    .chain()
+                    .after(hit_coin)
+                    .after(collect_pattern_coins)
+                    .after(spill_carried)
+                    .after(collect_dropped_coins)
+                    .after(bank_carried)
+                    .run_if(in_state(AppState::Game))
+                    .run_if(in_state(PauseState::Running))
+                    .run_if(not_suspended),
+            )
+            .add_systems(
+                Update,
+                // The menu backdrop runs wraparound too, so this orders against the system chained
+                // right after the game's.
+                (update_wrap_hints, update_speed_ribbon)
+                    .after(quantize_motion)
+                    .run_if(in_state(AppState::Game))
+                    .run_if(in_state(PauseState::Running))
+                    .run_if(not_suspended),
+            )
+            .add_systems(
+                Update,
+                (spawn_music_layers, play_health_sting)
+                    .after(hit_coin)
+                    .run_if(in_state(AppState::Game))
+                    .run_if(in_state(PauseState::Running))
+                    .run_if(not_suspended),
+            )
+            .add_systems(
+                OnEnter(AppState::Game),
+                (setup_game, setup_playfield_accent),
+            )
+            .add_systems(
+                OnExit(AppState::Game),
+                (
+                    (cleanup_game, cleanup_playfield_accent),
+                    apply_deferred,
+                    check_teardown,
+                )
+                    .chain(),
+            );
+
+        register_enemy_stat_diagnostics(app);
+        register_game_time(app);
+        register_bench_spans(app);
+    }
+}
+
+// Brackets the measured systems tightly, between their direct neighbors in the Update chain.
+fn register_bench_spans(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            begin_span(BenchSpan::MoveEnemy)
+                .after(update_aggression)
+                .before(move_enemy),
+            end_span(BenchSpan::MoveEnemy)
+                .after(move_enemy)
+                .before(show_pack_auras),
+            begin_span(BenchSpan::Collision)
+                .after(charge_coin)
+                .before(enemy_collision),
+            end_span(BenchSpan::Collision)
+                .after(enemy_collision)
+                .before(tick_invincibility),
+            begin_span(BenchSpan::Collision)
+                .after(hit_player)
+                .before(pickup_collision),
+            end_span(BenchSpan::Collision)
+                .after(pickup_collision)
+                .before(detect_cornered),
+        )
+            .run_if(resource_exists::<BenchSpans>())
+            .run_if(in_state(AppState::Game))
+            .run_if(in_state(PauseState::Running))
+            .run_if(not_suspended),
+    );
+}
+
+#[derive(Resource)]
+pub struct GameInfo {
+    pub(crate) points: i32,
+    pub(crate) score: i32,
+    pub(crate) carried: i32,
+    pub(crate) health: i8,
+}
+
+impl GameInfo {
+    pub fn points(&self) -> i32 {
+        self.points
+    }
+
+    pub fn score(&self) -> i32 {
+        self.score
+    }
+
+    pub fn carried(&self) -> i32 {
+        self.carried
+    }
+
+    pub fn health(&self) -> i8 {
+        self.health
+    }
+
+    // Latched by the fatal hit itself, a frame before the GameOver state applies. From then on
+    // nothing may score, pick up or spawn.
+    pub fn run_over(&self) -> bool {
+        self.health <= 0
+    }
+
+    pub(crate) fn add_health(&mut self, health: i8) {
+        self.health = (self.health + health).min(5);
+    }
+}
+
+impl Default for GameInfo {
+    fn default() -> Self {
+        Self {
+            points: 0,
+            score: 0,
+            carried: 0,
+            health: PLAYER_HEALTH,
+        }
+    }
+}
+
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GameMode {
+    #[default]
+    Classic,
+    Zen,
+    Deposit,
+    // The guided first run. It keeps no records, so it isn't in ALL.
+    Tutorial,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 3] = [GameMode::Classic, GameMode::Zen, GameMode::Deposit];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::Classic => "Classic",
+            GameMode::Zen => "Zen",
+            GameMode::Deposit => "Deposit",
+            GameMode::Tutorial => "Tutorial",
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct RunStats {
+    pub encountered: HashMap<EnemyType, u32>,
+    pub removed: HashMap<EnemyType, u32>,
+    pub elapsed: f32,
+    pub coin_times: Vec<f32>,
+    pub hits: u32,
+}
+
+#[derive(Resource, Default)]
+pub struct StateChecksums {
+    elapsed: f32,
+    recorded: Vec<u64>,
+    expected: Option<Vec<u64>>,
+    diverged: bool,
+}
+
+impl StateChecksums {
+    pub fn recorded(&self) -> &[u64] {
+        &self.recorded
+    }
+
+    pub fn expected(&self) -> Option<&[u64]> {
+        self.expected.as_deref()
+    }
+
+    pub fn expect(&mut self, expected: Vec<u64>) {
+        self.expected = Some(expected);
+    }
+
+    pub fn diverged(&self) -> bool {
+        self.diverged
+    }
+}
+
+#[derive(Component)]
+pub struct Music;
+
+#[derive(Component)]
+pub struct ScoreText;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HitSource {
+    Enemy(EnemyType),
+    // Nothing in the base game fires one yet, but a plugin can send hits from its own.
+    Projectile,
+}
+
+#[derive(Event)]
+pub struct HitPlayer {
+    pub source: HitSource,
+    pub direction: Vec3,
+    pub attacker_position: Vec3,
+}
+
+#[derive(Event, Default)]
+pub struct GainHealth;
+
+fn setup_game(
+    mut commands: Commands,
+    window: Query<&Window, With<PrimaryWindow>>,
+    asset_handles: Res<AssetHandles>,
+    save_data: Res<SaveData>,
+    game_mode: Res<GameMode>,
+    game_config: Res<GameConfig>,
+    mutators: Res<ActiveMutators>,
+    mut banners: ResMut<BannerQueue>,
+    mut queued_seed: ResMut<QueuedSeed>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    audio: Res<AudioAvailable>,
+) {
+    commands.init_resource::<GameInfo>();
+
+    let mut run_config = game_config.clone();
+    let mut game_rules = GameRules::for_difficulty(save_data.settings.difficulty);
+    mutators.apply(&mut run_config, &mut game_rules);
+    save_data
+        .settings
+        .arena
+        .apply(&mut run_config, &mut game_rules);
+    game_rules.victory = save_data.settings.victory && *game_mode == GameMode::Classic;
+    game_rules.safe_area = save_data.settings.safe_area;
+
+    commands.insert_resource(RunStats::default());
+    commands.insert_resource(PostHitGrace::default());
+    spawn_grace_indicator(&mut commands, &asset_handles);
+    commands.insert_resource(CoinTrail::default());
+    commands.insert_resource(SpeedRibbon::default());
+    commands.insert_resource(StateChecksums::default());
+    // The run starts after this frame's input was sampled, so the previous run's last input would
+    // otherwise move the new player for a frame.
+    commands.insert_resource(PlayerInput::default());
+
+    game_rules.deterministic = save_data.settings.deterministic && queued_seed.0.is_some();
+    let seed = queued_seed.0.take().unwrap_or_else(rand::random);
+    let mut rng = GameRng::from_seed(seed);
+    commands.insert_resource(RunDescriptor::new(
+        seed,
+        *game_mode,
+        save_data.settings.difficulty,
+        &mutators,
+    ));
+
+    commands.spawn((
+        ScoreText,
+        HudElement::new(HudKey::Score),
+        Text2dBundle {
+            text: Text::from_section(
+                "0",
+                TextStyle {
+                    font: asset_handles.font.clone(),
+                    font_size: 420.0,
+                    color: Color::DARK_GRAY,
+                },
+            )
+            .with_alignment(TextAlignment::Center),
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, render_z::BACKDROP)),
+            ..default()
+        },
+    ));
+
+    // Nothing can reach the player before they've had a moment to find the keys.
+    let mut invincibility = Invincibility::default();
+    if game_rules.start_grace {
+        invincibility.grant(InvincibilitySource::Spawn);
+        banners.push("Go!");
+    }
+
+    // The first coin keeps away from a spawn point known up front; a random one keeps away from
+    // the coin instead.
+    let window = window.single();
+    let half_extent = Vec2::new(window.width(), window.height()) / 2.0 - run_config.player_radius;
+    let planned_spawn = game_rules.player_spawn.fixed_position(half_extent);
+    let mut coin_history = CoinSpawnHistory::default();
+    let coin_position = get_coin_spawn_position(
+        &mut rng,
+        &mut coin_history,
+        &CoinSpawnConstraints::new(
+            window.width(),
+            window.height(),
+            planned_spawn.unwrap_or(Vec2::ZERO),
+            0,
+            &game_rules,
+        ),
+    );
+    let spawn_position = planned_spawn.unwrap_or_else(|| {
+        random_safe_spawn_position(&mut rng, half_extent, coin_position.truncate())
+    });
+
+    let player_tint = TintController::new(PLAYER_COLOR);
+    let player_material =
+        instance_material(&mut materials, &asset_handles.player_material, &player_tint);
+
+    let player = commands
+        .spawn((
+            Player,
+            invincibility,
+            Dash::default(),
+            player_tint,
+            Wraparound {
+                radius: run_config.player_radius,
+            },
+            // Always spawned at rest, whatever the previous player was doing.
+            Velocity(Vec3::ZERO),
+            ColorMesh2dBundle {
+                mesh: asset_handles.player_mesh.clone().into(),
+                material: player_material,
+                transform: Transform::from_translation(spawn_position.extend(render_z::PLAYER))
+                    .with_scale(Vec3::splat(run_config.player_radius / PLAYER_RADIUS)),
+                ..default()
+            },
+        ))
+        .id();
+    spawn_coin_arrow(&mut commands, &asset_handles, &save_data, player);
+    if *game_mode == GameMode::Deposit {
+        spawn_home_zone(&mut commands, &asset_handles, player);
+    }
+
+    if audio.0 {
+        commands.spawn((
+            AudioBundle {
+                source: match *game_mode {
+                    GameMode::Classic | GameMode::Deposit => asset_handles.music_3.clone(),
+                    GameMode::Zen | GameMode::Tutorial => asset_handles.music_5.clone(),
+                },
+                settings: PlaybackSettings::LOOP,
+            },
+            Music,
+        ));
+    }
+
+    commands
+        .spawn((
+            Coin::default(),
+            Pickup::new(PickupKind::Coin),
+            CoinCharge::default(),
+            Wraparound { radius: 0.0 },
+            ColorMesh2dBundle {
+                mesh: asset_handles.coin_mesh.clone().into(),
+                material: asset_handles.coin_material.clone(),
+                transform: Transform::from_translation(coin_position)
+                    .with_scale(Vec3::splat(run_config.coin_radius / COIN_RADIUS)),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(coin_value_label(&asset_handles));
+        });
+
+    commands.insert_resource(rng);
+    commands.insert_resource(coin_history);
+    commands.insert_resource(SpawnSideBag::default());
+    commands.insert_resource(Cornered::default());
+    commands.insert_resource(CoinContestTimer::default());
+    commands.insert_resource(LastHitTime::default());
+    commands.insert_resource(AggressionModifier::default());
+    commands.insert_resource(DelayedSpawns::default());
+    commands.insert_resource(CoinPatterns::default());
+    commands.insert_resource(PickupSpawner::default());
+    commands.insert_resource(game_rules);
+    commands.insert_resource(RunConfig(run_config));
+}
+
+fn cleanup_game(
+    game_info: Res<GameInfo>,
+    game_mode: Res<GameMode>,
+    run_stats: Res<RunStats>,
+    mutators: Res<ActiveMutators>,
+    mut last_score: ResMut<LastScore>,
+    mut save_data: ResMut<SaveData>,
+    mut session_stats: ResMut<SessionStats>,
+    mut commands: Commands,
+    query: Query<
+        Entity,
+        (
+            Without<Camera2d>,
+            Without<Window>,
+            Without<Handle<AudioSource>>,
+            Without<PlaybackSettings>,
+        ),
+    >,
+    music_query: Query<Entity, Or<(With<Music>, With<MusicLayer>)>>,
+) {
+    if *game_mode != GameMode::Tutorial {
+        session_stats.record_run(game_info.score, &run_stats);
+
+        let record = RunRecord {
+            score: game_info.score,
+            time: run_stats.elapsed,
+            difficulty: save_data.settings.difficulty,
+            feel: save_data.settings.movement_feel,
+            arena: save_data.settings.arena,
+            day: current_day(),
+            score_decay: mutators.contains(Mutator::ScoreDecay),
+        };
+        insert_record(save_data.records.entry(*game_mode).or_default(), record);
+    }
+
+    match *game_mode {
+        GameMode::Deposit => last_score.0 = Some(game_info.score),
+        GameMode::Classic => {
+            last_score.0 = Some(game_info.score);
+
+            if !mutators.is_empty() {
+                let key = mutators.record_key(save_data.settings.movement_feel);
+                let best = save_data.mutator_bests.entry(key).or_default();
+                *best = (*best).max(game_info.score);
+            } else if save_data
+                .best_score
+                .is_none_or(|best| game_info.score > best)
+            {
+                save_data.best_score = Some(game_info.score);
+            }
+        }
+        GameMode::Zen => {
+            if save_data.zen_best.is_none_or(|best| game_info.score > best) {
+                save_data.zen_best = Some(game_info.score);
+                save_data.zen_best_feel = save_data.settings.movement_feel;
+            }
+            commands.remove_resource::<RunStats>();
+        }
+        GameMode::Tutorial => commands.remove_resource::<RunStats>(),
+    }
+
+    commands.remove_resource::<GameInfo>();
+
+    query.iter().for_each(|entity| {
+        commands.entity(entity).despawn();
+    });
+
+    music_query.iter().for_each(|entity| {
+        commands.entity(entity).despawn();
+    });
+}
+
+// Debug builds only: after teardown, nothing but the camera, the window and one-shot sounds
+// that despawn themselves should be left. Anything else is logged, grouped by its components.
+fn check_teardown(world: &World) {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+
+    let mut leaked: HashMap<String, u32> = HashMap::new();
+    for entity in world.iter_entities() {
+        if entity.contains::<Camera2d>() || entity.contains::<Window>() {
+            continue;
+        }
+        if entity
+            .get::<PlaybackSettings>()
+            .is_some_and(|settings| matches!(settings.mode, PlaybackMode::Despawn))
+        {
+            continue;
+        }
+
+        let components = world
+            .inspect_entity(entity.id())
+            .iter()
+            .map(|info| info.name().rsplit("::").next().unwrap_or(info.name()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        *leaked.entry(components).or_default() += 1;
+    }
+
+    for (components, count) in leaked {
+        warn!("{} entities left after teardown: [{}]", count, components);
+    }
+}
+
+fn restart(
+    bindings: Res<InputBindings>,
+    input: Res<Input<KeyCode>>,
+    mut transitions: ResMut<RequestedTransition>,
+) {
+    if bindings.just_pressed(Action::Restart, &input) {
+        transitions.request(Transition::Restart);
+    }
+}
+
+fn update_music(
+    mut hit_player: EventReader<HitPlayer>,
+    mut gain_health: EventReader<GainHealth>,
+    game_info: Res<GameInfo>,
+    game_mode: Res<GameMode>,
+    asset_handles: Res<AssetHandles>,
+    query: Query<Entity, With<Music>>,
+    mut commands: Commands,
+    audio: Res<AudioAvailable>,
+) {
+    // However many hits and heals landed, the track only follows where health ended up, once.
+    let changed = hit_player.read().count() + gain_health.read().count() > 0;
+    if matches!(*game_mode, GameMode::Zen | GameMode::Tutorial)
+        || !changed
+        || game_info.health == 0
+        || !audio.0
+    {
+        return;
+    }
+
+    query.iter().for_each(|music| {
+        commands.entity(music).despawn();
+    });
+
+    commands.spawn((
+        AudioBundle {
+            source: get_music_handle(asset_handles, game_info.health),
+            settings: PlaybackSettings::LOOP,
+        },
+        Music,
+    ));
+}
+
+// A looping track whose sink has run dry means the output device went away. The track is
+// respawned for the current health, at most once per debounce window so a flapping device can't
+// spam restarts. Where no sink ever appears, detection never fires and only the pause menu's
+// "Restart audio" entry does.
+fn recover_music(
+    mut events: EventReader<MenuListEvent>,
+    mut since_restart: Local<f32>,
+    mut muted: ResMut<Muted>,
+    mut commands: Commands,
+    music_query: Query<(Entity, Option<&AudioSink>), With<Music>>,
+    game_info: Res<GameInfo>,
+    game_mode: Res<GameMode>,
+    asset_handles: Res<AssetHandles>,
+    time: Res<Time<Real>>,
+    audio: Res<AudioAvailable>,
+) {
+    *since_restart += time.delta_seconds();
+    if !audio.0 {
+        return;
+    }
+
+    let requested = events.read().any(|event| {
+        *event
+            == MenuListEvent::Activated {
+                list: "pause",
+                entry: "restart_audio",
+            }
+    });
+    let silent = music_query
+        .iter()
+        .any(|(_, sink)| sink.is_some_and(|sink| sink.empty()));
+
+    let restart = requested || (silent && *since_restart >= AUDIO_RECOVERY_DEBOUNCE);
+    if !restart {
+        return;
+    }
+    *since_restart = 0.0;
+
+    music_query.iter().for_each(|(entity, _)| {
+        commands.entity(entity).despawn();
+    });
+
+    let source = match *game_mode {
+        GameMode::Zen | GameMode::Tutorial => asset_handles.music_5.clone(),
+        GameMode::Classic | GameMode::Deposit => {
+            get_music_handle(asset_handles, game_info.health.clamp(1, 5))
+        }
+    };
+    commands.spawn((
+        AudioBundle {
+            source,
+            settings: PlaybackSettings::LOOP,
+        },
+        Music,
+    ));
+
+    // Re-applies the volume to the new sink.
+    muted.set_changed();
+}
+
+fn get_music_handle(asset_handles: Res<AssetHandles>, health: i8) -> Handle<AudioSource> {
+    match health {
+        1 => asset_handles.music_1.clone(),
+        2 => asset_handles.music_2.clone(),
+        3 => asset_handles.music_3.clone(),
+        4 => asset_handles.music_4.clone(),
+        5 => asset_handles.music_5.clone(),
+        _ => unreachable!(),
+    }
+}
+
+pub fn hit_coin(
+    mut hit_pickup: EventReader<HitPickup>,
+    mut gain_health: EventWriter<GainHealth>,
+    mut enemy_spawned: EventWriter<EnemySpawned>,
+    mut game_info: ResMut<GameInfo>,
+    mut rng: ResMut<GameRng>,
+    (mut trail, mut coin_history, mut spawn_bag, mut coin_relocated): (
+        ResMut<CoinTrail>,
+        ResMut<CoinSpawnHistory>,
+        ResMut<SpawnSideBag>,
+        EventWriter<CoinRelocated>,
+    ),
+    mut grace: ResMut<PostHitGrace>,
+    mut score_events: EventWriter<ScoreEvent>,
+    mut commands: Commands,
+    mut coin_query: Query<(
+        Entity,
+        &mut Coin,
+        &mut CoinCharge,
+        &mut Transform,
+        &mut Handle<ColorMaterial>,
+    )>,
+    ring_query: Query<Entity, With<CoinRing>>,
+    mut player_query: Query<(&Transform, &mut Invincibility), (With<Player>, Without<Coin>)>,
+    asset_handles: Res<AssetHandles>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    (game_mode, game_rules, cornered, mut delayed_spawns, mut budget): (
+        Res<GameMode>,
+        Res<GameRules>,
+        Res<Cornered>,
+        ResMut<DelayedSpawns>,
+        ResMut<SfxBudget>,
+    ),
+) {
+    let events = hit_pickup
+        .read()
+        .filter(|hit| hit.kind == PickupKind::Coin)
+        .count();
+    if events == 0 {
+        return;
+    }
+
+    let (coin_entity, mut coin, mut charge, mut transform, mut material) = coin_query.single_mut();
+    let (player_transform, mut invincibility) = player_query.single_mut();
+    let window = window.single();
+
+    // Each pickup is scored, sounded and answered with a spawn on its own; the coin moves on after
+    // every one of them. The score itself is applied later, from the events; `points` runs ahead
+    // of it for this frame's health coins and spawns.
+    let mut points = game_info.points;
+    for _ in 0..events {
+        let previous_points = points;
+        points += 1 + charge.bonus;
+
+        let value = coin_value(&charge, *game_mode, &game_rules);
+        score_events.send(ScoreEvent::CoinCollected {
+            points: 1 + charge.bonus,
+            value,
+        });
+
+        if coin.health {
+            game_info.add_health(1);
+            if game_rules.invincibility_frames {
+                invincibility.grant(InvincibilitySource::Pickup);
+            }
+            gain_health.send_default();
+            budget.play(
+                &mut commands,
+                asset_handles.health_sound.clone(),
+                PlaybackSettings::DESPAWN,
+            );
+        } else {
+            budget.play(
+                &mut commands,
+                asset_handles.coin_sound.clone(),
+                PlaybackSettings::DESPAWN,
+            );
+        }
+
+        spawn_floating_text(
+            &mut commands,
+            &asset_handles,
+            format!("+{}", value),
+            transform.translation,
+            if coin.health {
+                HEALTH_COLOR
+            } else {
+                COIN_COLOR
+            },
+        );
+
+        trail.push(transform.translation);
+
+        transform.translation = get_coin_spawn_position(
+            &mut rng,
+            &mut coin_history,
+            &CoinSpawnConstraints {
+                opposite_half: cornered.active,
+                ..CoinSpawnConstraints::new(
+                    window.width(),
+                    window.height(),
+                    player_transform.translation.truncate(),
+                    points,
+                    &game_rules,
+                )
+            },
+        );
+        coin_relocated.send(CoinRelocated {
+            position: transform.translation,
+            player_position: player_transform.translation,
+        });
+
+        *charge = CoinCharge::default();
+
+        coin.health = !matches!(*game_mode, GameMode::Zen | GameMode::Tutorial)
+            && game_rules.health_coins
+            && points / HEALTH_MULTIPLE > previous_points / HEALTH_MULTIPLE;
+
+        // The tutorial brings in its own enemies.
+        if matches!(*game_mode, GameMode::Zen | GameMode::Tutorial) || grace.consume() {
+            continue;
+        }
+
+        if cornered.active {
+            delayed_spawns.push(points);
+            continue;
+        }
+
+        enemy_spawned.send(spawn_next_enemy(
+            &mut commands,
+            &mut materials,
+            &mut rng,
+            &mut spawn_bag,
+            &game_rules,
+            player_transform.translation,
+            window,
+            points,
+            &asset_handles,
+        ));
+    }
+
+    // Only the rings go; the value label stays on the coin.
+    let rings: Vec<_> = ring_query.iter().collect();
+    commands.entity(coin_entity).remove_children(&rings);
+    rings.into_iter().for_each(|entity| {
+        commands.entity(entity).despawn();
+    });
+
+    *material = if coin.health {
+        asset_handles.health_material.clone()
+    } else {
+        asset_handles.coin_material.clone()
+    };
+}
+
+fn spawn_next_enemy(
+    commands: &mut Commands,
+    materials: &mut Assets<ColorMaterial>,
+    rng: &mut GameRng,
+    spawn_bag: &mut SpawnSideBag,
+    game_rules: &GameRules,
+    player_position: Vec3,
+    window: &Window,
+    points: i32,
+    asset_handles: &AssetHandles,
+) -> EnemySpawned {
+    let spawn_side = spawn_bag.next_side(
+        game_rules,
+        player_position,
+        window.width(),
+        window.height(),
+        rng,
+    );
+    let spawn_position = get_enemy_spawn_position(window.width(), window.height(), spawn_side, rng);
+
+    spawn_enemy(
+        commands,
+        materials,
+        rng,
+        points,
+        asset_handles,
+        EnemyType::for_points(points),
+        spawn_position,
+    )
+}
+
+// Held-back spawns come in as soon as the player is out of the corner, or when their delay runs
+// out regardless.
+fn release_delayed_spawns(
+    mut commands: Commands,
+    mut delayed_spawns: ResMut<DelayedSpawns>,
+    mut enemy_spawned: EventWriter<EnemySpawned>,
+    mut rng: ResMut<GameRng>,
+    mut spawn_bag: ResMut<SpawnSideBag>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    cornered: Res<Cornered>,
+    game_rules: Res<GameRules>,
+    asset_handles: Res<AssetHandles>,
+    game_time: Res<GameTime>,
+    player_query: Query<&Transform, With<Player>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+) {
+    if delayed_spawns.0.is_empty() {
+        return;
+    }
+    let (Ok(player_transform), Ok(window)) = (player_query.get_single(), window.get_single())
+    else {
+        return;
+    };
+
+    let mut released = Vec::new();
+    delayed_spawns.0.retain_mut(|(timer, points)| {
+        let due = timer.tick(game_time.delta()).finished() || !cornered.active;
+        if due {
+            released.push(*points);
+        }
+        !due
+    });
+
+    for points in released {
+        enemy_spawned.send(spawn_next_enemy(
+            &mut commands,
+            &mut materials,
+            &mut rng,
+            &mut spawn_bag,
+            &game_rules,
+            player_transform.translation,
+            window,
+            points,
+            &asset_handles,
+        ));
+    }
+}
+
+fn record_checksums(
+    mut checksums: ResMut<StateChecksums>,
+    game_info: Res<GameInfo>,
+    rng: Res<GameRng>,
+    player_transform: Query<&Transform, With<Player>>,
+    enemy_query: Query<(), With<Enemy>>,
+    time: Res<Time>,
+) {
+    checksums.elapsed += time.delta_seconds();
+    if checksums.elapsed < 1.0 {
+        return;
+    }
+    checksums.elapsed -= 1.0;
+
+    let Ok(player_transform) = player_transform.get_single() else {
+        return;
+    };
+
+    let checksum = util::state_checksum(&StateSnapshot {
+        player_position: player_transform.translation.truncate(),
+        points: game_info.points,
+        health: game_info.health,
+        enemy_count: enemy_query.iter().count(),
+        rng_words: rng.words(),
+    });
+    checksums.recorded.push(checksum);
+
+    if checksums.diverged {
+        return;
+    }
+
+    let divergence = checksums
+        .expected
+        .as_deref()
+        .and_then(|expected| util::first_divergence(&checksums.recorded, expected));
+
+    if let Some(second) = divergence {
+        warn!(
+            "Replay desync: state diverged at second {} (seed {})",
+            second + 1,
+            rng.seed()
+        );
+        checksums.diverged = true;
+    }
+}
+
+fn record_pacing(
+    mut hit_pickup: EventReader<HitPickup>,
+    mut run_stats: ResMut<RunStats>,
+    game_time: Res<GameTime>,
+) {
+    run_stats.elapsed = game_time.elapsed_seconds();
+
+    for _ in hit_pickup.read().filter(|hit| hit.kind == PickupKind::Coin) {
+        let elapsed = run_stats.elapsed;
+        run_stats.coin_times.push(elapsed);
+    }
+}
+
+fn hit_player(
+    mut hit_event: EventReader<HitPlayer>,
+    run_config: Res<RunConfig>,
+    game_mode: Res<GameMode>,
+    game_rules: Res<GameRules>,
+    victory: Res<Victory>,
+    (mut grace, mut last_hit, game_time, mut budget): (
+        ResMut<PostHitGrace>,
+        ResMut<LastHitTime>,
+        Res<GameTime>,
+        ResMut<SfxBudget>,
+    ),
+    mut game_info: ResMut<GameInfo>,
+    mut run_stats: ResMut<RunStats>,
+    mut commands: Commands,
+    mut transitions: ResMut<RequestedTransition>,
+    mut screen_shake: Query<&mut ScreenShake>,
+    asset_handles: Res<AssetHandles>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut player_query: Query<
+        (
+            Entity,
+            &Transform,
+            &mut Invincibility,
+            Option<&SizeModifier>,
+        ),
+        (With<Player>, Without<Enemy>),
+    >,
+    mut enemy_query: Query<(&Transform, &mut Velocity), (With<Enemy>, Without<Player>)>,
+    window: Query<&Window, With<PrimaryWindow>>,
+) {
+    if *game_mode == GameMode::Zen || victory.is_celebrating() {
+        hit_event.clear();
+        return;
+    }
+
+    let (player, player_transform, mut invincibility, size) = player_query.single_mut();
+    let window = window.single();
+    let contact_distance = player_radius(&run_config, size) + ENEMY_RADIUS;
+
+    let mut landed = false;
+    for hit in hit_event.read() {
+        // Every hit counts, but the invincibility an earlier hit this frame granted absorbs the
+        // ones after it, as it would on any later frame. The fatal hit is the last one.
+        if (landed && invincibility.is_active()) || game_info.run_over() {
+            continue;
+        }
+        landed = true;
+
+        // Tutorial hits show what a hit looks and sounds like without costing anything.
+        if *game_mode != GameMode::Tutorial {
+            game_info.health -= 1;
+        }
+        run_stats.hits += 1;
+        last_hit.0 = game_time.elapsed_seconds();
+
+        if game_rules.post_hit_grace {
+            grace.start();
+        }
+
+        if game_rules.invincibility_frames {
+            invincibility.grant(InvincibilitySource::Hit);
+        }
+
+        spawn_hit_indicator(
+            &mut commands,
+            &mut materials,
+            &asset_handles,
+            player,
+            hit.direction,
+        );
+        spawn_edge_flash(
+            &mut commands,
+            &mut materials,
+            &asset_handles,
+            hit.attacker_position,
+            window.width(),
+            window.height(),
+        );
+
+        let feedback = run_config.hit_feedback(hit.source);
+        budget.play(
+            &mut commands,
+            asset_handles.hit_sound(hit.source),
+            PlaybackSettings::DESPAWN.with_speed(feedback.sound_speed),
+        );
+
+        screen_shake.single_mut().add_trauma(feedback.trauma);
+
+        if game_info.run_over() {
+            transitions.request(Transition::Pause(PauseState::GameOver));
+        }
+
+        enemy_query
+            .par_iter_mut()
+            .for_each(|(transform, mut velocity)| {
+                let offset = (transform.translation - player_transform.translation).truncate();
+                let direction = offset.normalize_or_zero().extend(0.0);
+                let distance = offset.length();
+
+                let speed =
+                    feedback.knockback * E.powf(HIT_DECAY_RATE * (distance - contact_distance));
+
+                velocity.0 += direction * speed;
+            });
+    }
+}
+
+// Run conditions are all evaluated even when an earlier one fails, and GameInfo only exists once
+// a run has started.
+pub fn run_in_progress(game_info: Option<Res<GameInfo>>) -> bool {
+    game_info.is_some_and(|game_info| !game_info.run_over())
+}
+
+fn deterministic_run(game_rules: Option<Res<GameRules>>) -> bool {
+    game_rules.is_some_and(|game_rules| game_rules.deterministic)
+}
+
+fn enemy_collision(
+    player_query: Query<
+        (&Transform, &Invincibility, &Dash, Option<&SizeModifier>),
+        (With<Player>, Without<Enemy>),
+    >,
+    enemy_query: Query<(&Transform, &Enemy), Without<Player>>,
+    mut hit_event: EventWriter<HitPlayer>,
+    run_config: Res<RunConfig>,
+) {
+    if player_query.is_empty() || enemy_query.is_empty() {
+        return;
+    }
+
+    let (player_transform, invincibility, dash, size) = player_query.single();
+    if invincibility.is_active() || dash.is_active() {
+        return;
+    }
+    let contact_distance = player_radius(&run_config, size) + ENEMY_RADIUS;
+
+    let attacker = enemy_query.iter().find(|(enemy_transform, _)| {
+        player_transform
+            .translation
+            .truncate()
+            .distance_squared(enemy_transform.translation.truncate())
+            < contact_distance.powf(2.0)
+    });
+
+    if let Some((enemy_transform, enemy)) = attacker {
+        hit_event.send(HitPlayer {
+            source: HitSource::Enemy(enemy.enemy_type),
+            direction: (enemy_transform.translation - player_transform.translation)
+                .normalize_or_zero(),
+            attacker_position: enemy_transform.translation,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{
+        ecs::system::{CommandQueue, SystemState},
+        time::TimeUpdateStrategy,
+    };
+
+    use super::*;
+    use crate::{enemy::despawn_enemy, steering::BehaviorId, victory::RunWon};
+
+    fn collision_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_event::<HitPlayer>()
+            .add_event::<EnemyRemoved>()
+            .insert_resource(RunConfig(GameConfig::default()))
+            .add_systems(Update, enemy_collision);
+        app.world.spawn((
+            Player,
+            Transform::default(),
+            Invincibility::default(),
+            Dash::default(),
+        ));
+        app
+    }
+
+    fn spawn_enemy_on_player(app: &mut App) -> Entity {
+        app.world
+            .spawn((
+                Transform::default(),
+                Enemy {
+                    enemy_type: EnemyType::Red,
+                    speed: 0.0,
+                    accel: 0.0,
+                    future_prediction: 0.0,
+                    coin_pull: 0.0,
+                    threat: 0.0,
+                    behavior: BehaviorId::RED,
+                },
+            ))
+            .id()
+    }
+
+    fn hits(app: &App) -> usize {
+        app.world.resource::<Events<HitPlayer>>().len()
+    }
+
+    #[test]
+    fn overlapping_enemy_hits_the_player() {
+        let mut app = collision_app();
+        spawn_enemy_on_player(&mut app);
+
+        app.update();
+
+        assert_eq!(hits(&app), 1);
+    }
+
+    #[test]
+    fn dying_enemy_overlapping_the_player_does_not_hit() {
+        let mut app = collision_app();
+        let enemy = spawn_enemy_on_player(&mut app);
+
+        let mut queue = CommandQueue::default();
+        despawn_enemy(
+            &mut Commands::new(&mut queue, &app.world),
+            enemy,
+            EnemyType::Red,
+        );
+        queue.apply(&mut app.world);
+        app.update();
+
+        assert_eq!(hits(&app), 0);
+        assert_eq!(app.world.resource::<Events<EnemyRemoved>>().len(), 1);
+        assert!(app.world.get_entity(enemy).is_some());
+    }
+
+    #[test]
+    fn long_hitch_moves_the_player_one_capped_step() {
+        let mut app = collision_app();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs(3)))
+            .add_state::<AppState>()
+            .add_state::<PauseState>()
+            .insert_resource(PlayerInput {
+                direction: Vec3::X,
+                ..default()
+            })
+            .init_resource::<SaveData>()
+            .add_systems(Update, move_player.before(enemy_collision));
+        register_game_time(&mut app);
+        let player = app
+            .world
+            .query_filtered::<Entity, With<Player>>()
+            .single(&app.world);
+        app.world
+            .entity_mut(player)
+            .insert(Velocity(Vec3::new(PLAYER_MAX_SPEED, 0.0, 0.0)));
+        // Close enough that one uncapped step of Bevy's own 250ms limit would land on it.
+        let enemy = spawn_enemy_on_player(&mut app);
+        app.world.get_mut::<Transform>(enemy).unwrap().translation.x = 60.0;
+        // The first frame has no delta to move with.
+        app.update();
+
+        app.update();
+
+        let moved = app.world.get::<Transform>(player).unwrap().translation.x;
+        assert!(moved > 0.0 && moved <= PLAYER_MAX_SPEED * MAX_FRAME_DELTA + 1e-3);
+        assert_eq!(hits(&app), 0);
+    }
+
+    // What a running game's systems need, with the player and the coin both at the centre.
+    fn run_app() -> App {
+        let mut app = collision_app();
+        app.add_plugins(AssetPlugin::default())
+            .init_asset::<Mesh>()
+            .init_asset::<ColorMaterial>()
+            .init_asset::<AudioSource>()
+            .init_asset::<Font>()
+            .add_event::<HitPickup>()
+            .add_event::<GainHealth>()
+            .add_event::<EnemySpawned>()
+            .add_event::<CoinRelocated>()
+            .add_event::<ScoreEvent>()
+            .add_event::<RunWon>()
+            .init_resource::<GameInfo>()
+            .insert_resource(GameRng::from_seed(7))
+            .init_resource::<GameMode>()
+            .init_resource::<GameRules>()
+            .init_resource::<GameTime>()
+            .init_resource::<Victory>()
+            .init_resource::<PostHitGrace>()
+            .init_resource::<LastHitTime>()
+            .init_resource::<SfxBudget>()
+            .init_resource::<RunStats>()
+            .init_resource::<RequestedTransition>()
+            .init_resource::<CoinTrail>()
+            .init_resource::<CoinSpawnHistory>()
+            .init_resource::<SpawnSideBag>()
+            .init_resource::<Cornered>()
+            .init_resource::<DelayedSpawns>();
+
+        let mut state = SystemState::<(
+            Res<AssetServer>,
+            ResMut<Assets<Mesh>>,
+            ResMut<Assets<ColorMaterial>>,
+        )>::new(&mut app.world);
+        let (asset_server, meshes, materials) = state.get_mut(&mut app.world);
+        let asset_handles = AssetHandles::new(asset_server, meshes, materials);
+        app.insert_resource(asset_handles);
+
+        app.world.spawn((Window::default(), PrimaryWindow));
+        app.world.spawn(ScreenShake::default());
+        app.world.spawn((
+            Coin::default(),
+            Pickup::new(PickupKind::Coin),
+            CoinCharge::default(),
+            Transform::default(),
+            Handle::<ColorMaterial>::default(),
+        ));
+        app
+    }
+
+    // The player sits on an enemy and the coin at once, with the systems between the hit and the
+    // score chained and gated as GamePlugin does.
+    fn fatal_frame_app(health: i8) -> App {
+        let mut app = run_app();
+        app.insert_resource(GameInfo {
+            health,
+            ..default()
+        })
+        .add_systems(
+            Update,
+            (
+                hit_player,
+                pickup_collision.run_if(run_in_progress),
+                hit_coin.run_if(run_in_progress),
+                apply_score_events,
+            )
+                .chain()
+                .after(enemy_collision),
+        );
+        spawn_enemy_on_player(&mut app);
+        app
+    }
+
+    #[test]
+    fn coin_touched_on_the_fatal_frame_is_not_scored() {
+        let mut app = fatal_frame_app(1);
+
+        app.update();
+
+        let game_info = app.world.resource::<GameInfo>();
+        assert!(game_info.run_over());
+        assert_eq!(game_info.points(), 0);
+        assert!(app.world.resource::<Events<HitPickup>>().is_empty());
+        assert!(app.world.resource::<Events<EnemySpawned>>().is_empty());
+    }
+
+    #[test]
+    fn coin_touched_on_a_survived_hit_is_scored() {
+        let mut app = fatal_frame_app(2);
+
+        app.update();
+
+        let game_info = app.world.resource::<GameInfo>();
+        assert!(!game_info.run_over());
+        assert_eq!(game_info.points(), 1);
+        assert_eq!(app.world.resource::<Events<HitPickup>>().len(), 1);
+    }
+
+    #[test]
+    fn two_coins_in_one_frame_each_score_and_spawn() {
+        let mut app = run_app();
+        app.add_systems(Update, (hit_coin, apply_score_events).chain());
+        let player = app
+            .world
+            .query_filtered::<Entity, With<Player>>()
+            .single(&app.world);
+        let coin = app
+            .world
+            .query_filtered::<Entity, With<Coin>>()
+            .single(&app.world);
+        for _ in 0..2 {
+            app.world.send_event(HitPickup {
+                kind: PickupKind::Coin,
+                pickup: coin,
+                collector: player,
+            });
+        }
+
+        app.update();
+
+        assert_eq!(app.world.resource::<GameInfo>().points(), 2);
+        assert_eq!(app.world.resource::<Events<EnemySpawned>>().len(), 2);
+        assert_eq!(app.world.resource::<Events<CoinRelocated>>().len(), 2);
+        let enemies = app
+            .world
+            .query_filtered::<(), With<Enemy>>()
+            .iter(&app.world)
+            .count();
+        assert_eq!(enemies, 2);
+    }
+
+    #[test]
+    fn second_hit_in_a_frame_lands_only_without_invincibility() {
+        for (invincibility_frames, landed) in [(true, 1), (false, 2)] {
+            let mut app = run_app();
+            app.insert_resource(GameRules {
+                invincibility_frames,
+                ..default()
+            })
+            .add_systems(Update, hit_player);
+            for _ in 0..2 {
+                app.world.send_event(HitPlayer {
+                    source: HitSource::Enemy(EnemyType::Red),
+                    direction: Vec3::X,
+                    attacker_position: Vec3::new(30.0, 0.0, 0.0),
+                });
+            }
+
+            app.update();
+
+            assert_eq!(
+                app.world.resource::<GameInfo>().health(),
+                PLAYER_HEALTH - landed as i8
+            );
+            assert_eq!(app.world.resource::<RunStats>().hits, landed);
+        }
+    }
+
+    // A standing enemy `distance` to the right of the player, out of reach of any hit.
+    fn hit_app(distance: f32) -> (App, Entity) {
+        let mut app = run_app();
+        app.add_systems(Update, hit_player.after(enemy_collision));
+        let enemy = spawn_enemy_on_player(&mut app);
+        app.world.entity_mut(enemy).insert((
+            Transform::from_xyz(distance, 0.0, 0.0),
+            Velocity(Vec3::ZERO),
+        ));
+        (app, enemy)
+    }
+
+    // The trauma and the knockback one hit from `source` applied.
+    fn hit_from(source: HitSource) -> (f32, Vec3) {
+        let (mut app, enemy) = hit_app(100.0);
+        app.world.send_event(HitPlayer {
+            source,
+            direction: Vec3::X,
+            attacker_position: Vec3::new(100.0, 0.0, 0.0),
+        });
+
+        app.update();
+
+        let trauma = app
+            .world
+            .query::<&ScreenShake>()
+            .single(&app.world)
+            .trauma();
+        (trauma, app.world.get::<Velocity>(enemy).unwrap().0)
+    }
+
+    #[test]
+    fn each_hit_source_applies_its_own_feedback() {
+        let config = GameConfig::default();
+        let falloff = E.powf(HIT_DECAY_RATE * (100.0 - (PLAYER_RADIUS + ENEMY_RADIUS)));
+
+        for source in [
+            HitSource::Enemy(EnemyType::Red),
+            HitSource::Enemy(EnemyType::Purple),
+            HitSource::Projectile,
+        ] {
+            let feedback = config.hit_feedback(source);
+            let (trauma, knockback) = hit_from(source);
+
+            assert_eq!(trauma, feedback.trauma, "{:?}", source);
+            assert!(
+                (knockback.x - feedback.knockback * falloff).abs() < 0.01,
+                "{:?} pushed the enemy at {}",
+                source,
+                knockback.x
+            );
+            assert_eq!(knockback.y, 0.0);
+        }
+    }
+
+    #[test]
+    fn projectile_hits_shake_less_than_body_slams() {
+        let (projectile_trauma, projectile_knockback) = hit_from(HitSource::Projectile);
+
+        for enemy_type in EnemyType::ALL {
+            let (trauma, knockback) = hit_from(HitSource::Enemy(enemy_type));
+            assert!(projectile_trauma < trauma);
+            assert!(projectile_knockback.x < knockback.x);
+        }
+    }
+}
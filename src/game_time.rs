@@ -0,0 +1,160 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, time::TimeSystem};
+
+use crate::{config::*, pause::PauseState, AppState};
+
+// Gameplay timers tick on this instead of Time directly. It follows virtual time, so slowing it
+// with Time<Virtual>::set_relative_speed scales every timer alike, and it stands still outside a
+// running game: paused, won, game over and in the menus.
+#[derive(Resource, Default)]
+pub struct GameTime {
+    delta: Duration,
+    elapsed: Duration,
+}
+
+impl GameTime {
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    pub fn elapsed_seconds(&self) -> f32 {
+        self.elapsed.as_secs_f32()
+    }
+}
+
+pub fn register_game_time(app: &mut App) {
+    app.init_resource::<GameTime>()
+        .add_systems(Startup, cap_frame_delta)
+        .add_systems(First, update_game_time.after(TimeSystem))
+        .add_systems(OnEnter(AppState::Game), reset_game_time);
+}
+
+// A long hitch (a breakpoint, an OS stall) would otherwise arrive as one huge step that carries
+// everything across the screen, and through the player, in a single frame. Capping virtual time
+// caps Time and GameTime alike; the simulation falls behind the wall clock instead.
+fn cap_frame_delta(mut time: ResMut<Time<Virtual>>) {
+    time.set_max_delta(Duration::from_secs_f32(MAX_FRAME_DELTA));
+}
+
+fn update_game_time(
+    mut game_time: ResMut<GameTime>,
+    time: Res<Time<Virtual>>,
+    app_state: Res<State<AppState>>,
+    pause_state: Res<State<PauseState>>,
+) {
+    let running = *app_state.get() == AppState::Game && *pause_state.get() == PauseState::Running;
+
+    let delta = if running {
+        time.delta()
+    } else {
+        Duration::ZERO
+    };
+    game_time.delta = delta;
+    game_time.elapsed += delta;
+}
+
+fn reset_game_time(mut game_time: ResMut<GameTime>) {
+    *game_time = GameTime::default();
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+    use crate::player::{tick_invincibility, Invincibility, InvincibilitySource};
+
+    const FRAME: f32 = 1.0 / 64.0;
+
+    // A running game one frame in, with a player whose invincibility just started.
+    fn game_app() -> (App, Entity) {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+                FRAME,
+            )))
+            .add_state::<AppState>()
+            .add_state::<PauseState>()
+            .add_systems(Update, tick_invincibility);
+        register_game_time(&mut app);
+
+        app.world
+            .resource_mut::<NextState<AppState>>()
+            .set(AppState::Game);
+        app.update();
+
+        let mut invincibility = Invincibility::default();
+        invincibility.grant(InvincibilitySource::Hit);
+        let player = app.world.spawn(invincibility).id();
+        (app, player)
+    }
+
+    fn remaining(app: &App, player: Entity) -> f32 {
+        app.world.get::<Invincibility>(player).unwrap().remaining()
+    }
+
+    #[test]
+    fn running_frames_advance_gameplay_timers() {
+        let (mut app, player) = game_app();
+
+        app.update();
+
+        assert_eq!(app.world.resource::<GameTime>().delta_seconds(), FRAME);
+        assert_eq!(remaining(&app, player), PLAYER_INVINCIBILITY_TIME - FRAME);
+    }
+
+    #[test]
+    fn paused_frames_advance_no_gameplay_timer() {
+        let (mut app, player) = game_app();
+        app.world
+            .resource_mut::<NextState<PauseState>>()
+            .set(PauseState::Paused);
+        app.update();
+        let elapsed = app.world.resource::<GameTime>().elapsed_seconds();
+        let before = remaining(&app, player);
+
+        for _ in 0..10 {
+            app.update();
+        }
+
+        assert_eq!(app.world.resource::<GameTime>().delta_seconds(), 0.0);
+        assert_eq!(app.world.resource::<GameTime>().elapsed_seconds(), elapsed);
+        assert_eq!(remaining(&app, player), before);
+    }
+
+    #[test]
+    fn slow_motion_scales_gameplay_timers() {
+        let (mut app, player) = game_app();
+        app.world
+            .resource_mut::<Time<Virtual>>()
+            .set_relative_speed(0.5);
+
+        app.update();
+
+        assert_eq!(
+            app.world.resource::<GameTime>().delta_seconds(),
+            FRAME / 2.0
+        );
+        assert_eq!(
+            remaining(&app, player),
+            PLAYER_INVINCIBILITY_TIME - FRAME / 2.0
+        );
+    }
+
+    #[test]
+    fn menus_advance_no_gameplay_time() {
+        let (mut app, _) = game_app();
+        app.world
+            .resource_mut::<NextState<AppState>>()
+            .set(AppState::Menu);
+        app.update();
+        app.update();
+
+        assert_eq!(app.world.resource::<GameTime>().delta_seconds(), 0.0);
+    }
+}
@@ -0,0 +1,591 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::{prelude::*, window::PrimaryWindow};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    assets::AssetHandles,
+    config::*,
+    descriptor::{DescriptorError, QueuedSeed, RunDescriptor},
+    effects::text_label,
+    game::{GameInfo, GameMode, StateChecksums},
+    game_time::GameTime,
+    mutator::ActiveMutators,
+    pause::PauseState,
+    player::Player,
+    render_z,
+    storage::{self, SaveData},
+    util,
+    window::not_suspended,
+    AppState,
+};
+
+// Bumped whenever GhostFile changes shape; older files are refused rather than misread.
+pub const GHOST_VERSION: u32 = 1;
+
+const BEST_GHOST_FILE_NAME: &str = "best.ghost";
+const IMPORT_GHOST_FILE_NAME: &str = "import.ghost";
+const EXPORT_GHOST_DIR: &str = "ghosts";
+
+pub struct GhostPlugin;
+
+impl Plugin for GhostPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GhostRecorder>()
+            .init_resource::<RaceGhost>()
+            .add_systems(OnEnter(AppState::Menu), spawn_ghost_hint)
+            .add_systems(OnEnter(AppState::Game), reset_ghost_recorder)
+            .add_systems(OnEnter(PauseState::GameOver), save_best_ghost)
+            .add_systems(
+                Update,
+                (export_ghost, import_ghost).run_if(in_state(AppState::Menu)),
+            )
+            .add_systems(
+                Update,
+                start_race
+                    .run_if(in_state(AppState::Game))
+                    .run_if(resource_exists_and_changed::<RunDescriptor>()),
+            )
+            .add_systems(
+                Update,
+                (record_ghost, update_race)
+                    .run_if(in_state(AppState::Game))
+                    .run_if(in_state(PauseState::Running))
+                    .run_if(not_suspended),
+            );
+    }
+}
+
+// A finished run, small enough to pass around: the run code it was played on, the player's
+// position every GHOST_SAMPLE_INTERVAL seconds, the score timeline as (time, score) at every
+// change, and the run's per-second state checksums. The digest covers all of it, so a damaged or
+// hand-edited file is refused instead of racing nonsense.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GhostFile {
+    pub version: u32,
+    pub run: String,
+    pub score: i32,
+    pub duration: f32,
+    pub positions: Vec<(f32, f32)>,
+    pub timeline: Vec<(f32, i32)>,
+    pub checksums: Vec<u64>,
+    pub digest: u64,
+}
+
+#[derive(Debug)]
+pub enum GhostError {
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+    Version(u32),
+    Run(DescriptorError),
+    Corrupt,
+    Inconsistent(&'static str),
+    GameVersion(String),
+    Difficulty(Difficulty),
+    Mutators,
+    Seed { ghost: u64, selected: u64 },
+    NoSaveDir,
+}
+
+impl fmt::Display for GhostError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GhostError::Io(error) => write!(f, "{}", error),
+            GhostError::Parse(error) => write!(f, "{}", error),
+            GhostError::Serialize(error) => write!(f, "{}", error),
+            GhostError::Version(version) => write!(
+                f,
+                "ghost format version {} is not supported (expected {})",
+                version, GHOST_VERSION
+            ),
+            GhostError::Run(error) => write!(f, "{}", error),
+            GhostError::Corrupt => write!(f, "the file is damaged or was edited"),
+            GhostError::Inconsistent(reason) => {
+                write!(f, "the recording is inconsistent: {}", reason)
+            }
+            GhostError::GameVersion(version) => write!(
+                f,
+                "the ghost was recorded on version {}, this is {}",
+                version,
+                env!("CARGO_PKG_VERSION")
+            ),
+            GhostError::Difficulty(difficulty) => write!(
+                f,
+                "the ghost ran on {}, switch the difficulty to match",
+                difficulty.name()
+            ),
+            GhostError::Mutators => write!(f, "the ghost ran with different mutators"),
+            GhostError::Seed { ghost, selected } => write!(
+                f,
+                "the ghost ran seed {:x}, the selected run is seed {:x}",
+                ghost, selected
+            ),
+            GhostError::NoSaveDir => write!(f, "no save directory"),
+        }
+    }
+}
+
+impl std::error::Error for GhostError {}
+
+impl From<io::Error> for GhostError {
+    fn from(error: io::Error) -> Self {
+        GhostError::Io(error)
+    }
+}
+
+impl GhostFile {
+    fn new(
+        run: &RunDescriptor,
+        score: i32,
+        duration: f32,
+        recorder: &GhostRecorder,
+        checksums: &[u64],
+    ) -> Self {
+        let mut file = Self {
+            version: GHOST_VERSION,
+            run: run.to_string(),
+            score,
+            duration,
+            positions: recorder.positions.clone(),
+            timeline: recorder.timeline.clone(),
+            checksums: checksums.to_vec(),
+            digest: 0,
+        };
+        file.digest = file.compute_digest();
+        file
+    }
+
+    pub fn load(path: &Path) -> Result<Self, GhostError> {
+        let contents = fs::read_to_string(path)?;
+        let file: GhostFile = ron::from_str(&contents).map_err(GhostError::Parse)?;
+        if file.version != GHOST_VERSION {
+            return Err(GhostError::Version(file.version));
+        }
+        file.verify()?;
+        Ok(file)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), GhostError> {
+        let contents = ron::ser::to_string_pretty(self, PrettyConfig::default())
+            .map_err(GhostError::Serialize)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    pub fn descriptor(&self) -> Result<RunDescriptor, GhostError> {
+        self.run.parse().map_err(GhostError::Run)
+    }
+
+    fn compute_digest(&self) -> u64 {
+        let mut bytes = self.run.as_bytes().to_vec();
+        bytes.extend(self.score.to_le_bytes());
+        bytes.extend(self.duration.to_le_bytes());
+        for (x, y) in &self.positions {
+            bytes.extend(x.to_le_bytes());
+            bytes.extend(y.to_le_bytes());
+        }
+        for (time, score) in &self.timeline {
+            bytes.extend(time.to_le_bytes());
+            bytes.extend(score.to_le_bytes());
+        }
+        for checksum in &self.checksums {
+            bytes.extend(checksum.to_le_bytes());
+        }
+
+        util::bytes_checksum(&bytes)
+    }
+
+    // The digest catches damage; the rest catches a recorder that wrote something impossible.
+    fn verify(&self) -> Result<(), GhostError> {
+        self.descriptor()?;
+        if self.digest != self.compute_digest() {
+            return Err(GhostError::Corrupt);
+        }
+
+        let expected_samples = (self.duration / GHOST_SAMPLE_INTERVAL).ceil() as usize;
+        if self.positions.len().abs_diff(expected_samples) > 1 {
+            return Err(GhostError::Inconsistent(
+                "position samples don't cover the run",
+            ));
+        }
+        if self.checksums.len() + 1 < self.duration as usize {
+            return Err(GhostError::Inconsistent(
+                "checksums stop before the run does",
+            ));
+        }
+
+        let ordered = self.timeline.windows(2).all(|pair| pair[0].0 <= pair[1].0);
+        let in_run = self
+            .timeline
+            .iter()
+            .all(|(time, _)| (0.0..=self.duration).contains(time));
+        if !ordered || !in_run {
+            return Err(GhostError::Inconsistent("score timeline is out of order"));
+        }
+        if self.timeline.last().map_or(0, |(_, score)| *score) != self.score {
+            return Err(GhostError::Inconsistent(
+                "score timeline doesn't end at the score",
+            ));
+        }
+
+        Ok(())
+    }
+
+    // A ghost only races the exact run it was recorded on. Mode and seed come from the ghost; the
+    // difficulty and mutators have to be selected already, and a run code pasted for this session
+    // has to be the same seed.
+    fn check_selected(
+        &self,
+        difficulty: Difficulty,
+        mutators: &ActiveMutators,
+        queued_seed: Option<u64>,
+    ) -> Result<RunDescriptor, GhostError> {
+        let run = self.descriptor()?;
+        if run.version != env!("CARGO_PKG_VERSION") {
+            return Err(GhostError::GameVersion(run.version));
+        }
+        if run.difficulty != difficulty {
+            return Err(GhostError::Difficulty(run.difficulty));
+        }
+        if run.mutators != mutators.iter().collect::<Vec<_>>() {
+            return Err(GhostError::Mutators);
+        }
+        if let Some(selected) = queued_seed.filter(|selected| *selected != run.seed) {
+            return Err(GhostError::Seed {
+                ghost: run.seed,
+                selected,
+            });
+        }
+
+        Ok(run)
+    }
+
+    fn position_at(&self, time: f32) -> Option<Vec2> {
+        let sample = time / GHOST_SAMPLE_INTERVAL;
+        let index = sample.floor() as usize;
+        let (x, y) = *self.positions.get(index)?;
+        let from = Vec2::new(x, y);
+
+        // Wrapping across an edge would otherwise sweep the ghost through the whole playfield.
+        let to = self
+            .positions
+            .get(index + 1)
+            .map(|(x, y)| Vec2::new(*x, *y))
+            .filter(|to| to.distance(from) < GHOST_SAMPLE_INTERVAL * PLAYER_DASH_SPEED * 2.0)
+            .unwrap_or(from);
+
+        Some(from.lerp(to, sample.fract()))
+    }
+
+    fn score_at(&self, time: f32) -> i32 {
+        self.timeline
+            .iter()
+            .take_while(|(at, _)| *at <= time)
+            .last()
+            .map_or(0, |(_, score)| *score)
+    }
+}
+
+// The current run's ghost as it is being recorded.
+#[derive(Resource, Default)]
+struct GhostRecorder {
+    positions: Vec<(f32, f32)>,
+    timeline: Vec<(f32, i32)>,
+    next_sample: f32,
+    last_score: i32,
+}
+
+// The imported ghost being raced, kept across restarts of the same run.
+#[derive(Resource, Default)]
+struct RaceGhost(Option<GhostFile>);
+
+#[derive(Component)]
+struct GhostMarker;
+
+#[derive(Component)]
+struct GhostRaceText;
+
+#[derive(Component)]
+struct GhostMessage;
+
+fn best_ghost_path() -> Option<PathBuf> {
+    storage::save_dir().map(|dir| dir.join(BEST_GHOST_FILE_NAME))
+}
+
+// Under the best run thumbnail, which is the run an export shares.
+fn spawn_ghost_hint(mut commands: Commands, asset_handles: Res<AssetHandles>) {
+    commands.spawn(text_label(
+        &asset_handles,
+        format!(
+            "{:?}: export ghost   {:?}: race a ghost",
+            EXPORT_GHOST_KEY, IMPORT_GHOST_KEY
+        ),
+        RUN_STAMP_FONT_SIZE,
+        Color::DARK_GRAY,
+        (THUMBNAIL_POSITION - Vec2::new(0.0, THUMBNAIL_PLACEHOLDER_SIZE.y / 2.0 + 52.0))
+            .extend(render_z::BACKDROP),
+    ));
+}
+
+fn reset_ghost_recorder(mut recorder: ResMut<GhostRecorder>) {
+    *recorder = GhostRecorder::default();
+}
+
+fn record_ghost(
+    mut recorder: ResMut<GhostRecorder>,
+    game_info: Res<GameInfo>,
+    game_time: Res<GameTime>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    let elapsed = game_time.elapsed_seconds();
+    while recorder.next_sample <= elapsed {
+        let position = player_transform.translation;
+        recorder.positions.push((position.x, position.y));
+        recorder.next_sample += GHOST_SAMPLE_INTERVAL;
+    }
+
+    if game_info.score != recorder.last_score {
+        recorder.last_score = game_info.score;
+        recorder.timeline.push((elapsed, game_info.score));
+    }
+}
+
+// Same rule as the best run thumbnail: a new best unmutated Classic score, checked before cleanup
+// records it.
+fn save_best_ghost(
+    game_mode: Res<GameMode>,
+    mutators: Res<ActiveMutators>,
+    game_info: Res<GameInfo>,
+    save_data: Res<SaveData>,
+    descriptor: Res<RunDescriptor>,
+    recorder: Res<GhostRecorder>,
+    checksums: Res<StateChecksums>,
+    game_time: Res<GameTime>,
+) {
+    let new_best = *game_mode == GameMode::Classic
+        && mutators.is_empty()
+        && save_data
+            .best_score
+            .is_none_or(|best| game_info.score > best);
+    if !new_best || save_data.read_only {
+        return;
+    }
+
+    let Some(path) = best_ghost_path() else {
+        return;
+    };
+
+    let ghost = GhostFile::new(
+        &descriptor,
+        game_info.score,
+        game_time.elapsed_seconds(),
+        &recorder,
+        checksums.recorded(),
+    );
+    if let Err(error) = ghost.save(&path) {
+        warn!("Could not save the best run ghost: {}", error);
+    }
+}
+
+// Copies the best run's ghost out under a name that says which seed to race and what to beat.
+fn export_best_ghost() -> Result<PathBuf, GhostError> {
+    let path = best_ghost_path().ok_or(GhostError::NoSaveDir)?;
+    let ghost = GhostFile::load(&path)?;
+    let run = ghost.descriptor()?;
+    let export = path
+        .with_file_name(EXPORT_GHOST_DIR)
+        .join(format!("{:x}-{}.ghost", run.seed, ghost.score));
+
+    ghost.save(&export)?;
+    Ok(export)
+}
+
+fn show_ghost_message(
+    commands: &mut Commands,
+    asset_handles: &AssetHandles,
+    messages: &Query<Entity, With<GhostMessage>>,
+    message: String,
+) {
+    messages.iter().for_each(|entity| {
+        commands.entity(entity).despawn();
+    });
+    commands.spawn((
+        GhostMessage,
+        text_label(
+            asset_handles,
+            message,
+            RUN_STAMP_FONT_SIZE,
+            Color::DARK_GRAY,
+            Vec3::new(0.0, 330.0, render_z::BACKDROP),
+        ),
+    ));
+}
+
+fn export_ghost(
+    mut commands: Commands,
+    input: Res<Input<KeyCode>>,
+    asset_handles: Res<AssetHandles>,
+    messages: Query<Entity, With<GhostMessage>>,
+) {
+    if !input.just_pressed(EXPORT_GHOST_KEY) {
+        return;
+    }
+
+    let message = match export_best_ghost() {
+        Ok(path) => {
+            info!("Exported the best run ghost to {}", path.display());
+            format!("Ghost exported to {}", path.display())
+        }
+        Err(GhostError::Io(error)) if error.kind() == io::ErrorKind::NotFound => {
+            "No best run ghost yet".to_string()
+        }
+        Err(error) => format!("Could not export the ghost: {}", error),
+    };
+    show_ghost_message(&mut commands, &asset_handles, &messages, message);
+}
+
+// Races the ghost dropped at <save dir>/import.ghost, starting its run straight away like a pasted
+// run code does.
+fn import_ghost(
+    mut commands: Commands,
+    input: Res<Input<KeyCode>>,
+    asset_handles: Res<AssetHandles>,
+    save_data: Res<SaveData>,
+    mutators: Res<ActiveMutators>,
+    mut game_mode: ResMut<GameMode>,
+    mut queued_seed: ResMut<QueuedSeed>,
+    mut race_ghost: ResMut<RaceGhost>,
+    mut next_state: ResMut<NextState<AppState>>,
+    messages: Query<Entity, With<GhostMessage>>,
+) {
+    if !input.just_pressed(IMPORT_GHOST_KEY) {
+        return;
+    }
+
+    let Some(path) = storage::save_dir().map(|dir| dir.join(IMPORT_GHOST_FILE_NAME)) else {
+        let message = format!("Could not import a ghost: {}", GhostError::NoSaveDir);
+        show_ghost_message(&mut commands, &asset_handles, &messages, message);
+        return;
+    };
+
+    let imported = GhostFile::load(&path).and_then(|ghost| {
+        ghost
+            .check_selected(save_data.settings.difficulty, &mutators, queued_seed.0)
+            .map(|run| (ghost, run))
+    });
+
+    let (ghost, run) = match imported {
+        Ok(imported) => imported,
+        Err(GhostError::Io(error)) if error.kind() == io::ErrorKind::NotFound => {
+            let message = format!("Put a .ghost file at {} to race it", path.display());
+            show_ghost_message(&mut commands, &asset_handles, &messages, message);
+            return;
+        }
+        Err(error) => {
+            let message = format!("Could not import the ghost: {}", error);
+            show_ghost_message(&mut commands, &asset_handles, &messages, message);
+            return;
+        }
+    };
+
+    *game_mode = run.mode;
+    queued_seed.0 = Some(run.seed);
+    race_ghost.0 = Some(ghost);
+    next_state.set(AppState::Game);
+}
+
+// A restart rolls a new seed, which ends the race.
+fn start_race(
+    mut commands: Commands,
+    asset_handles: Res<AssetHandles>,
+    descriptor: Res<RunDescriptor>,
+    mut race_ghost: ResMut<RaceGhost>,
+    window: Query<&Window, With<PrimaryWindow>>,
+) {
+    let Some(ghost) = &race_ghost.0 else {
+        return;
+    };
+    if ghost.descriptor().ok().as_ref() != Some(&*descriptor) {
+        info!("The run no longer matches the imported ghost, ending the race");
+        race_ghost.0 = None;
+        return;
+    }
+
+    commands.spawn((
+        GhostMarker,
+        ColorMesh2dBundle {
+            mesh: asset_handles.player_mesh.clone().into(),
+            material: asset_handles.ghost_material.clone(),
+            transform: Transform::from_xyz(0.0, 0.0, render_z::TRAIL),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+    ));
+
+    let top = window.single().height() / 2.0 - GHOST_RACE_MARGIN;
+    commands.spawn((
+        GhostRaceText,
+        text_label(
+            &asset_handles,
+            "",
+            GHOST_RACE_FONT_SIZE,
+            GHOST_COLOR.with_a(1.0),
+            Vec3::new(0.0, top, render_z::HUD),
+        ),
+    ));
+}
+
+fn update_race(
+    race_ghost: Res<RaceGhost>,
+    game_info: Res<GameInfo>,
+    game_time: Res<GameTime>,
+    mut marker: Query<(&mut Transform, &mut Visibility), With<GhostMarker>>,
+    mut race_text: Query<&mut Text, With<GhostRaceText>>,
+) {
+    let Some(ghost) = &race_ghost.0 else {
+        return;
+    };
+
+    let elapsed = game_time.elapsed_seconds();
+    let position = ghost
+        .position_at(elapsed)
+        .filter(|_| elapsed <= ghost.duration);
+    for (mut transform, mut visibility) in marker.iter_mut() {
+        match position {
+            Some(position) => {
+                transform.translation = position.extend(transform.translation.z);
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+
+    let lead = game_info.score - ghost.score_at(elapsed);
+    let label = if position.is_none() && lead > 0 {
+        format!("Ghost beaten, it finished on {}", ghost.score)
+    } else if lead > 0 {
+        format!("Ahead of the ghost by {}", lead)
+    } else if lead < 0 {
+        format!("Behind the ghost by {}", -lead)
+    } else {
+        "Level with the ghost".to_string()
+    };
+
+    for mut text in race_text.iter_mut() {
+        if text.sections[0].value != label {
+            text.sections[0].value = label.clone();
+        }
+    }
+}
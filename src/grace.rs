@@ -0,0 +1,122 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{
+    assets::AssetHandles,
+    config::*,
+    effects::text_label,
+    game_time::GameTime,
+    hud::{HudElement, HudKey},
+    render_z,
+};
+
+#[derive(Resource)]
+pub struct PostHitGrace {
+    timer: Timer,
+    active: bool,
+}
+
+impl Default for PostHitGrace {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(POST_HIT_GRACE_TIME, TimerMode::Once),
+            active: false,
+        }
+    }
+}
+
+impl PostHitGrace {
+    pub fn start(&mut self) {
+        self.timer.reset();
+        self.active = true;
+    }
+
+    pub fn consume(&mut self) -> bool {
+        std::mem::replace(&mut self.active, false)
+    }
+
+    pub fn tick(&mut self, delta: f32) {
+        if self.active && self.timer.tick(Duration::from_secs_f32(delta)).finished() {
+            self.active = false;
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+}
+
+#[derive(Component)]
+pub struct GraceIndicator;
+
+pub fn spawn_grace_indicator(commands: &mut Commands, asset_handles: &AssetHandles) {
+    let mut label = text_label(
+        asset_handles,
+        "Spawn grace",
+        24.0,
+        Color::GRAY,
+        Vec3::new(0.0, -250.0, render_z::PLAYFIELD),
+    );
+    label.visibility = Visibility::Hidden;
+
+    commands.spawn((GraceIndicator, HudElement::new(HudKey::Grace), label));
+}
+
+pub fn tick_post_hit_grace(
+    mut grace: ResMut<PostHitGrace>,
+    mut indicator: Query<(&mut Visibility, &HudElement), With<GraceIndicator>>,
+    game_time: Res<GameTime>,
+) {
+    grace.tick(game_time.delta_seconds());
+
+    for (mut visibility, hud) in indicator.iter_mut() {
+        *visibility = if grace.is_active() && hud.visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grace_covers_the_first_coin_only() {
+        let mut grace = PostHitGrace::default();
+        assert!(!grace.consume());
+
+        grace.start();
+        assert!(grace.consume());
+        assert!(!grace.consume());
+    }
+
+    #[test]
+    fn grace_ends_on_the_boundary_frame() {
+        let mut grace = PostHitGrace::default();
+        grace.start();
+
+        let frame = POST_HIT_GRACE_TIME / 4.0;
+        for _ in 0..3 {
+            grace.tick(frame);
+        }
+        assert!(grace.is_active());
+
+        grace.tick(frame);
+        assert!(!grace.is_active());
+        assert!(!grace.consume());
+    }
+
+    #[test]
+    fn new_hit_restarts_the_window() {
+        let mut grace = PostHitGrace::default();
+        grace.start();
+        grace.tick(POST_HIT_GRACE_TIME - 0.5);
+        grace.start();
+        grace.tick(1.0);
+
+        assert!(grace.is_active());
+    }
+}
@@ -0,0 +1,116 @@
+use std::time::Duration;
+
+use bevy::{
+    input::gamepad::{GamepadEvent, GamepadRumbleIntensity, GamepadRumbleRequest},
+    prelude::*,
+};
+
+use crate::{
+    config::*,
+    game::{GainHealth, GameInfo, HitPlayer},
+    pickup::{HitPickup, PickupKind},
+    storage::SaveData,
+    AppState,
+};
+
+pub struct HapticsPlugin;
+
+impl Plugin for HapticsPlugin {
+    // PostUpdate, so a hit reads the health hit_player has already taken away.
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActivePad>()
+            .init_resource::<Rumble>()
+            .add_systems(PreUpdate, track_active_pad)
+            .add_systems(
+                PostUpdate,
+                rumble_on_events.run_if(in_state(AppState::Game)),
+            );
+    }
+}
+
+// The pad that last sent any input. Only that one rumbles, so a second controller lying on the
+// desk stays quiet.
+#[derive(Resource, Default)]
+struct ActivePad(Option<Gamepad>);
+
+// What is playing right now. Requests are never queued: a stronger one replaces it, a weaker one
+// while it lasts is dropped.
+#[derive(Resource, Default)]
+struct Rumble {
+    strength: f32,
+    until: f32,
+}
+
+fn track_active_pad(mut active_pad: ResMut<ActivePad>, mut events: EventReader<GamepadEvent>) {
+    for event in events.read() {
+        match event {
+            GamepadEvent::Connection(connection) => {
+                if connection.disconnected() && active_pad.0 == Some(connection.gamepad) {
+                    active_pad.0 = None;
+                }
+            }
+            GamepadEvent::Button(button) => active_pad.0 = Some(button.gamepad),
+            GamepadEvent::Axis(axis) => active_pad.0 = Some(axis.gamepad),
+        }
+    }
+}
+
+// Harder the closer the hit leaves the player to dying.
+fn hit_strength(health: i8) -> f32 {
+    let missing = 1.0 - health.clamp(0, PLAYER_HEALTH) as f32 / PLAYER_HEALTH as f32;
+    HAPTICS_HIT_MIN_STRENGTH + (1.0 - HAPTICS_HIT_MIN_STRENGTH) * missing
+}
+
+fn rumble_on_events(
+    mut hit_player: EventReader<HitPlayer>,
+    mut hit_pickup: EventReader<HitPickup>,
+    mut gain_health: EventReader<GainHealth>,
+    mut requests: EventWriter<GamepadRumbleRequest>,
+    mut rumble: ResMut<Rumble>,
+    active_pad: Res<ActivePad>,
+    save_data: Res<SaveData>,
+    game_info: Res<GameInfo>,
+    time: Res<Time<Real>>,
+) {
+    // Read every frame, so events from before the setting was turned on don't all fire at once.
+    let hit = hit_player.read().count() > 0;
+    let coin = hit_pickup.read().any(|hit| hit.kind == PickupKind::Coin);
+    let health = gain_health.read().count() > 0;
+
+    let Some(gamepad) = active_pad.0 else {
+        return;
+    };
+    if !save_data.settings.haptics {
+        return;
+    }
+
+    let (strength, duration) = if hit {
+        (hit_strength(game_info.health()), HAPTICS_HIT_DURATION)
+    } else if health {
+        (HAPTICS_HEALTH_STRENGTH, HAPTICS_HEALTH_DURATION)
+    } else if coin {
+        (HAPTICS_COIN_STRENGTH, HAPTICS_COIN_DURATION)
+    } else {
+        return;
+    };
+
+    let now = time.elapsed_seconds();
+    if now < rumble.until && strength < rumble.strength {
+        return;
+    }
+
+    // Rumble requests on one pad add up rather than replace each other, so stop first.
+    requests.send(GamepadRumbleRequest::Stop { gamepad });
+    requests.send(GamepadRumbleRequest::Add {
+        gamepad,
+        duration: Duration::from_secs_f32(duration),
+        intensity: GamepadRumbleIntensity {
+            strong_motor: strength,
+            weak_motor: strength,
+        },
+    });
+    *rumble = Rumble {
+        strength,
+        until: now + duration,
+    };
+}
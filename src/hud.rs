@@ -0,0 +1,355 @@
+use bevy::{prelude::*, sprite::Mesh2dHandle, window::PrimaryWindow};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    assets::AssetHandles,
+    audio::AudioAvailable,
+    config::*,
+    game::GameInfo,
+    menu_list::{EntryKind, MenuEntry, MenuListEvent},
+    player::{Dash, Player},
+    render_z,
+    storage::SaveData,
+    util::{pie_mesh, safe_half_extent},
+    AppState,
+};
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, change_hud_layout)
+            .add_systems(
+                OnEnter(AppState::Game),
+                (spawn_health_pips, spawn_dash_indicator),
+            )
+            .add_systems(
+                Update,
+                (apply_hud_layout, update_health_pips, update_dash_indicator)
+                    .chain()
+                    .run_if(in_state(AppState::Game)),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HudLayout {
+    #[default]
+    Default,
+    CornersOnly,
+    Minimal,
+}
+
+impl HudLayout {
+    pub const ALL: [HudLayout; 3] = [
+        HudLayout::Default,
+        HudLayout::CornersOnly,
+        HudLayout::Minimal,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            HudLayout::Default => "Default",
+            HudLayout::CornersOnly => "Corners only",
+            HudLayout::Minimal => "Minimal",
+        }
+    }
+
+    pub fn menu_entry(&self) -> MenuEntry {
+        MenuEntry::new(
+            "hud_layout",
+            "HUD layout",
+            EntryKind::Choice {
+                options: HudLayout::ALL.iter().map(HudLayout::name).collect(),
+                selected: HudLayout::ALL
+                    .iter()
+                    .position(|layout| layout == self)
+                    .unwrap_or(0),
+            },
+        )
+    }
+
+    // Returns the translation and scale for an element, or None when the layout hides it.
+    fn placement(&self, key: HudKey, half_extent: Vec2) -> Option<(Vec3, f32)> {
+        match (key, self) {
+            (HudKey::Score, HudLayout::Default) => {
+                Some((Vec3::new(0.0, 0.0, render_z::BACKDROP), 1.0))
+            }
+            (HudKey::Score, HudLayout::CornersOnly) => Some((
+                Vec3::new(
+                    -half_extent.x + HUD_CORNER_MARGIN,
+                    half_extent.y - HUD_CORNER_MARGIN,
+                    render_z::BACKDROP,
+                ),
+                HUD_CORNER_SCORE_SCALE,
+            )),
+            (HudKey::Score, HudLayout::Minimal) => Some((
+                Vec3::new(0.0, half_extent.y - HUD_MINIMAL_MARGIN, render_z::BACKDROP),
+                HUD_MINIMAL_SCORE_SCALE,
+            )),
+            (HudKey::Grace, HudLayout::Default) => {
+                Some((Vec3::new(0.0, -250.0, render_z::PLAYFIELD), 1.0))
+            }
+            (HudKey::Grace, HudLayout::CornersOnly) => Some((
+                Vec3::new(
+                    half_extent.x - HUD_CORNER_MARGIN * 2.0,
+                    -half_extent.y + HUD_CORNER_MARGIN,
+                    render_z::PLAYFIELD,
+                ),
+                1.0,
+            )),
+            (HudKey::Carried, HudLayout::Default | HudLayout::CornersOnly) => {
+                Some((Vec3::new(0.0, CARRIED_TEXT_OFFSET, render_z::HUD), 1.0))
+            }
+            (HudKey::Health, HudLayout::Default | HudLayout::CornersOnly) => Some((
+                Vec3::new(
+                    half_extent.x - HUD_CORNER_MARGIN * 2.0,
+                    half_extent.y - HUD_CORNER_MARGIN,
+                    render_z::HUD,
+                ),
+                1.0,
+            )),
+            (HudKey::Health, HudLayout::Minimal) => Some((
+                Vec3::new(
+                    half_extent.x - HUD_MINIMAL_MARGIN * 2.0,
+                    half_extent.y - HUD_MINIMAL_MARGIN,
+                    render_z::HUD,
+                ),
+                1.0,
+            )),
+            (HudKey::Decay, HudLayout::Default) => {
+                Some((Vec3::new(0.0, -90.0, render_z::BACKDROP), 1.0))
+            }
+            (HudKey::Decay, HudLayout::CornersOnly) => Some((
+                Vec3::new(
+                    -half_extent.x + HUD_CORNER_MARGIN,
+                    half_extent.y - HUD_CORNER_MARGIN * 2.0,
+                    render_z::HUD,
+                ),
+                1.0,
+            )),
+            (HudKey::Decay, HudLayout::Minimal) => Some((
+                Vec3::new(0.0, half_extent.y - HUD_MINIMAL_MARGIN * 2.0, render_z::HUD),
+                1.0,
+            )),
+            // Under the health pips, clear of the input display's bottom corners.
+            (HudKey::Dash, HudLayout::Default | HudLayout::CornersOnly) => Some((
+                Vec3::new(
+                    half_extent.x - HUD_CORNER_MARGIN,
+                    half_extent.y - HUD_CORNER_MARGIN * 2.0,
+                    render_z::HUD,
+                ),
+                1.0,
+            )),
+            (HudKey::Dash, HudLayout::Minimal) => Some((
+                Vec3::new(
+                    half_extent.x - HUD_MINIMAL_MARGIN,
+                    half_extent.y - HUD_MINIMAL_MARGIN * 2.0,
+                    render_z::HUD,
+                ),
+                1.0,
+            )),
+            (HudKey::Grace | HudKey::Carried, HudLayout::Minimal) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HudKey {
+    Score,
+    Grace,
+    Carried,
+    Health,
+    Decay,
+    Dash,
+}
+
+impl HudKey {
+    fn toggles_itself(self) -> bool {
+        matches!(self, HudKey::Grace | HudKey::Health | HudKey::Decay)
+    }
+}
+
+// `visible` is what the layout allows; elements that toggle themselves, like the grace
+// indicator, combine it with their own state.
+#[derive(Component)]
+pub struct HudElement {
+    pub key: HudKey,
+    pub visible: bool,
+}
+
+impl HudElement {
+    pub fn new(key: HudKey) -> Self {
+        Self { key, visible: true }
+    }
+}
+
+fn change_hud_layout(mut events: EventReader<MenuListEvent>, mut save_data: ResMut<SaveData>) {
+    for event in events.read() {
+        if let MenuListEvent::Changed {
+            entry: "hud_layout",
+            kind: EntryKind::Choice { selected, .. },
+            ..
+        } = event
+        {
+            save_data.settings.hud_layout = HudLayout::ALL[*selected];
+        }
+    }
+}
+
+fn apply_hud_layout(
+    save_data: Res<SaveData>,
+    mut query: Query<(&mut HudElement, &mut Transform, &mut Visibility)>,
+    window: Query<&Window, With<PrimaryWindow>>,
+) {
+    let Ok(window) = window.get_single() else {
+        return;
+    };
+    let half_extent = safe_half_extent(
+        Vec2::new(window.width(), window.height()) / 2.0,
+        save_data.settings.safe_area,
+    );
+    let layout = save_data.settings.hud_layout;
+
+    for (mut element, mut transform, mut visibility) in query.iter_mut() {
+        if !save_data.is_changed() && !element.is_added() {
+            continue;
+        }
+
+        let placement = layout.placement(element.key, half_extent);
+        element.visible = placement.is_some();
+        if let Some((translation, scale)) = placement {
+            transform.translation = translation;
+            transform.scale = Vec3::splat(scale);
+        }
+
+        if !element.key.toggles_itself() {
+            *visibility = if placement.is_some() {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+        }
+    }
+}
+
+#[derive(Component)]
+struct HealthPip(i8);
+
+fn spawn_health_pips(mut commands: Commands, asset_handles: Res<AssetHandles>) {
+    commands
+        .spawn((
+            HudElement::new(HudKey::Health),
+            SpatialBundle {
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            for index in 0..PLAYER_HEALTH {
+                parent.spawn((
+                    HealthPip(index),
+                    ColorMesh2dBundle {
+                        mesh: asset_handles.coin_mesh.clone().into(),
+                        material: asset_handles.health_material.clone(),
+                        // Laid out leftwards from the corner so the last pip sits at the margin.
+                        transform: Transform::from_xyz(
+                            -(index as f32) * HEALTH_PIP_SPACING,
+                            0.0,
+                            0.0,
+                        )
+                        .with_scale(Vec3::splat(HEALTH_PIP_RADIUS / COIN_RADIUS)),
+                        ..default()
+                    },
+                ));
+            }
+        });
+}
+
+// Without audio the music can't signal health, so the pips show regardless of the setting.
+fn update_health_pips(
+    mut root_query: Query<(&HudElement, &mut Visibility)>,
+    mut pip_query: Query<(&HealthPip, &mut Handle<ColorMaterial>)>,
+    game_info: Res<GameInfo>,
+    save_data: Res<SaveData>,
+    audio: Res<AudioAvailable>,
+    asset_handles: Res<AssetHandles>,
+) {
+    let wanted = save_data.settings.health_pips || !audio.0;
+    for (element, mut visibility) in root_query.iter_mut() {
+        if element.key != HudKey::Health {
+            continue;
+        }
+        let target = if wanted && element.visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if *visibility != target {
+            *visibility = target;
+        }
+    }
+
+    if !game_info.is_changed() {
+        return;
+    }
+    for (pip, mut material) in pip_query.iter_mut() {
+        // The rightmost pip is the last to empty.
+        let filled = pip.0 < game_info.health;
+        *material = if filled {
+            asset_handles.health_material.clone()
+        } else {
+            asset_handles.empty_health_material.clone()
+        };
+    }
+}
+
+#[derive(Component)]
+struct DashIndicator;
+
+fn spawn_dash_indicator(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    asset_handles: Res<AssetHandles>,
+) {
+    commands.spawn((
+        DashIndicator,
+        HudElement::new(HudKey::Dash),
+        ColorMesh2dBundle {
+            mesh: meshes.add(pie_mesh(1.0, DASH_INDICATOR_RADIUS)).into(),
+            material: asset_handles.dash_ready_material.clone(),
+            ..default()
+        },
+    ));
+}
+
+// A full disc while a dash is ready; otherwise a dim wedge that fills as the cooldown runs out.
+fn update_dash_indicator(
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut indicator_query: Query<(&Mesh2dHandle, &mut Handle<ColorMaterial>), With<DashIndicator>>,
+    dash_query: Query<&Dash, With<Player>>,
+    asset_handles: Res<AssetHandles>,
+) {
+    let (Ok((mesh, mut material)), Ok(dash)) =
+        (indicator_query.get_single_mut(), dash_query.get_single())
+    else {
+        return;
+    };
+
+    let readiness = dash.readiness();
+    let target = if readiness < 1.0 {
+        &asset_handles.dash_cooldown_material
+    } else {
+        &asset_handles.dash_ready_material
+    };
+    let switched = *material != *target;
+    if switched {
+        *material = target.clone();
+    }
+
+    if readiness < 1.0 || switched {
+        if let Some(mesh) = meshes.get_mut(&mesh.0) {
+            *mesh = pie_mesh(readiness, DASH_INDICATOR_RADIUS);
+        }
+    }
+}
@@ -0,0 +1,293 @@
+use bevy::prelude::*;
+
+pub trait InhibitBackend {
+    fn inhibit(&mut self);
+    fn uninhibit(&mut self);
+}
+
+pub struct SleepInhibitor {
+    backend: Box<dyn InhibitBackend>,
+    active: bool,
+}
+
+impl SleepInhibitor {
+    pub fn new(backend: Box<dyn InhibitBackend>) -> Self {
+        Self {
+            backend,
+            active: false,
+        }
+    }
+
+    pub fn acquire(&mut self) {
+        if !self.active {
+            self.backend.inhibit();
+            self.active = true;
+        }
+    }
+
+    pub fn release(&mut self) {
+        if self.active {
+            self.backend.uninhibit();
+            self.active = false;
+        }
+    }
+}
+
+impl Default for SleepInhibitor {
+    fn default() -> Self {
+        Self::new(platform_backend())
+    }
+}
+
+impl Drop for SleepInhibitor {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+pub fn acquire_sleep_inhibitor(mut inhibitor: NonSendMut<SleepInhibitor>) {
+    inhibitor.acquire();
+}
+
+pub fn release_sleep_inhibitor(mut inhibitor: NonSendMut<SleepInhibitor>) {
+    inhibitor.release();
+}
+
+#[cfg(all(feature = "inhibit-sleep", target_os = "windows"))]
+fn platform_backend() -> Box<dyn InhibitBackend> {
+    Box::new(native::ExecutionStateBackend)
+}
+
+#[cfg(all(feature = "inhibit-sleep", target_os = "macos"))]
+fn platform_backend() -> Box<dyn InhibitBackend> {
+    Box::new(native::ProcessBackend::new("caffeinate", &["-d", "-i"]))
+}
+
+#[cfg(all(feature = "inhibit-sleep", target_os = "linux"))]
+fn platform_backend() -> Box<dyn InhibitBackend> {
+    Box::new(native::ProcessBackend::new(
+        "systemd-inhibit",
+        &[
+            "--what=idle:sleep",
+            "--who=Gorbulet",
+            "--why=Run in progress",
+        ],
+    ))
+}
+
+#[cfg(not(all(
+    feature = "inhibit-sleep",
+    any(target_os = "windows", target_os = "macos", target_os = "linux")
+)))]
+fn platform_backend() -> Box<dyn InhibitBackend> {
+    struct NoopBackend;
+
+    impl InhibitBackend for NoopBackend {
+        fn inhibit(&mut self) {}
+
+        fn uninhibit(&mut self) {}
+    }
+
+    Box::new(NoopBackend)
+}
+
+#[cfg(all(feature = "inhibit-sleep", target_os = "windows"))]
+mod native {
+    use super::InhibitBackend;
+
+    const ES_CONTINUOUS: u32 = 0x8000_0000;
+    const ES_SYSTEM_REQUIRED: u32 = 0x0000_0001;
+    const ES_DISPLAY_REQUIRED: u32 = 0x0000_0002;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn SetThreadExecutionState(flags: u32) -> u32;
+    }
+
+    pub struct ExecutionStateBackend;
+
+    impl InhibitBackend for ExecutionStateBackend {
+        fn inhibit(&mut self) {
+            unsafe {
+                SetThreadExecutionState(ES_CONTINUOUS | ES_SYSTEM_REQUIRED | ES_DISPLAY_REQUIRED);
+            }
+        }
+
+        fn uninhibit(&mut self) {
+            unsafe {
+                SetThreadExecutionState(ES_CONTINUOUS);
+            }
+        }
+    }
+}
+
+#[cfg(all(
+    feature = "inhibit-sleep",
+    any(target_os = "macos", target_os = "linux")
+))]
+mod native {
+    use std::{
+        io::ErrorKind,
+        process::{Child, Command, Stdio},
+    };
+
+    use bevy::prelude::*;
+
+    use super::InhibitBackend;
+
+    // Holds the inhibition for as long as a child `cat` is alive. Closing its stdin ends it, which
+    // also happens automatically if the game crashes.
+    pub struct ProcessBackend {
+        program: &'static str,
+        args: &'static [&'static str],
+        child: Option<Child>,
+        // Set once the program turns out not to be installed, so later runs don't retry it.
+        missing: bool,
+    }
+
+    impl ProcessBackend {
+        pub fn new(program: &'static str, args: &'static [&'static str]) -> Self {
+            Self {
+                program,
+                args,
+                child: None,
+                missing: false,
+            }
+        }
+    }
+
+    impl InhibitBackend for ProcessBackend {
+        fn inhibit(&mut self) {
+            if self.missing {
+                return;
+            }
+
+            let child = Command::new(self.program)
+                .args(self.args)
+                .arg("cat")
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn();
+
+            match child {
+                Ok(child) => self.child = Some(child),
+                Err(error) if error.kind() == ErrorKind::NotFound => {
+                    info!(
+                        "{} is not installed, so the display may sleep during runs",
+                        self.program
+                    );
+                    self.missing = true;
+                }
+                Err(error) => warn!("Could not start {}: {}", self.program, error),
+            }
+        }
+
+        fn uninhibit(&mut self) {
+            if let Some(mut child) = self.child.take() {
+                drop(child.stdin.take());
+                if let Err(error) = child.wait() {
+                    warn!("Could not stop {}: {}", self.program, error);
+                }
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn missing_program_degrades_to_no_inhibition() {
+            let mut backend = ProcessBackend::new("gorbulet-no-such-inhibitor", &[]);
+
+            backend.inhibit();
+            assert!(backend.missing);
+            assert!(backend.child.is_none());
+
+            backend.inhibit();
+            backend.uninhibit();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Calls {
+        inhibit: Cell<u32>,
+        uninhibit: Cell<u32>,
+    }
+
+    struct FakeBackend(Rc<Calls>);
+
+    impl InhibitBackend for FakeBackend {
+        fn inhibit(&mut self) {
+            self.0.inhibit.set(self.0.inhibit.get() + 1);
+        }
+
+        fn uninhibit(&mut self) {
+            self.0.uninhibit.set(self.0.uninhibit.get() + 1);
+        }
+    }
+
+    fn fake_inhibitor() -> (SleepInhibitor, Rc<Calls>) {
+        let calls = Rc::new(Calls::default());
+        (
+            SleepInhibitor::new(Box::new(FakeBackend(calls.clone()))),
+            calls,
+        )
+    }
+
+    #[test]
+    fn acquire_and_release_are_idempotent() {
+        let (mut inhibitor, calls) = fake_inhibitor();
+
+        inhibitor.acquire();
+        inhibitor.acquire();
+        assert_eq!(calls.inhibit.get(), 1);
+
+        inhibitor.release();
+        inhibitor.release();
+        assert_eq!(calls.uninhibit.get(), 1);
+    }
+
+    #[test]
+    fn rapid_restarts_pair_every_acquire_with_one_release() {
+        let (mut inhibitor, calls) = fake_inhibitor();
+
+        for _ in 0..10 {
+            inhibitor.release();
+            inhibitor.acquire();
+            inhibitor.acquire();
+        }
+        inhibitor.release();
+
+        assert_eq!(calls.inhibit.get(), 10);
+        assert_eq!(calls.uninhibit.get(), 10);
+    }
+
+    #[test]
+    fn release_without_acquire_does_nothing() {
+        let (mut inhibitor, calls) = fake_inhibitor();
+
+        inhibitor.release();
+        drop(inhibitor);
+
+        assert_eq!(calls.uninhibit.get(), 0);
+    }
+
+    #[test]
+    fn drop_releases_a_held_inhibition() {
+        let (mut inhibitor, calls) = fake_inhibitor();
+
+        inhibitor.acquire();
+        drop(inhibitor);
+
+        assert_eq!(calls.uninhibit.get(), 1);
+    }
+}
@@ -0,0 +1,291 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    assets::AssetHandles,
+    config::*,
+    effects::text_label,
+    menu_list::{EntryKind, MenuEntry, MenuListEvent},
+    pause::PauseState,
+    player::{move_player, Action, InputBindings, PlayerInput},
+    render_z,
+    storage::SaveData,
+    util::safe_half_extent,
+    window::not_suspended,
+    AppState,
+};
+
+pub struct InputDisplayPlugin;
+
+impl Plugin for InputDisplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, change_input_display)
+            .add_systems(OnEnter(AppState::Game), spawn_input_display)
+            .add_systems(
+                Update,
+                update_input_display
+                    .after(move_player)
+                    .run_if(in_state(AppState::Game))
+                    .run_if(in_state(PauseState::Running))
+                    .run_if(not_suspended),
+            );
+    }
+}
+
+// Where the input display sits, if anywhere. It shows the PlayerInput move_player consumed, so a
+// repro or bench run shows its recorded input rather than the keyboard.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InputDisplay {
+    #[default]
+    Off,
+    BottomLeft,
+    BottomRight,
+}
+
+impl InputDisplay {
+    pub const ALL: [InputDisplay; 3] = [
+        InputDisplay::Off,
+        InputDisplay::BottomLeft,
+        InputDisplay::BottomRight,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            InputDisplay::Off => "Off",
+            InputDisplay::BottomLeft => "Bottom left",
+            InputDisplay::BottomRight => "Bottom right",
+        }
+    }
+
+    pub fn menu_entry(&self) -> MenuEntry {
+        MenuEntry::new(
+            "input_display",
+            "Input display",
+            EntryKind::Choice {
+                options: InputDisplay::ALL.iter().map(InputDisplay::name).collect(),
+                selected: InputDisplay::ALL
+                    .iter()
+                    .position(|display| display == self)
+                    .unwrap_or(0),
+            },
+        )
+    }
+
+    // The cluster's center, or None when it's hidden. The keys reach 1.5 key sizes left of it,
+    // 3.5 right and 1 down.
+    fn origin(&self, half_extent: Vec2) -> Option<Vec2> {
+        let y = -half_extent.y + INPUT_DISPLAY_MARGIN + INPUT_DISPLAY_KEY_SIZE;
+        match self {
+            InputDisplay::Off => None,
+            InputDisplay::BottomLeft => Some(Vec2::new(
+                -half_extent.x + INPUT_DISPLAY_MARGIN + INPUT_DISPLAY_KEY_SIZE * 1.5,
+                y,
+            )),
+            InputDisplay::BottomRight => Some(Vec2::new(
+                half_extent.x - INPUT_DISPLAY_MARGIN - INPUT_DISPLAY_KEY_SIZE * 3.5,
+                y,
+            )),
+        }
+    }
+}
+
+pub fn opacity_menu_entry(opacity: f32) -> MenuEntry {
+    MenuEntry::new(
+        "input_display_opacity",
+        "Input display opacity",
+        EntryKind::Slider {
+            value: opacity,
+            min: INPUT_DISPLAY_MIN_OPACITY,
+            max: 1.0,
+            step: 0.1,
+        },
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InputKey {
+    Up,
+    Down,
+    Left,
+    Right,
+    Dash,
+}
+
+impl InputKey {
+    const ALL: [InputKey; 5] = [
+        InputKey::Up,
+        InputKey::Down,
+        InputKey::Left,
+        InputKey::Right,
+        InputKey::Dash,
+    ];
+
+    // In key sizes from the cluster's center: WASD, with dash to the right of D.
+    fn offset(&self) -> Vec2 {
+        match self {
+            InputKey::Up => Vec2::new(0.0, 0.5),
+            InputKey::Down => Vec2::new(0.0, -0.5),
+            InputKey::Left => Vec2::new(-1.0, -0.5),
+            InputKey::Right => Vec2::new(1.0, -0.5),
+            InputKey::Dash => Vec2::new(2.5, -0.5),
+        }
+    }
+
+    fn width(&self) -> f32 {
+        match self {
+            InputKey::Dash => 2.0,
+            _ => 1.0,
+        }
+    }
+
+    fn action(&self) -> Action {
+        match self {
+            InputKey::Up => Action::Up,
+            InputKey::Down => Action::Down,
+            InputKey::Left => Action::Left,
+            InputKey::Right => Action::Right,
+            InputKey::Dash => Action::Dash,
+        }
+    }
+
+    fn pressed(&self, input: &PlayerInput) -> bool {
+        match self {
+            InputKey::Up => input.direction.y > 0.0,
+            InputKey::Down => input.direction.y < 0.0,
+            InputKey::Left => input.direction.x < 0.0,
+            InputKey::Right => input.direction.x > 0.0,
+            InputKey::Dash => input.dash,
+        }
+    }
+}
+
+#[derive(Component)]
+struct InputDisplayRoot;
+
+#[derive(Component)]
+struct InputDisplayKey(InputKey);
+
+#[derive(Component)]
+struct InputDisplayLabel;
+
+fn change_input_display(mut events: EventReader<MenuListEvent>, mut save_data: ResMut<SaveData>) {
+    for event in events.read() {
+        match event {
+            MenuListEvent::Changed {
+                entry: "input_display",
+                kind: EntryKind::Choice { selected, .. },
+                ..
+            } => save_data.settings.input_display = InputDisplay::ALL[*selected],
+            MenuListEvent::Changed {
+                entry: "input_display_opacity",
+                kind: EntryKind::Slider { value, .. },
+                ..
+            } => save_data.settings.input_display_opacity = *value,
+            _ => {}
+        }
+    }
+}
+
+// Always spawned, hidden, so the display can be switched on from the pause menu mid-run.
+fn spawn_input_display(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    asset_handles: Res<AssetHandles>,
+    bindings: Res<InputBindings>,
+) {
+    commands
+        .spawn((
+            InputDisplayRoot,
+            SpatialBundle {
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            for key in InputKey::ALL {
+                let position = key.offset() * INPUT_DISPLAY_KEY_SIZE;
+                let size =
+                    Vec2::new(key.width(), 1.0) * INPUT_DISPLAY_KEY_SIZE - INPUT_DISPLAY_KEY_GAP;
+                parent.spawn((
+                    InputDisplayKey(key),
+                    ColorMesh2dBundle {
+                        mesh: asset_handles.bar_mesh.clone().into(),
+                        material: materials.add(ColorMaterial::from(INPUT_DISPLAY_IDLE_COLOR)),
+                        transform: Transform::from_translation(position.extend(0.0))
+                            .with_scale(size.extend(1.0)),
+                        ..default()
+                    },
+                ));
+
+                let label = match key {
+                    InputKey::Dash => "Dash".to_string(),
+                    _ => bindings
+                        .keys(key.action())
+                        .next()
+                        .map_or_else(String::new, |key| format!("{:?}", key)),
+                };
+                parent.spawn((
+                    InputDisplayLabel,
+                    text_label(
+                        &asset_handles,
+                        label,
+                        INPUT_DISPLAY_FONT_SIZE,
+                        Color::BLACK,
+                        position.extend(render_z::CHILD_ABOVE),
+                    ),
+                ));
+            }
+        });
+}
+
+fn update_input_display(
+    player_input: Res<PlayerInput>,
+    save_data: Res<SaveData>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut root: Query<(&mut Transform, &mut Visibility), With<InputDisplayRoot>>,
+    keys: Query<(&InputDisplayKey, &Handle<ColorMaterial>)>,
+    mut labels: Query<&mut Text, With<InputDisplayLabel>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+) {
+    let (Ok((mut transform, mut visibility)), Ok(window)) =
+        (root.get_single_mut(), window.get_single())
+    else {
+        return;
+    };
+
+    let settings = &save_data.settings;
+    let half_extent = safe_half_extent(
+        Vec2::new(window.width(), window.height()) / 2.0,
+        settings.safe_area,
+    );
+    let Some(origin) = settings.input_display.origin(half_extent) else {
+        if *visibility != Visibility::Hidden {
+            *visibility = Visibility::Hidden;
+        }
+        return;
+    };
+    if *visibility != Visibility::Inherited {
+        *visibility = Visibility::Inherited;
+    }
+    transform.translation = origin.extend(render_z::HUD);
+
+    let opacity = settings.input_display_opacity;
+    for (key, handle) in keys.iter() {
+        let color = if key.0.pressed(&player_input) {
+            INPUT_DISPLAY_PRESSED_COLOR
+        } else {
+            INPUT_DISPLAY_IDLE_COLOR
+        };
+        let color = color.with_a(color.a() * opacity);
+        if let Some(material) = materials.get_mut(handle) {
+            if material.color != color {
+                material.color = color;
+            }
+        }
+    }
+    for mut text in labels.iter_mut() {
+        if text.sections[0].style.color.a() != opacity {
+            text.sections[0].style.color.set_a(opacity);
+        }
+    }
+}
@@ -0,0 +1,350 @@
+use bevy::prelude::*;
+
+#[cfg(all(feature = "devtools", not(target_arch = "wasm32")))]
+use crate::AppState;
+
+// Behind the devtools feature: INSPECT_KEY dumps the live playfield to a pretty JSON file under
+// <save dir>/snapshots, one file to attach to a bug report when something looks wrong.
+pub struct InspectPlugin;
+
+impl Plugin for InspectPlugin {
+    fn build(&self, _app: &mut App) {
+        #[cfg(all(feature = "devtools", not(target_arch = "wasm32")))]
+        _app.add_systems(
+            Update,
+            native::take_snapshot
+                .pipe(native::save_snapshot)
+                .run_if(in_state(AppState::Game)),
+        );
+    }
+}
+
+#[cfg(all(feature = "devtools", not(target_arch = "wasm32")))]
+mod native {
+    use std::{error::Error, fs, path::PathBuf};
+
+    use bevy::{core::FrameCount, prelude::*};
+    use serde::{Deserialize, Serialize};
+
+    use crate::{
+        aggression::AggressionModifier,
+        coin::{Coin, CoinCharge},
+        config::*,
+        cornered::{Cornered, DelayedSpawns},
+        enemy::{DyingEnemy, Enemy, EnemySpawned, EnemyType},
+        game::{GameInfo, HitPlayer},
+        game_time::GameTime,
+        grace::PostHitGrace,
+        leader::Leader,
+        lunge::Lunge,
+        movement::Velocity,
+        pickup::HitPickup,
+        player::{Invincibility, Player},
+        rng::GameRng,
+        storage,
+    };
+
+    const SNAPSHOT_DIR: &str = "snapshots";
+
+    // Plain data only, so a snapshot can be read back to rebuild the same enemy set.
+    #[derive(Serialize, Deserialize)]
+    pub struct WorldSnapshot {
+        pub frame: u32,
+        pub seed: u64,
+        pub rng_words: u64,
+        pub game_time: f32,
+        pub game: Option<GameSnapshot>,
+        pub player: Option<PlayerSnapshot>,
+        pub coins: Vec<CoinSnapshot>,
+        pub enemies: Vec<EnemySnapshot>,
+        pub timers: TimerSnapshot,
+        pub events: EventSnapshot,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct GameSnapshot {
+        pub points: i32,
+        pub score: i32,
+        pub carried: i32,
+        pub health: i8,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct PlayerSnapshot {
+        pub position: Vec2,
+        pub velocity: Vec2,
+        pub invincibility: Option<String>,
+        pub invincibility_remaining: f32,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct CoinSnapshot {
+        pub position: Vec2,
+        pub health: bool,
+        pub age: Option<f32>,
+        pub bonus: Option<i32>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct EnemySnapshot {
+        pub entity: u64,
+        pub enemy_type: EnemyType,
+        pub behavior: String,
+        pub position: Vec2,
+        pub velocity: Vec2,
+        pub speed: f32,
+        pub accel: f32,
+        pub future_prediction: f32,
+        pub coin_pull: f32,
+        pub threat: f32,
+        pub leader: bool,
+        pub lunge: Option<String>,
+        pub dying: bool,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct TimerSnapshot {
+        pub post_hit_grace: bool,
+        pub cornered: bool,
+        pub delayed_spawns: Vec<f32>,
+        pub aggression_accel_multiplier: f32,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct EventSnapshot {
+        pub hit_player: usize,
+        pub hit_pickup: usize,
+        pub enemy_spawned: usize,
+    }
+
+    pub fn take_snapshot(
+        input: Res<Input<KeyCode>>,
+        frame_count: Res<FrameCount>,
+        rng: Res<GameRng>,
+        game_time: Res<GameTime>,
+        game_info: Option<Res<GameInfo>>,
+        player_query: Query<(&Transform, &Velocity, &Invincibility), With<Player>>,
+        coin_query: Query<(&Transform, &Coin, Option<&CoinCharge>)>,
+        enemy_query: Query<(
+            Entity,
+            &Transform,
+            &Enemy,
+            &Velocity,
+            Option<&Leader>,
+            Option<&Lunge>,
+            Option<&DyingEnemy>,
+        )>,
+        grace: Res<PostHitGrace>,
+        cornered: Res<Cornered>,
+        delayed_spawns: Res<DelayedSpawns>,
+        aggression: Res<AggressionModifier>,
+        hit_player: Res<Events<HitPlayer>>,
+        hit_pickup: Res<Events<HitPickup>>,
+        enemy_spawned: Res<Events<EnemySpawned>>,
+    ) -> Option<WorldSnapshot> {
+        if !input.just_pressed(INSPECT_KEY) {
+            return None;
+        }
+
+        Some(WorldSnapshot {
+            frame: frame_count.0,
+            seed: rng.seed(),
+            rng_words: rng.words(),
+            game_time: game_time.elapsed_seconds(),
+            game: game_info.map(|game_info| GameSnapshot {
+                points: game_info.points(),
+                score: game_info.score(),
+                carried: game_info.carried(),
+                health: game_info.health(),
+            }),
+            player: player_query
+                .get_single()
+                .ok()
+                .map(|(transform, velocity, invincibility)| PlayerSnapshot {
+                    position: transform.translation.truncate(),
+                    velocity: velocity.0.truncate(),
+                    invincibility: invincibility.source().map(|source| format!("{:?}", source)),
+                    invincibility_remaining: invincibility.remaining(),
+                }),
+            coins: coin_query
+                .iter()
+                .map(|(transform, coin, charge)| CoinSnapshot {
+                    position: transform.translation.truncate(),
+                    health: coin.health,
+                    age: charge.map(|charge| charge.age),
+                    bonus: charge.map(|charge| charge.bonus),
+                })
+                .collect(),
+            enemies: enemy_query
+                .iter()
+                .map(
+                    |(entity, transform, enemy, velocity, leader, lunge, dying)| EnemySnapshot {
+                        entity: entity.to_bits(),
+                        enemy_type: enemy.enemy_type,
+                        behavior: enemy.behavior.0.to_string(),
+                        position: transform.translation.truncate(),
+                        velocity: velocity.0.truncate(),
+                        speed: enemy.speed,
+                        accel: enemy.accel,
+                        future_prediction: enemy.future_prediction,
+                        coin_pull: enemy.coin_pull,
+                        threat: enemy.threat,
+                        leader: leader.is_some(),
+                        lunge: lunge.map(|lunge| lunge.phase_name().to_string()),
+                        dying: dying.is_some(),
+                    },
+                )
+                .collect(),
+            timers: TimerSnapshot {
+                post_hit_grace: grace.is_active(),
+                cornered: cornered.active,
+                delayed_spawns: delayed_spawns
+                    .0
+                    .iter()
+                    .map(|(timer, _)| timer.remaining_secs())
+                    .collect(),
+                aggression_accel_multiplier: aggression.accel_multiplier,
+            },
+            events: EventSnapshot {
+                hit_player: hit_player.len(),
+                hit_pickup: hit_pickup.len(),
+                enemy_spawned: enemy_spawned.len(),
+            },
+        })
+    }
+
+    pub fn save_snapshot(In(snapshot): In<Option<WorldSnapshot>>) {
+        let Some(snapshot) = snapshot else {
+            return;
+        };
+
+        match write_snapshot(&snapshot) {
+            Ok(path) => info!("Wrote a world snapshot to {}", path.display()),
+            Err(error) => warn!("Could not write a world snapshot: {}", error),
+        }
+    }
+
+    fn write_snapshot(snapshot: &WorldSnapshot) -> Result<PathBuf, Box<dyn Error>> {
+        let dir = storage::save_dir()
+            .ok_or("no save directory")?
+            .join(SNAPSHOT_DIR);
+        fs::create_dir_all(&dir)?;
+
+        let path = dir.join(format!("{}-{}.json", snapshot.seed, snapshot.frame));
+        fs::write(&path, serde_json::to_string_pretty(snapshot)?)?;
+        Ok(path)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use bevy::ecs::system::RunSystemOnce;
+
+        use super::*;
+        use crate::steering::BehaviorId;
+
+        // Rebuilds a snapshot's enemies, to reproduce a bug that depends on their exact layout.
+        fn spawn_snapshot_enemies(world: &mut World, snapshot: &WorldSnapshot) {
+            for enemy in &snapshot.enemies {
+                let behavior = [BehaviorId::RED, BehaviorId::PURPLE]
+                    .into_iter()
+                    .find(|id| id.0 == enemy.behavior)
+                    .unwrap_or_else(|| BehaviorId(Box::leak(enemy.behavior.clone().into())));
+
+                let mut entity = world.spawn((
+                    Enemy {
+                        enemy_type: enemy.enemy_type,
+                        speed: enemy.speed,
+                        accel: enemy.accel,
+                        future_prediction: enemy.future_prediction,
+                        coin_pull: enemy.coin_pull,
+                        threat: enemy.threat,
+                        behavior,
+                    },
+                    Transform::from_translation(enemy.position.extend(0.0)),
+                    Velocity(enemy.velocity.extend(0.0)),
+                ));
+                if enemy.leader {
+                    entity.insert(Leader);
+                }
+            }
+        }
+
+        fn snapshot_world() -> World {
+            let mut world = World::new();
+            let mut input = Input::<KeyCode>::default();
+            input.press(INSPECT_KEY);
+            world.insert_resource(input);
+            world.insert_resource(GameRng::from_seed(42));
+            world.init_resource::<FrameCount>();
+            world.init_resource::<GameTime>();
+            world.init_resource::<PostHitGrace>();
+            world.init_resource::<Cornered>();
+            world.init_resource::<DelayedSpawns>();
+            world.init_resource::<AggressionModifier>();
+            world.init_resource::<Events<HitPlayer>>();
+            world.init_resource::<Events<HitPickup>>();
+            world.init_resource::<Events<EnemySpawned>>();
+            world
+        }
+
+        fn enemy(enemy_type: EnemyType, behavior: BehaviorId, speed: f32) -> Enemy {
+            Enemy {
+                enemy_type,
+                speed,
+                accel: speed * 2.0,
+                future_prediction: 0.4,
+                coin_pull: -0.25,
+                threat: 0.6,
+                behavior,
+            }
+        }
+
+        // Entity ids are the one thing that differs between two worlds.
+        fn without_entities(enemies: &[EnemySnapshot]) -> Vec<serde_json::Value> {
+            enemies
+                .iter()
+                .map(|enemy| {
+                    let mut value = serde_json::to_value(enemy).unwrap();
+                    value.as_object_mut().unwrap().remove("entity");
+                    value
+                })
+                .collect()
+        }
+
+        #[test]
+        fn snapshot_rebuilds_the_same_enemies() {
+            let mut world = snapshot_world();
+            world.spawn((
+                enemy(EnemyType::Red, BehaviorId::RED, 150.0),
+                Transform::from_xyz(-399.5, 120.25, 0.0),
+                Velocity(Vec3::new(80.0, -3.5, 0.0)),
+            ));
+            world.spawn((
+                enemy(EnemyType::Purple, BehaviorId("zigzag"), 95.0),
+                Transform::from_xyz(10.0, 299.75, 0.0),
+                Velocity(Vec3::new(0.0, 40.0, 0.0)),
+                Leader,
+            ));
+
+            let snapshot = world.run_system_once(take_snapshot).unwrap();
+            let json = serde_json::to_string_pretty(&snapshot).unwrap();
+            let mut rebuilt = snapshot_world();
+            spawn_snapshot_enemies(&mut rebuilt, &serde_json::from_str(&json).unwrap());
+            let again = rebuilt.run_system_once(take_snapshot).unwrap();
+
+            assert_eq!(again.enemies.len(), 2);
+            assert_eq!(
+                without_entities(&again.enemies),
+                without_entities(&snapshot.enemies)
+            );
+        }
+
+        #[test]
+        fn nothing_is_taken_without_the_key() {
+            let mut world = snapshot_world();
+            world.insert_resource(Input::<KeyCode>::default());
+
+            assert!(world.run_system_once(take_snapshot).is_none());
+        }
+    }
+}
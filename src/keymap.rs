@@ -0,0 +1,374 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use bevy::prelude::*;
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::*,
+    player::KeySlots,
+    storage::{self, StorageError},
+    InputBindings,
+};
+
+const BINDINGS_FILE_NAME: &str = "bindings.ron";
+// Where a file that didn't parse is moved before a fresh one is written, so hand edits survive.
+const BROKEN_FILE_NAME: &str = "bindings.ron.broken";
+const BINDINGS_FILE_VERSION: u32 = 1;
+const BINDINGS_POLL_INTERVAL: f32 = 1.0;
+
+// Keys are written by these names rather than through KeyCode's own serde, whose variant names
+// have changed between Bevy releases. Only the left side should ever need touching.
+macro_rules! key_names {
+    ($($key:ident),* $(,)?) => {
+        const KEY_NAMES: &[(KeyCode, &str)] = &[$((KeyCode::$key, stringify!($key))),*];
+    };
+}
+
+key_names![
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+    I,
+    J,
+    K,
+    L,
+    M,
+    N,
+    O,
+    P,
+    Q,
+    R,
+    S,
+    T,
+    U,
+    V,
+    W,
+    X,
+    Y,
+    Z,
+    Key1,
+    Key2,
+    Key3,
+    Key4,
+    Key5,
+    Key6,
+    Key7,
+    Key8,
+    Key9,
+    Key0,
+    Escape,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    Up,
+    Down,
+    Left,
+    Right,
+    Space,
+    Return,
+    Tab,
+    Back,
+    Delete,
+    Insert,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    ShiftLeft,
+    ShiftRight,
+    ControlLeft,
+    ControlRight,
+    AltLeft,
+    AltRight,
+    SuperLeft,
+    SuperRight,
+    Capital,
+    Comma,
+    Period,
+    Slash,
+    Backslash,
+    Semicolon,
+    Apostrophe,
+    Grave,
+    Minus,
+    Equals,
+    BracketLeft,
+    BracketRight,
+    Numpad0,
+    Numpad1,
+    Numpad2,
+    Numpad3,
+    Numpad4,
+    Numpad5,
+    Numpad6,
+    Numpad7,
+    Numpad8,
+    Numpad9,
+    NumpadAdd,
+    NumpadSubtract,
+    NumpadMultiply,
+    NumpadDivide,
+    NumpadDecimal,
+    NumpadEnter,
+];
+
+pub(crate) fn key_name(key: KeyCode) -> Option<&'static str> {
+    KEY_NAMES
+        .iter()
+        .find(|(named, _)| *named == key)
+        .map(|(_, name)| *name)
+}
+
+fn key_from_name(name: &str) -> Option<KeyCode> {
+    KEY_NAMES
+        .iter()
+        .find(|(_, named)| named.eq_ignore_ascii_case(name.trim()))
+        .map(|(key, _)| *key)
+}
+
+// bindings.ron as written to disk: up to BINDING_SLOTS key names per action. Actions missing from
+// the file keep their defaults.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+struct BindingsFile {
+    version: u32,
+    up: Vec<String>,
+    down: Vec<String>,
+    left: Vec<String>,
+    right: Vec<String>,
+    start: Vec<String>,
+    pause: Vec<String>,
+    restart: Vec<String>,
+    mute: Vec<String>,
+    dash: Vec<String>,
+}
+
+impl Default for BindingsFile {
+    fn default() -> Self {
+        Self::from(&InputBindings::default())
+    }
+}
+
+impl From<&InputBindings> for BindingsFile {
+    fn from(bindings: &InputBindings) -> Self {
+        let names = |slots: KeySlots| -> Vec<String> {
+            slots
+                .into_iter()
+                .flatten()
+                .filter_map(|key| {
+                    let name = key_name(key);
+                    if name.is_none() {
+                        warn!("{:?} has no name in bindings.ron and won't be saved", key);
+                    }
+                    name.map(str::to_string)
+                })
+                .collect()
+        };
+
+        Self {
+            version: BINDINGS_FILE_VERSION,
+            up: names(bindings.up),
+            down: names(bindings.down),
+            left: names(bindings.left),
+            right: names(bindings.right),
+            start: names(bindings.start),
+            pause: names(bindings.pause),
+            restart: names(bindings.restart),
+            mute: names(bindings.mute),
+            dash: names(bindings.dash),
+        }
+    }
+}
+
+impl BindingsFile {
+    // Unknown key names are skipped with a warning rather than failing the whole file.
+    fn to_bindings(&self) -> InputBindings {
+        let slots = |names: &[String]| -> KeySlots {
+            let mut slots = [None; BINDING_SLOTS];
+            let keys = names.iter().filter_map(|name| {
+                let key = key_from_name(name);
+                if key.is_none() {
+                    warn!("Unknown key {:?} in {}", name, BINDINGS_FILE_NAME);
+                }
+                key
+            });
+            for (slot, key) in slots.iter_mut().zip(keys) {
+                *slot = Some(key);
+            }
+            slots
+        };
+
+        InputBindings {
+            up: slots(&self.up),
+            down: slots(&self.down),
+            left: slots(&self.left),
+            right: slots(&self.right),
+            start: slots(&self.start),
+            pause: slots(&self.pause),
+            restart: slots(&self.restart),
+            mute: slots(&self.mute),
+            dash: slots(&self.dash),
+        }
+    }
+}
+
+fn bindings_path() -> Option<PathBuf> {
+    storage::config_dir().map(|dir| dir.join(BINDINGS_FILE_NAME))
+}
+
+fn read_bindings(path: &Path) -> Result<InputBindings, StorageError> {
+    let contents = fs::read_to_string(path)?;
+    let file: BindingsFile = ron::from_str(&contents).map_err(StorageError::Parse)?;
+    Ok(file.to_bindings())
+}
+
+fn write_bindings(path: &Path, bindings: &InputBindings) -> Result<(), StorageError> {
+    let contents =
+        ron::ser::to_string_pretty(&BindingsFile::from(bindings), PrettyConfig::default())
+            .map_err(StorageError::Serialize)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, contents)?;
+    Ok(())
+}
+
+// Reads bindings.ron, or creates it: from `fallback` when it doesn't exist yet, which carries
+// bindings over from saves that predate the file, and from the defaults when it doesn't parse.
+pub fn load_bindings(fallback: &InputBindings) -> InputBindings {
+    let Some(path) = bindings_path() else {
+        warn!("No config directory, bindings won't be kept");
+        return fallback.clone();
+    };
+
+    let bindings = match read_bindings(&path) {
+        Ok(bindings) => return bindings,
+        Err(StorageError::Io(error)) if error.kind() == std::io::ErrorKind::NotFound => {
+            fallback.clone()
+        }
+        Err(error) => {
+            warn!(
+                "Could not load {}: {}, using defaults",
+                path.display(),
+                error
+            );
+            if let Err(error) = fs::rename(&path, path.with_file_name(BROKEN_FILE_NAME)) {
+                warn!("Could not move {} aside: {}", path.display(), error);
+            }
+            InputBindings::default()
+        }
+    };
+
+    if let Err(error) = write_bindings(&path, &bindings) {
+        warn!("Could not write {}: {}", path.display(), error);
+    }
+    bindings
+}
+
+#[derive(Default)]
+pub struct BindingsWatch {
+    since_poll: f32,
+    // When the file was last read or written by us, so our own writes don't count as edits.
+    modified: Option<SystemTime>,
+}
+
+// Writes changes to the resource out, and picks up edits made to the file while the game runs.
+pub fn sync_bindings_file(
+    mut bindings: ResMut<InputBindings>,
+    mut watch: Local<BindingsWatch>,
+    time: Res<Time<Real>>,
+) {
+    let Some(path) = bindings_path() else {
+        return;
+    };
+    let modified = || fs::metadata(&path).and_then(|meta| meta.modified()).ok();
+
+    if bindings.is_changed() && !bindings.is_added() {
+        match write_bindings(&path, &bindings) {
+            Ok(()) => watch.modified = modified(),
+            Err(error) => warn!("Could not write {}: {}", path.display(), error),
+        }
+        return;
+    }
+
+    watch.since_poll += time.delta_seconds();
+    if watch.since_poll < BINDINGS_POLL_INTERVAL {
+        return;
+    }
+    watch.since_poll = 0.0;
+
+    let current = modified();
+    if watch.modified.is_none() || current == watch.modified {
+        watch.modified = watch.modified.or(current);
+        return;
+    }
+    watch.modified = current;
+
+    // A half-written or broken edit leaves the bindings as they were until the next good one.
+    match read_bindings(&path) {
+        Ok(loaded) => {
+            info!("Reloaded {}", path.display());
+            bindings.set_if_neq(loaded);
+        }
+        Err(error) => warn!("Could not reload {}: {}", path.display(), error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::reflect::{DynamicEnum, DynamicVariant, TypeInfo, Typed};
+
+    use super::*;
+    use crate::player::Action;
+
+    // Every KeyCode variant, whether or not it has a name here.
+    fn all_keys() -> Vec<KeyCode> {
+        let TypeInfo::Enum(info) = KeyCode::type_info() else {
+            unreachable!("KeyCode is an enum");
+        };
+        info.iter()
+            .filter_map(|variant| {
+                KeyCode::from_reflect(&DynamicEnum::new(variant.name(), DynamicVariant::Unit))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn every_capturable_key_survives_bindings_ron() {
+        let keys = all_keys();
+        assert!(keys.len() > KEY_NAMES.len());
+
+        for key in keys {
+            let mut bindings = InputBindings::default();
+            if bindings.bind(Action::Dash, 1, Some(key)).is_err() {
+                continue;
+            }
+
+            let reloaded = BindingsFile::from(&bindings).to_bindings();
+            assert!(
+                reloaded.slots(Action::Dash).contains(&Some(key)),
+                "{:?}",
+                key
+            );
+        }
+    }
+}
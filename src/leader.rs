@@ -0,0 +1,164 @@
+use bevy::prelude::*;
+
+use crate::{assets::AssetHandles, config::*, enemy::Enemy, flocking::NeighborGrid, render_z};
+
+// Leaders never chase the coin and move slower than the pack, but every other enemy near one
+// speeds up.
+#[derive(Component)]
+pub struct Leader;
+
+#[derive(Component)]
+pub struct PackAura;
+
+pub fn leader_outline(asset_handles: &AssetHandles) -> ColorMesh2dBundle {
+    ColorMesh2dBundle {
+        mesh: asset_handles.enemy_mesh.clone().into(),
+        material: asset_handles.leader_outline_material.clone(),
+        transform: Transform::from_translation(Vec3::new(0.0, 0.0, render_z::CHILD_BELOW))
+            .with_scale(Vec3::splat(LEADER_OUTLINE_SCALE)),
+        ..default()
+    }
+}
+
+pub fn pack_aura(asset_handles: &AssetHandles) -> (PackAura, ColorMesh2dBundle) {
+    (
+        PackAura,
+        ColorMesh2dBundle {
+            mesh: asset_handles.enemy_mesh.clone().into(),
+            material: asset_handles.pack_aura_material.clone(),
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, render_z::CHILD_BELOW))
+                .with_scale(Vec3::splat(PACK_AURA_SCALE)),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+    )
+}
+
+// A snapshot of where the leaders are, or None when there are none to follow.
+pub fn leader_grid(leaders: impl Iterator<Item = (Entity, Vec3)>) -> Option<NeighborGrid> {
+    let mut grid = None;
+    for (entity, position) in leaders {
+        grid.get_or_insert_with(|| NeighborGrid::new(LEADER_BUFF_RADIUS))
+            .insert(entity, position, Vec3::ZERO);
+    }
+    grid
+}
+
+pub fn pack_speed_multiplier(
+    grid: Option<&NeighborGrid>,
+    entity: Entity,
+    position: Vec3,
+    leader: bool,
+) -> f32 {
+    let buffed = !leader && grid.is_some_and(|grid| grid.any_within(entity, position));
+    if buffed {
+        1.0 + LEADER_SPEED_BUFF
+    } else {
+        1.0
+    }
+}
+
+pub fn show_pack_auras(
+    enemies: Query<(Entity, &Transform, Option<&Leader>), With<Enemy>>,
+    mut auras: Query<(&Parent, &mut Visibility), With<PackAura>>,
+) {
+    let grid = leader_grid(
+        enemies
+            .iter()
+            .filter(|(_, _, leader)| leader.is_some())
+            .map(|(entity, transform, _)| (entity, transform.translation)),
+    );
+
+    for (parent, mut visibility) in auras.iter_mut() {
+        // Dying enemies are no longer Enemies, so their auras go out with the buff.
+        let buffed = enemies
+            .get(parent.get())
+            .is_ok_and(|(entity, transform, leader)| {
+                pack_speed_multiplier(
+                    grid.as_ref(),
+                    entity,
+                    transform.translation,
+                    leader.is_some(),
+                ) > 1.0
+            });
+
+        let next = if buffed {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if *visibility != next {
+            *visibility = next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entity(index: u32) -> Entity {
+        Entity::from_raw(index)
+    }
+
+    #[test]
+    fn buff_applies_within_the_radius_only() {
+        let grid = leader_grid([(entity(0), Vec3::ZERO)].into_iter());
+
+        let near =
+            pack_speed_multiplier(grid.as_ref(), entity(1), Vec3::new(170.0, 0.0, 0.0), false);
+        let far =
+            pack_speed_multiplier(grid.as_ref(), entity(2), Vec3::new(190.0, 0.0, 0.0), false);
+
+        assert_eq!(near, 1.0 + LEADER_SPEED_BUFF);
+        assert_eq!(far, 1.0);
+    }
+
+    #[test]
+    fn buff_expires_as_an_enemy_moves_away() {
+        let grid = leader_grid([(entity(0), Vec3::new(-100.0, 50.0, 0.0))].into_iter());
+
+        let multipliers: Vec<f32> = [0.0, 60.0, 120.0]
+            .into_iter()
+            .map(|x| {
+                pack_speed_multiplier(grid.as_ref(), entity(1), Vec3::new(x, 50.0, 0.0), false)
+            })
+            .collect();
+
+        assert_eq!(
+            multipliers,
+            [1.0 + LEADER_SPEED_BUFF, 1.0 + LEADER_SPEED_BUFF, 1.0]
+        );
+    }
+
+    #[test]
+    fn leaders_do_not_buff_themselves_or_each_other() {
+        let grid = leader_grid(
+            [
+                (entity(0), Vec3::ZERO),
+                (entity(1), Vec3::new(20.0, 0.0, 0.0)),
+            ]
+            .into_iter(),
+        );
+
+        assert_eq!(
+            pack_speed_multiplier(grid.as_ref(), entity(0), Vec3::ZERO, true),
+            1.0
+        );
+        assert_eq!(
+            pack_speed_multiplier(grid.as_ref(), entity(1), Vec3::new(20.0, 0.0, 0.0), true),
+            1.0
+        );
+    }
+
+    #[test]
+    fn no_leaders_means_no_buff() {
+        let grid = leader_grid(std::iter::empty());
+
+        assert!(grid.is_none());
+        assert_eq!(
+            pack_speed_multiplier(grid.as_ref(), entity(1), Vec3::ZERO, false),
+            1.0
+        );
+    }
+}
@@ -0,0 +1,274 @@
+//! Gorbulet's game logic, packaged as Bevy plugins.
+//!
+//! Add [`GorbuletPlugin`] on top of `DefaultPlugins` to get the full game. The public surface is
+//! intentionally small: the plugins, [`AppState`], the [`GameInfo`] and [`InputBindings`]
+//! resources, the gameplay events, and the [`SteeringRegistry`] for custom enemy behaviors.
+
+// Bevy systems take their resources and queries as parameters, so a system that does real work
+// easily passes seven of them, and its query types nest filters inside tuples. Splitting those up
+// only to please these lints would make the systems harder to follow.
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
+mod accent;
+mod aggression;
+mod asset_pack;
+mod assets;
+mod assist;
+mod audio;
+mod bench;
+mod camera;
+mod changelog;
+mod clipboard;
+mod codex;
+mod coin;
+mod companion;
+mod config;
+mod controls;
+mod cornered;
+mod decay;
+mod decor;
+mod deposit;
+mod descriptor;
+mod effects;
+mod enemy;
+mod flocking;
+mod game;
+mod game_time;
+mod ghost;
+mod grace;
+mod haptics;
+mod hud;
+mod inhibit;
+mod input_display;
+mod inspect;
+mod keymap;
+mod leader;
+mod lunge;
+mod menu;
+mod menu_list;
+mod migrations;
+mod movement;
+mod music;
+#[cfg(feature = "music-envelope")]
+mod music_envelope;
+mod mutator;
+#[cfg(feature = "online")]
+mod online;
+mod pacing;
+mod pattern;
+mod pause;
+mod pickup;
+mod player;
+mod post_fx;
+mod quality;
+mod records;
+mod render_z;
+mod repro;
+mod rng;
+mod score;
+mod screenshot;
+mod session;
+mod settings;
+mod shrink;
+mod soak;
+mod spectate;
+mod speed_ribbon;
+mod stats;
+mod steering;
+mod storage;
+mod theme;
+mod tint;
+mod tips;
+mod trail;
+mod transition;
+mod tutorial;
+mod util;
+mod victory;
+mod window;
+mod wrap_hint;
+
+use bevy::{audio::SpatialScale, prelude::*};
+
+use assets::AssetHandles;
+use audio::{Muted, SfxBudget};
+use config::{SPATIAL_AUDIO_SCALE, SPATIAL_EAR_GAP};
+use session::SessionStats;
+
+pub use asset_pack::{AssetPack, AssetPackError};
+pub use audio::AudioAvailable;
+pub use bench::BenchPlugin;
+pub use camera::{cursor_to_world, screen_to_world, world_to_screen};
+pub use changelog::{Changelog, ChangelogEntry, ChangelogPlugin};
+pub use codex::CodexPlugin;
+pub use coin::Coin;
+pub use companion::{Companion, CompanionPlugin};
+pub use config::{Difficulty, GameConfig, GameRules, HitFeedback, PlayerSpawn, RunConfig};
+pub use controls::ControlsPlugin;
+pub use decay::{DecayPlugin, ScoreDecay};
+pub use descriptor::{DescriptorError, DescriptorPlugin, QueuedSeed, RunDescriptor};
+pub use effects::{EffectsPlugin, ScreenShake};
+pub use enemy::{Enemy, EnemyRemoved, EnemySpawned, EnemyType};
+pub use game::{GainHealth, GameInfo, GameMode, GamePlugin, HitPlayer, HitSource, StateChecksums};
+pub use ghost::{GhostError, GhostFile, GhostPlugin};
+pub use haptics::HapticsPlugin;
+pub use hud::{HudElement, HudKey, HudLayout, HudPlugin};
+pub use input_display::{InputDisplay, InputDisplayPlugin};
+pub use inspect::InspectPlugin;
+pub use menu::{LastScore, MenuPlugin};
+pub use menu_list::MenuListPlugin;
+#[cfg(feature = "music-envelope")]
+pub use music_envelope::MusicEnvelopePlugin;
+pub use mutator::{Mutator, MutatorPlugin};
+#[cfg(feature = "online")]
+pub use online::{
+    handshake_host, handshake_join, read_frame, write_frame, Message, OnlineError, OnlinePlugin,
+    PROTOCOL_VERSION,
+};
+pub use pause::{PausePlugin, PauseState};
+pub use pickup::{HitPickup, Pickup, PickupKind, PickupSpawner};
+pub use player::{
+    Action, Dash, GamepadBindings, InputBindings, Invincibility, InvincibilitySource, Player,
+};
+pub use post_fx::{PostFxParams, PostFxPlugin};
+pub use quality::{EffectsQuality, QualityPlugin};
+pub use records::RecordsPlugin;
+pub use repro::{ReproFile, ReproPlugin};
+pub use score::{BonusReason, PenaltyReason, ScoreEvent};
+pub use screenshot::ScreenshotPlugin;
+pub use settings::SettingsPlugin;
+pub use soak::{SoakGuard, SoakPlugin, SoakSnapshot};
+pub use spectate::{CameraMode, SpectatePlugin};
+pub use steering::{
+    BehaviorId, Chase, EdgeChase, SteerContext, SteeringBehavior, SteeringRegistry,
+};
+pub use theme::{Arena, Theme, ThemePlugin};
+pub use tips::TipsPlugin;
+pub use transition::{RequestedTransition, Transition, TransitionPlugin};
+pub use tutorial::TutorialPlugin;
+pub use victory::{RunWon, VictoryPlugin};
+pub use window::{Suspended, WindowIntegrationPlugin, WINDOW_TITLE};
+
+pub struct GorbuletPlugin;
+
+impl Plugin for GorbuletPlugin {
+    fn build(&self, app: &mut App) {
+        // Without the audio plugin the sounds still need an asset type to load into and the volume
+        // somewhere to go, even though nothing will ever play them.
+        let audio_plugin = app.world.contains_resource::<Assets<AudioSource>>();
+        if !audio_plugin {
+            app.init_asset::<AudioSource>()
+                .init_resource::<GlobalVolume>();
+        }
+
+        // Playfield distances scaled down to where rodio only pans and never attenuates.
+        app.insert_resource(SpatialScale::new_2d(SPATIAL_AUDIO_SCALE))
+            .init_resource::<GameConfig>()
+            .init_resource::<SteeringRegistry>()
+            .init_resource::<Muted>()
+            .init_resource::<SfxBudget>()
+            .insert_resource(AudioAvailable(audio_plugin))
+            .init_resource::<SessionStats>()
+            .init_resource::<GamepadBindings>()
+            .add_state::<AppState>()
+            .add_systems(Startup, (setup, audio::probe_audio_output))
+            .add_systems(
+                Update,
+                (
+                    (audio::toggle_mute, audio::sync_volume).chain(),
+                    (
+                        audio::refill_sfx_budget,
+                        audio::sweep_unplayed_sounds,
+                        audio::detect_audio_output,
+                    )
+                        .run_if(window::not_suspended),
+                ),
+            )
+            .add_systems(
+                Last,
+                (
+                    keymap::sync_bindings_file,
+                    storage::autosave,
+                    session::flush_session,
+                ),
+            )
+            .add_plugins((
+                MenuPlugin,
+                CodexPlugin,
+                RecordsPlugin,
+                MutatorPlugin,
+                MenuListPlugin,
+                SettingsPlugin,
+                ControlsPlugin,
+                ThemePlugin,
+                InspectPlugin,
+                TransitionPlugin,
+                SoakPlugin,
+                TutorialPlugin,
+                InputDisplayPlugin,
+                PostFxPlugin,
+                CompanionPlugin,
+            ))
+            .add_plugins((
+                HudPlugin,
+                TipsPlugin,
+                VictoryPlugin,
+                SpectatePlugin,
+                ScreenshotPlugin,
+                DescriptorPlugin,
+                ReproPlugin,
+                BenchPlugin,
+                GhostPlugin,
+                GamePlugin,
+                EffectsPlugin,
+                QualityPlugin,
+                PausePlugin,
+                HapticsPlugin,
+                WindowIntegrationPlugin,
+            ));
+
+        app.add_plugins((ChangelogPlugin, DecayPlugin));
+        #[cfg(feature = "online")]
+        app.add_plugins(OnlinePlugin);
+        #[cfg(feature = "music-envelope")]
+        app.add_plugins(MusicEnvelopePlugin);
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
+pub enum AppState {
+    #[default]
+    Menu,
+    Codex,
+    Records,
+    Mutators,
+    Settings,
+    Controls,
+    WhatsNew,
+    #[cfg(feature = "online")]
+    Online,
+    Game,
+}
+
+fn setup(
+    mut commands: Commands,
+    meshes: ResMut<Assets<Mesh>>,
+    materials: ResMut<Assets<ColorMaterial>>,
+    asset_server: Res<AssetServer>,
+) {
+    let save_data = storage::load_or_default();
+    let bindings = keymap::load_bindings(&save_data.settings.bindings);
+    for (first, second) in bindings.conflicts() {
+        warn!(
+            "Input bindings conflict: {} and {} share a key",
+            first, second
+        );
+    }
+    commands.insert_resource(bindings);
+    commands.insert_resource(save_data);
+    commands.insert_resource(AssetHandles::new(asset_server, meshes, materials));
+    commands.spawn((
+        Camera2dBundle::default(),
+        ScreenShake::default(),
+        SpatialListener::new(SPATIAL_EAR_GAP),
+    ));
+}
@@ -0,0 +1,138 @@
+use bevy::{audio::Volume, prelude::*};
+use rand::Rng;
+
+use crate::{
+    assets::AssetHandles, audio::SfxBudget, config::*, game_time::GameTime, render_z, rng::GameRng,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LungePhase {
+    Waiting,
+    Telegraph,
+    Lunging,
+}
+
+#[derive(Component)]
+pub struct Lunge {
+    phase: LungePhase,
+    timer: Timer,
+}
+
+impl Lunge {
+    pub fn new(rng: &mut GameRng) -> Self {
+        Self {
+            phase: LungePhase::Waiting,
+            timer: Timer::from_seconds(lunge_wait(rng), TimerMode::Once),
+        }
+    }
+
+    pub fn speed_multiplier(&self) -> f32 {
+        match self.phase {
+            LungePhase::Telegraph => LUNGE_TELEGRAPH_SPEED_MULTIPLIER,
+            _ => 1.0,
+        }
+    }
+
+    pub fn accel_multiplier(&self) -> f32 {
+        match self.phase {
+            LungePhase::Lunging => LUNGE_ACCEL_MULTIPLIER,
+            _ => 1.0,
+        }
+    }
+
+    #[cfg(feature = "devtools")]
+    pub fn phase_name(&self) -> &'static str {
+        match self.phase {
+            LungePhase::Waiting => "waiting",
+            LungePhase::Telegraph => "telegraph",
+            LungePhase::Lunging => "lunging",
+        }
+    }
+}
+
+#[derive(Component)]
+pub(crate) struct LungeOutline;
+
+fn lunge_wait(rng: &mut GameRng) -> f32 {
+    rng.gen_range(LUNGE_MIN_WAIT..LUNGE_MAX_WAIT)
+}
+
+pub fn update_lunges(
+    mut commands: Commands,
+    mut rng: ResMut<GameRng>,
+    mut budget: ResMut<SfxBudget>,
+    mut query: Query<(Entity, &mut Lunge)>,
+    outline_query: Query<(Entity, &Parent), With<LungeOutline>>,
+    asset_handles: Res<AssetHandles>,
+    game_time: Res<GameTime>,
+) {
+    for (entity, mut lunge) in query.iter_mut() {
+        if !lunge.timer.tick(game_time.delta()).finished() {
+            continue;
+        }
+
+        match lunge.phase {
+            LungePhase::Waiting => {
+                lunge.phase = LungePhase::Telegraph;
+                lunge.timer = Timer::from_seconds(LUNGE_TELEGRAPH_TIME, TimerMode::Once);
+
+                commands.entity(entity).with_children(|parent| {
+                    parent.spawn((
+                        LungeOutline,
+                        ColorMesh2dBundle {
+                            mesh: asset_handles.enemy_mesh.clone().into(),
+                            material: asset_handles.lunge_outline_material.clone(),
+                            transform: Transform::from_translation(Vec3::new(
+                                0.0,
+                                0.0,
+                                render_z::CHILD_BELOW,
+                            ))
+                            .with_scale(Vec3::splat(LUNGE_OUTLINE_SCALE)),
+                            ..default()
+                        },
+                    ));
+                });
+            }
+            LungePhase::Telegraph => {
+                lunge.phase = LungePhase::Lunging;
+                lunge.timer = Timer::from_seconds(LUNGE_TIME, TimerMode::Once);
+
+                outline_query
+                    .iter()
+                    .filter(|(_, parent)| parent.get() == entity)
+                    .for_each(|(outline, _)| {
+                        commands.entity(outline).despawn_recursive();
+                    });
+
+                budget.play(
+                    &mut commands,
+                    asset_handles.whoosh_sound.clone(),
+                    PlaybackSettings::DESPAWN
+                        .with_volume(Volume::new_relative(LUNGE_WHOOSH_VOLUME)),
+                );
+            }
+            LungePhase::Lunging => {
+                lunge.phase = LungePhase::Waiting;
+                lunge.timer = Timer::from_seconds(lunge_wait(&mut rng), TimerMode::Once);
+            }
+        }
+    }
+}
+
+pub fn flash_lunge_outlines(
+    mut outline_query: Query<(&Parent, &mut Visibility), With<LungeOutline>>,
+    lunge_query: Query<&Lunge>,
+) {
+    for (parent, mut visibility) in outline_query.iter_mut() {
+        let Ok(lunge) = lunge_query.get(parent.get()) else {
+            continue;
+        };
+
+        let flash = ((lunge.timer.elapsed_secs() / LUNGE_FLASH_INTERVAL) as u32).is_multiple_of(2);
+        *visibility = if flash {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
@@ -0,0 +1,373 @@
+use bevy::{prelude::*, sprite::Anchor, window::PrimaryWindow};
+
+use crate::{
+    asset_pack::AssetPack,
+    assets::AssetHandles,
+    audio::AudioAvailable,
+    config::*,
+    decor::{move_menu_decor, spawn_menu_decor},
+    effects::text_label,
+    game::{GameMode, RunStats},
+    menu_list::{spawn_menu_list, EntryKind, MenuEntry, MenuFocus, MenuListEvent},
+    movement::wraparound,
+    mutator::ActiveMutators,
+    pacing::spawn_pacing_graph,
+    player::{Action, GamepadBindings, InputBindings, MovementFeel},
+    quality::EffectsLevel,
+    render_z,
+    screenshot::best_run_thumbnail,
+    storage::SaveData,
+    theme::Theme,
+    transition::{RequestedTransition, Transition},
+    window::not_suspended,
+    AppState,
+};
+
+const MAIN_LIST: &str = "main";
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(LastScore(None))
+            .add_systems(Update, debug_start)
+            .add_systems(
+                Update,
+                (
+                    handle_main_menu,
+                    show_audio_unavailable,
+                    (move_menu_decor, wraparound)
+                        .chain()
+                        .run_if(|level: Res<EffectsLevel>| level.0.animated_background())
+                        .run_if(not_suspended),
+                )
+                    .run_if(in_state(AppState::Menu)),
+            )
+            .add_systems(OnEnter(AppState::Menu), setup_menu)
+            .add_systems(OnExit(AppState::Menu), cleanup_menu);
+    }
+}
+
+#[derive(Resource)]
+pub struct LastScore(pub Option<i32>);
+
+fn debug_start(
+    state: Res<State<AppState>>,
+    mut transitions: ResMut<RequestedTransition>,
+    mut game_mode: ResMut<GameMode>,
+    bindings: Res<InputBindings>,
+    input: Res<Input<KeyCode>>,
+    (pad_bindings, gamepads, buttons): (
+        Res<GamepadBindings>,
+        Res<Gamepads>,
+        Res<Input<GamepadButton>>,
+    ),
+) {
+    let start = bindings.just_pressed(Action::Start, &input)
+        || pad_bindings.just_pressed(Action::Start, &gamepads, &buttons);
+    if *state.get() != AppState::Game && start {
+        *game_mode = GameMode::Classic;
+        transitions.request(Transition::App(AppState::Game));
+    }
+}
+
+fn handle_main_menu(
+    mut events: EventReader<MenuListEvent>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut game_mode: ResMut<GameMode>,
+) {
+    for event in events.read() {
+        let MenuListEvent::Activated {
+            list: MAIN_LIST,
+            entry,
+        } = event
+        else {
+            continue;
+        };
+
+        match *entry {
+            "classic" => {
+                *game_mode = GameMode::Classic;
+                next_state.set(AppState::Game);
+            }
+            "zen" => {
+                *game_mode = GameMode::Zen;
+                next_state.set(AppState::Game);
+            }
+            "deposit" => {
+                *game_mode = GameMode::Deposit;
+                next_state.set(AppState::Game);
+            }
+            "tutorial" => {
+                *game_mode = GameMode::Tutorial;
+                next_state.set(AppState::Game);
+            }
+            "mutators" => next_state.set(AppState::Mutators),
+            "codex" => next_state.set(AppState::Codex),
+            "records" => next_state.set(AppState::Records),
+            "settings" => next_state.set(AppState::Settings),
+            #[cfg(feature = "online")]
+            "online" => next_state.set(AppState::Online),
+            _ => {}
+        }
+    }
+}
+
+fn setup_menu(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+    asset_handles: Res<AssetHandles>,
+    last_score: Res<LastScore>,
+    save_data: Res<SaveData>,
+    bindings: Res<InputBindings>,
+    mutators: Res<ActiveMutators>,
+    focus: Res<MenuFocus>,
+    theme: Res<Theme>,
+    run_stats: Option<Res<RunStats>>,
+    asset_pack: Option<Res<AssetPack>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    gamepads: Res<Gamepads>,
+) {
+    spawn_menu_decor(&mut commands, &mut materials, &asset_handles, &theme);
+
+    commands.spawn(Text2dBundle {
+        text: Text::from_section(
+            if gamepads.iter().next().is_some() {
+                format!(
+                    "Press {} or pad South to Start",
+                    bindings.label(Action::Start)
+                )
+            } else {
+                format!("Press {} to Start", bindings.label(Action::Start))
+            },
+            TextStyle {
+                font: asset_handles.font.clone(),
+                font_size: 120.0,
+                color: Color::DARK_GRAY,
+            },
+        )
+        .with_alignment(TextAlignment::Center),
+        transform: Transform::from_translation(Vec3::new(0.0, -120.0, render_z::BACKDROP)),
+        ..default()
+    });
+
+    let zen_label = match save_data.zen_best {
+        Some(zen_best) => match save_data.zen_best_feel {
+            MovementFeel::Momentum => format!("Zen (best {})", zen_best),
+            feel => format!("Zen (best {}, {})", zen_best, feel.name()),
+        },
+        None => "Zen".to_string(),
+    };
+    let entries = vec![
+        MenuEntry::new("classic", "Classic", EntryKind::Action),
+        MenuEntry::new("zen", zen_label, EntryKind::Action),
+        MenuEntry::new("deposit", "Deposit", EntryKind::Action),
+        MenuEntry::new(
+            "tutorial",
+            if save_data.tutorial_done {
+                "Tutorial"
+            } else {
+                "Tutorial (new? start here)"
+            },
+            EntryKind::Action,
+        ),
+        MenuEntry::new("online", "Online race", EntryKind::Action),
+        MenuEntry::new("mutators", "Mutators", EntryKind::Action),
+        MenuEntry::new("codex", "Codex", EntryKind::Action),
+        MenuEntry::new("records", "Records", EntryKind::Action),
+        MenuEntry::new("settings", "Settings", EntryKind::Action),
+    ]
+    .into_iter()
+    .filter(|entry| entry.id != "online" || cfg!(feature = "online"))
+    .collect();
+    spawn_menu_list(
+        &mut commands,
+        &asset_handles,
+        &focus,
+        MAIN_LIST,
+        entries,
+        Vec3::new(0.0, -200.0, render_z::BACKDROP),
+    );
+
+    commands.spawn(Text2dBundle {
+        text: Text::from_section(
+            format!(
+                "Difficulty: {}    Arena: {}",
+                save_data.settings.difficulty.name(),
+                theme.arena.name()
+            ),
+            TextStyle {
+                font: asset_handles.font.clone(),
+                font_size: 28.0,
+                color: Color::DARK_GRAY,
+            },
+        )
+        .with_alignment(TextAlignment::Center),
+        transform: Transform::from_translation(Vec3::new(0.0, 300.0, render_z::BACKDROP)),
+        ..default()
+    });
+
+    if !mutators.is_empty() {
+        commands.spawn(Text2dBundle {
+            text: Text::from_section(
+                format!(
+                    "Mutators: {} (x{:.2})",
+                    mutators.key(),
+                    mutators.multiplier()
+                ),
+                TextStyle {
+                    font: asset_handles.font.clone(),
+                    font_size: 28.0,
+                    color: Color::DARK_GRAY,
+                },
+            )
+            .with_alignment(TextAlignment::Center),
+            transform: Transform::from_translation(Vec3::new(0.0, 270.0, render_z::BACKDROP)),
+            ..default()
+        });
+    }
+
+    // Always on screen while another asset pack is in use, so sounds are never judged against the
+    // wrong one.
+    if let Some(pack) = asset_pack {
+        let window = window.single();
+        let corner = Vec2::new(-window.width(), -window.height()) / 2.0
+            + Vec2::splat(ASSET_PACK_LABEL_MARGIN);
+        commands.spawn(Text2dBundle {
+            text: Text::from_section(
+                format!("Assets: {}", pack.name),
+                TextStyle {
+                    font: asset_handles.font.clone(),
+                    font_size: 20.0,
+                    color: Color::DARK_GRAY,
+                },
+            ),
+            text_anchor: Anchor::BottomLeft,
+            transform: Transform::from_translation(corner.extend(render_z::BACKDROP)),
+            ..default()
+        });
+    }
+
+    if let Some(best) = save_data.best_score {
+        spawn_best_run(
+            &mut commands,
+            &mut materials,
+            &mut images,
+            &asset_handles,
+            best,
+        );
+    }
+
+    if let Some(score) = last_score.0 {
+        commands.spawn(Text2dBundle {
+            text: Text::from_section(
+                score.to_string(),
+                TextStyle {
+                    font: asset_handles.font.clone(),
+                    font_size: 120.0,
+                    color: Color::DARK_GRAY,
+                },
+            )
+            .with_alignment(TextAlignment::Center),
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, render_z::BACKDROP)),
+            ..default()
+        });
+
+        if let Some(run_stats) = run_stats.filter(|run_stats| !run_stats.coin_times.is_empty()) {
+            spawn_pacing_graph(
+                &mut commands,
+                &asset_handles,
+                &run_stats.coin_times,
+                run_stats.elapsed,
+            );
+        }
+    }
+}
+
+#[derive(Component)]
+struct AudioUnavailableNote;
+
+// Audio is only found missing a moment after startup, so this can't live in setup_menu.
+fn show_audio_unavailable(
+    mut commands: Commands,
+    audio: Res<AudioAvailable>,
+    asset_handles: Res<AssetHandles>,
+    note_query: Query<(), With<AudioUnavailableNote>>,
+) {
+    if audio.0 || !note_query.is_empty() {
+        return;
+    }
+
+    commands.spawn((
+        AudioUnavailableNote,
+        text_label(
+            &asset_handles,
+            "Audio unavailable: health pips are shown in game",
+            24.0,
+            Color::DARK_GRAY,
+            Vec3::new(0.0, 240.0, render_z::BACKDROP),
+        ),
+    ));
+}
+
+fn spawn_best_run(
+    commands: &mut Commands,
+    materials: &mut Assets<ColorMaterial>,
+    images: &mut Assets<Image>,
+    asset_handles: &AssetHandles,
+    best: i32,
+) {
+    let position = THUMBNAIL_POSITION.extend(render_z::BACKDROP);
+
+    match best_run_thumbnail(images) {
+        Some(thumbnail) => {
+            commands.spawn(SpriteBundle {
+                texture: thumbnail,
+                transform: Transform::from_translation(position),
+                ..default()
+            });
+        }
+        None => {
+            commands.spawn(ColorMesh2dBundle {
+                mesh: asset_handles.bar_mesh.clone().into(),
+                material: materials.add(ColorMaterial::from(Color::rgb(0.15, 0.15, 0.15))),
+                transform: Transform::from_translation(position)
+                    .with_scale(THUMBNAIL_PLACEHOLDER_SIZE.extend(1.0)),
+                ..default()
+            });
+            commands.spawn(text_label(
+                asset_handles,
+                "No screenshot",
+                20.0,
+                Color::DARK_GRAY,
+                position + Vec3::new(0.0, 0.0, render_z::CHILD_ABOVE),
+            ));
+        }
+    }
+
+    commands.spawn(text_label(
+        asset_handles,
+        format!("Best: {}", best),
+        28.0,
+        Color::DARK_GRAY,
+        position - Vec3::new(0.0, THUMBNAIL_PLACEHOLDER_SIZE.y / 2.0 + 24.0, 0.0),
+    ));
+}
+
+pub fn cleanup_menu(
+    mut commands: Commands,
+    query: Query<
+        Entity,
+        (
+            Without<Camera2d>,
+            Without<Window>,
+            Without<Handle<AudioSource>>,
+            Without<PlaybackSettings>,
+        ),
+    >,
+) {
+    query.iter().for_each(|entity| {
+        commands.entity(entity).despawn();
+    });
+}
@@ -0,0 +1,432 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::{
+    assets::AssetHandles,
+    config::*,
+    player::{Action, InputBindings},
+};
+
+pub struct MenuListPlugin;
+
+impl Plugin for MenuListPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MenuFocus>()
+            .add_event::<MenuListEvent>()
+            .add_systems(Update, (navigate_menu_lists, render_menu_lists).chain());
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EntryKind {
+    Action,
+    Toggle(bool),
+    Slider {
+        value: f32,
+        min: f32,
+        max: f32,
+        step: f32,
+    },
+    Choice {
+        options: Vec<&'static str>,
+        selected: usize,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct MenuEntry {
+    pub id: &'static str,
+    pub label: String,
+    pub kind: EntryKind,
+}
+
+impl MenuEntry {
+    pub fn new(id: &'static str, label: impl Into<String>, kind: EntryKind) -> Self {
+        Self {
+            id,
+            label: label.into(),
+            kind,
+        }
+    }
+
+    fn text(&self) -> String {
+        match &self.kind {
+            EntryKind::Action => self.label.clone(),
+            EntryKind::Toggle(value) => {
+                format!("{}: {}", self.label, if *value { "On" } else { "Off" })
+            }
+            EntryKind::Slider {
+                value, min, max, ..
+            } => {
+                let filled = ((value - min) / (max - min) * 10.0).round() as usize;
+                format!(
+                    "{}: [{}{}]",
+                    self.label,
+                    "|".repeat(filled),
+                    ".".repeat(10 - filled.min(10))
+                )
+            }
+            EntryKind::Choice { options, selected } => {
+                format!("{}: < {} >", self.label, options[*selected])
+            }
+        }
+    }
+
+    // Returns whether the value changed.
+    fn adjust(&mut self, direction: i32) -> bool {
+        match &mut self.kind {
+            EntryKind::Action => false,
+            EntryKind::Toggle(value) => {
+                *value = !*value;
+                true
+            }
+            EntryKind::Slider {
+                value,
+                min,
+                max,
+                step,
+            } => {
+                let adjusted = (*value + *step * direction as f32).clamp(*min, *max);
+                let changed = adjusted != *value;
+                *value = adjusted;
+                changed
+            }
+            EntryKind::Choice { options, selected } => {
+                let count = options.len() as i32;
+                *selected = (*selected as i32 + direction).rem_euclid(count) as usize;
+                true
+            }
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct MenuList {
+    pub id: &'static str,
+    pub entries: Vec<MenuEntry>,
+    focus: usize,
+}
+
+impl MenuList {
+    pub fn focused(&self) -> &MenuEntry {
+        &self.entries[self.focus]
+    }
+}
+
+// Limits a list to this many rows on screen, scrolling to keep the focused entry among them.
+#[derive(Component)]
+pub struct MenuListWindow(pub usize);
+
+#[derive(Component)]
+struct MenuEntryText(usize);
+
+// Remembers the focused entry of every list, so returning from a sub-screen lands where the
+// player left off.
+#[derive(Resource, Default)]
+pub struct MenuFocus(HashMap<&'static str, usize>);
+
+impl MenuFocus {
+    fn restore(&self, id: &'static str, count: usize) -> usize {
+        self.0
+            .get(id)
+            .copied()
+            .filter(|focus| *focus < count)
+            .unwrap_or(0)
+    }
+}
+
+#[derive(Event, Debug, Clone, PartialEq)]
+pub enum MenuListEvent {
+    Activated {
+        list: &'static str,
+        entry: &'static str,
+    },
+    Changed {
+        list: &'static str,
+        entry: &'static str,
+        kind: EntryKind,
+    },
+    Back {
+        list: &'static str,
+    },
+}
+
+pub fn spawn_menu_list(
+    commands: &mut Commands,
+    asset_handles: &AssetHandles,
+    focus: &MenuFocus,
+    id: &'static str,
+    entries: Vec<MenuEntry>,
+    position: Vec3,
+) -> Entity {
+    let focus = focus.restore(id, entries.len());
+    let count = entries.len();
+
+    commands
+        .spawn((
+            MenuList { id, entries, focus },
+            SpatialBundle::from_transform(Transform::from_translation(position)),
+        ))
+        .with_children(|parent| {
+            for index in 0..count {
+                parent.spawn((
+                    MenuEntryText(index),
+                    Text2dBundle {
+                        text: Text::from_section(
+                            "",
+                            TextStyle {
+                                font: asset_handles.font.clone(),
+                                font_size: MENU_LIST_FONT_SIZE,
+                                color: Color::DARK_GRAY,
+                            },
+                        )
+                        .with_alignment(TextAlignment::Center),
+                        transform: Transform::from_translation(Vec3::new(
+                            0.0,
+                            -MENU_LIST_SPACING * index as f32,
+                            0.0,
+                        )),
+                        ..default()
+                    },
+                ));
+            }
+        })
+        .id()
+}
+
+fn navigate_menu_lists(
+    bindings: Res<InputBindings>,
+    input: Res<Input<KeyCode>>,
+    mut focus: ResMut<MenuFocus>,
+    mut events: EventWriter<MenuListEvent>,
+    mut lists: Query<&mut MenuList>,
+) {
+    for mut list in lists.iter_mut() {
+        let count = list.entries.len();
+        if count == 0 {
+            continue;
+        }
+
+        if bindings.just_pressed(Action::Pause, &input) {
+            events.send(MenuListEvent::Back { list: list.id });
+            continue;
+        }
+
+        if input.any_just_pressed(bindings.keys(Action::Up).chain([KeyCode::Up])) {
+            list.focus = (list.focus + count - 1) % count;
+            focus.0.insert(list.id, list.focus);
+        }
+        if input.any_just_pressed(bindings.keys(Action::Down).chain([KeyCode::Down])) {
+            list.focus = (list.focus + 1) % count;
+            focus.0.insert(list.id, list.focus);
+        }
+
+        let direction =
+            if input.any_just_pressed(bindings.keys(Action::Left).chain([KeyCode::Left])) {
+                -1
+            } else if input.any_just_pressed(bindings.keys(Action::Right).chain([KeyCode::Right])) {
+                1
+            } else {
+                0
+            };
+        let confirm = input.just_pressed(KeyCode::Return);
+
+        let id = list.id;
+        let index = list.focus;
+
+        if confirm && list.entries[index].kind == EntryKind::Action {
+            events.send(MenuListEvent::Activated {
+                list: id,
+                entry: list.entries[index].id,
+            });
+        } else if confirm || direction != 0 {
+            let entry = &mut list.entries[index];
+            if entry.adjust(if direction == 0 { 1 } else { direction }) {
+                events.send(MenuListEvent::Changed {
+                    list: id,
+                    entry: entry.id,
+                    kind: entry.kind.clone(),
+                });
+            }
+        }
+    }
+}
+
+fn render_menu_lists(
+    lists: Query<(&MenuList, &Children, Option<&MenuListWindow>), Changed<MenuList>>,
+    mut texts: Query<(&MenuEntryText, &mut Text, &mut Transform, &mut Visibility)>,
+) {
+    for (list, children, window) in lists.iter() {
+        let rows = window.map_or(list.entries.len(), |window| window.0.max(1));
+        let top = list
+            .focus
+            .saturating_sub(rows / 2)
+            .min(list.entries.len().saturating_sub(rows));
+
+        for child in children.iter() {
+            let Ok((entry, mut text, mut transform, mut visibility)) = texts.get_mut(*child) else {
+                continue;
+            };
+
+            let row = entry.0 as f32 - top as f32;
+            transform.translation.y = -MENU_LIST_SPACING * row;
+            *visibility = if (top..top + rows).contains(&entry.0) {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+
+            let focused = entry.0 == list.focus;
+            text.sections[0].value = list.entries[entry.0].text();
+            text.sections[0].style.color = if focused {
+                Color::WHITE
+            } else {
+                Color::DARK_GRAY
+            };
+            transform.scale = Vec3::splat(if focused { MENU_LIST_FOCUS_SCALE } else { 1.0 });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::input::{keyboard::KeyboardInput, ButtonState, InputPlugin};
+
+    use super::*;
+
+    fn menu_app(entries: Vec<MenuEntry>) -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, InputPlugin))
+            .init_resource::<InputBindings>()
+            .init_resource::<MenuFocus>()
+            .add_event::<MenuListEvent>()
+            .add_systems(Update, navigate_menu_lists);
+        app.world.spawn(MenuList {
+            id: "test",
+            entries,
+            focus: 0,
+        });
+        app
+    }
+
+    fn entries() -> Vec<MenuEntry> {
+        vec![
+            MenuEntry::new("play", "Play", EntryKind::Action),
+            MenuEntry::new("music", "Music", EntryKind::Toggle(false)),
+            MenuEntry::new(
+                "volume",
+                "Volume",
+                EntryKind::Slider {
+                    value: 1.0,
+                    min: 0.0,
+                    max: 1.0,
+                    step: 0.1,
+                },
+            ),
+        ]
+    }
+
+    fn send_key(app: &mut App, key_code: KeyCode, state: ButtonState) {
+        app.world.send_event(KeyboardInput {
+            scan_code: 0,
+            key_code: Some(key_code),
+            state,
+            window: Entity::PLACEHOLDER,
+        });
+    }
+
+    // Presses the key for one frame and returns the events the list sent in that frame.
+    fn tap(app: &mut App, key_code: KeyCode) -> Vec<MenuListEvent> {
+        send_key(app, key_code, ButtonState::Pressed);
+        app.update();
+        let events = app
+            .world
+            .resource_mut::<Events<MenuListEvent>>()
+            .drain()
+            .collect();
+        send_key(app, key_code, ButtonState::Released);
+        app.update();
+        events
+    }
+
+    fn focused(app: &mut App) -> &'static str {
+        app.world
+            .query::<&MenuList>()
+            .single(&app.world)
+            .focused()
+            .id
+    }
+
+    #[test]
+    fn focus_wraps_at_both_ends() {
+        let mut app = menu_app(entries());
+
+        tap(&mut app, KeyCode::Up);
+        assert_eq!(focused(&mut app), "volume");
+
+        tap(&mut app, KeyCode::S);
+        assert_eq!(focused(&mut app), "play");
+    }
+
+    #[test]
+    fn confirm_activates_actions_and_adjusts_values() {
+        let mut app = menu_app(entries());
+
+        assert_eq!(
+            tap(&mut app, KeyCode::Return),
+            [MenuListEvent::Activated {
+                list: "test",
+                entry: "play",
+            }]
+        );
+
+        tap(&mut app, KeyCode::Down);
+        assert_eq!(
+            tap(&mut app, KeyCode::Right),
+            [MenuListEvent::Changed {
+                list: "test",
+                entry: "music",
+                kind: EntryKind::Toggle(true),
+            }]
+        );
+    }
+
+    #[test]
+    fn slider_at_its_limit_sends_nothing() {
+        let mut app = menu_app(entries());
+        tap(&mut app, KeyCode::Up);
+
+        assert!(tap(&mut app, KeyCode::Right).is_empty());
+        assert_eq!(tap(&mut app, KeyCode::Left).len(), 1);
+    }
+
+    #[test]
+    fn pause_key_backs_out() {
+        let mut app = menu_app(entries());
+
+        assert_eq!(
+            tap(&mut app, KeyCode::Escape),
+            [MenuListEvent::Back { list: "test" }]
+        );
+    }
+
+    #[test]
+    fn focus_survives_a_sub_screen() {
+        let mut app = menu_app(entries());
+        tap(&mut app, KeyCode::Down);
+        tap(&mut app, KeyCode::Down);
+
+        let list = app
+            .world
+            .query_filtered::<Entity, With<MenuList>>()
+            .single(&app.world);
+        app.world.despawn(list);
+
+        let focus = app.world.resource::<MenuFocus>();
+        assert_eq!(focus.restore("test", 3), 2);
+        // A list that lost entries in the meantime starts over.
+        assert_eq!(focus.restore("test", 2), 0);
+        assert_eq!(focus.restore("other", 3), 0);
+    }
+}
@@ -0,0 +1,164 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    storage::{SaveData, StorageError},
+    InputBindings,
+};
+
+// Bump this and append a step to MIGRATIONS whenever the save format changes in a way serde
+// defaults can't absorb.
+pub const SAVE_VERSION: u32 = 3;
+
+// Saves written before versioning existed carry no version field and count as version 1.
+const UNVERSIONED: u32 = 1;
+
+// MIGRATIONS[n] upgrades a version n + 1 save to version n + 2.
+const MIGRATIONS: [fn(&mut SaveData); (SAVE_VERSION - UNVERSIONED) as usize] = [v1_to_v2, v2_to_v3];
+
+#[derive(Deserialize)]
+struct VersionProbe {
+    #[serde(default = "unversioned")]
+    version: u32,
+}
+
+fn unversioned() -> u32 {
+    UNVERSIONED
+}
+
+pub fn parse(contents: &str) -> Result<SaveData, StorageError> {
+    let VersionProbe { version } = ron::from_str(contents).map_err(StorageError::Parse)?;
+    let mut save_data: SaveData = ron::from_str(contents).map_err(StorageError::Parse)?;
+
+    if version > SAVE_VERSION {
+        warn!(
+            "Save data is version {}, newer than the supported version {}; loading it read-only",
+            version, SAVE_VERSION
+        );
+        save_data.read_only = true;
+        return Ok(save_data);
+    }
+
+    for migration in &MIGRATIONS[(version.max(UNVERSIONED) - UNVERSIONED) as usize..] {
+        migration(&mut save_data);
+    }
+    save_data.version = SAVE_VERSION;
+
+    Ok(save_data)
+}
+
+// Version 1 is everything from the codex-only file onwards. Every field added since then has a
+// serde default, so the only change is stamping the version.
+fn v1_to_v2(save_data: &mut SaveData) {
+    save_data.version = 2;
+}
+
+// The bindings as they were saved up to version 2: one key per action, with the arrow keys
+// hardcoded as a fallback for movement.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct LegacyBindings {
+    up: KeyCode,
+    down: KeyCode,
+    left: KeyCode,
+    right: KeyCode,
+    start: KeyCode,
+    pause: KeyCode,
+    restart: KeyCode,
+    mute: KeyCode,
+    dash: KeyCode,
+}
+
+impl Default for LegacyBindings {
+    fn default() -> Self {
+        Self {
+            up: KeyCode::W,
+            down: KeyCode::S,
+            left: KeyCode::A,
+            right: KeyCode::D,
+            start: KeyCode::Space,
+            pause: KeyCode::Escape,
+            restart: KeyCode::R,
+            mute: KeyCode::M,
+            dash: KeyCode::ShiftLeft,
+        }
+    }
+}
+
+// Bindings moved into the settings with two keys per action. The arrow fallback becomes the
+// second movement slot, so existing players keep exactly the keys they had.
+fn v2_to_v3(save_data: &mut SaveData) {
+    let legacy = &save_data.bindings;
+    let movement = |key: KeyCode, arrow: KeyCode| [Some(key), (key != arrow).then_some(arrow)];
+
+    save_data.settings.bindings = InputBindings {
+        up: movement(legacy.up, KeyCode::Up),
+        down: movement(legacy.down, KeyCode::Down),
+        left: movement(legacy.left, KeyCode::Left),
+        right: movement(legacy.right, KeyCode::Right),
+        start: [Some(legacy.start), None],
+        pause: [Some(legacy.pause), None],
+        restart: [Some(legacy.restart), None],
+        mute: [Some(legacy.mute), None],
+        dash: [Some(legacy.dash), None],
+    };
+    save_data.version = 3;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EnemyType;
+
+    #[test]
+    fn unversioned_codex_save_is_upgraded() {
+        let save_data = parse("(codex: {Red: 4, Purple: 1})").unwrap();
+
+        assert_eq!(save_data.version, SAVE_VERSION);
+        assert_eq!(save_data.codex[&EnemyType::Red], 4);
+        assert_eq!(save_data.codex[&EnemyType::Purple], 1);
+        assert_eq!(save_data.best_score, None);
+        assert_eq!(save_data.settings.bindings, InputBindings::default());
+        assert!(!save_data.read_only);
+    }
+
+    #[test]
+    fn version_two_bindings_move_into_the_settings() {
+        let save_data = parse(
+            "(version: 2, best_score: Some(12), bindings: (up: I, down: K, left: J, right: Right))",
+        )
+        .unwrap();
+
+        let bindings = &save_data.settings.bindings;
+        assert_eq!(save_data.version, SAVE_VERSION);
+        assert_eq!(save_data.best_score, Some(12));
+        assert_eq!(bindings.up, [Some(KeyCode::I), Some(KeyCode::Up)]);
+        assert_eq!(bindings.left, [Some(KeyCode::J), Some(KeyCode::Left)]);
+        // Bound to the arrow itself, so there's no separate fallback left to keep.
+        assert_eq!(bindings.right, [Some(KeyCode::Right), None]);
+        assert_eq!(bindings.dash, [Some(KeyCode::ShiftLeft), None]);
+    }
+
+    #[test]
+    fn current_version_is_not_migrated_again() {
+        let save_data =
+            parse("(version: 3, settings: (bindings: (up: (Some(I), None))), bindings: (up: W))")
+                .unwrap();
+
+        assert_eq!(save_data.settings.bindings.up, [Some(KeyCode::I), None]);
+    }
+
+    #[test]
+    fn newer_save_loads_read_only() {
+        let save_data = parse("(version: 99, best_score: Some(3))").unwrap();
+
+        assert!(save_data.read_only);
+        assert_eq!(save_data.version, 99);
+        assert_eq!(save_data.best_score, Some(3));
+    }
+
+    #[test]
+    fn garbage_is_a_parse_error() {
+        assert!(matches!(parse("(codex: "), Err(StorageError::Parse(_))));
+    }
+}
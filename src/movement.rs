@@ -0,0 +1,47 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::config::DETERMINISTIC_QUANTUM;
+
+#[derive(Component, Default)]
+pub struct Wraparound {
+    pub radius: f32,
+}
+
+#[derive(Component)]
+pub struct Velocity(pub Vec3);
+
+pub fn wraparound(
+    mut query: Query<(&mut Transform, &Wraparound)>,
+    window: Query<&Window, With<PrimaryWindow>>,
+) {
+    let window = window.single();
+    query
+        .par_iter_mut()
+        .for_each(|(mut transform, wraparound)| {
+            let left = -window.width() / 2.0 - wraparound.radius;
+            let right = -left;
+            let top = window.height() / 2.0 + wraparound.radius;
+            let bottom = -top;
+
+            if transform.translation.x < left {
+                transform.translation.x = right;
+            } else if transform.translation.x > right {
+                transform.translation.x = left;
+            }
+            if transform.translation.y > top {
+                transform.translation.y = bottom;
+            } else if transform.translation.y < bottom {
+                transform.translation.y = top;
+            }
+        });
+}
+
+// Deterministic runs only. Snaps what moved this frame to DETERMINISTIC_QUANTUM, so the last-bit
+// differences between platforms' float math are rounded away before they can compound.
+pub fn quantize_motion(mut query: Query<(&mut Transform, &mut Velocity)>) {
+    let quantize = |value: Vec3| (value / DETERMINISTIC_QUANTUM).round() * DETERMINISTIC_QUANTUM;
+    for (mut transform, mut velocity) in query.iter_mut() {
+        transform.translation = quantize(transform.translation);
+        velocity.0 = quantize(velocity.0);
+    }
+}
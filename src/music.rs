@@ -0,0 +1,73 @@
+use bevy::{
+    asset::LoadState,
+    audio::{PlaybackMode, Volume},
+    prelude::*,
+};
+
+use crate::{
+    assets::AssetHandles,
+    audio::AudioAvailable,
+    config::*,
+    game::{GainHealth, GameInfo},
+};
+
+// An extra loop laid over the health track once the run reaches its milestone. Layers stay for
+// the rest of the run.
+#[derive(Component)]
+pub struct MusicLayer(pub i32);
+
+pub fn spawn_music_layers(
+    mut commands: Commands,
+    game_info: Res<GameInfo>,
+    asset_handles: Res<AssetHandles>,
+    asset_server: Res<AssetServer>,
+    layers: Query<&MusicLayer>,
+    audio: Res<AudioAvailable>,
+) {
+    if !audio.0 {
+        return;
+    }
+
+    for (milestone, handle) in MUSIC_LAYER_MILESTONES
+        .iter()
+        .zip(&asset_handles.music_layers)
+    {
+        if game_info.points() < *milestone || layers.iter().any(|layer| layer.0 == *milestone) {
+            continue;
+        }
+
+        // A missing layer file just leaves the music as it was.
+        if asset_server.get_load_state(handle) == Some(LoadState::Failed) {
+            continue;
+        }
+
+        commands.spawn((
+            MusicLayer(*milestone),
+            AudioBundle {
+                source: handle.clone(),
+                settings: PlaybackSettings {
+                    mode: PlaybackMode::Loop,
+                    volume: Volume::new_relative(MUSIC_LAYER_VOLUME),
+                    ..default()
+                },
+            },
+        ));
+    }
+}
+
+// There is no beat clock to quantize against yet, so the sting plays right away.
+pub fn play_health_sting(
+    mut gain_health: EventReader<GainHealth>,
+    mut commands: Commands,
+    asset_handles: Res<AssetHandles>,
+    audio: Res<AudioAvailable>,
+) {
+    if gain_health.read().count() == 0 || !audio.0 {
+        return;
+    }
+
+    commands.spawn(AudioBundle {
+        source: asset_handles.sting_sound.clone(),
+        settings: PlaybackSettings::DESPAWN,
+    });
+}
@@ -0,0 +1,353 @@
+use std::{
+    collections::HashMap,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    audio::{Decodable, Source},
+    prelude::*,
+    tasks::{block_on, AsyncComputeTaskPool, Task},
+};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    assets::AssetHandles, config::*, game::Music, menu::LastScore, render_z, storage::StorageError,
+    AppState,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::assets::asset_root;
+
+// Written next to the track it was computed from, e.g. 3.ogg.envelope.ron.
+const CACHE_SUFFIX: &str = ".envelope.ron";
+const CACHE_VERSION: u32 = 1;
+
+pub struct MusicEnvelopePlugin;
+
+impl Plugin for MusicEnvelopePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunMusic>()
+            .init_resource::<Envelopes>()
+            .add_systems(Update, follow_run_music.run_if(in_state(AppState::Game)))
+            .add_systems(OnEnter(AppState::Menu), spawn_envelope_bar)
+            .add_systems(
+                Update,
+                (analyze_run_music, pulse_envelope_bar)
+                    .chain()
+                    .run_if(in_state(AppState::Menu)),
+            );
+    }
+}
+
+// Coarse loudness of a track: the RMS of each MUSIC_ENVELOPE_WINDOW, scaled so the loudest window
+// is 1.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Envelope {
+    version: u32,
+    // Size of the file it was computed from, so a replaced track isn't shown with a stale cache.
+    source_len: usize,
+    window: f32,
+    levels: Vec<f32>,
+}
+
+impl Envelope {
+    fn from_samples(
+        samples: impl Iterator<Item = f32>,
+        channels: u16,
+        sample_rate: u32,
+        source_len: usize,
+    ) -> Self {
+        let window_len =
+            ((sample_rate as f32 * MUSIC_ENVELOPE_WINDOW) as usize * channels as usize).max(1);
+
+        let mut levels = Vec::new();
+        let mut sum = 0.0;
+        let mut count = 0;
+        for sample in samples {
+            sum += sample * sample;
+            count += 1;
+            if count == window_len {
+                levels.push((sum / count as f32).sqrt());
+                sum = 0.0;
+                count = 0;
+            }
+        }
+        if count > 0 {
+            levels.push((sum / count as f32).sqrt());
+        }
+
+        let loudest = levels.iter().copied().fold(0.0, f32::max);
+        if loudest > 0.0 {
+            levels.iter_mut().for_each(|level| *level /= loudest);
+        }
+
+        Self {
+            version: CACHE_VERSION,
+            source_len,
+            window: MUSIC_ENVELOPE_WINDOW,
+            levels,
+        }
+    }
+
+    fn analyze(source: &AudioSource) -> Self {
+        let decoder = source.decoder();
+        let channels = decoder.channels();
+        let sample_rate = decoder.sample_rate();
+        Self::from_samples(
+            decoder.map(f32::from),
+            channels,
+            sample_rate,
+            source.bytes.len(),
+        )
+    }
+
+    // Interpolated between windows. The music loops, so positions past the end wrap around.
+    fn level_at(&self, position: f32) -> f32 {
+        if self.levels.is_empty() {
+            return 0.0;
+        }
+
+        let duration = self.window * self.levels.len() as f32;
+        let index = position.rem_euclid(duration) / self.window;
+        let current = self.levels[index as usize % self.levels.len()];
+        let next = self.levels[(index as usize + 1) % self.levels.len()];
+        current + (next - current) * index.fract()
+    }
+}
+
+fn cache_path(asset_root: &Path, asset_path: &Path) -> PathBuf {
+    let mut name = OsString::from(asset_path.as_os_str());
+    name.push(CACHE_SUFFIX);
+    asset_root.join(name)
+}
+
+// None when there is no cache, or one left over from another version or another file.
+fn read_cache(path: &Path, source_len: usize) -> Option<Envelope> {
+    let contents = fs::read_to_string(path).ok()?;
+    ron::from_str::<Envelope>(&contents)
+        .ok()
+        .filter(|envelope| envelope.version == CACHE_VERSION && envelope.source_len == source_len)
+}
+
+fn write_cache(path: &Path, envelope: &Envelope) -> Result<(), StorageError> {
+    let contents = ron::ser::to_string_pretty(envelope, PrettyConfig::default())
+        .map_err(StorageError::Serialize)?;
+    fs::write(path, contents)?;
+    Ok(())
+}
+
+// Envelopes are cached in the asset folder. There is none on the web, so they're only ever kept in
+// memory there.
+#[cfg(not(target_arch = "wasm32"))]
+fn cache_root() -> Option<PathBuf> {
+    Some(asset_root())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn cache_root() -> Option<PathBuf> {
+    None
+}
+
+// Reads the cache or, failing that, decodes the whole track and writes one for next time.
+fn load_or_analyze(source: AudioSource, cache: Option<PathBuf>) -> Envelope {
+    let source_len = source.bytes.len();
+    if let Some(envelope) = cache
+        .as_deref()
+        .and_then(|path| read_cache(path, source_len))
+    {
+        return envelope;
+    }
+
+    let envelope = Envelope::analyze(&source);
+    if let Some(path) = cache {
+        if let Err(error) = write_cache(&path, &envelope) {
+            warn!("Could not cache {}: {}", path.display(), error);
+        }
+    }
+    envelope
+}
+
+// The track playing when the run ended and how far into it playback got, so the menu's bar picks
+// up where the music stopped and keeps time with it from there.
+#[derive(Resource, Default)]
+struct RunMusic {
+    playing: Option<(Entity, Handle<AudioSource>)>,
+    position: f32,
+}
+
+#[derive(Resource, Default)]
+struct Envelopes {
+    ready: HashMap<AssetId<AudioSource>, Envelope>,
+    pending: Option<(AssetId<AudioSource>, Task<Envelope>)>,
+}
+
+#[derive(Component)]
+struct EnvelopeBar;
+
+// The music restarts from the top whenever the track is swapped or respawned, and stands still
+// while its sink is paused.
+fn follow_run_music(
+    mut run_music: ResMut<RunMusic>,
+    time: Res<Time<Real>>,
+    music: Query<(Entity, &Handle<AudioSource>, Option<&AudioSink>), With<Music>>,
+) {
+    let Some((entity, handle, sink)) = music.iter().next() else {
+        return;
+    };
+
+    if run_music.playing.as_ref().map(|(playing, _)| *playing) != Some(entity) {
+        run_music.playing = Some((entity, handle.clone()));
+        run_music.position = 0.0;
+    }
+    if !sink.is_some_and(|sink| sink.is_paused()) {
+        run_music.position += time.delta_seconds();
+    }
+}
+
+fn spawn_envelope_bar(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    asset_handles: Res<AssetHandles>,
+    last_score: Res<LastScore>,
+    run_music: Res<RunMusic>,
+) {
+    if last_score.0.is_none() || run_music.playing.is_none() {
+        return;
+    }
+
+    commands.spawn((
+        EnvelopeBar,
+        ColorMesh2dBundle {
+            mesh: asset_handles.bar_mesh.clone().into(),
+            material: materials.add(ColorMaterial::from(Color::DARK_GRAY)),
+            transform: Transform::from_translation(Vec3::new(
+                0.0,
+                MUSIC_ENVELOPE_BAR_Y,
+                render_z::BACKDROP,
+            ))
+            .with_scale(Vec3::new(0.0, MUSIC_ENVELOPE_BAR_HEIGHT, 1.0)),
+            ..default()
+        },
+    ));
+}
+
+// Analysis runs off the main thread, once per track, and only for a track someone is about to see
+// a bar for.
+fn analyze_run_music(
+    mut envelopes: ResMut<Envelopes>,
+    run_music: Res<RunMusic>,
+    sources: Res<Assets<AudioSource>>,
+    asset_server: Res<AssetServer>,
+    bar: Query<(), With<EnvelopeBar>>,
+) {
+    if let Some((id, task)) = envelopes.pending.take() {
+        if task.is_finished() {
+            envelopes.ready.insert(id, block_on(task));
+        } else {
+            envelopes.pending = Some((id, task));
+        }
+        return;
+    }
+
+    let Some((_, handle)) = run_music.playing.as_ref() else {
+        return;
+    };
+    if bar.is_empty() || envelopes.ready.contains_key(&handle.id()) {
+        return;
+    }
+    let Some(source) = sources.get(handle).cloned() else {
+        return;
+    };
+
+    let cache = asset_server
+        .get_path(handle.id())
+        .zip(cache_root())
+        .map(|(asset_path, root)| cache_path(&root, asset_path.path()));
+    let task = AsyncComputeTaskPool::get().spawn(async move { load_or_analyze(source, cache) });
+    envelopes.pending = Some((handle.id(), task));
+}
+
+// Flat until the envelope is ready, so a slow analysis only ever shows as a late start.
+fn pulse_envelope_bar(
+    mut run_music: ResMut<RunMusic>,
+    envelopes: Res<Envelopes>,
+    time: Res<Time<Real>>,
+    mut bar: Query<&mut Transform, With<EnvelopeBar>>,
+) {
+    let Ok(mut transform) = bar.get_single_mut() else {
+        return;
+    };
+    let Some((_, handle)) = run_music.playing.as_ref() else {
+        return;
+    };
+    let Some(envelope) = envelopes.ready.get(&handle.id()) else {
+        return;
+    };
+
+    let level = envelope.level_at(run_music.position);
+    transform.scale.x = MUSIC_ENVELOPE_BAR_WIDTH * level;
+    run_music.position += time.delta_seconds();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 1000;
+
+    // One window of silence, then one of a square wave at half volume, in stereo.
+    fn quiet_then_loud() -> Envelope {
+        let window = (SAMPLE_RATE as f32 * MUSIC_ENVELOPE_WINDOW) as usize * 2;
+        let samples = (0..window * 2).map(|index| {
+            if index < window {
+                0.0
+            } else if index % 2 == 0 {
+                0.5
+            } else {
+                -0.5
+            }
+        });
+        Envelope::from_samples(samples, 2, SAMPLE_RATE, 123)
+    }
+
+    #[test]
+    fn envelope_follows_the_loudness_of_each_window() {
+        let envelope = quiet_then_loud();
+
+        assert_eq!(envelope.levels, vec![0.0, 1.0]);
+        assert_eq!(envelope.level_at(0.0), 0.0);
+        assert_eq!(envelope.level_at(MUSIC_ENVELOPE_WINDOW * 0.5), 0.5);
+        assert_eq!(envelope.level_at(MUSIC_ENVELOPE_WINDOW), 1.0);
+    }
+
+    #[test]
+    fn level_wraps_around_with_the_looping_track() {
+        let envelope = quiet_then_loud();
+        let duration = MUSIC_ENVELOPE_WINDOW * 2.0;
+
+        for position in [0.0, 0.01, 0.06, 0.09] {
+            let wrapped = envelope.level_at(position + duration * 3.0);
+            assert!((wrapped - envelope.level_at(position)).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn cache_from_another_file_is_ignored() {
+        let dir = env::temp_dir().join("gorbulet-envelope-cache");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = cache_path(&dir, Path::new("3.ogg"));
+        assert_eq!(path, dir.join("3.ogg.envelope.ron"));
+
+        let envelope = quiet_then_loud();
+        write_cache(&path, &envelope).unwrap();
+
+        assert_eq!(read_cache(&path, 123), Some(envelope));
+        assert_eq!(read_cache(&path, 124), None);
+    }
+}
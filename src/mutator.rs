@@ -0,0 +1,302 @@
+use bevy::{prelude::*, sprite::Anchor};
+
+use crate::{
+    assets::AssetHandles,
+    config::*,
+    menu::cleanup_menu,
+    player::{Action, InputBindings, MovementFeel},
+    render_z,
+    storage::SaveData,
+    AppState,
+};
+
+pub struct MutatorPlugin;
+
+impl Plugin for MutatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActiveMutators>()
+            .add_systems(
+                Update,
+                mutator_navigation.run_if(in_state(AppState::Mutators)),
+            )
+            .add_systems(OnEnter(AppState::Mutators), setup_mutators)
+            .add_systems(OnExit(AppState::Mutators), cleanup_menu);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mutator {
+    DoubleEnemySpeed,
+    TinyPlayer,
+    NoInvincibility,
+    GiantCoins,
+    NoHealthCoins,
+    SlipperyPlayer,
+    NoGrace,
+    ScoreDecay,
+}
+
+impl Mutator {
+    pub const ALL: [Mutator; 8] = [
+        Mutator::DoubleEnemySpeed,
+        Mutator::TinyPlayer,
+        Mutator::NoInvincibility,
+        Mutator::GiantCoins,
+        Mutator::NoHealthCoins,
+        Mutator::SlipperyPlayer,
+        Mutator::NoGrace,
+        Mutator::ScoreDecay,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mutator::DoubleEnemySpeed => "Double enemy speed",
+            Mutator::TinyPlayer => "Tiny player",
+            Mutator::NoInvincibility => "No invincibility frames",
+            Mutator::GiantCoins => "Giant coins",
+            Mutator::NoHealthCoins => "No health coins",
+            Mutator::SlipperyPlayer => "Slippery player",
+            Mutator::NoGrace => "No post-hit grace",
+            Mutator::ScoreDecay => "Score decay",
+        }
+    }
+
+    pub fn multiplier(&self) -> f32 {
+        match self {
+            Mutator::DoubleEnemySpeed => 2.0,
+            Mutator::TinyPlayer => 0.75,
+            Mutator::NoInvincibility => 1.5,
+            Mutator::GiantCoins => 0.75,
+            Mutator::NoHealthCoins => 1.5,
+            Mutator::SlipperyPlayer => 1.25,
+            Mutator::NoGrace => 1.2,
+            Mutator::ScoreDecay => 1.3,
+        }
+    }
+
+    pub fn apply(&self, config: &mut GameConfig, rules: &mut GameRules) {
+        match self {
+            Mutator::DoubleEnemySpeed => config.enemy_speed_multiplier *= 2.0,
+            Mutator::TinyPlayer => config.player_radius = MUTATOR_TINY_PLAYER_RADIUS,
+            Mutator::NoInvincibility => rules.invincibility_frames = false,
+            Mutator::GiantCoins => config.coin_radius *= MUTATOR_GIANT_COIN_SCALE,
+            Mutator::NoHealthCoins => rules.health_coins = false,
+            Mutator::SlipperyPlayer => config.player_accel *= MUTATOR_SLIPPERY_ACCEL_SCALE,
+            Mutator::NoGrace => rules.post_hit_grace = false,
+            Mutator::ScoreDecay => rules.score_decay = true,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ActiveMutators(Vec<Mutator>);
+
+impl ActiveMutators {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Mutator> + '_ {
+        self.0.iter().copied()
+    }
+
+    pub fn contains(&self, mutator: Mutator) -> bool {
+        self.0.contains(&mutator)
+    }
+
+    pub fn toggle(&mut self, mutator: Mutator) {
+        if self.contains(mutator) {
+            self.0.retain(|active| *active != mutator);
+        } else {
+            self.0.push(mutator);
+            self.0
+                .sort_by_key(|active| Mutator::ALL.iter().position(|m| m == active));
+        }
+    }
+
+    pub fn multiplier(&self) -> f32 {
+        self.0.iter().map(Mutator::multiplier).product()
+    }
+
+    pub fn apply(&self, config: &mut GameConfig, rules: &mut GameRules) {
+        for mutator in &self.0 {
+            mutator.apply(config, rules);
+        }
+        rules.score_multiplier *= self.multiplier();
+    }
+
+    // Movement feel changes how hard a run is, so records made with a non-default feel are kept
+    // apart.
+    pub fn record_key(&self, feel: MovementFeel) -> String {
+        match feel {
+            MovementFeel::Momentum => self.key(),
+            _ => format!("{} [{}]", self.key(), feel.name()),
+        }
+    }
+
+    pub fn key(&self) -> String {
+        self.0
+            .iter()
+            .map(Mutator::name)
+            .collect::<Vec<_>>()
+            .join(" + ")
+    }
+}
+
+#[derive(Resource)]
+struct MutatorSelection(usize);
+
+#[derive(Component)]
+struct MutatorEntry(usize);
+
+#[derive(Component)]
+struct MutatorSummary;
+
+fn setup_mutators(
+    mut commands: Commands,
+    asset_handles: Res<AssetHandles>,
+    active: Res<ActiveMutators>,
+    save_data: Res<SaveData>,
+    bindings: Res<InputBindings>,
+) {
+    commands.insert_resource(MutatorSelection(0));
+
+    commands.spawn(Text2dBundle {
+        text: Text::from_section(
+            "Mutators",
+            TextStyle {
+                font: asset_handles.font.clone(),
+                font_size: 80.0,
+                color: Color::DARK_GRAY,
+            },
+        )
+        .with_alignment(TextAlignment::Center),
+        transform: Transform::from_translation(Vec3::new(0.0, 260.0, render_z::BACKDROP)),
+        ..default()
+    });
+
+    for (index, mutator) in Mutator::ALL.iter().enumerate() {
+        commands.spawn((
+            MutatorEntry(index),
+            Text2dBundle {
+                text: Text::from_section(
+                    mutator_label(*mutator, &active),
+                    TextStyle {
+                        font: asset_handles.font.clone(),
+                        font_size: 36.0,
+                        color: if index == 0 {
+                            Color::WHITE
+                        } else {
+                            Color::DARK_GRAY
+                        },
+                    },
+                ),
+                text_anchor: Anchor::CenterLeft,
+                transform: Transform::from_translation(Vec3::new(
+                    -260.0,
+                    160.0 - index as f32 * MUTATOR_ROW_SPACING,
+                    render_z::BACKDROP,
+                )),
+                ..default()
+            },
+        ));
+    }
+
+    commands.spawn((
+        MutatorSummary,
+        Text2dBundle {
+            text: Text::from_section(
+                summary_label(&active, &save_data),
+                TextStyle {
+                    font: asset_handles.font.clone(),
+                    font_size: 32.0,
+                    color: Color::GRAY,
+                },
+            )
+            .with_alignment(TextAlignment::Center),
+            transform: Transform::from_translation(Vec3::new(0.0, -200.0, render_z::BACKDROP)),
+            ..default()
+        },
+    ));
+
+    commands.spawn(Text2dBundle {
+        text: Text::from_section(
+            format!(
+                "Up/Down to browse, Enter to toggle, {} to start, {} to return",
+                bindings.label(Action::Start),
+                bindings.label(Action::Pause)
+            ),
+            TextStyle {
+                font: asset_handles.font.clone(),
+                font_size: 28.0,
+                color: Color::DARK_GRAY,
+            },
+        )
+        .with_alignment(TextAlignment::Center),
+        transform: Transform::from_translation(Vec3::new(0.0, -260.0, render_z::BACKDROP)),
+        ..default()
+    });
+}
+
+fn mutator_navigation(
+    bindings: Res<InputBindings>,
+    input: Res<Input<KeyCode>>,
+    save_data: Res<SaveData>,
+    mut active: ResMut<ActiveMutators>,
+    mut selection: ResMut<MutatorSelection>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut entries: Query<(&MutatorEntry, &mut Text), Without<MutatorSummary>>,
+    mut summary: Query<&mut Text, With<MutatorSummary>>,
+) {
+    if bindings.just_pressed(Action::Pause, &input) {
+        next_state.set(AppState::Menu);
+        return;
+    }
+
+    let count = Mutator::ALL.len();
+    if input.any_just_pressed([KeyCode::Up, KeyCode::W]) {
+        selection.0 = (selection.0 + count - 1) % count;
+    }
+    if input.any_just_pressed([KeyCode::Down, KeyCode::S]) {
+        selection.0 = (selection.0 + 1) % count;
+    }
+    if input.just_pressed(KeyCode::Return) {
+        active.toggle(Mutator::ALL[selection.0]);
+    }
+
+    if !selection.is_changed() && !active.is_changed() {
+        return;
+    }
+
+    for (entry, mut text) in entries.iter_mut() {
+        text.sections[0].value = mutator_label(Mutator::ALL[entry.0], &active);
+        text.sections[0].style.color = if entry.0 == selection.0 {
+            Color::WHITE
+        } else {
+            Color::DARK_GRAY
+        };
+    }
+
+    summary.single_mut().sections[0].value = summary_label(&active, &save_data);
+}
+
+fn mutator_label(mutator: Mutator, active: &ActiveMutators) -> String {
+    format!(
+        "[{}] {}  x{:.2}",
+        if active.contains(mutator) { "x" } else { " " },
+        mutator.name(),
+        mutator.multiplier()
+    )
+}
+
+fn summary_label(active: &ActiveMutators, save_data: &SaveData) -> String {
+    let best = save_data
+        .mutator_bests
+        .get(&active.record_key(save_data.settings.movement_feel))
+        .map_or("-".to_string(), |best| best.to_string());
+    format!(
+        "Score multiplier: x{:.2}   Best: {}",
+        active.multiplier(),
+        best
+    )
+}
@@ -0,0 +1,739 @@
+use std::{
+    fmt,
+    io::{self, Read, Write},
+    net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use bevy::{prelude::*, window::PrimaryWindow};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    assets::AssetHandles,
+    config::*,
+    descriptor::QueuedSeed,
+    effects::{text_label, BannerQueue},
+    game::{GameInfo, GameMode},
+    menu::cleanup_menu,
+    menu_list::{spawn_menu_list, EntryKind, MenuEntry, MenuFocus, MenuList, MenuListEvent},
+    mutator::ActiveMutators,
+    pause::PauseState,
+    player::{Action, InputBindings, Player},
+    render_z,
+    storage::SaveData,
+    AppState,
+};
+
+// Bumped whenever Message changes shape. Hello has to keep its layout across versions, so an
+// older peer can still be told why it was turned away.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+// Far more than any message needs; a longer length prefix means the stream is not ours.
+const MAX_FRAME_LEN: usize = 1024;
+
+const ONLINE_LIST: &str = "online";
+
+pub struct OnlinePlugin;
+
+impl Plugin for OnlinePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OnlineAddress>()
+            .add_systems(OnEnter(AppState::Online), setup_online)
+            .add_systems(
+                Update,
+                (handle_online_menu, type_online_address, poll_lobby)
+                    .chain()
+                    .run_if(in_state(AppState::Online)),
+            )
+            .add_systems(OnExit(AppState::Online), (cleanup_menu, close_lobby))
+            .add_systems(
+                OnEnter(AppState::Game),
+                spawn_peer_markers.run_if(resource_exists::<OnlineRace>()),
+            )
+            .add_systems(
+                Update,
+                (receive_peer_messages, broadcast_state, update_peer_markers)
+                    .chain()
+                    .run_if(in_state(AppState::Game))
+                    .run_if(resource_exists::<OnlineRace>()),
+            )
+            .add_systems(
+                OnEnter(PauseState::GameOver),
+                send_finished.run_if(resource_exists::<OnlineRace>()),
+            )
+            // A restart rolls a new seed, which ends the race like leaving does.
+            .add_systems(OnExit(AppState::Game), end_online_race);
+    }
+}
+
+// Everything the two peers say to each other. Both play the host's seed on their own; nothing
+// but these summaries crosses the wire.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Message {
+    Hello {
+        version: u32,
+        seed: u64,
+        difficulty: Difficulty,
+    },
+    State {
+        points: i32,
+        health: i8,
+        pos: (f32, f32),
+    },
+    Finished {
+        score: i32,
+    },
+}
+
+#[derive(Debug)]
+pub enum OnlineError {
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+    FrameTooLarge(usize),
+    Version(u32),
+    Handshake,
+    Difficulty(Difficulty),
+    Mutators,
+    Address(String),
+}
+
+impl fmt::Display for OnlineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OnlineError::Io(error) => write!(f, "{}", error),
+            OnlineError::Parse(error) => write!(f, "{}", error),
+            OnlineError::Serialize(error) => write!(f, "{}", error),
+            OnlineError::FrameTooLarge(len) => {
+                write!(f, "the peer sent a {} byte message, not a race", len)
+            }
+            OnlineError::Version(version) => write!(
+                f,
+                "the peer speaks protocol version {} (expected {})",
+                version, PROTOCOL_VERSION
+            ),
+            OnlineError::Handshake => write!(f, "the peer did not answer the handshake"),
+            OnlineError::Difficulty(difficulty) => write!(
+                f,
+                "the host plays on {}, switch the difficulty to match",
+                difficulty.name()
+            ),
+            OnlineError::Mutators => write!(f, "turn off mutators to race online"),
+            OnlineError::Address(address) => write!(f, "{} is not an address", address),
+        }
+    }
+}
+
+impl std::error::Error for OnlineError {}
+
+impl From<io::Error> for OnlineError {
+    fn from(error: io::Error) -> Self {
+        OnlineError::Io(error)
+    }
+}
+
+// Each frame is a big-endian u32 length followed by that many bytes of RON.
+pub fn write_frame(writer: &mut impl Write, message: &Message) -> Result<(), OnlineError> {
+    let body = ron::to_string(message).map_err(OnlineError::Serialize)?;
+    if body.len() > MAX_FRAME_LEN {
+        return Err(OnlineError::FrameTooLarge(body.len()));
+    }
+
+    writer.write_all(&(body.len() as u32).to_be_bytes())?;
+    writer.write_all(body.as_bytes())?;
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn read_frame(reader: &mut impl Read) -> Result<Message, OnlineError> {
+    let mut len = [0; 4];
+    reader.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_FRAME_LEN {
+        return Err(OnlineError::FrameTooLarge(len));
+    }
+
+    let mut body = vec![0; len];
+    reader.read_exact(&mut body)?;
+    let body = String::from_utf8(body)
+        .map_err(|error| OnlineError::Io(io::Error::new(io::ErrorKind::InvalidData, error)))?;
+    ron::from_str(&body).map_err(OnlineError::Parse)
+}
+
+// The host offers the run, and the joiner echoes the seed back to accept it.
+pub fn handshake_host(
+    stream: &mut (impl Read + Write),
+    seed: u64,
+    difficulty: Difficulty,
+) -> Result<(), OnlineError> {
+    write_frame(
+        stream,
+        &Message::Hello {
+            version: PROTOCOL_VERSION,
+            seed,
+            difficulty,
+        },
+    )?;
+
+    match read_frame(stream)? {
+        Message::Hello { version, .. } if version != PROTOCOL_VERSION => {
+            Err(OnlineError::Version(version))
+        }
+        Message::Hello { seed: echoed, .. } if echoed == seed => Ok(()),
+        _ => Err(OnlineError::Handshake),
+    }
+}
+
+pub fn handshake_join(stream: &mut (impl Read + Write)) -> Result<(u64, Difficulty), OnlineError> {
+    let Message::Hello {
+        version,
+        seed,
+        difficulty,
+    } = read_frame(stream)?
+    else {
+        return Err(OnlineError::Handshake);
+    };
+    if version != PROTOCOL_VERSION {
+        return Err(OnlineError::Version(version));
+    }
+
+    write_frame(
+        stream,
+        &Message::Hello {
+            version: PROTOCOL_VERSION,
+            seed,
+            difficulty,
+        },
+    )?;
+    Ok((seed, difficulty))
+}
+
+enum PeerEvent {
+    Message(Message),
+    Disconnected(String),
+}
+
+// An open race connection: one thread reads frames into `incoming`, another writes whatever is
+// sent on `outgoing`. Dropping it closes the socket, which ends both threads.
+struct Connection {
+    outgoing: Sender<Message>,
+    incoming: Mutex<Receiver<PeerEvent>>,
+}
+
+impl Connection {
+    fn spawn(stream: TcpStream) -> Result<Self, OnlineError> {
+        stream.set_nodelay(true)?;
+        stream.set_read_timeout(Some(Duration::from_secs_f32(ONLINE_TIMEOUT)))?;
+
+        let (outgoing, outgoing_rx) = mpsc::channel::<Message>();
+        let (incoming_tx, incoming) = mpsc::channel();
+
+        let mut writer = stream.try_clone()?;
+        thread::spawn(move || {
+            for message in outgoing_rx {
+                if write_frame(&mut writer, &message).is_err() {
+                    break;
+                }
+            }
+            let _ = writer.shutdown(Shutdown::Both);
+        });
+
+        let mut reader = stream;
+        thread::spawn(move || loop {
+            match read_frame(&mut reader) {
+                Ok(message) => {
+                    if incoming_tx.send(PeerEvent::Message(message)).is_err() {
+                        break;
+                    }
+                }
+                Err(error) => {
+                    let _ = incoming_tx.send(PeerEvent::Disconnected(error.to_string()));
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            outgoing,
+            incoming: Mutex::new(incoming),
+        })
+    }
+}
+
+fn host(seed: u64, difficulty: Difficulty, cancel: &AtomicBool) -> Result<Connection, OnlineError> {
+    let listener = TcpListener::bind(("0.0.0.0", ONLINE_PORT))?;
+    // Polled, so backing out of the lobby doesn't leave the port bound until someone connects.
+    listener.set_nonblocking(true)?;
+    let mut stream = loop {
+        if cancel.load(Ordering::Relaxed) {
+            return Err(io::Error::from(io::ErrorKind::Interrupted).into());
+        }
+        match listener.accept() {
+            Ok((stream, _)) => break stream,
+            Err(error) if error.kind() == io::ErrorKind::WouldBlock => {
+                thread::sleep(Duration::from_secs_f32(ONLINE_POLL_INTERVAL));
+            }
+            Err(error) => return Err(error.into()),
+        }
+    };
+
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs_f32(ONLINE_TIMEOUT)))?;
+    handshake_host(&mut stream, seed, difficulty)?;
+    Connection::spawn(stream)
+}
+
+fn join(address: &str) -> Result<(Connection, u64, Difficulty), OnlineError> {
+    let address = if address.contains(':') {
+        address.to_string()
+    } else {
+        format!("{}:{}", address, ONLINE_PORT)
+    };
+    let socket_address = address
+        .to_socket_addrs()
+        .ok()
+        .and_then(|mut addresses| addresses.next())
+        .ok_or_else(|| OnlineError::Address(address.clone()))?;
+
+    let mut stream =
+        TcpStream::connect_timeout(&socket_address, Duration::from_secs_f32(ONLINE_TIMEOUT))?;
+    stream.set_read_timeout(Some(Duration::from_secs_f32(ONLINE_TIMEOUT)))?;
+    let (seed, difficulty) = handshake_join(&mut stream)?;
+    Ok((Connection::spawn(stream)?, seed, difficulty))
+}
+
+// What the other player last told us.
+#[derive(Debug, Clone, Copy, Default)]
+struct PeerState {
+    points: i32,
+    health: i8,
+    position: Option<Vec2>,
+    finished: Option<i32>,
+}
+
+// The race in progress. Once the peer is gone, `connection` is dropped and the run carries on
+// solo.
+#[derive(Resource)]
+pub struct OnlineRace {
+    connection: Option<Connection>,
+    peer: PeerState,
+    send_timer: Timer,
+}
+
+impl OnlineRace {
+    fn new(connection: Connection) -> Self {
+        Self {
+            connection: Some(connection),
+            peer: PeerState::default(),
+            send_timer: Timer::from_seconds(ONLINE_STATE_INTERVAL, TimerMode::Repeating),
+        }
+    }
+
+    fn send(&mut self, message: Message) {
+        let sent = self
+            .connection
+            .as_ref()
+            .is_some_and(|connection| connection.outgoing.send(message).is_ok());
+        if !sent {
+            self.connection = None;
+        }
+    }
+}
+
+// The address typed on the online screen, kept for the next visit.
+#[derive(Resource)]
+struct OnlineAddress(String);
+
+impl Default for OnlineAddress {
+    fn default() -> Self {
+        Self("127.0.0.1".to_string())
+    }
+}
+
+enum LobbyEvent {
+    Connected(Connection, u64, Difficulty),
+    Failed(OnlineError),
+}
+
+// A host or join attempt running on its own thread.
+#[derive(Resource)]
+struct Lobby {
+    events: Mutex<Receiver<LobbyEvent>>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl Lobby {
+    fn start(
+        attempt: impl FnOnce(&AtomicBool) -> Result<(Connection, u64, Difficulty), OnlineError>
+            + Send
+            + 'static,
+    ) -> Self {
+        let (sender, events) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let thread_cancel = cancel.clone();
+        thread::spawn(move || {
+            let event = match attempt(&thread_cancel) {
+                Ok((connection, seed, difficulty)) => {
+                    LobbyEvent::Connected(connection, seed, difficulty)
+                }
+                Err(error) => LobbyEvent::Failed(error),
+            };
+            let _ = sender.send(event);
+        });
+
+        Self {
+            events: Mutex::new(events),
+            cancel,
+        }
+    }
+}
+
+impl Drop for Lobby {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+#[derive(Component)]
+struct OnlineStatus;
+
+#[derive(Component)]
+struct PeerMarker;
+
+#[derive(Component)]
+struct PeerText;
+
+fn address_label(address: &str) -> String {
+    format!("Join {}", address)
+}
+
+fn setup_online(
+    mut commands: Commands,
+    asset_handles: Res<AssetHandles>,
+    address: Res<OnlineAddress>,
+    bindings: Res<InputBindings>,
+    focus: Res<MenuFocus>,
+) {
+    commands.spawn(text_label(
+        &asset_handles,
+        "Online race",
+        80.0,
+        Color::DARK_GRAY,
+        Vec3::new(0.0, 260.0, render_z::BACKDROP),
+    ));
+
+    spawn_menu_list(
+        &mut commands,
+        &asset_handles,
+        &focus,
+        ONLINE_LIST,
+        vec![
+            MenuEntry::new(
+                "host",
+                format!("Host on port {}", ONLINE_PORT),
+                EntryKind::Action,
+            ),
+            MenuEntry::new("join", address_label(&address.0), EntryKind::Action),
+        ],
+        Vec3::new(0.0, 140.0, render_z::BACKDROP),
+    );
+
+    commands.spawn((
+        OnlineStatus,
+        text_label(
+            &asset_handles,
+            "",
+            28.0,
+            Color::DARK_GRAY,
+            Vec3::new(0.0, -60.0, render_z::BACKDROP),
+        ),
+    ));
+
+    commands.spawn(text_label(
+        &asset_handles,
+        format!(
+            "Type an address to join, Backspace to erase, {} to return",
+            bindings.label(Action::Pause)
+        ),
+        28.0,
+        Color::DARK_GRAY,
+        Vec3::new(0.0, -260.0, render_z::BACKDROP),
+    ));
+}
+
+fn set_status(status: &mut Query<&mut Text, With<OnlineStatus>>, message: String) {
+    for mut text in status.iter_mut() {
+        text.sections[0].value = message.clone();
+    }
+}
+
+fn handle_online_menu(
+    mut commands: Commands,
+    mut events: EventReader<MenuListEvent>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut status: Query<&mut Text, With<OnlineStatus>>,
+    save_data: Res<SaveData>,
+    mutators: Res<ActiveMutators>,
+    address: Res<OnlineAddress>,
+    lobby: Option<Res<Lobby>>,
+) {
+    for event in events.read() {
+        match event {
+            MenuListEvent::Back { list: ONLINE_LIST } => {
+                if lobby.is_some() {
+                    commands.remove_resource::<Lobby>();
+                    set_status(&mut status, "Cancelled".to_string());
+                } else {
+                    next_state.set(AppState::Menu);
+                }
+            }
+            MenuListEvent::Activated {
+                list: ONLINE_LIST,
+                entry,
+            } => {
+                if lobby.is_some() {
+                    continue;
+                }
+                if !mutators.is_empty() {
+                    set_status(&mut status, OnlineError::Mutators.to_string());
+                    continue;
+                }
+
+                match *entry {
+                    "host" => {
+                        let seed = rand::random();
+                        let difficulty = save_data.settings.difficulty;
+                        commands.insert_resource(Lobby::start(move |cancel| {
+                            host(seed, difficulty, cancel)
+                                .map(|connection| (connection, seed, difficulty))
+                        }));
+                        set_status(
+                            &mut status,
+                            format!("Waiting for a player on port {}", ONLINE_PORT),
+                        );
+                    }
+                    "join" => {
+                        let address = address.0.clone();
+                        set_status(&mut status, format!("Connecting to {}", address));
+                        commands.insert_resource(Lobby::start(move |_| join(&address)));
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// Digits, dots and colons go into the address; nothing else is typed, so the menu keys still
+// navigate.
+fn type_online_address(
+    mut characters: EventReader<ReceivedCharacter>,
+    input: Res<Input<KeyCode>>,
+    mut address: ResMut<OnlineAddress>,
+    mut lists: Query<&mut MenuList>,
+) {
+    let mut changed = false;
+    for event in characters.read() {
+        if event.char.is_ascii_digit() || matches!(event.char, '.' | ':') {
+            address.0.push(event.char);
+            changed = true;
+        }
+    }
+    if input.just_pressed(KeyCode::Back) {
+        changed |= address.0.pop().is_some();
+    }
+    if !changed {
+        return;
+    }
+
+    for mut list in lists.iter_mut().filter(|list| list.id == ONLINE_LIST) {
+        if let Some(entry) = list.entries.iter_mut().find(|entry| entry.id == "join") {
+            entry.label = address_label(&address.0);
+        }
+    }
+}
+
+fn poll_lobby(
+    mut commands: Commands,
+    lobby: Option<Res<Lobby>>,
+    save_data: Res<SaveData>,
+    mut game_mode: ResMut<GameMode>,
+    mut queued_seed: ResMut<QueuedSeed>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut status: Query<&mut Text, With<OnlineStatus>>,
+) {
+    let Some(lobby) = lobby else {
+        return;
+    };
+    let Ok(event) = lobby.events.lock().unwrap().try_recv() else {
+        return;
+    };
+    commands.remove_resource::<Lobby>();
+
+    match event {
+        LobbyEvent::Connected(_, _, difficulty) if difficulty != save_data.settings.difficulty => {
+            set_status(&mut status, OnlineError::Difficulty(difficulty).to_string());
+        }
+        LobbyEvent::Connected(connection, seed, _) => {
+            info!("Online race connected, seed {:x}", seed);
+            *game_mode = GameMode::Classic;
+            queued_seed.0 = Some(seed);
+            commands.insert_resource(OnlineRace::new(connection));
+            next_state.set(AppState::Game);
+        }
+        LobbyEvent::Failed(error) => {
+            set_status(&mut status, format!("Could not connect: {}", error));
+        }
+    }
+}
+
+fn close_lobby(mut commands: Commands) {
+    commands.remove_resource::<Lobby>();
+}
+
+fn spawn_peer_markers(
+    mut commands: Commands,
+    asset_handles: Res<AssetHandles>,
+    window: Query<&Window, With<PrimaryWindow>>,
+) {
+    commands.spawn((
+        PeerMarker,
+        ColorMesh2dBundle {
+            mesh: asset_handles.player_mesh.clone().into(),
+            material: asset_handles.ghost_material.clone(),
+            transform: Transform::from_xyz(0.0, 0.0, render_z::TRAIL),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+    ));
+
+    let top = window.single().height() / 2.0 - GHOST_RACE_MARGIN;
+    commands.spawn((
+        PeerText,
+        text_label(
+            &asset_handles,
+            "",
+            GHOST_RACE_FONT_SIZE,
+            GHOST_COLOR.with_a(1.0),
+            Vec3::new(0.0, top, render_z::HUD),
+        ),
+    ));
+}
+
+fn receive_peer_messages(mut race: ResMut<OnlineRace>, mut banners: ResMut<BannerQueue>) {
+    let race = &mut *race;
+    let Some(connection) = &race.connection else {
+        return;
+    };
+
+    let mut disconnected = None;
+    for event in connection.incoming.lock().unwrap().try_iter() {
+        match event {
+            PeerEvent::Message(Message::State {
+                points,
+                health,
+                pos,
+            }) => {
+                race.peer.points = points;
+                race.peer.health = health;
+                race.peer.position = Some(Vec2::new(pos.0, pos.1));
+            }
+            PeerEvent::Message(Message::Finished { score }) => {
+                race.peer.finished = Some(score);
+            }
+            // Only valid during the handshake.
+            PeerEvent::Message(Message::Hello { .. }) => {}
+            PeerEvent::Disconnected(reason) => disconnected = Some(reason),
+        }
+    }
+
+    if let Some(reason) = disconnected {
+        info!("Online race peer disconnected: {}", reason);
+        race.connection = None;
+        race.peer.position = None;
+        banners.push("Opponent left, racing solo");
+    }
+}
+
+// Sent through pauses and after game over too, so the peer can tell a quiet player from a lost
+// connection.
+fn broadcast_state(
+    mut race: ResMut<OnlineRace>,
+    game_info: Option<Res<GameInfo>>,
+    player_query: Query<&Transform, With<Player>>,
+    time: Res<Time<Real>>,
+) {
+    if !race.send_timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let Some(game_info) = game_info else {
+        return;
+    };
+
+    let position = player_query
+        .get_single()
+        .map_or(Vec2::ZERO, |transform| transform.translation.truncate());
+    race.send(Message::State {
+        points: game_info.points(),
+        health: game_info.health(),
+        pos: (position.x, position.y),
+    });
+}
+
+fn send_finished(mut race: ResMut<OnlineRace>, game_info: Res<GameInfo>) {
+    race.send(Message::Finished {
+        score: game_info.score(),
+    });
+}
+
+fn update_peer_markers(
+    race: Res<OnlineRace>,
+    game_info: Res<GameInfo>,
+    mut marker: Query<(&mut Transform, &mut Visibility), With<PeerMarker>>,
+    mut peer_text: Query<&mut Text, With<PeerText>>,
+) {
+    let peer = race.peer;
+    let position = peer.position.filter(|_| peer.finished.is_none());
+    for (mut transform, mut visibility) in marker.iter_mut() {
+        match position {
+            Some(position) => {
+                transform.translation = position.extend(transform.translation.z);
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+
+    let label = if race.connection.is_none() {
+        "Opponent disconnected".to_string()
+    } else if let Some(score) = peer.finished {
+        format!("Opponent finished on {}", score)
+    } else {
+        let lead = game_info.points() - peer.points;
+        let standing = if lead > 0 {
+            format!("ahead by {}", lead)
+        } else if lead < 0 {
+            format!("behind by {}", -lead)
+        } else {
+            "level".to_string()
+        };
+        format!("Opponent: {} health, you are {}", peer.health, standing)
+    };
+
+    for mut text in peer_text.iter_mut() {
+        if text.sections[0].value != label {
+            text.sections[0].value = label.clone();
+        }
+    }
+}
+
+fn end_online_race(mut commands: Commands) {
+    commands.remove_resource::<OnlineRace>();
+}
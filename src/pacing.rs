@@ -0,0 +1,145 @@
+use bevy::prelude::*;
+
+use crate::{assets::AssetHandles, config::*, render_z};
+
+#[derive(Component)]
+pub struct PacingGraph;
+
+pub fn bucket_coins(coin_times: &[f32], duration: f32, bucket_seconds: f32) -> Vec<u32> {
+    let bucket_count = ((duration / bucket_seconds).ceil() as usize).max(1);
+    let mut buckets = vec![0; bucket_count];
+
+    for time in coin_times {
+        let index = ((time / bucket_seconds) as usize).min(bucket_count - 1);
+        buckets[index] += 1;
+    }
+
+    buckets
+}
+
+pub fn merge_buckets(buckets: &[u32], max_buckets: usize) -> Vec<u32> {
+    if buckets.len() <= max_buckets {
+        return buckets.to_vec();
+    }
+
+    let merge = buckets.len().div_ceil(max_buckets);
+    buckets
+        .chunks(merge)
+        .map(|chunk| chunk.iter().sum())
+        .collect()
+}
+
+// A bar standing on `base` and growing upward, drawn with the menu text.
+pub fn bar_bundle(
+    asset_handles: &AssetHandles,
+    material: Handle<ColorMaterial>,
+    base: Vec2,
+    width: f32,
+    height: f32,
+) -> ColorMesh2dBundle {
+    ColorMesh2dBundle {
+        mesh: asset_handles.bar_mesh.clone().into(),
+        material,
+        transform: Transform::from_translation(Vec3::new(
+            base.x,
+            base.y + height / 2.0,
+            render_z::BACKDROP,
+        ))
+        .with_scale(Vec3::new(width, height, 1.0)),
+        ..default()
+    }
+}
+
+pub fn spawn_pacing_graph(
+    commands: &mut Commands,
+    asset_handles: &AssetHandles,
+    coin_times: &[f32],
+    duration: f32,
+) {
+    let raw_buckets = bucket_coins(coin_times, duration, PACING_BUCKET_SECONDS);
+    let buckets = merge_buckets(&raw_buckets, PACING_MAX_BARS);
+    let window_seconds = PACING_BUCKET_SECONDS * raw_buckets.len().div_ceil(buckets.len()) as f32;
+
+    let peak = buckets.iter().copied().max().unwrap_or(0).max(1);
+    let slot_width = PACING_GRAPH_WIDTH / buckets.len() as f32;
+    let bar_width = (slot_width - PACING_BAR_GAP).max(1.0);
+    let left = -PACING_GRAPH_WIDTH / 2.0 + slot_width / 2.0;
+
+    for (index, count) in buckets.iter().enumerate() {
+        let height = (*count as f32 / peak as f32 * PACING_GRAPH_HEIGHT).max(PACING_BAR_GAP);
+
+        commands.spawn((
+            PacingGraph,
+            bar_bundle(
+                asset_handles,
+                asset_handles.coin_material.clone(),
+                Vec2::new(left + slot_width * index as f32, PACING_GRAPH_Y),
+                bar_width,
+                height,
+            ),
+        ));
+    }
+
+    commands.spawn((
+        PacingGraph,
+        Text2dBundle {
+            text: Text::from_section(
+                format!("Peak: {} per {}s", peak, window_seconds),
+                TextStyle {
+                    font: asset_handles.font.clone(),
+                    font_size: 24.0,
+                    color: Color::DARK_GRAY,
+                },
+            )
+            .with_alignment(TextAlignment::Center),
+            transform: Transform::from_translation(Vec3::new(
+                0.0,
+                PACING_GRAPH_Y + PACING_GRAPH_HEIGHT + 20.0,
+                render_z::BACKDROP,
+            )),
+            ..default()
+        },
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coins_land_in_their_window() {
+        let buckets = bucket_coins(&[0.5, 9.9, 10.0, 25.0], 30.0, 10.0);
+
+        assert_eq!(buckets, vec![2, 1, 1]);
+    }
+
+    #[test]
+    fn partial_last_window_gets_a_bucket() {
+        assert_eq!(bucket_coins(&[21.0], 21.0, 10.0), vec![0, 0, 1]);
+    }
+
+    #[test]
+    fn coin_on_the_final_frame_stays_in_range() {
+        assert_eq!(bucket_coins(&[20.0], 20.0, 10.0), vec![0, 1]);
+    }
+
+    #[test]
+    fn empty_run_has_one_empty_bucket() {
+        assert_eq!(bucket_coins(&[], 0.0, 10.0), vec![0]);
+    }
+
+    #[test]
+    fn short_runs_are_not_merged() {
+        assert_eq!(merge_buckets(&[1, 2, 3], 30), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn long_runs_merge_down_to_the_cap() {
+        let buckets: Vec<u32> = (1..=61).collect();
+        let merged = merge_buckets(&buckets, 30);
+
+        assert!(merged.len() <= 30);
+        assert_eq!(merged.iter().sum::<u32>(), buckets.iter().sum::<u32>());
+        assert_eq!(merged[0], 1 + 2 + 3);
+    }
+}
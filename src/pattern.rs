@@ -0,0 +1,243 @@
+use std::f32::consts::TAU;
+
+use bevy::{prelude::*, window::PrimaryWindow};
+use rand::Rng;
+
+use crate::{
+    assets::AssetHandles,
+    audio::AudioAvailable,
+    config::*,
+    effects::spawn_floating_text,
+    enemy::{get_enemy_spawn_position, spawn_enemy, EnemySpawned, EnemyType, SpawnSideBag},
+    game::{GameInfo, GameMode},
+    game_time::GameTime,
+    grace::PostHitGrace,
+    player::Player,
+    render_z,
+    rng::GameRng,
+    score::ScoreEvent,
+    shrink::{player_radius, SizeModifier},
+    util::safe_half_extent,
+};
+
+#[derive(Component)]
+pub struct PatternCoin {
+    group: u32,
+    lifetime: Timer,
+}
+
+#[derive(Resource)]
+pub struct CoinPatterns {
+    next_at: i32,
+    next_group: u32,
+}
+
+impl Default for CoinPatterns {
+    fn default() -> Self {
+        Self {
+            next_at: PATTERN_EVERY,
+            next_group: 0,
+        }
+    }
+}
+
+pub fn spawn_coin_patterns(
+    mut commands: Commands,
+    mut patterns: ResMut<CoinPatterns>,
+    mut rng: ResMut<GameRng>,
+    game_info: Res<GameInfo>,
+    player_query: Query<&Transform, With<Player>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    asset_handles: Res<AssetHandles>,
+    game_rules: Res<GameRules>,
+) {
+    if game_info.points() < patterns.next_at {
+        return;
+    }
+    patterns.next_at = (game_info.points() / PATTERN_EVERY + 1) * PATTERN_EVERY;
+
+    let group = patterns.next_group;
+    patterns.next_group += 1;
+
+    let player_position = player_query
+        .get_single()
+        .map_or(Vec2::ZERO, |transform| transform.translation.truncate());
+    let window = window.single();
+    let half_extent = safe_half_extent(
+        Vec2::new(window.width(), window.height()) / 2.0,
+        game_rules.safe_area,
+    ) - COIN_RADIUS * PATTERN_COIN_SCALE;
+
+    let positions = if rng.gen_bool(0.5) {
+        ring_positions(half_extent, player_position, &mut rng)
+    } else {
+        line_positions(half_extent, player_position, &mut rng)
+    };
+
+    for position in positions {
+        commands.spawn((
+            PatternCoin {
+                group,
+                lifetime: Timer::from_seconds(PATTERN_LIFETIME, TimerMode::Once),
+            },
+            ColorMesh2dBundle {
+                mesh: asset_handles.coin_mesh.clone().into(),
+                material: asset_handles.coin_material.clone(),
+                transform: Transform::from_translation(position.extend(render_z::PICKUP))
+                    .with_scale(Vec3::splat(PATTERN_COIN_SCALE)),
+                ..default()
+            },
+        ));
+    }
+}
+
+// The whole ring stays inside the playfield, centred away from the player when a few tries allow.
+fn ring_positions(half_extent: Vec2, player_position: Vec2, rng: &mut GameRng) -> Vec<Vec2> {
+    let bounds = half_extent - PATTERN_RING_RADIUS;
+
+    let mut center = Vec2::ZERO;
+    for _ in 0..PATTERN_PLACEMENT_TRIES {
+        center = Vec2::new(
+            rng.gen_range(-bounds.x..bounds.x),
+            rng.gen_range(-bounds.y..bounds.y),
+        );
+        if center.distance(player_position) > PATTERN_MIN_PLAYER_DISTANCE + PATTERN_RING_RADIUS {
+            break;
+        }
+    }
+
+    (0..PATTERN_RING_COUNT)
+        .map(|index| {
+            center
+                + Vec2::from_angle(TAU * index as f32 / PATTERN_RING_COUNT as f32)
+                    * PATTERN_RING_RADIUS
+        })
+        .collect()
+}
+
+// A horizontal line spanning the playfield, on a row away from the player when a few tries allow.
+fn line_positions(half_extent: Vec2, player_position: Vec2, rng: &mut GameRng) -> Vec<Vec2> {
+    let mut y = 0.0;
+    for _ in 0..PATTERN_PLACEMENT_TRIES {
+        y = rng.gen_range(-half_extent.y..half_extent.y);
+        if (y - player_position.y).abs() > PATTERN_MIN_PLAYER_DISTANCE {
+            break;
+        }
+    }
+
+    (0..PATTERN_LINE_COUNT)
+        .map(|index| {
+            let x = -half_extent.x
+                + 2.0 * half_extent.x * index as f32 / (PATTERN_LINE_COUNT - 1) as f32;
+            Vec2::new(x, y)
+        })
+        .collect()
+}
+
+// Pattern coins are worth one point each. Only the last coin of a group brings an enemy, following
+// the same rules as the main coin.
+pub fn collect_pattern_coins(
+    mut commands: Commands,
+    game_info: Res<GameInfo>,
+    mut rng: ResMut<GameRng>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    (mut enemy_spawned, mut score_events): (EventWriter<EnemySpawned>, EventWriter<ScoreEvent>),
+    (mut spawn_bag, mut grace): (ResMut<SpawnSideBag>, ResMut<PostHitGrace>),
+    coin_query: Query<(Entity, &PatternCoin, &Transform)>,
+    player_query: Query<(&Transform, Option<&SizeModifier>), (With<Player>, Without<PatternCoin>)>,
+    (run_config, game_mode, game_rules): (Res<RunConfig>, Res<GameMode>, Res<GameRules>),
+    window: Query<&Window, With<PrimaryWindow>>,
+    asset_handles: Res<AssetHandles>,
+    audio: Res<AudioAvailable>,
+) {
+    let Ok((player_transform, size)) = player_query.get_single() else {
+        return;
+    };
+    let player_position = player_transform.translation.truncate();
+    let pickup_distance =
+        player_radius(&run_config, size) + run_config.coin_radius * PATTERN_COIN_SCALE;
+
+    let collected: Vec<(Entity, u32, Vec3)> = coin_query
+        .iter()
+        .filter(|(_, _, transform)| {
+            transform.translation.truncate().distance(player_position) < pickup_distance
+        })
+        .map(|(entity, coin, transform)| (entity, coin.group, transform.translation))
+        .collect();
+    if collected.is_empty() {
+        return;
+    }
+
+    // This frame's points, ahead of the score events being applied.
+    let points = game_info.points() + collected.len() as i32;
+    for (entity, _, position) in &collected {
+        commands.entity(*entity).despawn();
+
+        let value = if *game_mode == GameMode::Deposit {
+            1
+        } else {
+            game_rules.score_multiplier.round().max(1.0) as i32
+        };
+        score_events.send(ScoreEvent::CoinCollected { points: 1, value });
+
+        spawn_floating_text(
+            &mut commands,
+            &asset_handles,
+            format!("+{}", value),
+            *position,
+            COIN_COLOR,
+        );
+    }
+
+    if audio.0 {
+        commands.spawn(AudioBundle {
+            source: asset_handles.coin_sound.clone(),
+            settings: PlaybackSettings::DESPAWN,
+        });
+    }
+
+    let group_finished = collected.iter().any(|(_, group, _)| {
+        coin_query.iter().all(|(entity, coin, _)| {
+            coin.group != *group || collected.iter().any(|(taken, _, _)| *taken == entity)
+        })
+    });
+    if !group_finished
+        || matches!(*game_mode, GameMode::Zen | GameMode::Tutorial)
+        || grace.consume()
+    {
+        return;
+    }
+
+    let window = window.single();
+    let spawn_side = spawn_bag.next_side(
+        &game_rules,
+        player_transform.translation,
+        window.width(),
+        window.height(),
+        &mut rng,
+    );
+    let spawn_position =
+        get_enemy_spawn_position(window.width(), window.height(), spawn_side, &mut rng);
+
+    enemy_spawned.send(spawn_enemy(
+        &mut commands,
+        &mut materials,
+        &mut rng,
+        points,
+        &asset_handles,
+        EnemyType::for_points(points),
+        spawn_position,
+    ));
+}
+
+pub fn expire_pattern_coins(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut PatternCoin)>,
+    game_time: Res<GameTime>,
+) {
+    for (entity, mut coin) in query.iter_mut() {
+        if coin.lifetime.tick(game_time.delta()).finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,357 @@
+use bevy::{
+    input::{gamepad::GamepadEvent, mouse::MouseMotion, touch::TouchInput},
+    prelude::*,
+    window::WindowFocused,
+};
+
+use crate::{
+    assets::AssetHandles,
+    bench::BenchScenario,
+    config::*,
+    enemy::Enemy,
+    game::GameMode,
+    menu_list::{spawn_menu_list, EntryKind, MenuEntry, MenuFocus},
+    player::{Action, InputBindings, Player},
+    render_z,
+    repro::ReproPlayback,
+    storage::SaveData,
+    transition::{RequestedTransition, Transition},
+    window::{not_suspended, Suspended},
+    AppState,
+};
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<PauseState>()
+            .init_resource::<IdleTracker>()
+            .add_systems(
+                Update,
+                (
+                    toggle_pause,
+                    // A repro or a bench plays without any real input, which would look idle.
+                    track_idle
+                        .run_if(not(resource_exists::<ReproPlayback>()))
+                        .run_if(not(resource_exists::<BenchScenario>()))
+                        .run_if(not_suspended),
+                    pause_on_unfocus,
+                    pause_on_suspend,
+                )
+                    .run_if(in_state(AppState::Game)),
+            )
+            .add_systems(OnEnter(AppState::Game), reset_idle)
+            .add_systems(OnEnter(PauseState::Paused), pause)
+            .add_systems(OnExit(PauseState::Paused), resume)
+            .add_systems(OnExit(AppState::Game), reset_pause);
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused,
+    Won,
+    GameOver,
+}
+
+#[derive(Resource, Default)]
+struct IdleTracker {
+    idle: f32,
+    reason: PauseReason,
+}
+
+// Only an idle pause resumes on any input. The others wait for the pause key, so coming back to
+// the window can't drop the player straight into a running game.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum PauseReason {
+    #[default]
+    Manual,
+    Idle,
+    Unfocused,
+    Minimized,
+}
+
+impl PauseReason {
+    fn title(&self) -> &'static str {
+        match self {
+            PauseReason::Manual => "Paused",
+            PauseReason::Idle => "Paused (idle)",
+            PauseReason::Unfocused => "Paused — window unfocused",
+            PauseReason::Minimized => "Paused — window minimized",
+        }
+    }
+}
+
+#[derive(Component)]
+struct PauseText;
+
+#[derive(Component)]
+struct PauseTitle;
+
+fn toggle_pause(
+    bindings: Res<InputBindings>,
+    input: Res<Input<KeyCode>>,
+    game_mode: Res<GameMode>,
+    pause_state: Res<State<PauseState>>,
+    mut transitions: ResMut<RequestedTransition>,
+) {
+    if !bindings.just_pressed(Action::Pause, &input) {
+        return;
+    }
+
+    if matches!(*game_mode, GameMode::Zen | GameMode::Tutorial) {
+        transitions.request(Transition::App(AppState::Menu));
+        return;
+    }
+
+    transitions.request(Transition::Pause(match pause_state.get() {
+        PauseState::Running => PauseState::Paused,
+        PauseState::Paused => PauseState::Running,
+        PauseState::Won | PauseState::GameOver => return,
+    }));
+}
+
+fn track_idle(
+    mut tracker: ResMut<IdleTracker>,
+    keys: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    mut mouse_motion: EventReader<MouseMotion>,
+    mut touches: EventReader<TouchInput>,
+    mut gamepad_events: EventReader<GamepadEvent>,
+    save_data: Res<SaveData>,
+    pause_state: Res<State<PauseState>>,
+    mut transitions: ResMut<RequestedTransition>,
+    player_query: Query<&Transform, (With<Player>, Without<Enemy>)>,
+    enemy_query: Query<&Transform, (With<Enemy>, Without<Player>)>,
+    time: Res<Time<Real>>,
+) {
+    let any_input = keys.get_pressed().next().is_some()
+        || mouse_buttons.get_pressed().next().is_some()
+        || mouse_motion.read().count() > 0
+        || touches.read().count() > 0
+        || gamepad_events
+            .read()
+            .filter(|event| is_gamepad_activity(event, save_data.settings.stick_deadzone))
+            .count()
+            > 0;
+
+    if any_input {
+        tracker.idle = 0.0;
+        if tracker.reason == PauseReason::Idle && *pause_state.get() == PauseState::Paused {
+            transitions.request(Transition::Pause(PauseState::Running));
+        }
+        return;
+    }
+
+    if *pause_state.get() != PauseState::Running {
+        return;
+    }
+
+    tracker.idle += time.delta_seconds();
+    if tracker.idle < IDLE_PAUSE_TIME {
+        return;
+    }
+
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let in_danger = enemy_query.iter().any(|enemy_transform| {
+        enemy_transform
+            .translation
+            .truncate()
+            .distance_squared(player_transform.translation.truncate())
+            < IDLE_DANGER_RADIUS.powf(2.0)
+    });
+
+    if !in_danger {
+        tracker.reason = PauseReason::Idle;
+        transitions.request(Transition::Pause(PauseState::Paused));
+    }
+}
+
+// A resting stick drifts and reports small axis changes, and releases and connections aren't
+// deliberate input either, so none of them keep the game awake.
+fn is_gamepad_activity(event: &GamepadEvent, deadzone: f32) -> bool {
+    match event {
+        GamepadEvent::Button(button) => button.value > 0.0,
+        GamepadEvent::Axis(axis) => axis.value.abs() > deadzone,
+        GamepadEvent::Connection(_) => false,
+    }
+}
+
+fn pause_on_unfocus(
+    mut focus_events: EventReader<WindowFocused>,
+    mut tracker: ResMut<IdleTracker>,
+    save_data: Res<SaveData>,
+    game_mode: Res<GameMode>,
+    pause_state: Res<State<PauseState>>,
+    mut transitions: ResMut<RequestedTransition>,
+    mut title: Query<&mut Text, With<PauseTitle>>,
+) {
+    let unfocused = focus_events.read().any(|event| !event.focused);
+    if !unfocused
+        || !save_data.settings.pause_on_unfocus
+        || matches!(*game_mode, GameMode::Zen | GameMode::Tutorial)
+    {
+        return;
+    }
+
+    match pause_state.get() {
+        // A death or win requested this frame outranks the pause.
+        PauseState::Running => {
+            tracker.reason = PauseReason::Unfocused;
+            transitions.request(Transition::Pause(PauseState::Paused));
+        }
+        // An idle pause would resume on the first key press after returning to the window.
+        PauseState::Paused if tracker.reason == PauseReason::Idle => {
+            tracker.reason = PauseReason::Unfocused;
+            for mut text in title.iter_mut() {
+                text.sections[0].value = tracker.reason.title().to_string();
+            }
+        }
+        _ => {}
+    }
+}
+
+// Unlike losing focus, minimizing always pauses, since the window can't be watched at all. Zen and
+// the tutorial have no pause screen and simply freeze with virtual time.
+fn pause_on_suspend(
+    suspended: Res<Suspended>,
+    mut tracker: ResMut<IdleTracker>,
+    game_mode: Res<GameMode>,
+    pause_state: Res<State<PauseState>>,
+    mut transitions: ResMut<RequestedTransition>,
+    mut title: Query<&mut Text, With<PauseTitle>>,
+) {
+    if !suspended.is_changed() || suspended.is_added() {
+        return;
+    }
+
+    // The time spent minimized isn't idle time.
+    tracker.idle = 0.0;
+    if !suspended.0 || matches!(*game_mode, GameMode::Zen | GameMode::Tutorial) {
+        return;
+    }
+
+    match pause_state.get() {
+        PauseState::Running => {
+            tracker.reason = PauseReason::Minimized;
+            transitions.request(Transition::Pause(PauseState::Paused));
+        }
+        PauseState::Paused if tracker.reason == PauseReason::Idle => {
+            tracker.reason = PauseReason::Minimized;
+            for mut text in title.iter_mut() {
+                text.sections[0].value = tracker.reason.title().to_string();
+            }
+        }
+        _ => {}
+    }
+}
+
+fn reset_idle(mut tracker: ResMut<IdleTracker>) {
+    *tracker = IdleTracker::default();
+}
+
+fn pause(
+    mut commands: Commands,
+    mut time: ResMut<Time<Virtual>>,
+    asset_handles: Res<AssetHandles>,
+    tracker: Res<IdleTracker>,
+    save_data: Res<SaveData>,
+    focus: Res<MenuFocus>,
+) {
+    time.pause();
+
+    let list = spawn_menu_list(
+        &mut commands,
+        &asset_handles,
+        &focus,
+        "pause",
+        vec![
+            save_data.settings.hud_layout.menu_entry(),
+            save_data.settings.input_display.menu_entry(),
+            save_data.settings.movement_feel.menu_entry(),
+            MenuEntry::new("restart_audio", "Restart audio", EntryKind::Action),
+        ],
+        Vec3::new(0.0, -120.0, render_z::HUD),
+    );
+    commands.entity(list).insert(PauseText);
+
+    commands.spawn((
+        PauseText,
+        PauseTitle,
+        Text2dBundle {
+            text: Text::from_section(
+                tracker.reason.title(),
+                TextStyle {
+                    font: asset_handles.font.clone(),
+                    font_size: 120.0,
+                    color: Color::WHITE,
+                },
+            )
+            .with_alignment(TextAlignment::Center),
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, render_z::HUD)),
+            ..default()
+        },
+    ));
+}
+
+fn resume(
+    mut commands: Commands,
+    mut time: ResMut<Time<Virtual>>,
+    mut tracker: ResMut<IdleTracker>,
+    query: Query<Entity, With<PauseText>>,
+) {
+    time.unpause();
+    *tracker = IdleTracker::default();
+
+    query.iter().for_each(|entity| {
+        commands.entity(entity).despawn_recursive();
+    });
+}
+
+fn reset_pause(mut next_state: ResMut<NextState<PauseState>>) {
+    next_state.set(PauseState::Running);
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::input::gamepad::{
+        GamepadAxisChangedEvent, GamepadAxisType, GamepadButtonChangedEvent, GamepadButtonType,
+    };
+
+    use super::*;
+
+    const DEADZONE: f32 = 0.2;
+
+    fn axis(value: f32) -> GamepadEvent {
+        GamepadEvent::Axis(GamepadAxisChangedEvent::new(
+            Gamepad::new(0),
+            GamepadAxisType::LeftStickX,
+            value,
+        ))
+    }
+
+    fn button(value: f32) -> GamepadEvent {
+        GamepadEvent::Button(GamepadButtonChangedEvent::new(
+            Gamepad::new(0),
+            GamepadButtonType::South,
+            value,
+        ))
+    }
+
+    #[test]
+    fn stick_drift_is_not_activity() {
+        assert!(!is_gamepad_activity(&axis(0.05), DEADZONE));
+        assert!(!is_gamepad_activity(&axis(-DEADZONE), DEADZONE));
+        assert!(is_gamepad_activity(&axis(-0.6), DEADZONE));
+    }
+
+    #[test]
+    fn only_button_presses_are_activity() {
+        assert!(is_gamepad_activity(&button(1.0), DEADZONE));
+        assert!(!is_gamepad_activity(&button(0.0), DEADZONE));
+    }
+}
@@ -0,0 +1,259 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+use rand::Rng;
+
+use crate::{
+    assets::AssetHandles,
+    config::*,
+    game::GameInfo,
+    game_time::GameTime,
+    player::Player,
+    render_z,
+    rng::GameRng,
+    shrink::{player_radius, SizeModifier},
+};
+
+// Everything the player collects by touching it. The coin is always on the field and moves on
+// when collected; the PickupSpawner places the rest, which vanish when their lifetime runs out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PickupKind {
+    Coin,
+    Shrink,
+}
+
+impl PickupKind {
+    // The kinds the spawner chooses between, in the order their weights are drawn against.
+    pub const SPAWNED: [PickupKind; 1] = [PickupKind::Shrink];
+
+    pub fn radius(&self, run_config: &RunConfig) -> f32 {
+        match self {
+            PickupKind::Coin => run_config.coin_radius,
+            PickupKind::Shrink => COIN_RADIUS * SHRINK_PICKUP_SCALE,
+        }
+    }
+
+    pub fn lifetime(&self) -> Option<f32> {
+        match self {
+            PickupKind::Coin => None,
+            PickupKind::Shrink => Some(SHRINK_PICKUP_LIFETIME),
+        }
+    }
+
+    fn max_on_field(&self) -> usize {
+        match self {
+            PickupKind::Coin | PickupKind::Shrink => 1,
+        }
+    }
+
+    // Seconds after one is placed before the spawner will place another.
+    fn cooldown(&self) -> f32 {
+        match self {
+            PickupKind::Coin => 0.0,
+            PickupKind::Shrink => SHRINK_PICKUP_COOLDOWN,
+        }
+    }
+
+    // The odds of the kind being picked at `points`, relative to the others. Zero leaves it out.
+    fn spawn_weight(&self, _points: i32) -> f32 {
+        match self {
+            PickupKind::Coin => 0.0,
+            PickupKind::Shrink => 1.0,
+        }
+    }
+
+    fn material(&self, asset_handles: &AssetHandles) -> Handle<ColorMaterial> {
+        match self {
+            PickupKind::Coin => asset_handles.coin_material.clone(),
+            PickupKind::Shrink => asset_handles.shrink_material.clone(),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Pickup {
+    pub kind: PickupKind,
+    lifetime: Option<Timer>,
+}
+
+impl Pickup {
+    pub fn new(kind: PickupKind) -> Self {
+        Self {
+            kind,
+            lifetime: kind
+                .lifetime()
+                .map(|seconds| Timer::from_seconds(seconds, TimerMode::Once)),
+        }
+    }
+}
+
+// One per pickup touched, so pickups that overlap the player on the same frame are each handled
+// by their own kind's system. The collector is the player, or the companion orb picking the coin
+// up on the player's behalf.
+#[derive(Event)]
+pub struct HitPickup {
+    pub kind: PickupKind,
+    pub pickup: Entity,
+    pub collector: Entity,
+}
+
+pub fn pickup_collision(
+    player_query: Query<
+        (Entity, &Transform, Option<&SizeModifier>),
+        (With<Player>, Without<Pickup>),
+    >,
+    pickup_query: Query<(Entity, &Pickup, &Transform), Without<Player>>,
+    mut hit_pickup: EventWriter<HitPickup>,
+    run_config: Res<RunConfig>,
+) {
+    let Ok((player, player_transform, size)) = player_query.get_single() else {
+        return;
+    };
+    let player_position = player_transform.translation.truncate();
+    let radius = player_radius(&run_config, size);
+
+    for (entity, pickup, transform) in pickup_query.iter() {
+        let reach = radius + pickup.kind.radius(&run_config);
+        if transform
+            .translation
+            .truncate()
+            .distance_squared(player_position)
+            < reach.powf(2.0)
+        {
+            hit_pickup.send(HitPickup {
+                kind: pickup.kind,
+                pickup: entity,
+                collector: player,
+            });
+        }
+    }
+}
+
+// Offers a pickup every PICKUP_SPAWN_EVERY points. Which kind is a weighted draw between those
+// off cooldown and under their own cap; nothing comes while PICKUP_MAX_ON_FIELD are out.
+#[derive(Resource)]
+pub struct PickupSpawner {
+    next_at: i32,
+    // Seconds left, indexed like PickupKind::SPAWNED.
+    cooldowns: [f32; PickupKind::SPAWNED.len()],
+}
+
+impl Default for PickupSpawner {
+    fn default() -> Self {
+        Self {
+            next_at: PICKUP_SPAWN_EVERY,
+            cooldowns: [0.0; PickupKind::SPAWNED.len()],
+        }
+    }
+}
+
+// A single candidate is taken without a draw, so runs that only ever offer one kind use the same
+// random numbers as before there was a choice.
+fn choose_kind(rng: &mut GameRng, candidates: &[(PickupKind, f32)]) -> Option<PickupKind> {
+    if candidates.len() <= 1 {
+        return candidates.first().map(|(kind, _)| *kind);
+    }
+
+    let total: f32 = candidates.iter().map(|(_, weight)| weight).sum();
+    let mut roll = rng.gen_range(0.0..total);
+    for (kind, weight) in candidates {
+        if roll < *weight {
+            return Some(*kind);
+        }
+        roll -= weight;
+    }
+    candidates.last().map(|(kind, _)| *kind)
+}
+
+pub fn spawn_pickups(
+    mut commands: Commands,
+    mut spawner: ResMut<PickupSpawner>,
+    mut rng: ResMut<GameRng>,
+    game_info: Res<GameInfo>,
+    game_time: Res<GameTime>,
+    run_config: Res<RunConfig>,
+    player_query: Query<&Transform, With<Player>>,
+    pickup_query: Query<&Pickup>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    asset_handles: Res<AssetHandles>,
+) {
+    for cooldown in spawner.cooldowns.iter_mut() {
+        *cooldown = (*cooldown - game_time.delta_seconds()).max(0.0);
+    }
+
+    let points = game_info.points();
+    if points < spawner.next_at {
+        return;
+    }
+    spawner.next_at = (points / PICKUP_SPAWN_EVERY + 1) * PICKUP_SPAWN_EVERY;
+
+    let on_field = |kind: PickupKind| {
+        pickup_query
+            .iter()
+            .filter(|pickup| pickup.kind == kind)
+            .count()
+    };
+    let placed: usize = PickupKind::SPAWNED.into_iter().map(on_field).sum();
+    if placed >= PICKUP_MAX_ON_FIELD {
+        return;
+    }
+
+    let candidates: Vec<(PickupKind, f32)> = PickupKind::SPAWNED
+        .into_iter()
+        .zip(spawner.cooldowns)
+        .filter(|(kind, cooldown)| *cooldown <= 0.0 && on_field(*kind) < kind.max_on_field())
+        .map(|(kind, _)| (kind, kind.spawn_weight(points)))
+        .filter(|(_, weight)| *weight > 0.0)
+        .collect();
+    let Some(kind) = choose_kind(&mut rng, &candidates) else {
+        return;
+    };
+    if let Some(index) = PickupKind::SPAWNED
+        .iter()
+        .position(|spawned| *spawned == kind)
+    {
+        spawner.cooldowns[index] = kind.cooldown();
+    }
+
+    let player_position = player_query
+        .get_single()
+        .map_or(Vec2::ZERO, |transform| transform.translation.truncate());
+    let window = window.single();
+    let radius = kind.radius(&run_config);
+    let half_extent = Vec2::new(window.width(), window.height()) / 2.0 - radius;
+
+    let mut position = Vec2::ZERO;
+    for _ in 0..PICKUP_PLACEMENT_TRIES {
+        position = Vec2::new(
+            rng.gen_range(-half_extent.x..half_extent.x),
+            rng.gen_range(-half_extent.y..half_extent.y),
+        );
+        if position.distance(player_position) > PICKUP_MIN_PLAYER_DISTANCE {
+            break;
+        }
+    }
+
+    commands.spawn((
+        Pickup::new(kind),
+        ColorMesh2dBundle {
+            mesh: asset_handles.coin_mesh.clone().into(),
+            material: kind.material(&asset_handles),
+            transform: Transform::from_translation(position.extend(render_z::PICKUP))
+                .with_scale(Vec3::splat(radius / COIN_RADIUS)),
+            ..default()
+        },
+    ));
+}
+
+pub fn expire_pickups(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Pickup)>,
+    game_time: Res<GameTime>,
+) {
+    for (entity, mut pickup) in query.iter_mut() {
+        let Some(lifetime) = pickup.lifetime.as_mut() else {
+            continue;
+        };
+        if lifetime.tick(game_time.delta()).finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,979 @@
+use std::fmt;
+
+use bevy::{
+    input::{keyboard::KeyboardInput, ButtonState},
+    prelude::*,
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::*,
+    game_time::GameTime,
+    keymap,
+    menu_list::{EntryKind, MenuEntry, MenuListEvent},
+    movement::Velocity,
+    rng::GameRng,
+    storage::{SaveData, Settings},
+    util::{lerp, vec3_move_toward},
+};
+
+#[derive(Component)]
+pub struct Player;
+
+// A point at least PLAYER_SPAWN_EDGE_FRACTION of the playfield away from every edge and, if any
+// try allows it, PLAYER_SPAWN_COIN_DISTANCE from the coin. Otherwise the try furthest from it.
+pub fn random_safe_spawn_position(rng: &mut GameRng, half_extent: Vec2, coin: Vec2) -> Vec2 {
+    let area = half_extent * (1.0 - 2.0 * PLAYER_SPAWN_EDGE_FRACTION);
+
+    let mut candidate = || {
+        Vec2::new(
+            rng.gen_range(-area.x..=area.x),
+            rng.gen_range(-area.y..=area.y),
+        )
+    };
+
+    let mut best = candidate();
+    for _ in 1..PLAYER_SPAWN_TRIES {
+        if best.distance(coin) >= PLAYER_SPAWN_COIN_DISTANCE {
+            break;
+        }
+        let next = candidate();
+        if next.distance(coin) > best.distance(coin) {
+            best = next;
+        }
+    }
+    best
+}
+
+#[derive(Resource, Default)]
+pub struct PlayerInput {
+    pub direction: Vec3,
+    pub dash: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MovementFeel {
+    #[default]
+    Momentum,
+    Responsive,
+    Instant,
+}
+
+impl MovementFeel {
+    pub const ALL: [MovementFeel; 3] = [
+        MovementFeel::Momentum,
+        MovementFeel::Responsive,
+        MovementFeel::Instant,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MovementFeel::Momentum => "Momentum",
+            MovementFeel::Responsive => "Responsive",
+            MovementFeel::Instant => "Instant",
+        }
+    }
+
+    pub fn menu_entry(&self) -> MenuEntry {
+        MenuEntry::new(
+            "movement_feel",
+            "Movement",
+            EntryKind::Choice {
+                options: MovementFeel::ALL.iter().map(MovementFeel::name).collect(),
+                selected: MovementFeel::ALL
+                    .iter()
+                    .position(|feel| feel == self)
+                    .unwrap_or(0),
+            },
+        )
+    }
+
+    // `accel` is the run's player acceleration; Responsive scales its own acceleration by the same
+    // factor so mutators like Slippery still apply.
+    pub fn step(&self, velocity: Vec3, direction: Vec3, accel: f32, dt: f32) -> Vec3 {
+        let target = direction * PLAYER_MAX_SPEED;
+
+        match self {
+            MovementFeel::Momentum => vec3_move_toward(velocity, target, accel * dt),
+            MovementFeel::Responsive => {
+                let accel = accel / PLAYER_ACCEL * PLAYER_RESPONSIVE_ACCEL;
+                let velocity = vec3_move_toward(velocity, target, accel * dt);
+                if direction == Vec3::ZERO {
+                    velocity * release_damping(dt)
+                } else {
+                    velocity
+                }
+            }
+            MovementFeel::Instant => target,
+        }
+    }
+}
+
+// Extra slowdown applied each frame with no input, on top of the deceleration toward zero.
+fn release_damping(dt: f32) -> f32 {
+    (-PLAYER_RESPONSIVE_DAMPING * dt).exp()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvincibilitySource {
+    Hit,
+    Pickup,
+    Spawn,
+}
+
+impl InvincibilitySource {
+    pub fn duration(&self) -> f32 {
+        match self {
+            InvincibilitySource::Hit => PLAYER_INVINCIBILITY_TIME,
+            InvincibilitySource::Pickup => PLAYER_PICKUP_INVINCIBILITY_TIME,
+            InvincibilitySource::Spawn => PLAYER_SPAWN_INVINCIBILITY_TIME,
+        }
+    }
+}
+
+#[derive(Component, Default)]
+pub struct Invincibility {
+    source: Option<InvincibilitySource>,
+    remaining: f32,
+}
+
+impl Invincibility {
+    pub fn grant(&mut self, source: InvincibilitySource) {
+        let duration = source.duration();
+        if duration > self.remaining {
+            self.source = Some(source);
+            self.remaining = duration;
+        }
+    }
+
+    pub fn tick(&mut self, delta: f32) {
+        self.remaining = (self.remaining - delta).max(0.0);
+        if self.remaining == 0.0 {
+            self.source = None;
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.remaining > 0.0
+    }
+
+    pub fn source(&self) -> Option<InvincibilitySource> {
+        self.source
+    }
+
+    pub fn remaining(&self) -> f32 {
+        self.remaining
+    }
+}
+
+// Enemies pass through the player while a dash lasts. That is kept apart from Invincibility, so
+// dashing neither extends nor cuts short the frames a hit or pickup granted.
+#[derive(Component, Default)]
+pub struct Dash {
+    direction: Vec3,
+    remaining: f32,
+    cooldown: f32,
+}
+
+impl Dash {
+    fn start(&mut self, direction: Vec3) {
+        if self.cooldown > 0.0 {
+            return;
+        }
+        // A half-tilted stick still dashes at full speed.
+        self.direction = direction.normalize_or_zero();
+        self.remaining = PLAYER_DASH_TIME;
+        self.cooldown = PLAYER_DASH_COOLDOWN;
+    }
+
+    fn tick(&mut self, delta: f32) {
+        self.remaining = (self.remaining - delta).max(0.0);
+        self.cooldown = (self.cooldown - delta).max(0.0);
+    }
+
+    fn velocity(&self) -> Vec3 {
+        let speed = lerp(
+            PLAYER_MAX_SPEED,
+            PLAYER_DASH_SPEED,
+            self.remaining / PLAYER_DASH_TIME,
+        );
+        self.direction * speed
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.remaining > 0.0
+    }
+
+    // From 0 right after a dash up to 1 once the next one is ready.
+    pub fn readiness(&self) -> f32 {
+        1.0 - (self.cooldown / PLAYER_DASH_COOLDOWN).clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    Start,
+    Pause,
+    Restart,
+    Mute,
+    Dash,
+}
+
+impl Action {
+    pub const ALL: [Action; 9] = [
+        Action::Up,
+        Action::Down,
+        Action::Left,
+        Action::Right,
+        Action::Start,
+        Action::Pause,
+        Action::Restart,
+        Action::Mute,
+        Action::Dash,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Up => "Up",
+            Action::Down => "Down",
+            Action::Left => "Left",
+            Action::Right => "Right",
+            Action::Start => "Start",
+            Action::Pause => "Pause / Back",
+            Action::Restart => "Restart",
+            Action::Mute => "Mute",
+            Action::Dash => "Dash",
+        }
+    }
+}
+
+pub type KeySlots = [Option<KeyCode>; BINDING_SLOTS];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindingError {
+    Reserved(KeyCode),
+    // Keys bindings.ron has no name for would be gone after a restart.
+    Unsaveable(KeyCode),
+}
+
+impl fmt::Display for BindingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BindingError::Reserved(key) => write!(f, "{:?} is reserved", key),
+            BindingError::Unsaveable(key) => write!(f, "{:?} can't be saved", key),
+        }
+    }
+}
+
+// Escape and the function keys belong to the system: they stay usable no matter what the player
+// binds, so they can't be bound. Pause keeps Escape as its default.
+pub fn is_reserved(key: KeyCode) -> bool {
+    matches!(
+        key,
+        KeyCode::Escape
+            | KeyCode::F1
+            | KeyCode::F2
+            | KeyCode::F3
+            | KeyCode::F4
+            | KeyCode::F5
+            | KeyCode::F6
+            | KeyCode::F7
+            | KeyCode::F8
+            | KeyCode::F9
+            | KeyCode::F10
+            | KeyCode::F11
+            | KeyCode::F12
+    )
+}
+
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputBindings {
+    pub up: KeySlots,
+    pub down: KeySlots,
+    pub left: KeySlots,
+    pub right: KeySlots,
+    pub start: KeySlots,
+    pub pause: KeySlots,
+    pub restart: KeySlots,
+    pub mute: KeySlots,
+    pub dash: KeySlots,
+}
+
+impl InputBindings {
+    pub fn slots(&self, action: Action) -> KeySlots {
+        match action {
+            Action::Up => self.up,
+            Action::Down => self.down,
+            Action::Left => self.left,
+            Action::Right => self.right,
+            Action::Start => self.start,
+            Action::Pause => self.pause,
+            Action::Restart => self.restart,
+            Action::Mute => self.mute,
+            Action::Dash => self.dash,
+        }
+    }
+
+    fn slots_mut(&mut self, action: Action) -> &mut KeySlots {
+        match action {
+            Action::Up => &mut self.up,
+            Action::Down => &mut self.down,
+            Action::Left => &mut self.left,
+            Action::Right => &mut self.right,
+            Action::Start => &mut self.start,
+            Action::Pause => &mut self.pause,
+            Action::Restart => &mut self.restart,
+            Action::Mute => &mut self.mute,
+            Action::Dash => &mut self.dash,
+        }
+    }
+
+    pub fn keys(&self, action: Action) -> impl Iterator<Item = KeyCode> {
+        self.slots(action).into_iter().flatten()
+    }
+
+    pub fn pressed(&self, action: Action, input: &Input<KeyCode>) -> bool {
+        input.any_pressed(self.keys(action))
+    }
+
+    pub fn just_pressed(&self, action: Action, input: &Input<KeyCode>) -> bool {
+        input.any_just_pressed(self.keys(action))
+    }
+
+    pub fn label(&self, action: Action) -> String {
+        let keys = self
+            .keys(action)
+            .map(|key| format!("{:?}", key))
+            .collect::<Vec<_>>();
+        if keys.is_empty() {
+            "(unbound)".to_string()
+        } else {
+            keys.join("/")
+        }
+    }
+
+    // Passing None clears the slot.
+    // A key already bound elsewhere trades places with the slot's old key, so a key never ends up
+    // on two actions. Returns the action that received the old key, if any.
+    pub fn bind(
+        &mut self,
+        action: Action,
+        slot: usize,
+        key: Option<KeyCode>,
+    ) -> Result<Option<Action>, BindingError> {
+        if let Some(key) = key.filter(|key| is_reserved(*key)) {
+            return Err(BindingError::Reserved(key));
+        }
+        if let Some(key) = key.filter(|key| keymap::key_name(*key).is_none()) {
+            return Err(BindingError::Unsaveable(key));
+        }
+
+        let previous = self.slots(action)[slot];
+        let holder = key.and_then(|key| {
+            Action::ALL.iter().find_map(|other| {
+                self.slots(*other)
+                    .iter()
+                    .position(|bound| *bound == Some(key))
+                    .filter(|other_slot| (*other, *other_slot) != (action, slot))
+                    .map(|other_slot| (*other, other_slot))
+            })
+        });
+
+        if let Some((other, other_slot)) = holder {
+            self.slots_mut(other)[other_slot] = previous;
+        }
+        self.slots_mut(action)[slot] = key;
+        Ok(holder
+            .map(|(other, _)| other)
+            .filter(|other| *other != action))
+    }
+
+    pub fn conflicts(&self) -> Vec<(&'static str, &'static str)> {
+        let bindings = Action::ALL
+            .iter()
+            .flat_map(|action| self.keys(*action).map(|key| (action.name(), key)))
+            .collect::<Vec<_>>();
+        let mut conflicts = Vec::new();
+
+        for (index, (name, key)) in bindings.iter().enumerate() {
+            for (other_name, other_key) in &bindings[index + 1..] {
+                if key == other_key && name != other_name {
+                    conflicts.push((*name, *other_name));
+                }
+            }
+        }
+
+        conflicts
+    }
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        Self {
+            up: [Some(KeyCode::W), Some(KeyCode::Up)],
+            down: [Some(KeyCode::S), Some(KeyCode::Down)],
+            left: [Some(KeyCode::A), Some(KeyCode::Left)],
+            right: [Some(KeyCode::D), Some(KeyCode::Right)],
+            start: [Some(KeyCode::Space), None],
+            pause: [Some(KeyCode::Escape), None],
+            restart: [Some(KeyCode::R), None],
+            mute: [Some(KeyCode::M), None],
+            dash: [Some(KeyCode::ShiftLeft), None],
+        }
+    }
+}
+
+pub type ButtonSlots = [Option<GamepadButtonType>; BINDING_SLOTS];
+
+// The pad's buttons for each action, read from every connected gamepad. Movement also takes the
+// left stick; see sample_player_input.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct GamepadBindings {
+    pub up: ButtonSlots,
+    pub down: ButtonSlots,
+    pub left: ButtonSlots,
+    pub right: ButtonSlots,
+    pub start: ButtonSlots,
+    pub pause: ButtonSlots,
+    pub restart: ButtonSlots,
+    pub mute: ButtonSlots,
+    pub dash: ButtonSlots,
+}
+
+impl GamepadBindings {
+    pub fn slots(&self, action: Action) -> ButtonSlots {
+        match action {
+            Action::Up => self.up,
+            Action::Down => self.down,
+            Action::Left => self.left,
+            Action::Right => self.right,
+            Action::Start => self.start,
+            Action::Pause => self.pause,
+            Action::Restart => self.restart,
+            Action::Mute => self.mute,
+            Action::Dash => self.dash,
+        }
+    }
+
+    fn buttons<'a>(
+        &self,
+        action: Action,
+        gamepads: &'a Gamepads,
+    ) -> impl Iterator<Item = GamepadButton> + 'a {
+        let slots = self.slots(action);
+        gamepads.iter().flat_map(move |gamepad| {
+            slots
+                .into_iter()
+                .flatten()
+                .map(move |button_type| GamepadButton::new(gamepad, button_type))
+        })
+    }
+
+    pub fn pressed(
+        &self,
+        action: Action,
+        gamepads: &Gamepads,
+        buttons: &Input<GamepadButton>,
+    ) -> bool {
+        buttons.any_pressed(self.buttons(action, gamepads))
+    }
+
+    pub fn just_pressed(
+        &self,
+        action: Action,
+        gamepads: &Gamepads,
+        buttons: &Input<GamepadButton>,
+    ) -> bool {
+        buttons.any_just_pressed(self.buttons(action, gamepads))
+    }
+}
+
+impl Default for GamepadBindings {
+    fn default() -> Self {
+        Self {
+            up: [Some(GamepadButtonType::DPadUp), None],
+            down: [Some(GamepadButtonType::DPadDown), None],
+            left: [Some(GamepadButtonType::DPadLeft), None],
+            right: [Some(GamepadButtonType::DPadRight), None],
+            start: [Some(GamepadButtonType::South), None],
+            pause: [Some(GamepadButtonType::Start), None],
+            restart: [Some(GamepadButtonType::Select), None],
+            mute: [None, None],
+            dash: [
+                Some(GamepadButtonType::West),
+                Some(GamepadButtonType::RightTrigger),
+            ],
+        }
+    }
+}
+
+pub fn change_movement_feel(
+    mut events: EventReader<MenuListEvent>,
+    mut save_data: ResMut<SaveData>,
+) {
+    for event in events.read() {
+        if let MenuListEvent::Changed {
+            entry: "movement_feel",
+            kind: EntryKind::Choice { selected, .. },
+            ..
+        } = event
+        {
+            save_data.settings.movement_feel = MovementFeel::ALL[*selected];
+        }
+    }
+}
+
+// Runs in PreUpdate, right after Bevy's input systems, so the game chain sees this frame's keys.
+pub fn sample_player_input(
+    mut player_input: ResMut<PlayerInput>,
+    mut keyboard_events: EventReader<KeyboardInput>,
+    bindings: Res<InputBindings>,
+    input: Res<Input<KeyCode>>,
+    save_data: Res<SaveData>,
+    (pad_bindings, gamepads, buttons, axes): (
+        Res<GamepadBindings>,
+        Res<Gamepads>,
+        Res<Input<GamepadButton>>,
+        Res<Axis<GamepadAxis>>,
+    ),
+) {
+    // Keys win over the pad, so a controller left on the desk can't drift the player.
+    player_input.direction = get_direction(&bindings, &input);
+    if player_input.direction == Vec3::ZERO {
+        player_input.direction = get_pad_direction(
+            &pad_bindings,
+            &gamepads,
+            &buttons,
+            &axes,
+            &save_data.settings,
+        );
+    }
+
+    // The raw events still carry a dash whose key was released before this frame ran.
+    let raw_dash = keyboard_events.read().any(|event| {
+        event.state == ButtonState::Pressed
+            && event
+                .key_code
+                .is_some_and(|key| bindings.keys(Action::Dash).any(|dash| dash == key))
+    });
+    player_input.dash = if save_data.settings.raw_dash_input {
+        raw_dash
+    } else {
+        bindings.just_pressed(Action::Dash, &input)
+    } || pad_bindings.just_pressed(Action::Dash, &gamepads, &buttons);
+}
+
+pub fn move_player(
+    player_input: Res<PlayerInput>,
+    mut query: Query<(&mut Transform, &mut Velocity, &mut Dash), With<Player>>,
+    run_config: Res<RunConfig>,
+    save_data: Res<SaveData>,
+    time: Res<Time>,
+) {
+    if query.is_empty() {
+        return;
+    }
+
+    let (mut transform, mut velocity, mut dash) = query.single_mut();
+
+    dash.tick(time.delta_seconds());
+    if player_input.dash && player_input.direction != Vec3::ZERO {
+        dash.start(player_input.direction);
+    }
+
+    // The dash holds the velocity for its whole length under every movement feel, and hands it
+    // back at full speed.
+    if dash.is_active() {
+        velocity.0 = dash.velocity();
+    } else {
+        velocity.0 = save_data.settings.movement_feel.step(
+            velocity.0,
+            player_input.direction,
+            run_config.player_accel,
+            time.delta_seconds(),
+        );
+    }
+
+    transform.translation += velocity.0 * time.delta_seconds();
+}
+
+pub fn tick_invincibility(mut query: Query<&mut Invincibility>, game_time: Res<GameTime>) {
+    for mut invincibility in query.iter_mut() {
+        if invincibility.is_active() {
+            invincibility.tick(game_time.delta_seconds());
+        }
+    }
+}
+
+fn get_direction(bindings: &InputBindings, input: &Input<KeyCode>) -> Vec3 {
+    let mut direction = Vec3::ZERO;
+
+    for (action, step) in [
+        (Action::Up, Vec3::Y),
+        (Action::Down, Vec3::NEG_Y),
+        (Action::Left, Vec3::NEG_X),
+        (Action::Right, Vec3::X),
+    ] {
+        if bindings.pressed(action, input) {
+            direction += step;
+        }
+    }
+
+    direction.normalize_or_zero()
+}
+
+// The d-pad counts as a full push. The left stick goes through remap_stick, so its tilt scales the
+// target speed. Any connected pad can move the player; with none connected this is zero.
+fn get_pad_direction(
+    pad_bindings: &GamepadBindings,
+    gamepads: &Gamepads,
+    buttons: &Input<GamepadButton>,
+    axes: &Axis<GamepadAxis>,
+    settings: &Settings,
+) -> Vec3 {
+    let mut direction = Vec3::ZERO;
+    for (action, step) in [
+        (Action::Up, Vec3::Y),
+        (Action::Down, Vec3::NEG_Y),
+        (Action::Left, Vec3::NEG_X),
+        (Action::Right, Vec3::X),
+    ] {
+        if pad_bindings.pressed(action, gamepads, buttons) {
+            direction += step;
+        }
+    }
+    if direction != Vec3::ZERO {
+        return direction.normalize_or_zero();
+    }
+
+    let stick = gamepads
+        .iter()
+        .map(|gamepad| {
+            Vec2::new(
+                axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX))
+                    .unwrap_or(0.0),
+                axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY))
+                    .unwrap_or(0.0),
+            )
+        })
+        .max_by(|a, b| a.length_squared().total_cmp(&b.length_squared()))
+        .unwrap_or(Vec2::ZERO);
+
+    remap_stick(
+        stick,
+        settings.stick_deadzone,
+        settings.stick_curve_exponent,
+    )
+    .extend(0.0)
+}
+
+// A raw stick reading to a direction whose length is the share of full speed: zero up to the dead
+// zone, then rising along the curve to 1 at the rim. Square gates read past the rim on diagonals;
+// that counts as full tilt, so no direction is faster than another.
+pub fn remap_stick(stick: Vec2, deadzone: f32, curve_exponent: f32) -> Vec2 {
+    // The settings come from a hand-editable save.
+    let deadzone = deadzone.clamp(0.0, GAMEPAD_STICK_DEADZONE_MAX);
+    let curve_exponent = curve_exponent.clamp(GAMEPAD_STICK_CURVE_MIN, GAMEPAD_STICK_CURVE_MAX);
+
+    let tilt = ((stick.length().min(1.0) - deadzone) / (1.0 - deadzone)).clamp(0.0, 1.0);
+    stick.normalize_or_zero() * tilt.powf(curve_exponent)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{
+        input::{InputPlugin, InputSystem},
+        time::TimeUpdateStrategy,
+    };
+
+    use super::*;
+
+    #[test]
+    fn longer_grant_replaces_a_shorter_window() {
+        let mut invincibility = Invincibility::default();
+        invincibility.grant(InvincibilitySource::Pickup);
+        invincibility.grant(InvincibilitySource::Hit);
+
+        assert_eq!(invincibility.source(), Some(InvincibilitySource::Hit));
+        assert_eq!(invincibility.remaining(), PLAYER_INVINCIBILITY_TIME);
+    }
+
+    #[test]
+    fn shorter_grant_never_cuts_a_window_short() {
+        let mut invincibility = Invincibility::default();
+        invincibility.grant(InvincibilitySource::Hit);
+        invincibility.grant(InvincibilitySource::Pickup);
+
+        assert_eq!(invincibility.source(), Some(InvincibilitySource::Hit));
+        assert_eq!(invincibility.remaining(), PLAYER_INVINCIBILITY_TIME);
+    }
+
+    #[test]
+    fn regrant_extends_only_past_the_remaining_time() {
+        let mut invincibility = Invincibility::default();
+        invincibility.grant(InvincibilitySource::Spawn);
+        invincibility.tick(PLAYER_SPAWN_INVINCIBILITY_TIME - 0.2);
+        invincibility.grant(InvincibilitySource::Pickup);
+
+        assert_eq!(invincibility.source(), Some(InvincibilitySource::Pickup));
+        assert_eq!(invincibility.remaining(), PLAYER_PICKUP_INVINCIBILITY_TIME);
+    }
+
+    #[test]
+    fn window_runs_out() {
+        let mut invincibility = Invincibility::default();
+        assert!(!invincibility.is_active());
+
+        invincibility.grant(InvincibilitySource::Pickup);
+        assert!(invincibility.is_active());
+
+        invincibility.tick(PLAYER_PICKUP_INVINCIBILITY_TIME + 1.0);
+        assert!(!invincibility.is_active());
+        assert_eq!(invincibility.source(), None);
+        assert_eq!(invincibility.remaining(), 0.0);
+    }
+
+    #[test]
+    fn release_damping_is_frame_rate_independent() {
+        assert_eq!(release_damping(0.0), 1.0);
+        assert!((release_damping(0.5) - release_damping(0.25).powi(2)).abs() < 1e-6);
+        assert!(release_damping(1.0 / 60.0) < 1.0);
+    }
+
+    #[test]
+    fn responsive_stops_sooner_than_momentum_on_release() {
+        let moving = Vec3::new(PLAYER_MAX_SPEED, 0.0, 0.0);
+        let dt = 1.0 / 60.0;
+
+        let momentum = MovementFeel::Momentum.step(moving, Vec3::ZERO, PLAYER_ACCEL, dt);
+        let responsive = MovementFeel::Responsive.step(moving, Vec3::ZERO, PLAYER_ACCEL, dt);
+
+        assert_eq!(momentum.x, PLAYER_MAX_SPEED - PLAYER_ACCEL * dt);
+        let expected = (PLAYER_MAX_SPEED - PLAYER_RESPONSIVE_ACCEL * dt) * release_damping(dt);
+        assert!((responsive.x - expected).abs() < 1e-3);
+    }
+
+    #[test]
+    fn responsive_does_not_damp_while_steering() {
+        let velocity = Vec3::new(100.0, 0.0, 0.0);
+        let dt = 1.0 / 60.0;
+
+        let stepped = MovementFeel::Responsive.step(velocity, Vec3::X, PLAYER_ACCEL, dt);
+
+        assert!((stepped.x - (100.0 + PLAYER_RESPONSIVE_ACCEL * dt)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn responsive_follows_the_run_accel() {
+        let dt = 1.0 / 60.0;
+        let slippery = MovementFeel::Responsive.step(Vec3::ZERO, Vec3::X, PLAYER_ACCEL / 2.0, dt);
+
+        assert!((slippery.x - PLAYER_RESPONSIVE_ACCEL / 2.0 * dt).abs() < 1e-3);
+    }
+
+    #[test]
+    fn instant_snaps_to_full_speed() {
+        let direction = Vec3::new(0.6, 0.8, 0.0);
+
+        assert_eq!(
+            MovementFeel::Instant.step(Vec3::ZERO, direction, PLAYER_ACCEL, 1.0 / 60.0),
+            direction * PLAYER_MAX_SPEED
+        );
+        assert_eq!(
+            MovementFeel::Instant.step(direction, Vec3::ZERO, PLAYER_ACCEL, 1.0 / 60.0),
+            Vec3::ZERO
+        );
+    }
+
+    fn input_app(settings: Settings) -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, InputPlugin))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+                1.0 / 60.0,
+            )))
+            .init_resource::<PlayerInput>()
+            .init_resource::<InputBindings>()
+            .init_resource::<GamepadBindings>()
+            .insert_resource(SaveData {
+                settings,
+                ..default()
+            })
+            .insert_resource(RunConfig(GameConfig::default()))
+            .add_systems(PreUpdate, sample_player_input.after(InputSystem))
+            .add_systems(Update, move_player);
+        app.world.spawn((
+            Player,
+            Transform::default(),
+            Velocity(Vec3::ZERO),
+            Dash::default(),
+        ));
+        // The first frame has no delta to move with.
+        app.update();
+        app
+    }
+
+    fn send_key(app: &mut App, key_code: KeyCode, state: ButtonState) {
+        app.world.send_event(KeyboardInput {
+            scan_code: 0,
+            key_code: Some(key_code),
+            state,
+            window: Entity::PLACEHOLDER,
+        });
+    }
+
+    fn player(app: &mut App) -> (Vec3, bool) {
+        let (transform, dash) = app
+            .world
+            .query_filtered::<(&Transform, &Dash), With<Player>>()
+            .single(&app.world);
+        (transform.translation, dash.is_active())
+    }
+
+    #[test]
+    fn key_press_moves_the_player_on_the_same_frame() {
+        let mut app = input_app(Settings::default());
+
+        send_key(&mut app, KeyCode::D, ButtonState::Pressed);
+        app.update();
+
+        assert!(player(&mut app).0.x > 0.0);
+    }
+
+    #[test]
+    fn raw_dash_input_catches_a_tap_within_one_frame() {
+        let mut app = input_app(Settings {
+            raw_dash_input: true,
+            ..default()
+        });
+
+        send_key(&mut app, KeyCode::D, ButtonState::Pressed);
+        app.update();
+        send_key(&mut app, KeyCode::ShiftLeft, ButtonState::Pressed);
+        send_key(&mut app, KeyCode::ShiftLeft, ButtonState::Released);
+        app.update();
+
+        assert!(player(&mut app).1);
+    }
+
+    // An 800x600 playfield, less the player's radius.
+    const HALF_EXTENT: Vec2 = Vec2::new(384.0, 284.0);
+
+    #[test]
+    fn safe_spawn_keeps_away_from_the_edges() {
+        let area = HALF_EXTENT * (1.0 - 2.0 * PLAYER_SPAWN_EDGE_FRACTION);
+
+        for seed in 0..1000 {
+            let coin = Vec2::new(seed as f32 % 300.0 - 150.0, 0.0);
+            let spawn =
+                random_safe_spawn_position(&mut GameRng::from_seed(seed), HALF_EXTENT, coin);
+
+            assert!(spawn.abs().cmple(area).all(), "{:?}", spawn);
+        }
+    }
+
+    #[test]
+    fn safe_spawn_keeps_away_from_a_coin_it_can_avoid() {
+        let coin = HALF_EXTENT * (1.0 - 2.0 * PLAYER_SPAWN_EDGE_FRACTION);
+
+        for seed in 0..500 {
+            let spawn =
+                random_safe_spawn_position(&mut GameRng::from_seed(seed), HALF_EXTENT, coin);
+
+            assert!(
+                spawn.distance(coin) >= PLAYER_SPAWN_COIN_DISTANCE,
+                "{:?}",
+                spawn
+            );
+        }
+    }
+
+    #[test]
+    fn safe_spawn_never_does_worse_than_its_first_try() {
+        // Nowhere in the spawn area is far enough from a coin at the center.
+        let area = HALF_EXTENT * (1.0 - 2.0 * PLAYER_SPAWN_EDGE_FRACTION);
+        assert!(area.length() < PLAYER_SPAWN_COIN_DISTANCE);
+
+        for seed in 0..100 {
+            let mut rng = GameRng::from_seed(seed);
+            let first_try = Vec2::new(
+                rng.gen_range(-area.x..=area.x),
+                rng.gen_range(-area.y..=area.y),
+            );
+            let spawn =
+                random_safe_spawn_position(&mut GameRng::from_seed(seed), HALF_EXTENT, Vec2::ZERO);
+
+            assert!(spawn.length() >= first_try.length());
+        }
+    }
+
+    #[test]
+    fn safe_spawn_is_seeded() {
+        let coin = Vec2::new(50.0, -20.0);
+        let spawn =
+            |seed| random_safe_spawn_position(&mut GameRng::from_seed(seed), HALF_EXTENT, coin);
+
+        assert_eq!(spawn(9), spawn(9));
+        assert_ne!(spawn(9), spawn(10));
+    }
+
+    #[test]
+    fn stick_inside_the_dead_zone_reads_as_centered() {
+        for stick in [Vec2::ZERO, Vec2::new(0.1, 0.0), Vec2::new(-0.1, 0.12)] {
+            assert_eq!(remap_stick(stick, 0.2, 1.0), Vec2::ZERO);
+        }
+    }
+
+    #[test]
+    fn tilt_rises_from_the_dead_zone_to_the_rim() {
+        let tilt = |length: f32| remap_stick(Vec2::X * length, 0.2, 1.0).length();
+
+        assert_eq!(tilt(0.2), 0.0);
+        assert!((tilt(0.6) - 0.5).abs() < 1e-5);
+        assert_eq!(tilt(1.0), 1.0);
+    }
+
+    #[test]
+    fn curve_bends_the_tilt_but_keeps_both_ends() {
+        let tilt = |length: f32, curve: f32| remap_stick(Vec2::Y * length, 0.0, curve).length();
+
+        assert!((tilt(0.5, 2.0) - 0.25).abs() < 1e-5);
+        assert!((tilt(0.5, 0.5) - 0.5f32.sqrt()).abs() < 1e-5);
+        for curve in [0.5, 1.0, 2.0, 3.0] {
+            assert_eq!(tilt(0.0, curve), 0.0);
+            assert_eq!(tilt(1.0, curve), 1.0);
+        }
+    }
+
+    #[test]
+    fn full_diagonal_is_no_faster_than_full_cardinal() {
+        // A square gate reads both axes at full tilt in the corner.
+        let diagonal = remap_stick(Vec2::ONE, 0.2, 1.5);
+
+        assert!((diagonal.length() - 1.0).abs() < 1e-5);
+        assert!((diagonal.x - diagonal.y).abs() < 1e-6);
+        assert_eq!(remap_stick(Vec2::X, 0.2, 1.5), Vec2::X);
+    }
+
+    #[test]
+    fn out_of_range_settings_are_clamped() {
+        let stick = Vec2::new(0.7, 0.0);
+
+        assert_eq!(
+            remap_stick(stick, 0.9, 1.0),
+            remap_stick(stick, GAMEPAD_STICK_DEADZONE_MAX, 1.0)
+        );
+        assert_eq!(
+            remap_stick(stick, 0.2, 10.0),
+            remap_stick(stick, 0.2, GAMEPAD_STICK_CURVE_MAX)
+        );
+        assert_eq!(remap_stick(stick, -1.0, 1.0), stick);
+    }
+}
@@ -0,0 +1,354 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    assets::AssetHandles,
+    config::*,
+    deposit::HomeZone,
+    game::{GameInfo, GameMode, HitPlayer},
+    pause::PauseState,
+    quality::EffectsLevel,
+    render_z,
+    storage::SaveData,
+    window::not_suspended,
+    AppState,
+};
+
+pub struct PostFxPlugin;
+
+impl Plugin for PostFxPlugin {
+    fn build(&self, app: &mut App) {
+        #[cfg(feature = "post-fx")]
+        shader::build(app);
+
+        app.init_resource::<PostFxParams>()
+            .add_systems(OnEnter(AppState::Game), spawn_fallback_overlays)
+            .add_systems(
+                Update,
+                (write_post_fx_params, update_fallback_overlays)
+                    .chain()
+                    .run_if(in_state(AppState::Game))
+                    .run_if(not_suspended),
+            )
+            .add_systems(OnExit(AppState::Game), reset_post_fx_params);
+    }
+}
+
+// Full-screen effects for this frame, written by write_post_fx_params and drawn by the post-fx
+// overlay, or by plain sprite overlays when the shader is compiled out or the effects level is
+// too low for it. Strengths run from 0 to 1.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct PostFxParams {
+    pub vignette: f32,
+    pub vignette_color: Color,
+    // Toward gray, for the death sequence.
+    pub desaturation: f32,
+    // A zone worth heading for: center and radius in world space.
+    pub highlight: Option<(Vec2, f32)>,
+    pub highlight_strength: f32,
+}
+
+impl Default for PostFxParams {
+    fn default() -> Self {
+        Self {
+            vignette: 0.0,
+            vignette_color: POST_FX_VIGNETTE_COLOR,
+            desaturation: 0.0,
+            highlight: None,
+            highlight_strength: 0.0,
+        }
+    }
+}
+
+fn use_shader(effects_level: &EffectsLevel) -> bool {
+    cfg!(feature = "post-fx") && effects_level.0.post_fx()
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum FallbackOverlay {
+    Desaturation,
+    Highlight,
+    Vignette,
+}
+
+// A hit flashes the vignette in and lets it fade; with reduced flashing it never goes past
+// POST_FX_REDUCED_VIGNETTE. Game over fades the color out, and a Deposit run with coins in hand
+// highlights the home zone.
+fn write_post_fx_params(
+    mut params: ResMut<PostFxParams>,
+    mut hit_player: EventReader<HitPlayer>,
+    save_data: Res<SaveData>,
+    pause_state: Res<State<PauseState>>,
+    game_mode: Res<GameMode>,
+    game_info: Option<Res<GameInfo>>,
+    home_zone: Query<&Transform, With<HomeZone>>,
+    time: Res<Time<Real>>,
+) {
+    let dt = time.delta_seconds();
+    let peak = if save_data.settings.reduce_flashing {
+        POST_FX_REDUCED_VIGNETTE
+    } else {
+        1.0
+    };
+
+    if hit_player.read().count() > 0 {
+        params.vignette = peak;
+    } else {
+        params.vignette = (params.vignette - dt / POST_FX_VIGNETTE_TIME).max(0.0);
+    }
+
+    params.desaturation = if *pause_state.get() == PauseState::GameOver {
+        (params.desaturation + dt / POST_FX_DESATURATION_TIME).min(1.0)
+    } else {
+        0.0
+    };
+
+    let carrying = *game_mode == GameMode::Deposit
+        && game_info.is_some_and(|game_info| game_info.carried() > 0);
+    let zone = home_zone
+        .get_single()
+        .ok()
+        .filter(|_| carrying)
+        .map(|transform| {
+            (
+                transform.translation.truncate(),
+                HOME_ZONE_RADIUS * POST_FX_HIGHLIGHT_SCALE,
+            )
+        });
+    // Keep the last zone while fading out, so it doesn't vanish mid-fade.
+    if zone.is_some() {
+        params.highlight = zone;
+    }
+    let target = if zone.is_some() { 1.0 } else { 0.0 };
+    let step = dt / POST_FX_HIGHLIGHT_FADE_TIME;
+    params.highlight_strength += (target - params.highlight_strength).clamp(-step, step);
+    if params.highlight_strength <= 0.0 {
+        params.highlight = None;
+    }
+}
+
+fn reset_post_fx_params(mut params: ResMut<PostFxParams>) {
+    *params = PostFxParams::default();
+}
+
+fn spawn_fallback_overlays(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    asset_handles: Res<AssetHandles>,
+) {
+    for (overlay, mesh) in [
+        (FallbackOverlay::Desaturation, &asset_handles.bar_mesh),
+        (FallbackOverlay::Highlight, &asset_handles.home_zone_mesh),
+        (FallbackOverlay::Vignette, &asset_handles.bar_mesh),
+    ] {
+        commands.spawn((
+            overlay,
+            ColorMesh2dBundle {
+                mesh: mesh.clone().into(),
+                material: materials.add(ColorMaterial::from(Color::NONE)),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+        ));
+    }
+}
+
+// The chunkier stand-ins: flat full-screen tints and a plain disc, sized and colored from the same
+// params as the shader.
+fn update_fallback_overlays(
+    params: Res<PostFxParams>,
+    effects_level: Res<EffectsLevel>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut overlays: Query<(
+        &FallbackOverlay,
+        &Handle<ColorMaterial>,
+        &mut Transform,
+        &mut Visibility,
+    )>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    camera: Query<&Transform, (With<Camera2d>, Without<FallbackOverlay>)>,
+) {
+    let (Ok(window), Ok(camera)) = (window.get_single(), camera.get_single()) else {
+        return;
+    };
+    let fallback = !use_shader(&effects_level);
+    let screen = Vec2::new(window.width(), window.height()) * POST_FX_OVERSCAN;
+
+    for (overlay, handle, mut transform, mut visibility) in overlays.iter_mut() {
+        let (color, translation, scale) = match overlay {
+            FallbackOverlay::Desaturation => (
+                POST_FX_FALLBACK_GRAY.with_a(params.desaturation * POST_FX_FALLBACK_GRAY.a()),
+                camera.translation.truncate(),
+                screen,
+            ),
+            FallbackOverlay::Highlight => {
+                let (center, radius) = params.highlight.unwrap_or((Vec2::ZERO, 0.0));
+                (
+                    POST_FX_HIGHLIGHT_COLOR
+                        .with_a(POST_FX_HIGHLIGHT_COLOR.a() * params.highlight_strength),
+                    center,
+                    Vec2::splat(radius / HOME_ZONE_RADIUS),
+                )
+            }
+            FallbackOverlay::Vignette => (
+                params
+                    .vignette_color
+                    .with_a(params.vignette * POST_FX_FALLBACK_VIGNETTE_ALPHA),
+                camera.translation.truncate(),
+                screen,
+            ),
+        };
+
+        let wanted = if fallback && color.a() > 0.0 {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if *visibility != wanted {
+            *visibility = wanted;
+        }
+        if wanted == Visibility::Hidden {
+            continue;
+        }
+
+        transform.translation = translation.extend(render_z::POST_FX);
+        transform.scale = scale.extend(1.0);
+        if let Some(material) = materials.get_mut(handle) {
+            material.color = color;
+        }
+    }
+}
+
+// The ShaderType derive emits a size check fn per field that rustc reports as unused.
+#[cfg(feature = "post-fx")]
+#[allow(dead_code)]
+mod shader {
+    use bevy::{
+        asset::load_internal_asset,
+        prelude::*,
+        render::render_resource::{AsBindGroup, ShaderRef, ShaderType},
+        sprite::{Material2d, Material2dPlugin, MaterialMesh2dBundle},
+        window::PrimaryWindow,
+    };
+
+    use super::{use_shader, PostFxParams};
+    use crate::{assets::AssetHandles, config::*, quality::EffectsLevel, render_z, AppState};
+
+    // Built into the binary, so an asset pack never has to carry it.
+    const POST_FX_SHADER: Handle<Shader> =
+        Handle::weak_from_u128(0x9b3e_51c2_7a04_4d8f_b612_3c7e_e08a_5f19);
+
+    pub fn build(app: &mut App) {
+        load_internal_asset!(app, POST_FX_SHADER, "post_fx.wgsl", Shader::from_wgsl);
+
+        app.add_plugins(Material2dPlugin::<PostFxMaterial>::default())
+            .add_systems(OnEnter(AppState::Game), spawn_post_fx_overlay)
+            .add_systems(
+                Update,
+                update_post_fx_overlay
+                    .after(super::write_post_fx_params)
+                    .run_if(in_state(AppState::Game)),
+            );
+    }
+
+    #[derive(ShaderType, Debug, Clone, Copy, Default)]
+    struct PostFxUniform {
+        vignette_color: Vec4,
+        highlight_color: Vec4,
+        highlight_center: Vec2,
+        vignette: f32,
+        desaturation: f32,
+        highlight_radius: f32,
+        highlight_strength: f32,
+    }
+
+    impl From<&PostFxParams> for PostFxUniform {
+        fn from(params: &PostFxParams) -> Self {
+            let (highlight_center, highlight_radius) = params.highlight.unwrap_or_default();
+            Self {
+                vignette_color: params.vignette_color.as_linear_rgba_f32().into(),
+                highlight_color: POST_FX_HIGHLIGHT_COLOR.as_linear_rgba_f32().into(),
+                highlight_center,
+                vignette: params.vignette,
+                desaturation: params.desaturation,
+                highlight_radius,
+                highlight_strength: params.highlight_strength,
+            }
+        }
+    }
+
+    #[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+    pub struct PostFxMaterial {
+        #[uniform(0)]
+        uniform: PostFxUniform,
+    }
+
+    impl Material2d for PostFxMaterial {
+        fn fragment_shader() -> ShaderRef {
+            POST_FX_SHADER.into()
+        }
+    }
+
+    #[derive(Component)]
+    struct PostFxOverlay;
+
+    fn spawn_post_fx_overlay(
+        mut commands: Commands,
+        mut materials: ResMut<Assets<PostFxMaterial>>,
+        asset_handles: Res<AssetHandles>,
+    ) {
+        commands.spawn((
+            PostFxOverlay,
+            MaterialMesh2dBundle {
+                mesh: asset_handles.bar_mesh.clone().into(),
+                material: materials.add(PostFxMaterial {
+                    uniform: PostFxUniform::default(),
+                }),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+        ));
+    }
+
+    // Hidden while every effect is at rest, so an idle frame costs nothing.
+    fn update_post_fx_overlay(
+        params: Res<PostFxParams>,
+        effects_level: Res<EffectsLevel>,
+        mut materials: ResMut<Assets<PostFxMaterial>>,
+        mut overlay: Query<
+            (&Handle<PostFxMaterial>, &mut Transform, &mut Visibility),
+            With<PostFxOverlay>,
+        >,
+        window: Query<&Window, With<PrimaryWindow>>,
+        camera: Query<&Transform, (With<Camera2d>, Without<PostFxOverlay>)>,
+    ) {
+        let (Ok((handle, mut transform, mut visibility)), Ok(window), Ok(camera)) = (
+            overlay.get_single_mut(),
+            window.get_single(),
+            camera.get_single(),
+        ) else {
+            return;
+        };
+
+        let idle = params.vignette == 0.0
+            && params.desaturation == 0.0
+            && params.highlight_strength == 0.0;
+        let wanted = if use_shader(&effects_level) && !idle {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if *visibility != wanted {
+            *visibility = wanted;
+        }
+        if wanted == Visibility::Hidden {
+            return;
+        }
+
+        transform.translation = camera.translation.truncate().extend(render_z::POST_FX);
+        transform.scale =
+            (Vec2::new(window.width(), window.height()) * POST_FX_OVERSCAN).extend(1.0);
+        if let Some(material) = materials.get_mut(handle) {
+            material.uniform = PostFxUniform::from(&*params);
+        }
+    }
+}
@@ -0,0 +1,47 @@
+// Full-screen overlay for PostFxParams, drawn over the playfield and under the HUD. It can only
+// blend over the scene, so "desaturation" washes toward gray rather than reading the scene back.
+
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+
+struct PostFx {
+    vignette_color: vec4<f32>,
+    highlight_color: vec4<f32>,
+    highlight_center: vec2<f32>,
+    vignette: f32,
+    desaturation: f32,
+    highlight_radius: f32,
+    highlight_strength: f32,
+};
+
+@group(1) @binding(0) var<uniform> post_fx: PostFx;
+
+const DESATURATION_GRAY: vec3<f32> = vec3<f32>(0.35, 0.35, 0.35);
+const DESATURATION_ALPHA: f32 = 0.6;
+
+fn over(top: vec4<f32>, bottom: vec4<f32>) -> vec4<f32> {
+    let alpha = top.a + bottom.a * (1.0 - top.a);
+    if alpha <= 0.0 {
+        return vec4<f32>(0.0);
+    }
+    let rgb = (top.rgb * top.a + bottom.rgb * bottom.a * (1.0 - top.a)) / alpha;
+    return vec4<f32>(rgb, alpha);
+}
+
+@fragment
+fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
+    var color = vec4<f32>(DESATURATION_GRAY, post_fx.desaturation * DESATURATION_ALPHA);
+
+    if post_fx.highlight_radius > 0.0 {
+        let distance = distance(mesh.world_position.xy, post_fx.highlight_center);
+        let glow = 1.0 - smoothstep(post_fx.highlight_radius * 0.5, post_fx.highlight_radius, distance);
+        let highlight = post_fx.highlight_color;
+        color = over(vec4<f32>(highlight.rgb, highlight.a * glow * post_fx.highlight_strength), color);
+    }
+
+    // 0 at the center, 1 at the middle of each edge.
+    let edge = length((mesh.uv - vec2<f32>(0.5)) * 2.0);
+    let vignette = smoothstep(0.4, 1.3, edge) * post_fx.vignette;
+    color = over(vec4<f32>(post_fx.vignette_color.rgb, post_fx.vignette_color.a * vignette), color);
+
+    return color;
+}
@@ -0,0 +1,266 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::*,
+    menu_list::{EntryKind, MenuEntry, MenuListEvent},
+    storage::SaveData,
+};
+
+pub struct QualityPlugin;
+
+impl Plugin for QualityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EffectsLevel>()
+            .init_resource::<QualityController>()
+            .add_systems(
+                Update,
+                (change_effects_quality, update_effects_level).chain(),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EffectsQuality {
+    High,
+    Medium,
+    Low,
+    #[default]
+    Auto,
+}
+
+impl EffectsQuality {
+    pub const ALL: [EffectsQuality; 4] = [
+        EffectsQuality::High,
+        EffectsQuality::Medium,
+        EffectsQuality::Low,
+        EffectsQuality::Auto,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EffectsQuality::High => "High",
+            EffectsQuality::Medium => "Medium",
+            EffectsQuality::Low => "Low",
+            EffectsQuality::Auto => "Auto",
+        }
+    }
+
+    pub fn menu_entry(&self) -> MenuEntry {
+        MenuEntry::new(
+            "effects_quality",
+            "Effects",
+            EntryKind::Choice {
+                options: EffectsQuality::ALL
+                    .iter()
+                    .map(EffectsQuality::name)
+                    .collect(),
+                selected: EffectsQuality::ALL
+                    .iter()
+                    .position(|quality| quality == self)
+                    .unwrap_or(0),
+            },
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum QualityLevel {
+    Low,
+    Medium,
+    #[default]
+    High,
+}
+
+impl QualityLevel {
+    fn lower(self) -> Self {
+        match self {
+            QualityLevel::High => QualityLevel::Medium,
+            QualityLevel::Medium | QualityLevel::Low => QualityLevel::Low,
+        }
+    }
+
+    fn higher(self) -> Self {
+        match self {
+            QualityLevel::Low => QualityLevel::Medium,
+            QualityLevel::Medium | QualityLevel::High => QualityLevel::High,
+        }
+    }
+
+    pub fn floating_text(self) -> bool {
+        self >= QualityLevel::High
+    }
+
+    pub fn screen_shake(self) -> bool {
+        self >= QualityLevel::Medium
+    }
+
+    pub fn animated_background(self) -> bool {
+        self >= QualityLevel::Medium
+    }
+
+    pub fn coin_beacon(self) -> bool {
+        self >= QualityLevel::Medium
+    }
+
+    pub fn wrap_hints(self) -> bool {
+        self >= QualityLevel::Medium
+    }
+
+    pub fn speed_ribbon(self) -> bool {
+        self >= QualityLevel::Medium
+    }
+
+    pub fn post_fx(self) -> bool {
+        self >= QualityLevel::Medium
+    }
+}
+
+// The effects quality in use, resolved from the setting and, on Auto, from frame times.
+#[derive(Resource, Default)]
+pub struct EffectsLevel(pub QualityLevel);
+
+// Averages frame times over a rolling window and steps the level down when the average is over
+// budget, or back up when it is comfortably under. The gap between the two thresholds, and
+// starting a fresh window after every step, keep it from flip-flopping.
+#[derive(Resource, Default)]
+pub struct QualityController {
+    level: QualityLevel,
+    frames: VecDeque<f32>,
+    total: f32,
+}
+
+impl QualityController {
+    pub fn update(&mut self, frame_time: f32) -> QualityLevel {
+        // Loading and window changes stall single frames; those say nothing about the effects.
+        if frame_time > QUALITY_MAX_FRAME_TIME {
+            return self.level;
+        }
+
+        self.frames.push_back(frame_time);
+        self.total += frame_time;
+        if self.total < QUALITY_WINDOW {
+            return self.level;
+        }
+
+        let average = self.total / self.frames.len() as f32;
+        let next = if average > QUALITY_STEP_DOWN_FRAME_TIME {
+            self.level.lower()
+        } else if average < QUALITY_STEP_UP_FRAME_TIME {
+            self.level.higher()
+        } else {
+            self.level
+        };
+
+        if next != self.level {
+            self.level = next;
+            self.frames.clear();
+            self.total = 0.0;
+            return self.level;
+        }
+
+        while self.total >= QUALITY_WINDOW {
+            let Some(oldest) = self.frames.pop_front() else {
+                break;
+            };
+            self.total -= oldest;
+        }
+
+        self.level
+    }
+}
+
+fn change_effects_quality(mut events: EventReader<MenuListEvent>, mut save_data: ResMut<SaveData>) {
+    for event in events.read() {
+        if let MenuListEvent::Changed {
+            entry: "effects_quality",
+            kind: EntryKind::Choice { selected, .. },
+            ..
+        } = event
+        {
+            save_data.settings.effects_quality = EffectsQuality::ALL[*selected];
+        }
+    }
+}
+
+fn update_effects_level(
+    save_data: Res<SaveData>,
+    mut controller: ResMut<QualityController>,
+    mut level: ResMut<EffectsLevel>,
+    time: Res<Time<Real>>,
+) {
+    let next = match save_data.settings.effects_quality {
+        EffectsQuality::High => QualityLevel::High,
+        EffectsQuality::Medium => QualityLevel::Medium,
+        EffectsQuality::Low => QualityLevel::Low,
+        EffectsQuality::Auto => controller.update(time.delta_seconds()),
+    };
+
+    if level.0 != next {
+        level.0 = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SLOW: f32 = 0.025;
+    const MIDDLING: f32 = 0.016;
+    const FAST: f32 = 0.008;
+
+    // Feeds frames of the given length until just under `seconds` have passed.
+    fn run(controller: &mut QualityController, frame_time: f32, seconds: f32) -> QualityLevel {
+        let frames = (seconds / frame_time) as usize;
+        let mut level = controller.level;
+        for _ in 0..frames {
+            level = controller.update(frame_time);
+        }
+        level
+    }
+
+    #[test]
+    fn slow_window_steps_down_once() {
+        let mut controller = QualityController::default();
+
+        assert_eq!(run(&mut controller, SLOW, 1.9), QualityLevel::High);
+        assert_eq!(run(&mut controller, SLOW, 0.2), QualityLevel::Medium);
+        // A fresh window has to fill before the next step.
+        assert_eq!(run(&mut controller, SLOW, 1.9), QualityLevel::Medium);
+        assert_eq!(run(&mut controller, SLOW, 0.2), QualityLevel::Low);
+        assert_eq!(run(&mut controller, SLOW, 10.0), QualityLevel::Low);
+    }
+
+    #[test]
+    fn fast_window_steps_back_up() {
+        let mut controller = QualityController::default();
+        run(&mut controller, SLOW, 4.2);
+        assert_eq!(controller.level, QualityLevel::Low);
+
+        assert_eq!(run(&mut controller, FAST, 2.1), QualityLevel::Medium);
+        assert_eq!(run(&mut controller, FAST, 2.1), QualityLevel::High);
+        assert_eq!(run(&mut controller, FAST, 10.0), QualityLevel::High);
+    }
+
+    #[test]
+    fn frame_times_between_the_thresholds_hold_the_level() {
+        let mut controller = QualityController::default();
+        run(&mut controller, SLOW, 2.1);
+
+        assert_eq!(run(&mut controller, MIDDLING, 20.0), QualityLevel::Medium);
+    }
+
+    #[test]
+    fn single_stalls_are_ignored() {
+        let mut controller = QualityController::default();
+
+        for _ in 0..20 {
+            controller.update(1.0);
+        }
+
+        assert_eq!(controller.level, QualityLevel::High);
+        assert!(controller.frames.is_empty());
+    }
+}
@@ -0,0 +1,195 @@
+use bevy::{prelude::*, sprite::Anchor};
+
+use crate::{
+    assets::AssetHandles,
+    config::*,
+    effects::text_label,
+    game::GameMode,
+    menu::cleanup_menu,
+    menu_list::{spawn_menu_list, EntryKind, MenuEntry, MenuFocus, MenuListEvent},
+    player::{Action, InputBindings},
+    render_z,
+    storage::{current_day, RunRecord, SaveData},
+    AppState,
+};
+
+const RECORDS_LIST: &str = "records";
+
+pub struct RecordsPlugin;
+
+impl Plugin for RecordsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Records), setup_records)
+            .add_systems(Update, handle_records.run_if(in_state(AppState::Records)))
+            .add_systems(OnExit(AppState::Records), cleanup_menu);
+    }
+}
+
+#[derive(Component)]
+struct RecordRow;
+
+fn setup_records(
+    mut commands: Commands,
+    asset_handles: Res<AssetHandles>,
+    save_data: Res<SaveData>,
+    bindings: Res<InputBindings>,
+    focus: Res<MenuFocus>,
+) {
+    commands.spawn(text_label(
+        &asset_handles,
+        "Records",
+        80.0,
+        Color::DARK_GRAY,
+        Vec3::new(0.0, 260.0, render_z::BACKDROP),
+    ));
+
+    spawn_menu_list(
+        &mut commands,
+        &asset_handles,
+        &focus,
+        RECORDS_LIST,
+        vec![MenuEntry::new(
+            "mode",
+            "Mode",
+            EntryKind::Choice {
+                options: GameMode::ALL.iter().map(GameMode::name).collect(),
+                selected: 0,
+            },
+        )],
+        Vec3::new(0.0, 190.0, render_z::BACKDROP),
+    );
+
+    spawn_record_rows(&mut commands, &asset_handles, &save_data, GameMode::ALL[0]);
+
+    commands.spawn(text_label(
+        &asset_handles,
+        format!(
+            "Left/Right to change mode, {} to return",
+            bindings.label(Action::Pause)
+        ),
+        28.0,
+        Color::DARK_GRAY,
+        Vec3::new(0.0, -260.0, render_z::BACKDROP),
+    ));
+}
+
+// Records set today are drawn in white.
+fn spawn_record_rows(
+    commands: &mut Commands,
+    asset_handles: &AssetHandles,
+    save_data: &SaveData,
+    mode: GameMode,
+) {
+    let records = save_data
+        .records
+        .get(&mode)
+        .map_or(&[][..], |records| &records[..]);
+
+    if records.is_empty() {
+        commands.spawn((
+            RecordRow,
+            text_label(
+                asset_handles,
+                "No runs yet",
+                32.0,
+                Color::DARK_GRAY,
+                Vec3::new(0.0, 80.0, render_z::BACKDROP),
+            ),
+        ));
+        return;
+    }
+
+    let today = current_day();
+    for (rank, record) in records.iter().enumerate() {
+        commands.spawn((
+            RecordRow,
+            Text2dBundle {
+                text: Text::from_section(
+                    record_label(rank, record),
+                    TextStyle {
+                        font: asset_handles.font.clone(),
+                        font_size: 28.0,
+                        color: if record.day == today {
+                            Color::WHITE
+                        } else {
+                            Color::DARK_GRAY
+                        },
+                    },
+                ),
+                text_anchor: Anchor::CenterLeft,
+                transform: Transform::from_translation(Vec3::new(
+                    -340.0,
+                    130.0 - rank as f32 * RECORDS_ROW_SPACING,
+                    render_z::BACKDROP,
+                )),
+                ..default()
+            },
+        ));
+    }
+}
+
+fn record_label(rank: usize, record: &RunRecord) -> String {
+    format!(
+        "{:>2}. {:>5}  {:>3}:{:02}  {:<6}  {:<7}  {:<10}  {}{}",
+        rank + 1,
+        record.score,
+        record.time as u32 / 60,
+        record.time as u32 % 60,
+        record.difficulty.name(),
+        record.arena.name(),
+        record.feel.name(),
+        format_day(record.day),
+        if record.score_decay { "  decay" } else { "" }
+    )
+}
+
+// Days since the epoch to a YYYY-MM-DD civil date (Howard Hinnant's days_from_civil, inverted).
+fn format_day(day: u64) -> String {
+    let z = day as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day_of_month = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+
+    format!("{:04}-{:02}-{:02}", year, month, day_of_month)
+}
+
+fn handle_records(
+    mut commands: Commands,
+    mut events: EventReader<MenuListEvent>,
+    mut next_state: ResMut<NextState<AppState>>,
+    asset_handles: Res<AssetHandles>,
+    save_data: Res<SaveData>,
+    rows: Query<Entity, With<RecordRow>>,
+) {
+    for event in events.read() {
+        match event {
+            MenuListEvent::Back { list: RECORDS_LIST } => next_state.set(AppState::Menu),
+            MenuListEvent::Changed {
+                list: RECORDS_LIST,
+                kind: EntryKind::Choice { selected, .. },
+                ..
+            } => {
+                rows.iter().for_each(|entity| {
+                    commands.entity(entity).despawn();
+                });
+                spawn_record_rows(
+                    &mut commands,
+                    &asset_handles,
+                    &save_data,
+                    GameMode::ALL[*selected],
+                );
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,78 @@
+// Draw order of the 2D scene, back to front. Children sit just above or below their parent
+// instead of taking a layer of their own.
+pub const BACKGROUND: f32 = -20.0;
+// The big score, menus and other text the playfield draws over.
+pub const BACKDROP: f32 = -10.0;
+// Markings on the playfield floor, like the home zone and the grace bar.
+pub const PLAYFIELD: f32 = -5.0;
+pub const TRAIL: f32 = -2.0;
+pub const PICKUP: f32 = -1.0;
+pub const ENEMY: f32 = 1.0;
+// Above enemies, so the player stays visible when sliding under a crowd.
+pub const PLAYER: f32 = 2.0;
+pub const EFFECTS: f32 = 5.0;
+// Full-screen washes over the whole playfield, still under the HUD.
+pub const POST_FX: f32 = 8.0;
+pub const HUD: f32 = 10.0;
+
+pub const CHILD_ABOVE: f32 = 0.1;
+pub const CHILD_BELOW: f32 = -0.1;
+
+const _: () = assert!(
+    BACKGROUND < BACKDROP
+        && BACKDROP < PLAYFIELD
+        && PLAYFIELD < TRAIL
+        && TRAIL < PICKUP
+        && PICKUP < ENEMY
+        && ENEMY < PLAYER
+        && PLAYER < EFFECTS
+        && EFFECTS < POST_FX
+        && POST_FX < HUD
+);
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::*;
+    use crate::{
+        coin::{get_coin_spawn_position, CoinSpawnConstraints, CoinSpawnHistory},
+        config::{Difficulty, GameRules},
+        enemy::{get_enemy_spawn_position, SpawnSide},
+        rng::GameRng,
+    };
+
+    const LAYERS: [f32; 10] = [
+        BACKGROUND, BACKDROP, PLAYFIELD, TRAIL, PICKUP, ENEMY, PLAYER, EFFECTS, POST_FX, HUD,
+    ];
+
+    #[test]
+    fn children_stay_between_the_neighboring_layers() {
+        for pair in LAYERS.windows(2) {
+            assert!(pair[0] + CHILD_ABOVE < pair[1] + CHILD_BELOW);
+        }
+    }
+
+    #[test]
+    fn spawn_helpers_use_their_layer() {
+        let mut rng = GameRng::from_seed(447);
+
+        for side in SpawnSide::ALL {
+            assert_eq!(
+                get_enemy_spawn_position(800.0, 600.0, side, &mut rng).z,
+                ENEMY
+            );
+        }
+
+        let constraints = CoinSpawnConstraints::new(
+            800.0,
+            600.0,
+            Vec2::ZERO,
+            0,
+            &GameRules::for_difficulty(Difficulty::Normal),
+        );
+        let coin =
+            get_coin_spawn_position(&mut rng, &mut CoinSpawnHistory::default(), &constraints);
+        assert_eq!(coin.z, PICKUP);
+    }
+}
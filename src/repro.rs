@@ -0,0 +1,320 @@
+use std::{
+    env, fmt, fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use bevy::{app::AppExit, prelude::*, time::TimeUpdateStrategy, window::PrimaryWindow};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::*,
+    descriptor::{DescriptorError, QueuedSeed, RunDescriptor},
+    game::{GameMode, StateChecksums},
+    game_time::GameTime,
+    mutator::ActiveMutators,
+    player::{sample_player_input, MovementFeel, PlayerInput},
+    storage::SaveData,
+    AppState,
+};
+
+// Bumped whenever ReproFile changes shape; older files are refused rather than misread.
+pub const REPRO_VERSION: u32 = 1;
+
+const RECORD_FLAG: &str = "--record-repro";
+const PLAY_FLAG: &str = "--play-repro";
+
+// Everything needed to play the start of a run back exactly: the run code, the tuning it ran
+// with, the playfield size, and each frame's time step and input. Frames are (delta, direction x,
+// direction y, dash) to keep the file small.
+#[derive(Serialize, Deserialize)]
+pub struct ReproFile {
+    pub version: u32,
+    pub run: String,
+    pub config: GameConfig,
+    pub movement_feel: MovementFeel,
+    pub victory: bool,
+    pub playfield: (f32, f32),
+    pub frames: Vec<(f32, f32, f32, bool)>,
+    pub checksums: Vec<u64>,
+}
+
+#[derive(Debug)]
+pub enum ReproError {
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+    Version(u32),
+    Run(DescriptorError),
+}
+
+impl fmt::Display for ReproError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReproError::Io(error) => write!(f, "{}", error),
+            ReproError::Parse(error) => write!(f, "{}", error),
+            ReproError::Serialize(error) => write!(f, "{}", error),
+            ReproError::Version(version) => write!(
+                f,
+                "repro format version {} is not supported (expected {})",
+                version, REPRO_VERSION
+            ),
+            ReproError::Run(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for ReproError {}
+
+impl From<io::Error> for ReproError {
+    fn from(error: io::Error) -> Self {
+        ReproError::Io(error)
+    }
+}
+
+impl ReproFile {
+    pub fn load(path: &Path) -> Result<Self, ReproError> {
+        let contents = fs::read_to_string(path)?;
+        let file: ReproFile = ron::from_str(&contents).map_err(ReproError::Parse)?;
+        if file.version != REPRO_VERSION {
+            return Err(ReproError::Version(file.version));
+        }
+        Ok(file)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ReproError> {
+        let contents = ron::ser::to_string(self).map_err(ReproError::Serialize)?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+}
+
+pub struct ReproPlugin;
+
+impl Plugin for ReproPlugin {
+    fn build(&self, app: &mut App) {
+        let args: Vec<String> = env::args().collect();
+        let flag_path = |flag: &str| {
+            args.iter()
+                .position(|arg| arg == flag)
+                .and_then(|index| args.get(index + 1))
+                .map(PathBuf::from)
+        };
+
+        if let Some(path) = flag_path(PLAY_FLAG) {
+            match ReproFile::load(&path) {
+                Ok(file) => {
+                    app.insert_resource(ReproPlayback {
+                        file,
+                        cursor: 0,
+                        printed: 0,
+                    })
+                    .add_systems(PostStartup, start_playback)
+                    .add_systems(First, drive_playback_time.before(bevy::time::TimeSystem))
+                    .add_systems(PreUpdate, drive_playback_input.after(sample_player_input))
+                    .add_systems(Last, advance_playback.run_if(in_state(AppState::Game)));
+                }
+                Err(error) => error!("Could not load {}: {}", path.display(), error),
+            }
+        } else if let Some(path) = flag_path(RECORD_FLAG) {
+            app.insert_resource(ReproRecorder {
+                path,
+                frames: Vec::new(),
+                finished: false,
+            })
+            .add_systems(Last, record_frame.run_if(in_state(AppState::Game)))
+            .add_systems(OnExit(AppState::Game), finish_recording);
+        }
+    }
+}
+
+#[derive(Resource)]
+struct ReproRecorder {
+    path: PathBuf,
+    frames: Vec<(f32, f32, f32, bool)>,
+    finished: bool,
+}
+
+#[derive(Resource)]
+pub struct ReproPlayback {
+    file: ReproFile,
+    cursor: usize,
+    printed: usize,
+}
+
+// Only the first run after launch is recorded, and only its first REPRO_DURATION seconds.
+fn record_frame(
+    mut recorder: ResMut<ReproRecorder>,
+    player_input: Res<PlayerInput>,
+    time: Res<Time<Virtual>>,
+    game_time: Res<GameTime>,
+    descriptor: Res<RunDescriptor>,
+    game_config: Res<GameConfig>,
+    save_data: Res<SaveData>,
+    checksums: Res<StateChecksums>,
+    window: Query<&Window, With<PrimaryWindow>>,
+) {
+    if recorder.finished {
+        return;
+    }
+
+    recorder.frames.push((
+        time.delta_seconds(),
+        player_input.direction.x,
+        player_input.direction.y,
+        player_input.dash,
+    ));
+
+    if game_time.elapsed_seconds() >= REPRO_DURATION {
+        write_recording(
+            &mut recorder,
+            &descriptor,
+            &game_config,
+            &save_data,
+            &checksums,
+            window.single(),
+        );
+    }
+}
+
+// A run that ends early is written out as far as it got.
+fn finish_recording(
+    mut recorder: ResMut<ReproRecorder>,
+    descriptor: Res<RunDescriptor>,
+    game_config: Res<GameConfig>,
+    save_data: Res<SaveData>,
+    checksums: Res<StateChecksums>,
+    window: Query<&Window, With<PrimaryWindow>>,
+) {
+    if !recorder.finished {
+        write_recording(
+            &mut recorder,
+            &descriptor,
+            &game_config,
+            &save_data,
+            &checksums,
+            window.single(),
+        );
+    }
+}
+
+fn write_recording(
+    recorder: &mut ReproRecorder,
+    descriptor: &RunDescriptor,
+    game_config: &GameConfig,
+    save_data: &SaveData,
+    checksums: &StateChecksums,
+    window: &Window,
+) {
+    recorder.finished = true;
+
+    let file = ReproFile {
+        version: REPRO_VERSION,
+        run: descriptor.to_string(),
+        config: game_config.clone(),
+        movement_feel: save_data.settings.movement_feel,
+        victory: save_data.settings.victory,
+        playfield: (window.width(), window.height()),
+        frames: std::mem::take(&mut recorder.frames),
+        checksums: checksums.recorded().to_vec(),
+    };
+
+    match file.save(&recorder.path) {
+        Ok(()) => info!("Wrote repro to {}", recorder.path.display()),
+        Err(error) => error!("Could not write {}: {}", recorder.path.display(), error),
+    }
+}
+
+// Sets up the recorded run and starts it. The save is left untouched while playing back.
+fn start_playback(
+    playback: Res<ReproPlayback>,
+    mut game_mode: ResMut<GameMode>,
+    mut game_config: ResMut<GameConfig>,
+    mut save_data: ResMut<SaveData>,
+    mut mutators: ResMut<ActiveMutators>,
+    mut queued_seed: ResMut<QueuedSeed>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut window: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let file = &playback.file;
+    let descriptor = match file.run.parse::<RunDescriptor>().map_err(ReproError::Run) {
+        Ok(descriptor) => descriptor,
+        Err(error) => {
+            error!("Could not play back the repro: {}", error);
+            return;
+        }
+    };
+
+    save_data.read_only = true;
+    save_data.settings.difficulty = descriptor.difficulty;
+    save_data.settings.movement_feel = file.movement_feel;
+    save_data.settings.victory = file.victory;
+    save_data.settings.pause_on_unfocus = false;
+
+    *game_mode = descriptor.mode;
+    *game_config = file.config.clone();
+    *mutators = ActiveMutators::default();
+    for mutator in descriptor.mutators {
+        mutators.toggle(mutator);
+    }
+    queued_seed.0 = Some(descriptor.seed);
+
+    let (width, height) = file.playfield;
+    window.single_mut().resolution.set(width, height);
+
+    next_state.set(AppState::Game);
+}
+
+fn drive_playback_time(playback: Res<ReproPlayback>, mut strategy: ResMut<TimeUpdateStrategy>) {
+    let delta = playback
+        .file
+        .frames
+        .get(playback.cursor)
+        .map_or(0.0, |(delta, ..)| *delta);
+    *strategy = TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(delta));
+}
+
+fn drive_playback_input(playback: Res<ReproPlayback>, mut player_input: ResMut<PlayerInput>) {
+    if let Some((_, x, y, dash)) = playback.file.frames.get(playback.cursor) {
+        player_input.direction = Vec3::new(*x, *y, 0.0);
+        player_input.dash = *dash;
+    }
+}
+
+fn advance_playback(
+    mut playback: ResMut<ReproPlayback>,
+    mut checksums: ResMut<StateChecksums>,
+    mut app_exit: EventWriter<AppExit>,
+) {
+    if checksums.expected().is_none() {
+        checksums.expect(playback.file.checksums.clone());
+    }
+
+    for (second, checksum) in checksums
+        .recorded()
+        .iter()
+        .enumerate()
+        .skip(playback.printed)
+    {
+        let verdict = match playback.file.checksums.get(second) {
+            Some(expected) if expected == checksum => "ok",
+            Some(_) => "DIVERGED",
+            None => "not recorded",
+        };
+        println!("second {:>2}: {:016x} {}", second + 1, checksum, verdict);
+    }
+    playback.printed = checksums.recorded().len();
+
+    playback.cursor += 1;
+    if playback.cursor >= playback.file.frames.len() {
+        println!(
+            "Repro finished: {}",
+            if checksums.diverged() {
+                "state diverged from the recording"
+            } else {
+                "matched the recording"
+            }
+        );
+        app_exit.send(AppExit);
+    }
+}
@@ -0,0 +1,60 @@
+use bevy::prelude::*;
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+// Every random draw a run makes comes from here, so a seed replays the same run. The systems that
+// draw are ordered, and any new one has to be slotted into this order rather than left to the
+// scheduler:
+//
+// 1. setup_game: the coin, then the player's spawn
+// 2. update_lunges
+// 3. hit_coin, then release_delayed_spawns
+// 4. spill_carried (Deposit)
+// 5. spawn_coin_patterns, then collect_pattern_coins
+// 6. spawn_pickups
+// 7. advance_tutorial (Tutorial)
+#[derive(Resource)]
+pub struct GameRng {
+    rng: StdRng,
+    seed: u64,
+    words: u64,
+}
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            seed,
+            words: 0,
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn words(&self) -> u64 {
+        self.words
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.words += 1;
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.words += 2;
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.words += dest.len().div_ceil(4) as u64;
+        self.rng.fill_bytes(dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
@@ -0,0 +1,256 @@
+use bevy::prelude::*;
+
+use crate::{
+    config::*,
+    game::{GameInfo, GameMode, ScoreText},
+    victory::RunWon,
+};
+
+// Everything that changes points, score or what's carried is sent as one of these, and
+// apply_score_events is the only system that writes them into GameInfo. Replaying the same events
+// through GameInfo::apply_score always lands on the same totals.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoreEvent {
+    // `points` toward the run's progress; `value` toward the score, or toward what's carried in
+    // Deposit.
+    CoinCollected { points: i32, value: i32 },
+    Bonus { reason: BonusReason, value: i32 },
+    // Taken from what's carried, or from the score when decayed.
+    Penalty { reason: PenaltyReason, value: i32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BonusReason {
+    // Pays `carried` coins out of what's carried. Coins picked up on the same frame stay carried.
+    Banked { carried: i32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PenaltyReason {
+    Spilled,
+    // Taken from the score instead, by the score decay mutator.
+    Decayed,
+}
+
+impl GameInfo {
+    pub(crate) fn apply_score(&mut self, event: ScoreEvent, carrying: bool) {
+        match event {
+            ScoreEvent::CoinCollected { points, value } => {
+                self.points += points;
+                if carrying {
+                    self.carried += value;
+                } else {
+                    self.score += value;
+                }
+            }
+            ScoreEvent::Bonus {
+                reason: BonusReason::Banked { carried },
+                value,
+            } => {
+                self.carried -= carried;
+                self.score += value;
+            }
+            ScoreEvent::Penalty {
+                reason: PenaltyReason::Spilled,
+                value,
+            } => {
+                self.carried -= value;
+            }
+            ScoreEvent::Penalty {
+                reason: PenaltyReason::Decayed,
+                value,
+            } => {
+                self.score -= value;
+            }
+        }
+    }
+}
+
+// Runs after every system that sends ScoreEvents. Once the run is over only penalties still land,
+// so a pickup on the frame of the fatal hit can't score posthumously.
+pub fn apply_score_events(
+    mut events: EventReader<ScoreEvent>,
+    mut game_info: ResMut<GameInfo>,
+    mut run_won: EventWriter<RunWon>,
+    game_mode: Res<GameMode>,
+    game_rules: Res<GameRules>,
+) {
+    if events.is_empty() {
+        return;
+    }
+
+    let previous_points = game_info.points;
+    let carrying = *game_mode == GameMode::Deposit;
+    for event in events.read() {
+        if game_info.run_over() && !matches!(event, ScoreEvent::Penalty { .. }) {
+            continue;
+        }
+        game_info.apply_score(*event, carrying);
+    }
+
+    if game_rules.victory && previous_points < VICTORY_POINTS && game_info.points >= VICTORY_POINTS
+    {
+        run_won.send_default();
+    }
+}
+
+pub fn update_score_text(
+    game_info: Res<GameInfo>,
+    mut score_text: Query<&mut Text, With<ScoreText>>,
+) {
+    if !game_info.is_changed() {
+        return;
+    }
+
+    for mut text in score_text.iter_mut() {
+        let score = game_info.score.to_string();
+        if text.sections[0].value != score {
+            text.sections[0].value = score;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Resource, Default)]
+    struct Recorded(Vec<ScoreEvent>);
+
+    fn record(mut events: EventReader<ScoreEvent>, mut recorded: ResMut<Recorded>) {
+        recorded.0.extend(events.read().copied());
+    }
+
+    #[derive(Resource, Default)]
+    struct Wins(usize);
+
+    fn count_wins(mut run_won: EventReader<RunWon>, mut wins: ResMut<Wins>) {
+        wins.0 += run_won.read().count();
+    }
+
+    fn score_app(game_mode: GameMode) -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_event::<ScoreEvent>()
+            .add_event::<RunWon>()
+            .init_resource::<GameInfo>()
+            .init_resource::<Recorded>()
+            .insert_resource(game_mode)
+            .insert_resource(GameRules {
+                victory: true,
+                ..default()
+            })
+            .add_systems(Update, (apply_score_events, record).chain());
+        app
+    }
+
+    fn coin(value: i32) -> ScoreEvent {
+        ScoreEvent::CoinCollected { points: 1, value }
+    }
+
+    fn totals(game_info: &GameInfo) -> (i32, i32, i32) {
+        (game_info.points, game_info.score, game_info.carried)
+    }
+
+    fn replay(events: &[ScoreEvent], carrying: bool) -> (i32, i32, i32) {
+        let mut game_info = GameInfo::default();
+        for event in events {
+            game_info.apply_score(*event, carrying);
+        }
+        totals(&game_info)
+    }
+
+    // Sends each frame's events and runs the frame.
+    fn play(app: &mut App, frames: &[&[ScoreEvent]]) {
+        for events in frames {
+            for event in *events {
+                app.world.send_event(*event);
+            }
+            app.update();
+        }
+    }
+
+    #[test]
+    fn replaying_a_classic_run_reproduces_the_score() {
+        let mut app = score_app(GameMode::Classic);
+        play(
+            &mut app,
+            &[
+                &[coin(1)],
+                &[],
+                &[coin(3), coin(2)],
+                &[ScoreEvent::Penalty {
+                    reason: PenaltyReason::Decayed,
+                    value: 2,
+                }],
+                &[coin(5)],
+            ],
+        );
+
+        let recorded = &app.world.resource::<Recorded>().0;
+        assert_eq!(recorded.len(), 5);
+        assert_eq!(totals(app.world.resource::<GameInfo>()), (4, 9, 0));
+        assert_eq!(replay(recorded, false), (4, 9, 0));
+    }
+
+    #[test]
+    fn replaying_a_deposit_run_reproduces_the_score() {
+        let mut app = score_app(GameMode::Deposit);
+        play(
+            &mut app,
+            &[
+                &[coin(1), coin(1)],
+                &[ScoreEvent::Penalty {
+                    reason: PenaltyReason::Spilled,
+                    value: 1,
+                }],
+                &[coin(1), coin(1)],
+                // The coin picked up on the banking frame stays carried.
+                &[
+                    ScoreEvent::Bonus {
+                        reason: BonusReason::Banked { carried: 3 },
+                        value: 6,
+                    },
+                    coin(1),
+                ],
+            ],
+        );
+
+        let recorded = &app.world.resource::<Recorded>().0;
+        assert_eq!(totals(app.world.resource::<GameInfo>()), (5, 6, 1));
+        assert_eq!(replay(recorded, true), (5, 6, 1));
+    }
+
+    #[test]
+    fn only_penalties_land_once_the_run_is_over() {
+        let mut app = score_app(GameMode::Classic);
+        play(&mut app, &[&[coin(4)]]);
+        app.world.resource_mut::<GameInfo>().health = 0;
+
+        play(
+            &mut app,
+            &[&[
+                coin(7),
+                ScoreEvent::Penalty {
+                    reason: PenaltyReason::Decayed,
+                    value: 1,
+                },
+            ]],
+        );
+
+        assert_eq!(totals(app.world.resource::<GameInfo>()), (1, 3, 0));
+    }
+
+    #[test]
+    fn crossing_the_victory_points_wins_once() {
+        let mut app = score_app(GameMode::Classic);
+        app.init_resource::<Wins>()
+            .add_systems(Update, count_wins.after(apply_score_events));
+
+        for _ in 0..VICTORY_POINTS + 3 {
+            play(&mut app, &[&[coin(1)]]);
+        }
+
+        assert_eq!(app.world.resource::<Wins>().0, 1);
+    }
+}
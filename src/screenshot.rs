@@ -0,0 +1,129 @@
+use bevy::prelude::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::pause::PauseState;
+
+pub const BEST_RUN_FILE_NAME: &str = "best.png";
+
+pub struct ScreenshotPlugin;
+
+impl Plugin for ScreenshotPlugin {
+    // There is no file system to keep the thumbnail in on the web.
+    fn build(&self, _app: &mut App) {
+        #[cfg(not(target_arch = "wasm32"))]
+        _app.add_systems(OnEnter(PauseState::GameOver), native::capture_best_run);
+    }
+}
+
+// The thumbnail of the best run's final frame, if one has been saved.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn best_run_thumbnail(images: &mut Assets<Image>) -> Option<Handle<Image>> {
+    native::load_thumbnail().map(|image| images.add(image))
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn best_run_thumbnail(_images: &mut Assets<Image>) -> Option<Handle<Image>> {
+    None
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use std::{error::Error, fs, path::Path};
+
+    use bevy::{prelude::*, render::view::screenshot::ScreenshotManager, window::PrimaryWindow};
+    use image::{DynamicImage, Rgba, RgbaImage};
+
+    use super::BEST_RUN_FILE_NAME;
+    use crate::{
+        config::*,
+        game::{GameInfo, GameMode},
+        mutator::ActiveMutators,
+        storage::{self, SaveData},
+    };
+
+    // Runs on the frame the player dies, before the spectate camera moves and long before
+    // cleanup despawns the playfield. The best score itself is only recorded at cleanup.
+    pub fn capture_best_run(
+        game_mode: Res<GameMode>,
+        mutators: Res<ActiveMutators>,
+        game_info: Res<GameInfo>,
+        save_data: Res<SaveData>,
+        window: Query<Entity, With<PrimaryWindow>>,
+        mut screenshots: ResMut<ScreenshotManager>,
+    ) {
+        let new_best = *game_mode == GameMode::Classic
+            && mutators.is_empty()
+            && save_data
+                .best_score
+                .is_none_or(|best| game_info.score > best);
+        if !new_best || save_data.read_only {
+            return;
+        }
+
+        let (Some(dir), Ok(window)) = (storage::save_dir(), window.get_single()) else {
+            return;
+        };
+
+        let captured = screenshots.take_screenshot(window, move |image| {
+            if let Err(error) = save_thumbnail(image, &dir) {
+                warn!("Could not save the best run thumbnail: {}", error);
+            }
+        });
+        if captured.is_err() {
+            warn!("Could not capture the best run: a screenshot is already pending");
+        }
+    }
+
+    fn save_thumbnail(image: Image, dir: &Path) -> Result<(), Box<dyn Error>> {
+        let frame = image.try_into_dynamic()?.to_rgba8();
+        let factor = (frame.width() / THUMBNAIL_WIDTH).max(1);
+
+        fs::create_dir_all(dir)?;
+        box_downscale(&frame, factor).save(dir.join(BEST_RUN_FILE_NAME))?;
+        Ok(())
+    }
+
+    // Averages each factor x factor block into one pixel. The frame's alpha is whatever the clear
+    // color left behind, so the thumbnail is made opaque.
+    fn box_downscale(source: &RgbaImage, factor: u32) -> RgbaImage {
+        let width = (source.width() / factor).max(1);
+        let height = (source.height() / factor).max(1);
+        let samples = factor * factor;
+
+        RgbaImage::from_fn(width, height, |x, y| {
+            let mut sum = [0u32; 3];
+            for dy in 0..factor {
+                for dx in 0..factor {
+                    let pixel = source.get_pixel(
+                        (x * factor + dx).min(source.width() - 1),
+                        (y * factor + dy).min(source.height() - 1),
+                    );
+                    for (total, channel) in sum.iter_mut().zip(pixel.0) {
+                        *total += channel as u32;
+                    }
+                }
+            }
+
+            let [r, g, b] = sum.map(|total| (total / samples) as u8);
+            Rgba([r, g, b, u8::MAX])
+        })
+    }
+
+    pub fn load_thumbnail() -> Option<Image> {
+        let path = storage::save_dir()?.join(BEST_RUN_FILE_NAME);
+        if !path.exists() {
+            return None;
+        }
+
+        match image::open(&path) {
+            Ok(thumbnail) => Some(Image::from_dynamic(
+                DynamicImage::ImageRgba8(thumbnail.to_rgba8()),
+                true,
+            )),
+            Err(error) => {
+                warn!("Could not load {}: {}", path.display(), error);
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use std::fmt;
+
+use bevy::{app::AppExit, prelude::*};
+use serde::Serialize;
+
+use crate::{
+    game::{GameInfo, RunStats},
+    storage,
+};
+
+#[derive(Resource, Serialize, Default)]
+pub struct SessionStats {
+    pub runs: u32,
+    pub coins: u32,
+    pub best_score: Option<i32>,
+    pub time_in_game: f32,
+    pub hits_taken: u32,
+}
+
+impl SessionStats {
+    pub fn record_run(&mut self, score: i32, run_stats: &RunStats) {
+        self.runs += 1;
+        self.coins += run_stats.coin_times.len() as u32;
+        self.best_score = Some(self.best_score.map_or(score, |best| best.max(score)));
+        self.time_in_game += run_stats.elapsed;
+        self.hits_taken += run_stats.hits;
+    }
+}
+
+impl fmt::Display for SessionStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} runs, {} coins, best score {}, {:.0}s in game, {} hits taken",
+            self.runs,
+            self.coins,
+            self.best_score.unwrap_or(0),
+            self.time_in_game,
+            self.hits_taken
+        )
+    }
+}
+
+pub fn flush_session(
+    mut app_exit: EventReader<AppExit>,
+    mut session_stats: ResMut<SessionStats>,
+    game_info: Option<Res<GameInfo>>,
+    run_stats: Option<Res<RunStats>>,
+) {
+    if app_exit.read().count() == 0 {
+        return;
+    }
+
+    if let (Some(game_info), Some(run_stats)) = (game_info, run_stats) {
+        session_stats.record_run(game_info.score(), &run_stats);
+    }
+
+    if session_stats.runs == 0 {
+        return;
+    }
+
+    info!("Session summary: {}", *session_stats);
+    if let Err(error) = storage::save_session(&session_stats) {
+        warn!("Could not write session summary: {}", error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(elapsed: f32, coins: usize, hits: u32) -> RunStats {
+        RunStats {
+            elapsed,
+            coin_times: vec![0.0; coins],
+            hits,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn empty_session_has_no_best_score() {
+        let session_stats = SessionStats::default();
+
+        assert_eq!(session_stats.runs, 0);
+        assert_eq!(session_stats.best_score, None);
+    }
+
+    #[test]
+    fn runs_accumulate_across_a_session() {
+        let mut session_stats = SessionStats::default();
+        session_stats.record_run(12, &run(30.0, 4, 3));
+        session_stats.record_run(-2, &run(5.5, 0, 3));
+        session_stats.record_run(7, &run(20.0, 2, 1));
+
+        assert_eq!(session_stats.runs, 3);
+        assert_eq!(session_stats.coins, 6);
+        assert_eq!(session_stats.best_score, Some(12));
+        assert_eq!(session_stats.time_in_game, 55.5);
+        assert_eq!(session_stats.hits_taken, 7);
+        assert_eq!(
+            session_stats.to_string(),
+            "3 runs, 6 coins, best score 12, 56s in game, 7 hits taken"
+        );
+    }
+
+    #[test]
+    fn negative_only_session_keeps_its_best() {
+        let mut session_stats = SessionStats::default();
+        session_stats.record_run(-5, &run(1.0, 0, 3));
+        session_stats.record_run(-3, &run(1.0, 0, 3));
+
+        assert_eq!(session_stats.best_score, Some(-3));
+    }
+}
@@ -0,0 +1,406 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    assets::AssetHandles,
+    companion::companion_unlocked,
+    config::*,
+    effects::text_label,
+    input_display,
+    menu::cleanup_menu,
+    menu_list::{spawn_menu_list, EntryKind, MenuEntry, MenuFocus, MenuList, MenuListEvent},
+    mutator::ActiveMutators,
+    pacing::bar_bundle,
+    player::{Action, InputBindings},
+    render_z,
+    stats::{expected_accel, expected_speed},
+    storage::{SaveData, SettingToggle},
+    theme::Arena,
+    tips::TipsSeen,
+    util::safe_half_extent,
+    AppState,
+};
+
+const SETTINGS_LIST: &str = "settings";
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Settings), setup_settings)
+            .add_systems(
+                Update,
+                (
+                    apply_settings,
+                    refresh_difficulty_preview,
+                    update_safe_area_overlay,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Settings)),
+            )
+            .add_systems(OnExit(AppState::Settings), cleanup_menu);
+    }
+}
+
+#[derive(Component)]
+struct DifficultyPreview;
+
+#[derive(Component)]
+struct SafeAreaOverlay;
+
+fn setup_settings(
+    mut commands: Commands,
+    asset_handles: Res<AssetHandles>,
+    save_data: Res<SaveData>,
+    bindings: Res<InputBindings>,
+    focus: Res<MenuFocus>,
+    game_config: Res<GameConfig>,
+    mutators: Res<ActiveMutators>,
+) {
+    commands.spawn(text_label(
+        &asset_handles,
+        "Settings",
+        80.0,
+        Color::DARK_GRAY,
+        Vec3::new(0.0, 260.0, render_z::BACKDROP),
+    ));
+
+    let settings = &save_data.settings;
+    let mut entries = vec![MenuEntry::new(
+        "difficulty",
+        "Difficulty",
+        EntryKind::Choice {
+            options: Difficulty::ALL.iter().map(Difficulty::name).collect(),
+            selected: Difficulty::ALL
+                .iter()
+                .position(|difficulty| *difficulty == settings.difficulty)
+                .unwrap_or(0),
+        },
+    )];
+    entries.push(settings.arena.menu_entry());
+    entries.extend(
+        SettingToggle::ALL
+            .iter()
+            .filter(|toggle| **toggle != SettingToggle::Companion || companion_unlocked(&save_data))
+            .map(|toggle| {
+                MenuEntry::new(
+                    toggle.name(),
+                    toggle.name(),
+                    EntryKind::Toggle(toggle.value(settings)),
+                )
+            }),
+    );
+    entries.push(settings.hud_layout.menu_entry());
+    entries.push(settings.input_display.menu_entry());
+    entries.push(input_display::opacity_menu_entry(
+        settings.input_display_opacity,
+    ));
+    entries.push(settings.movement_feel.menu_entry());
+    entries.push(MenuEntry::new(
+        "stick_deadzone",
+        "Stick dead zone",
+        EntryKind::Slider {
+            value: settings.stick_deadzone,
+            min: 0.0,
+            max: GAMEPAD_STICK_DEADZONE_MAX,
+            step: GAMEPAD_STICK_DEADZONE_STEP,
+        },
+    ));
+    entries.push(MenuEntry::new(
+        "stick_curve",
+        "Stick curve",
+        EntryKind::Slider {
+            value: settings.stick_curve_exponent,
+            min: GAMEPAD_STICK_CURVE_MIN,
+            max: GAMEPAD_STICK_CURVE_MAX,
+            step: GAMEPAD_STICK_CURVE_STEP,
+        },
+    ));
+    entries.push(settings.effects_quality.menu_entry());
+    entries.push(MenuEntry::new("controls", "Controls", EntryKind::Action));
+    entries.push(MenuEntry::new(
+        "reset_tips",
+        "Reset tips",
+        EntryKind::Action,
+    ));
+    entries.push(MenuEntry::new(
+        "volume",
+        "Volume",
+        EntryKind::Slider {
+            value: settings.volume,
+            min: 0.0,
+            max: 1.0,
+            step: 0.1,
+        },
+    ));
+    entries.push(MenuEntry::new(
+        "safe_area",
+        "Safe area",
+        EntryKind::Slider {
+            value: settings.safe_area,
+            min: 0.0,
+            max: SAFE_AREA_MAX,
+            step: SAFE_AREA_STEP,
+        },
+    ));
+
+    spawn_menu_list(
+        &mut commands,
+        &asset_handles,
+        &focus,
+        SETTINGS_LIST,
+        entries,
+        Vec3::new(0.0, 170.0, render_z::BACKDROP),
+    );
+
+    spawn_difficulty_preview(
+        &mut commands,
+        &asset_handles,
+        settings.difficulty,
+        settings.arena,
+        &game_config,
+        &mutators,
+    );
+
+    // One bar per edge; update_safe_area_overlay places them.
+    for _ in 0..4 {
+        commands.spawn((
+            SafeAreaOverlay,
+            ColorMesh2dBundle {
+                mesh: asset_handles.bar_mesh.clone().into(),
+                material: asset_handles.safe_area_material.clone(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+        ));
+    }
+
+    commands.spawn(text_label(
+        &asset_handles,
+        format!(
+            "Up/Down to browse, Left/Right to change, {} to return",
+            bindings.label(Action::Pause)
+        ),
+        28.0,
+        Color::DARK_GRAY,
+        Vec3::new(0.0, -300.0, render_z::BACKDROP),
+    ));
+}
+
+// Samples the same curves spawn_enemy rolls around, without the per-enemy deviation, with the
+// run config the selected difficulty, arena and mutators would produce.
+fn spawn_difficulty_preview(
+    commands: &mut Commands,
+    asset_handles: &AssetHandles,
+    difficulty: Difficulty,
+    arena: Arena,
+    game_config: &GameConfig,
+    mutators: &ActiveMutators,
+) {
+    let mut run_config = game_config.clone();
+    let mut game_rules = GameRules::for_difficulty(difficulty);
+    mutators.apply(&mut run_config, &mut game_rules);
+    arena.apply(&mut run_config, &mut game_rules);
+
+    let max_speed = ENEMY_MAX_SPEED * run_config.enemy_speed_multiplier;
+    for (index, points) in PREVIEW_POINTS.iter().enumerate() {
+        let base = PREVIEW_ORIGIN + Vec2::new(PREVIEW_SPACING * index as f32, 0.0);
+        let speed = expected_speed(*points) * run_config.enemy_speed_multiplier;
+        let accel = expected_accel(*points);
+
+        commands.spawn((
+            DifficultyPreview,
+            bar_bundle(
+                asset_handles,
+                asset_handles.enemy_material_red.clone(),
+                base - Vec2::new(PREVIEW_BAR_WIDTH / 2.0 + 1.0, 0.0),
+                PREVIEW_BAR_WIDTH,
+                speed / max_speed * PREVIEW_BAR_HEIGHT,
+            ),
+        ));
+        commands.spawn((
+            DifficultyPreview,
+            bar_bundle(
+                asset_handles,
+                asset_handles.enemy_material_purple.clone(),
+                base + Vec2::new(PREVIEW_BAR_WIDTH / 2.0 + 1.0, 0.0),
+                PREVIEW_BAR_WIDTH,
+                accel / ENEMY_MAX_ACCEL * PREVIEW_BAR_HEIGHT,
+            ),
+        ));
+        commands.spawn((
+            DifficultyPreview,
+            text_label(
+                asset_handles,
+                format!("{}pts\n{:.0}/{:.0}", points, speed, accel),
+                18.0,
+                Color::DARK_GRAY,
+                (base - Vec2::new(0.0, 24.0)).extend(render_z::BACKDROP),
+            ),
+        ));
+    }
+
+    commands.spawn((
+        DifficultyPreview,
+        text_label(
+            asset_handles,
+            format!(
+                "Enemy speed / accel{}",
+                if game_rules.post_hit_grace {
+                    ""
+                } else {
+                    ", no grace"
+                }
+            ),
+            20.0,
+            Color::DARK_GRAY,
+            (PREVIEW_ORIGIN + Vec2::new(PREVIEW_SPACING, PREVIEW_BAR_HEIGHT + 20.0))
+                .extend(render_z::BACKDROP),
+        ),
+    ));
+}
+
+fn refresh_difficulty_preview(
+    mut commands: Commands,
+    mut events: EventReader<MenuListEvent>,
+    asset_handles: Res<AssetHandles>,
+    save_data: Res<SaveData>,
+    game_config: Res<GameConfig>,
+    mutators: Res<ActiveMutators>,
+    preview: Query<Entity, With<DifficultyPreview>>,
+) {
+    let changed = events.read().any(|event| {
+        matches!(
+            event,
+            MenuListEvent::Changed {
+                list: SETTINGS_LIST,
+                entry: "difficulty" | "arena",
+                ..
+            }
+        )
+    });
+    if !changed {
+        return;
+    }
+
+    preview.iter().for_each(|entity| {
+        commands.entity(entity).despawn();
+    });
+    spawn_difficulty_preview(
+        &mut commands,
+        &asset_handles,
+        save_data.settings.difficulty,
+        save_data.settings.arena,
+        &game_config,
+        &mutators,
+    );
+}
+
+fn apply_settings(
+    mut events: EventReader<MenuListEvent>,
+    mut save_data: ResMut<SaveData>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    for event in events.read() {
+        match event {
+            MenuListEvent::Back {
+                list: SETTINGS_LIST,
+            } => next_state.set(AppState::Menu),
+            MenuListEvent::Activated {
+                list: SETTINGS_LIST,
+                entry: "controls",
+            } => next_state.set(AppState::Controls),
+            MenuListEvent::Activated {
+                list: SETTINGS_LIST,
+                entry: "reset_tips",
+            } => {
+                save_data.tip_runs = 0;
+                save_data.tips_seen = TipsSeen::default();
+            }
+            MenuListEvent::Changed {
+                list: SETTINGS_LIST,
+                entry,
+                kind,
+            } => match kind {
+                EntryKind::Choice { selected, .. } if *entry == "difficulty" => {
+                    save_data.settings.difficulty = Difficulty::ALL[*selected];
+                }
+                EntryKind::Choice { selected, .. } if *entry == "arena" => {
+                    save_data.settings.arena = Arena::ALL[*selected];
+                }
+                EntryKind::Toggle(value) => {
+                    if let Some(toggle) = SettingToggle::ALL
+                        .iter()
+                        .find(|toggle| toggle.name() == *entry)
+                    {
+                        toggle.set(&mut save_data.settings, *value);
+                    }
+                }
+                EntryKind::Slider { value, .. } if *entry == "volume" => {
+                    save_data.settings.volume = *value;
+                }
+                EntryKind::Slider { value, .. } if *entry == "safe_area" => {
+                    save_data.settings.safe_area = *value;
+                }
+                EntryKind::Slider { value, .. } if *entry == "stick_deadzone" => {
+                    save_data.settings.stick_deadzone = *value;
+                }
+                EntryKind::Slider { value, .. } if *entry == "stick_curve" => {
+                    save_data.settings.stick_curve_exponent = *value;
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+}
+
+// Outlines the rectangle the HUD and coins keep to, only while the safe area entry is focused.
+fn update_safe_area_overlay(
+    save_data: Res<SaveData>,
+    lists: Query<&MenuList>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    mut overlay: Query<(&mut Transform, &mut Visibility), With<SafeAreaOverlay>>,
+) {
+    let adjusting = lists
+        .iter()
+        .any(|list| list.id == SETTINGS_LIST && list.focused().id == "safe_area");
+    let Ok(window) = window.get_single() else {
+        return;
+    };
+    let half_extent = safe_half_extent(
+        Vec2::new(window.width(), window.height()) / 2.0,
+        save_data.settings.safe_area,
+    );
+    let thickness = SAFE_AREA_OVERLAY_THICKNESS;
+    let wanted = if adjusting {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+
+    let edges = [
+        (
+            Vec2::new(0.0, half_extent.y),
+            Vec2::new(half_extent.x * 2.0, thickness),
+        ),
+        (
+            Vec2::new(0.0, -half_extent.y),
+            Vec2::new(half_extent.x * 2.0, thickness),
+        ),
+        (
+            Vec2::new(-half_extent.x, 0.0),
+            Vec2::new(thickness, half_extent.y * 2.0),
+        ),
+        (
+            Vec2::new(half_extent.x, 0.0),
+            Vec2::new(thickness, half_extent.y * 2.0),
+        ),
+    ];
+    for ((mut transform, mut visibility), (center, size)) in overlay.iter_mut().zip(edges) {
+        *transform =
+            Transform::from_translation(center.extend(render_z::HUD)).with_scale(size.extend(1.0));
+        if *visibility != wanted {
+            *visibility = wanted;
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use bevy::prelude::*;
+
+use crate::{
+    assets::AssetHandles,
+    audio::AudioAvailable,
+    config::*,
+    effects::spawn_floating_text,
+    game_time::GameTime,
+    movement::Wraparound,
+    pickup::{HitPickup, Pickup, PickupKind},
+    player::Player,
+    tint::TintController,
+};
+
+// On the player while shrunk. Picking up another shrink only refreshes the timer, it never stacks.
+#[derive(Component)]
+pub struct SizeModifier {
+    timer: Timer,
+}
+
+impl SizeModifier {
+    fn new() -> Self {
+        Self {
+            timer: Timer::from_seconds(SHRINK_TIME, TimerMode::Once),
+        }
+    }
+
+    pub fn scale(&self) -> f32 {
+        SHRINK_SCALE
+    }
+
+    fn warning(&self) -> bool {
+        self.timer.remaining_secs() <= SHRINK_WARNING_TIME
+    }
+}
+
+// The player's radius for collisions, pickups and wraparound, shrunk while a SizeModifier is on.
+pub fn player_radius(run_config: &RunConfig, size: Option<&SizeModifier>) -> f32 {
+    run_config.player_radius * size.map_or(1.0, SizeModifier::scale)
+}
+
+// A rare pickup that shrinks the player for a while: easier to slip between enemies, but coins
+// have to be reached more precisely too.
+pub fn collect_shrink_pickups(
+    mut commands: Commands,
+    mut hit_pickup: EventReader<HitPickup>,
+    pickup_query: Query<&Transform, With<Pickup>>,
+    mut player_query: Query<(Entity, Option<&mut SizeModifier>), With<Player>>,
+    asset_handles: Res<AssetHandles>,
+    audio: Res<AudioAvailable>,
+) {
+    let hits: Vec<Entity> = hit_pickup
+        .read()
+        .filter(|hit| hit.kind == PickupKind::Shrink)
+        .map(|hit| hit.pickup)
+        .collect();
+    let Ok((player, mut size)) = player_query.get_single_mut() else {
+        return;
+    };
+
+    for pickup in hits {
+        let Ok(transform) = pickup_query.get(pickup) else {
+            continue;
+        };
+
+        commands.entity(pickup).despawn();
+        match size.as_mut() {
+            Some(size) => size.timer.reset(),
+            None => {
+                commands.entity(player).insert(SizeModifier::new());
+            }
+        }
+
+        if audio.0 {
+            commands.spawn(AudioBundle {
+                source: asset_handles.coin_sound.clone(),
+                settings: PlaybackSettings::DESPAWN.with_speed(SHRINK_PICKUP_PITCH),
+            });
+        }
+        spawn_floating_text(
+            &mut commands,
+            &asset_handles,
+            "Shrunk!",
+            transform.translation,
+            SHRINK_PICKUP_COLOR,
+        );
+    }
+}
+
+// Flashes the player for the last SHRINK_WARNING_TIME seconds, then lets them grow back.
+pub fn tick_size_modifier(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut SizeModifier, &mut TintController)>,
+    game_time: Res<GameTime>,
+) {
+    for (entity, mut size, mut tint) in query.iter_mut() {
+        if size.timer.tick(game_time.delta()).finished() {
+            tint.flash = None;
+            commands.entity(entity).remove::<SizeModifier>();
+            continue;
+        }
+
+        let flash = size.warning()
+            && ((size.timer.remaining_secs() / SHRINK_FLASH_INTERVAL) as u32).is_multiple_of(2);
+        let wanted = flash.then_some(SHRINK_PICKUP_COLOR);
+        if tint.flash != wanted {
+            tint.flash = wanted;
+        }
+    }
+}
+
+// The mesh and the wraparound edge follow the player's radius. Growing back happens here too, on
+// the frame after the SizeModifier is removed.
+pub fn apply_size_modifier(
+    mut query: Query<(&mut Transform, &mut Wraparound, Option<&SizeModifier>), With<Player>>,
+    run_config: Res<RunConfig>,
+) {
+    for (mut transform, mut wraparound, size) in query.iter_mut() {
+        let radius = player_radius(&run_config, size);
+        let scale = Vec3::splat(radius / PLAYER_RADIUS);
+        if transform.scale != scale {
+            transform.scale = scale;
+        }
+        if wraparound.radius != radius {
+            wraparound.radius = radius;
+        }
+    }
+}
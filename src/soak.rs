@@ -0,0 +1,125 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use bevy::{ecs::entity::Entities, prelude::*, text::FontAtlasSets};
+
+use crate::{
+    config::*,
+    game::{RunStats, StateChecksums},
+    trail::CoinTrail,
+    AppState,
+};
+
+// Watches for allocations that outlive the state they were made in. A couple of frames into every
+// state, once despawns and dropped assets have gone through, it counts entities, assets and the
+// longest per-run buffers, and warns about any count that went up on each of the last SOAK_WINDOW
+// entries of that state. Cheap enough to run in every build.
+pub struct SoakPlugin;
+
+impl Plugin for SoakPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SoakGuard>()
+            .add_systems(Last, track_soak);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SoakSnapshot {
+    pub state: AppState,
+    pub counts: Vec<(&'static str, usize)>,
+}
+
+#[derive(Resource, Default)]
+pub struct SoakGuard {
+    settling: Option<(AppState, u32)>,
+    history: HashMap<AppState, VecDeque<SoakSnapshot>>,
+    warned: HashSet<(AppState, &'static str)>,
+}
+
+impl SoakGuard {
+    pub fn latest(&self, state: AppState) -> Option<&SoakSnapshot> {
+        self.history.get(&state).and_then(VecDeque::back)
+    }
+
+    fn record(&mut self, snapshot: SoakSnapshot) {
+        let state = snapshot.state;
+        let history = self.history.entry(state).or_default();
+        history.push_back(snapshot);
+        if history.len() > SOAK_WINDOW + 1 {
+            history.pop_front();
+        }
+        if history.len() <= SOAK_WINDOW {
+            return;
+        }
+
+        for (index, (name, count)) in history[SOAK_WINDOW].counts.iter().enumerate() {
+            let growing = history
+                .iter()
+                .zip(history.iter().skip(1))
+                .all(|(before, after)| after.counts[index].1 > before.counts[index].1);
+            if growing && self.warned.insert((state, *name)) {
+                warn!(
+                    "{} grew on each of the last {} entries into {:?}, now {}",
+                    name, SOAK_WINDOW, state, count
+                );
+            }
+        }
+    }
+}
+
+fn track_soak(
+    mut guard: ResMut<SoakGuard>,
+    app_state: Res<State<AppState>>,
+    entities: &Entities,
+    meshes: Res<Assets<Mesh>>,
+    materials: Res<Assets<ColorMaterial>>,
+    images: Res<Assets<Image>>,
+    fonts: Res<Assets<Font>>,
+    font_atlases: Res<FontAtlasSets>,
+    audio_sources: Res<Assets<AudioSource>>,
+    sounds: Query<(), With<Handle<AudioSource>>>,
+    run_stats: Option<Res<RunStats>>,
+    checksums: Option<Res<StateChecksums>>,
+    trail: Option<Res<CoinTrail>>,
+) {
+    if app_state.is_changed() {
+        guard.settling = Some((*app_state.get(), 0));
+    }
+
+    let Some((state, frames)) = guard.settling else {
+        return;
+    };
+    if frames < SOAK_SETTLE_FRAMES {
+        guard.settling = Some((state, frames + 1));
+        return;
+    }
+    guard.settling = None;
+
+    // Every new glyph size or full atlas page adds an image that lives as long as the font does.
+    // That is a cache rather than a leak, so the glyph atlases are left out of the image count.
+    let glyph_atlases: usize = fonts
+        .ids()
+        .filter_map(|font| font_atlases.get(font))
+        .flat_map(|set| set.iter().map(|(_, atlases)| atlases.len()))
+        .sum();
+
+    guard.record(SoakSnapshot {
+        state,
+        counts: vec![
+            ("entities", entities.len() as usize),
+            ("sound entities", sounds.iter().count()),
+            ("meshes", meshes.len()),
+            ("materials", materials.len()),
+            ("images", images.len() - glyph_atlases),
+            ("audio sources", audio_sources.len()),
+            (
+                "coin times",
+                run_stats.map_or(0, |run_stats| run_stats.coin_times.len()),
+            ),
+            (
+                "checksums",
+                checksums.map_or(0, |checksums| checksums.recorded().len()),
+            ),
+            ("coin trail", trail.map_or(0, |trail| trail.len())),
+        ],
+    });
+}
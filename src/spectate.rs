@@ -0,0 +1,117 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    assets::AssetHandles,
+    config::*,
+    effects::{text_label, ScreenShake},
+    pause::PauseState,
+    player::{Action, InputBindings, PlayerInput},
+    render_z, AppState,
+};
+
+pub struct SpectatePlugin;
+
+impl Plugin for SpectatePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraMode>()
+            .add_systems(OnEnter(PauseState::GameOver), start_spectating)
+            .add_systems(Update, spectate.run_if(in_state(PauseState::GameOver)))
+            .add_systems(OnExit(PauseState::GameOver), stop_spectating);
+    }
+}
+
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CameraMode {
+    #[default]
+    Fixed,
+    Free,
+}
+
+#[derive(Resource)]
+struct SpectateTimer(Timer);
+
+// Text that stays put on screen while the camera pans, at this offset from the camera.
+#[derive(Component)]
+pub struct SpectateText(pub Vec2);
+
+fn start_spectating(
+    mut commands: Commands,
+    mut camera_mode: ResMut<CameraMode>,
+    mut screen_shake: Query<&mut ScreenShake>,
+    asset_handles: Res<AssetHandles>,
+    bindings: Res<InputBindings>,
+) {
+    *camera_mode = CameraMode::Free;
+    screen_shake
+        .iter_mut()
+        .for_each(|mut screen_shake| *screen_shake = ScreenShake::default());
+
+    commands.insert_resource(SpectateTimer(Timer::from_seconds(
+        SPECTATE_TIME,
+        TimerMode::Once,
+    )));
+
+    commands.spawn((
+        SpectateText(Vec2::ZERO),
+        text_label(
+            &asset_handles,
+            format!(
+                "Game over - look around, {} to continue",
+                bindings.label(Action::Start)
+            ),
+            36.0,
+            Color::WHITE,
+            Vec3::new(0.0, 0.0, render_z::HUD),
+        ),
+    ));
+}
+
+// The death freezes the playfield; the player pans the camera until the timer runs out or they
+// skip ahead.
+fn spectate(
+    mut timer: ResMut<SpectateTimer>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut camera: Query<&mut Transform, With<Camera2d>>,
+    mut text: Query<(&mut Transform, &SpectateText), Without<Camera2d>>,
+    player_input: Res<PlayerInput>,
+    bindings: Res<InputBindings>,
+    input: Res<Input<KeyCode>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    time: Res<Time>,
+) {
+    timer.0.tick(time.delta());
+    if timer.0.finished()
+        || bindings.just_pressed(Action::Start, &input)
+        || bindings.just_pressed(Action::Pause, &input)
+    {
+        next_state.set(AppState::Menu);
+        return;
+    }
+
+    let window = window.single();
+    let half_extent = Vec2::new(window.width(), window.height()) / 2.0;
+
+    let mut camera = camera.single_mut();
+    let position = (camera.translation.truncate()
+        + player_input.direction.truncate() * SPECTATE_PAN_SPEED * time.delta_seconds())
+    .clamp(-half_extent, half_extent);
+
+    camera.translation.x = position.x;
+    camera.translation.y = position.y;
+    for (mut transform, SpectateText(offset)) in text.iter_mut() {
+        transform.translation.x = position.x + offset.x;
+        transform.translation.y = position.y + offset.y;
+    }
+}
+
+fn stop_spectating(
+    mut camera_mode: ResMut<CameraMode>,
+    mut camera: Query<&mut Transform, With<Camera2d>>,
+) {
+    *camera_mode = CameraMode::Fixed;
+
+    for mut transform in camera.iter_mut() {
+        transform.translation.x = 0.0;
+        transform.translation.y = 0.0;
+    }
+}
@@ -0,0 +1,262 @@
+use std::collections::VecDeque;
+
+use bevy::{
+    prelude::*,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+    sprite::Mesh2dHandle,
+    window::PrimaryWindow,
+};
+
+use crate::{
+    assets::AssetHandles, config::*, game_time::GameTime, movement::Velocity, player::Player,
+    quality::EffectsLevel, render_z,
+};
+
+// Recent player positions and speeds, newest first, sampled at SPEED_RIBBON_SAMPLE_RATE. The
+// ribbon drawn from them is invisible at a crawl and bright at full speed, so it shows at a
+// glance whether a dash or a dodge will have the speed behind it.
+#[derive(Resource)]
+pub struct SpeedRibbon {
+    samples: VecDeque<(Vec2, f32)>,
+    timer: Timer,
+}
+
+impl Default for SpeedRibbon {
+    fn default() -> Self {
+        Self {
+            samples: VecDeque::with_capacity(SPEED_RIBBON_LENGTH),
+            timer: Timer::from_seconds(1.0 / SPEED_RIBBON_SAMPLE_RATE, TimerMode::Repeating),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct SpeedRibbonMarker;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RibbonPoint {
+    pub position: Vec2,
+    pub width: f32,
+    pub color: Color,
+}
+
+pub fn speed_ribbon_color(speed: f32) -> Color {
+    let t = ((speed - SPEED_RIBBON_MIN_SPEED) / (PLAYER_MAX_SPEED - SPEED_RIBBON_MIN_SPEED))
+        .clamp(0.0, 1.0);
+    SPEED_RIBBON_COLOR.with_a(SPEED_RIBBON_COLOR.a() * t)
+}
+
+// Builds the ribbon through the points, head first, as quads between neighbors. Two neighbors
+// further apart than max_step are taken as a wraparound and the ribbon is split there instead of
+// being stretched across the playfield. None when no piece has two points to span.
+pub fn ribbon_mesh(points: &[RibbonPoint], max_step: f32) -> Option<Mesh> {
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut colors: Vec<[f32; 4]> = Vec::new();
+    let mut indices: Vec<u32> = Vec::new();
+
+    let mut start = 0;
+    for end in 1..=points.len() {
+        let split = end == points.len()
+            || points[end].position.distance(points[end - 1].position) > max_step;
+        if !split {
+            continue;
+        }
+
+        let run = &points[start..end];
+        start = end;
+        if run.len() < 2 {
+            continue;
+        }
+
+        let first = positions.len() as u32;
+        for (index, point) in run.iter().enumerate() {
+            let previous = run[index.saturating_sub(1)].position;
+            let next = run[(index + 1).min(run.len() - 1)].position;
+            let side = (previous - next).normalize_or_zero().perp() * point.width / 2.0;
+
+            let color = point.color.as_linear_rgba_f32();
+            positions.push((point.position + side).extend(0.0).to_array());
+            positions.push((point.position - side).extend(0.0).to_array());
+            colors.extend([color, color]);
+        }
+        for segment in 0..run.len() as u32 - 1 {
+            let [left_a, right_a] = [first + segment * 2, first + segment * 2 + 1];
+            let [left_b, right_b] = [left_a + 2, right_a + 2];
+            indices.extend([left_a, right_a, right_b, left_a, right_b, left_b]);
+        }
+    }
+
+    if indices.is_empty() {
+        return None;
+    }
+
+    let vertices = positions.len();
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 0.0, 1.0]; vertices]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0, 0.0]; vertices]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    Some(mesh)
+}
+
+// Samples at a fixed rate, but rebuilds every frame with the player's current position as the
+// head, so the ribbon never lags behind the player.
+pub fn update_speed_ribbon(
+    mut commands: Commands,
+    mut ribbon: ResMut<SpeedRibbon>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut ribbon_query: Query<(&Mesh2dHandle, &mut Visibility), With<SpeedRibbonMarker>>,
+    player_query: Query<(&Transform, &Velocity), With<Player>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    effects_level: Res<EffectsLevel>,
+    game_time: Res<GameTime>,
+    asset_handles: Res<AssetHandles>,
+) {
+    let Ok((player_transform, velocity)) = player_query.get_single() else {
+        return;
+    };
+
+    let mesh = if effects_level.0.speed_ribbon() {
+        let head = (player_transform.translation.truncate(), velocity.0.length());
+        if ribbon.timer.tick(game_time.delta()).just_finished() {
+            ribbon.samples.push_front(head);
+            ribbon.samples.truncate(SPEED_RIBBON_LENGTH);
+        }
+
+        let count = ribbon.samples.len() + 1;
+        let points: Vec<RibbonPoint> = std::iter::once(head)
+            .chain(ribbon.samples.iter().copied())
+            .enumerate()
+            .map(|(age, (position, speed))| RibbonPoint {
+                position,
+                width: SPEED_RIBBON_WIDTH * (1.0 - age as f32 / count as f32),
+                color: speed_ribbon_color(speed),
+            })
+            .collect();
+
+        let window = window.single();
+        ribbon_mesh(&points, window.width().min(window.height()) / 2.0)
+    } else {
+        ribbon.samples.clear();
+        None
+    };
+
+    match (ribbon_query.get_single_mut(), mesh) {
+        (Ok((handle, mut visibility)), Some(mesh)) => {
+            if let Some(existing) = meshes.get_mut(&handle.0) {
+                *existing = mesh;
+            }
+            if *visibility != Visibility::Inherited {
+                *visibility = Visibility::Inherited;
+            }
+        }
+        (Ok((_, mut visibility)), None) => {
+            if *visibility != Visibility::Hidden {
+                *visibility = Visibility::Hidden;
+            }
+        }
+        (Err(_), Some(mesh)) => {
+            commands.spawn((
+                SpeedRibbonMarker,
+                ColorMesh2dBundle {
+                    mesh: meshes.add(mesh).into(),
+                    material: asset_handles.speed_ribbon_material.clone(),
+                    transform: Transform::from_xyz(0.0, 0.0, render_z::TRAIL),
+                    ..default()
+                },
+            ));
+        }
+        (Err(_), None) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::mesh::VertexAttributeValues;
+
+    use super::*;
+
+    fn points(positions: &[(f32, f32)]) -> Vec<RibbonPoint> {
+        positions
+            .iter()
+            .map(|&(x, y)| RibbonPoint {
+                position: Vec2::new(x, y),
+                width: 4.0,
+                color: Color::CYAN,
+            })
+            .collect()
+    }
+
+    fn positions(mesh: &Mesh) -> Vec<[f32; 3]> {
+        match mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
+            Some(VertexAttributeValues::Float32x3(positions)) => positions.clone(),
+            _ => panic!("ribbon mesh has no positions"),
+        }
+    }
+
+    fn triangles(mesh: &Mesh) -> usize {
+        mesh.indices().map_or(0, |indices| indices.len() / 3)
+    }
+
+    #[test]
+    fn straight_run_is_two_triangles_per_segment() {
+        let mesh = ribbon_mesh(&points(&[(0.0, 0.0), (10.0, 0.0), (20.0, 0.0)]), 50.0).unwrap();
+
+        assert_eq!(triangles(&mesh), 4);
+        assert_eq!(
+            positions(&mesh),
+            [
+                [0.0, -2.0, 0.0],
+                [0.0, 2.0, 0.0],
+                [10.0, -2.0, 0.0],
+                [10.0, 2.0, 0.0],
+                [20.0, -2.0, 0.0],
+                [20.0, 2.0, 0.0],
+            ]
+        );
+    }
+
+    #[test]
+    fn width_follows_each_point() {
+        let mut tapered = points(&[(0.0, 0.0), (0.0, 10.0)]);
+        tapered[1].width = 0.0;
+
+        let positions = positions(&ribbon_mesh(&tapered, 50.0).unwrap());
+
+        assert_eq!(positions[0], [2.0, 0.0, 0.0]);
+        assert_eq!(positions[2], positions[3]);
+    }
+
+    #[test]
+    fn wraparound_splits_the_ribbon() {
+        let mesh = ribbon_mesh(
+            &points(&[(390.0, 0.0), (398.0, 0.0), (-396.0, 0.0), (-388.0, 0.0)]),
+            50.0,
+        )
+        .unwrap();
+
+        // Two pieces of one segment each, with nothing spanning the playfield between them.
+        assert_eq!(triangles(&mesh), 4);
+        let indices: Vec<usize> = mesh.indices().unwrap().iter().collect();
+        assert!(indices[..6].iter().all(|&index| index < 4));
+        assert!(indices[6..].iter().all(|&index| index >= 4));
+    }
+
+    #[test]
+    fn lone_points_draw_nothing() {
+        assert!(ribbon_mesh(&points(&[]), 50.0).is_none());
+        assert!(ribbon_mesh(&points(&[(0.0, 0.0)]), 50.0).is_none());
+        assert!(ribbon_mesh(&points(&[(0.0, 0.0), (300.0, 0.0)]), 50.0).is_none());
+    }
+
+    #[test]
+    fn color_fades_out_below_the_minimum_speed() {
+        assert_eq!(speed_ribbon_color(0.0).a(), 0.0);
+        assert_eq!(speed_ribbon_color(SPEED_RIBBON_MIN_SPEED).a(), 0.0);
+        assert_eq!(
+            speed_ribbon_color(PLAYER_MAX_SPEED * 2.0),
+            SPEED_RIBBON_COLOR
+        );
+    }
+}
@@ -0,0 +1,99 @@
+use std::f32::consts::E;
+
+use bevy::prelude::*;
+
+use crate::{config::*, util::lerp};
+
+pub fn speed_curve(points: i32) -> f32 {
+    1.0 / (1.0 + E.powf(-SPEED_GROWTH_RATE * (points as f32 - SPEED_MIDPOINT)))
+}
+
+pub fn accel_curve(points: i32) -> f32 {
+    1.0 / (1.0 + E.powf(-ACCEL_GROWTH_RATE * (points as f32 - ACCEL_MIDPOINT)))
+}
+
+pub fn expected_speed(points: i32) -> f32 {
+    speed_curve(points) * (ENEMY_MAX_SPEED - ENEMY_MIN_SPEED) + ENEMY_MIN_SPEED
+}
+
+pub fn expected_accel(points: i32) -> f32 {
+    accel_curve(points) * (ENEMY_MAX_ACCEL - ENEMY_MIN_ACCEL) + ENEMY_MIN_ACCEL
+}
+
+pub fn threat_score(points: i32, speed: f32, accel: f32, future_prediction: f32) -> f32 {
+    let speed_threat = ((speed - expected_speed(points)) / SPEED_MAX_DEVIATION + 1.0) / 2.0;
+    let accel_threat = ((accel - expected_accel(points)) / ACCEL_MAX_DEVIATION + 1.0) / 2.0;
+
+    ((speed_threat + accel_threat + future_prediction) / 3.0).clamp(0.0, 1.0)
+}
+
+pub fn threat_tint(base: Color, threat: f32) -> Color {
+    let amount = threat * ENEMY_THREAT_TINT;
+
+    Color::rgba(
+        lerp(base.r(), ENEMY_THREAT_COLOR.r(), amount),
+        lerp(base.g(), ENEMY_THREAT_COLOR.g(), amount),
+        lerp(base.b(), ENEMY_THREAT_COLOR.b(), amount),
+        base.a(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POINTS: i32 = 20;
+
+    #[test]
+    fn average_roll_is_middling_threat() {
+        let threat = threat_score(POINTS, expected_speed(POINTS), expected_accel(POINTS), 0.5);
+
+        assert!((threat - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn faster_rolls_are_more_threatening() {
+        let speed = expected_speed(POINTS);
+        let accel = expected_accel(POINTS);
+
+        assert!(
+            threat_score(POINTS, speed + SPEED_MAX_DEVIATION / 2.0, accel, 0.5)
+                > threat_score(POINTS, speed, accel, 0.5)
+        );
+        assert!(
+            threat_score(POINTS, speed, accel + ACCEL_MAX_DEVIATION / 2.0, 0.5)
+                > threat_score(POINTS, speed, accel, 0.5)
+        );
+        assert!(threat_score(POINTS, speed, accel, 1.0) > threat_score(POINTS, speed, accel, 0.0));
+    }
+
+    #[test]
+    fn threat_is_relative_to_the_point_level() {
+        let speed = expected_speed(0);
+        let accel = expected_accel(0);
+
+        assert!(threat_score(0, speed, accel, 0.5) > threat_score(60, speed, accel, 0.5));
+    }
+
+    #[test]
+    fn threat_stays_normalized() {
+        let low = threat_score(POINTS, 0.0, 0.0, 0.0);
+        let high = threat_score(POINTS, 10_000.0, 10_000.0, 1.0);
+
+        assert_eq!(low, 0.0);
+        assert_eq!(high, 1.0);
+    }
+
+    #[test]
+    fn tint_moves_toward_the_threat_color() {
+        let base = Color::rgba(0.2, 0.4, 0.6, 0.8);
+
+        assert_eq!(threat_tint(base, 0.0), base);
+
+        let tinted = threat_tint(base, 1.0);
+        assert_eq!(tinted.a(), base.a());
+        assert!(
+            (tinted.r() - ENEMY_THREAT_COLOR.r()).abs() < (base.r() - ENEMY_THREAT_COLOR.r()).abs()
+        );
+    }
+}
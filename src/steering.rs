@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::{
+    config::*,
+    util::{vec3_move_toward, wraparound_tracking_position},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BehaviorId(pub &'static str);
+
+impl BehaviorId {
+    pub const RED: BehaviorId = BehaviorId("red");
+    pub const PURPLE: BehaviorId = BehaviorId("purple");
+}
+
+pub struct SteerContext {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub speed: f32,
+    pub accel: f32,
+    pub future_prediction: f32,
+    pub coin_pull: f32,
+    pub player_position: Vec3,
+    pub player_velocity: Vec3,
+    pub coin_position: Vec3,
+    pub playfield: Vec2,
+    pub dt: f32,
+}
+
+impl SteerContext {
+    pub fn predicted_player_position(&self) -> Vec3 {
+        self.player_position + self.player_velocity * self.future_prediction
+    }
+
+    // The coin's pull is blended into the heading before the speed clamp, so it bends an enemy's
+    // path without ever pushing it past its speed.
+    pub fn steer_toward(&self, direction: Vec3) -> Vec3 {
+        let coin_direction = (self.coin_position - self.position).normalize_or_zero();
+        let weight = (self.coin_pull * ENEMY_COIN_PULL).clamp(-1.0, 1.0);
+        let heading =
+            (direction * (1.0 - weight.abs()) + coin_direction * weight).clamp_length_max(1.0);
+
+        vec3_move_toward(self.velocity, heading * self.speed, self.accel * self.dt)
+    }
+}
+
+pub trait SteeringBehavior: Send + Sync {
+    fn desired_velocity(&self, ctx: &SteerContext) -> Vec3;
+}
+
+pub struct Chase;
+
+impl SteeringBehavior for Chase {
+    fn desired_velocity(&self, ctx: &SteerContext) -> Vec3 {
+        let direction = (ctx.predicted_player_position() - ctx.position).normalize_or_zero();
+        ctx.steer_toward(direction)
+    }
+}
+
+pub struct EdgeChase;
+
+impl SteeringBehavior for EdgeChase {
+    fn desired_velocity(&self, ctx: &SteerContext) -> Vec3 {
+        let track_position = wraparound_tracking_position(
+            ctx.position,
+            ctx.predicted_player_position(),
+            ctx.playfield.x,
+            ctx.playfield.y,
+        );
+        let direction = (track_position - ctx.position).normalize_or_zero();
+        ctx.steer_toward(direction)
+    }
+}
+
+#[derive(Resource)]
+pub struct SteeringRegistry {
+    behaviors: HashMap<BehaviorId, Box<dyn SteeringBehavior>>,
+}
+
+impl SteeringRegistry {
+    pub fn register(&mut self, id: BehaviorId, behavior: impl SteeringBehavior + 'static) {
+        self.behaviors.insert(id, Box::new(behavior));
+    }
+
+    pub fn get(&self, id: BehaviorId) -> Option<&dyn SteeringBehavior> {
+        self.behaviors.get(&id).map(|behavior| behavior.as_ref())
+    }
+}
+
+impl Default for SteeringRegistry {
+    fn default() -> Self {
+        let mut registry = Self {
+            behaviors: HashMap::new(),
+        };
+        registry.register(BehaviorId::RED, Chase);
+        registry.register(BehaviorId::PURPLE, EdgeChase);
+        registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // An enemy at the origin that can reach any velocity within a single step.
+    fn context(player_position: Vec3) -> SteerContext {
+        SteerContext {
+            position: Vec3::ZERO,
+            velocity: Vec3::ZERO,
+            speed: 200.0,
+            accel: 1.0e6,
+            future_prediction: 0.0,
+            coin_pull: 0.0,
+            player_position,
+            player_velocity: Vec3::ZERO,
+            coin_position: Vec3::ZERO,
+            playfield: Vec2::new(800.0, 600.0),
+            dt: 1.0,
+        }
+    }
+
+    #[test]
+    fn chase_heads_straight_for_the_player_at_full_speed() {
+        let velocity = Chase.desired_velocity(&context(Vec3::new(0.0, 150.0, 0.0)));
+
+        assert!(velocity.abs_diff_eq(Vec3::new(0.0, 200.0, 0.0), 1e-3));
+    }
+
+    #[test]
+    fn chase_leads_a_moving_player() {
+        let mut ctx = context(Vec3::new(100.0, 0.0, 0.0));
+        ctx.player_velocity = Vec3::new(0.0, 100.0, 0.0);
+        ctx.future_prediction = 1.0;
+
+        let velocity = Chase.desired_velocity(&ctx);
+
+        assert!(velocity.abs_diff_eq(Vec3::new(1.0, 1.0, 0.0).normalize() * 200.0, 1e-3));
+    }
+
+    #[test]
+    fn chase_is_limited_by_accel() {
+        let mut ctx = context(Vec3::new(100.0, 0.0, 0.0));
+        ctx.accel = 50.0;
+        ctx.dt = 0.1;
+
+        let velocity = Chase.desired_velocity(&ctx);
+
+        assert!(velocity.abs_diff_eq(Vec3::new(5.0, 0.0, 0.0), 1e-3));
+    }
+
+    #[test]
+    fn edge_chase_takes_the_short_way_across_the_seam() {
+        let mut ctx = context(Vec3::new(380.0, 0.0, 0.0));
+        ctx.position = Vec3::new(-380.0, 0.0, 0.0);
+
+        assert!(EdgeChase.desired_velocity(&ctx).x < 0.0);
+        assert!(Chase.desired_velocity(&ctx).x > 0.0);
+    }
+
+    #[test]
+    fn edge_chase_matches_chase_away_from_the_edges() {
+        let ctx = context(Vec3::new(50.0, -80.0, 0.0));
+
+        assert_eq!(
+            EdgeChase.desired_velocity(&ctx),
+            Chase.desired_velocity(&ctx)
+        );
+    }
+
+    #[test]
+    fn registry_keeps_the_built_ins_when_a_behavior_is_added() {
+        const CUSTOM: BehaviorId = BehaviorId("custom");
+        struct Stand;
+
+        impl SteeringBehavior for Stand {
+            fn desired_velocity(&self, _: &SteerContext) -> Vec3 {
+                Vec3::ZERO
+            }
+        }
+
+        let mut registry = SteeringRegistry::default();
+        assert!(registry.get(CUSTOM).is_none());
+
+        registry.register(CUSTOM, Stand);
+        let ctx = context(Vec3::new(0.0, 150.0, 0.0));
+
+        assert_eq!(
+            registry.get(CUSTOM).unwrap().desired_velocity(&ctx),
+            Vec3::ZERO
+        );
+        assert_eq!(
+            registry
+                .get(BehaviorId::RED)
+                .unwrap()
+                .desired_velocity(&ctx),
+            Chase.desired_velocity(&ctx)
+        );
+        assert!(registry.get(BehaviorId::PURPLE).is_some());
+    }
+
+    #[test]
+    fn coin_pull_never_pushes_past_the_speed_stat() {
+        for coin_pull in [-1000.0, -5.0, -1.0, 0.0, 1.0, 5.0, 1000.0] {
+            let mut ctx = context(Vec3::new(0.0, 150.0, 0.0));
+            ctx.coin_pull = coin_pull;
+            ctx.coin_position = Vec3::new(-150.0, -20.0, 0.0);
+
+            for _ in 0..10 {
+                ctx.velocity = Chase.desired_velocity(&ctx);
+                assert!(ctx.velocity.length() <= ctx.speed + 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn coin_pull_bends_the_heading_toward_the_coin() {
+        let mut ctx = context(Vec3::new(0.0, 150.0, 0.0));
+        ctx.coin_position = Vec3::new(150.0, 0.0, 0.0);
+
+        ctx.coin_pull = 1.0;
+        assert!(Chase.desired_velocity(&ctx).x > 0.0);
+
+        ctx.coin_pull = -1.0;
+        assert!(Chase.desired_velocity(&ctx).x < 0.0);
+    }
+}
@@ -0,0 +1,646 @@
+use std::{
+    collections::HashMap,
+    env, fmt,
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::{app::AppExit, prelude::*};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{Difficulty, GAMEPAD_STICK_CURVE, GAMEPAD_STICK_DEADZONE, RECORDS_PER_MODE},
+    game::GameMode,
+    hud::HudLayout,
+    input_display::InputDisplay,
+    migrations::{self, LegacyBindings, SAVE_VERSION},
+    player::MovementFeel,
+    quality::EffectsQuality,
+    session::SessionStats,
+    theme::Arena,
+    tips::TipsSeen,
+    EnemyType, InputBindings,
+};
+
+const SAVE_FILE_NAME: &str = "save.ron";
+const BACKUP_FILE_NAME: &str = "save.ron.bak";
+const TEMP_FILE_NAME: &str = "save.ron.tmp";
+// Where a save that can't be read, backup and all, is moved before defaults take its place.
+const BROKEN_FILE_NAME: &str = "save.ron.broken";
+const SESSION_FILE_NAME: &str = "session.ron";
+
+const AUTOSAVE_INTERVAL: f32 = 1.0;
+
+#[derive(Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SaveData {
+    pub version: u32,
+    pub codex: HashMap<EnemyType, u32>,
+    pub codex_removed: HashMap<EnemyType, u32>,
+    // Single-key bindings from version 2 saves, only read so they can be migrated.
+    #[serde(skip_serializing)]
+    pub bindings: LegacyBindings,
+    // Best plain Classic score: no mutators, any movement feel.
+    pub best_score: Option<i32>,
+    pub zen_best: Option<i32>,
+    pub zen_best_feel: MovementFeel,
+    pub mutator_bests: HashMap<String, i32>,
+    pub records: HashMap<GameMode, Vec<RunRecord>>,
+    pub tip_runs: u32,
+    pub tips_seen: TipsSeen,
+    pub completed: bool,
+    pub fastest_win: Option<f32>,
+    pub tutorial_done: bool,
+    // The build whose changelog notes were last shown; see changelog.rs.
+    pub last_version: Option<String>,
+    pub settings: Settings,
+    // Set when the file came from a newer build, so it is never overwritten by this one.
+    #[serde(skip)]
+    pub read_only: bool,
+}
+
+impl Default for SaveData {
+    fn default() -> Self {
+        Self {
+            version: SAVE_VERSION,
+            codex: HashMap::default(),
+            codex_removed: HashMap::default(),
+            bindings: LegacyBindings::default(),
+            best_score: None,
+            zen_best: None,
+            zen_best_feel: MovementFeel::default(),
+            mutator_bests: HashMap::default(),
+            records: HashMap::default(),
+            tip_runs: 0,
+            tips_seen: TipsSeen::default(),
+            completed: false,
+            fastest_win: None,
+            tutorial_done: false,
+            last_version: None,
+            settings: Settings::default(),
+            read_only: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub score: i32,
+    pub time: f32,
+    pub difficulty: Difficulty,
+    pub feel: MovementFeel,
+    // Records from before arenas existed were all played in the classic one.
+    #[serde(default)]
+    pub arena: Arena,
+    // Days since the Unix epoch, in UTC.
+    pub day: u64,
+    // Played with the score decay mutator, which changes what a score means.
+    #[serde(default)]
+    pub score_decay: bool,
+}
+
+pub fn current_day() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs() / 86_400)
+}
+
+// Keeps records sorted best first, a longer survival time breaking score ties, and returns the
+// new record's rank if it made the cut.
+pub fn insert_record(records: &mut Vec<RunRecord>, record: RunRecord) -> Option<usize> {
+    let rank = records
+        .iter()
+        .position(|other| {
+            record.score > other.score || (record.score == other.score && record.time > other.time)
+        })
+        .unwrap_or(records.len());
+
+    records.insert(rank, record);
+    records.truncate(RECORDS_PER_MODE);
+
+    (rank < RECORDS_PER_MODE).then_some(rank)
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub difficulty: Difficulty,
+    pub arena: Arena,
+    pub coin_trail: bool,
+    pub coin_arrow: bool,
+    pub raw_dash_input: bool,
+    pub volume: f32,
+    pub hud_layout: HudLayout,
+    pub movement_feel: MovementFeel,
+    pub tips: bool,
+    pub victory: bool,
+    pub pause_on_unfocus: bool,
+    pub haptics: bool,
+    pub deterministic: bool,
+    pub reduce_flashing: bool,
+    // Only used once the orb is unlocked; see companion_unlocked.
+    pub companion: bool,
+    // Always shown when there is no audio, since the music is otherwise the only health cue.
+    pub health_pips: bool,
+    pub effects_quality: EffectsQuality,
+    // Percent of each window dimension kept clear on every side.
+    pub safe_area: f32,
+    pub input_display: InputDisplay,
+    pub input_display_opacity: f32,
+    pub stick_deadzone: f32,
+    pub stick_curve_exponent: f32,
+    // Bindings live in bindings.ron now. These are only read, to seed that file from older saves.
+    #[serde(skip_serializing)]
+    pub bindings: InputBindings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            difficulty: Difficulty::default(),
+            arena: Arena::default(),
+            coin_trail: false,
+            coin_arrow: false,
+            raw_dash_input: false,
+            volume: 1.0,
+            hud_layout: HudLayout::default(),
+            movement_feel: MovementFeel::default(),
+            tips: true,
+            victory: false,
+            pause_on_unfocus: true,
+            haptics: true,
+            deterministic: false,
+            reduce_flashing: false,
+            companion: true,
+            health_pips: false,
+            effects_quality: EffectsQuality::default(),
+            safe_area: 0.0,
+            input_display: InputDisplay::default(),
+            input_display_opacity: 0.8,
+            stick_deadzone: GAMEPAD_STICK_DEADZONE,
+            stick_curve_exponent: GAMEPAD_STICK_CURVE,
+            bindings: InputBindings::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingToggle {
+    CoinTrail,
+    CoinArrow,
+    RawDashInput,
+    Tips,
+    Victory,
+    PauseOnUnfocus,
+    Haptics,
+    Deterministic,
+    ReduceFlashing,
+    Companion,
+    HealthPips,
+}
+
+impl SettingToggle {
+    pub const ALL: [SettingToggle; 11] = [
+        SettingToggle::CoinTrail,
+        SettingToggle::CoinArrow,
+        SettingToggle::RawDashInput,
+        SettingToggle::Tips,
+        SettingToggle::Victory,
+        SettingToggle::PauseOnUnfocus,
+        SettingToggle::Haptics,
+        SettingToggle::Deterministic,
+        SettingToggle::ReduceFlashing,
+        SettingToggle::Companion,
+        SettingToggle::HealthPips,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SettingToggle::CoinTrail => "Coin trail",
+            SettingToggle::CoinArrow => "Coin arrow",
+            SettingToggle::RawDashInput => "Raw dash input",
+            SettingToggle::Tips => "Tips",
+            SettingToggle::Victory => "Victory goal",
+            SettingToggle::PauseOnUnfocus => "Pause when unfocused",
+            SettingToggle::Haptics => "Rumble",
+            SettingToggle::Deterministic => "Deterministic seeded runs",
+            SettingToggle::ReduceFlashing => "Reduce flashing",
+            SettingToggle::Companion => "Companion orb",
+            SettingToggle::HealthPips => "Health pips",
+        }
+    }
+
+    pub fn value(&self, settings: &Settings) -> bool {
+        match self {
+            SettingToggle::CoinTrail => settings.coin_trail,
+            SettingToggle::CoinArrow => settings.coin_arrow,
+            SettingToggle::RawDashInput => settings.raw_dash_input,
+            SettingToggle::Tips => settings.tips,
+            SettingToggle::Victory => settings.victory,
+            SettingToggle::PauseOnUnfocus => settings.pause_on_unfocus,
+            SettingToggle::Haptics => settings.haptics,
+            SettingToggle::Deterministic => settings.deterministic,
+            SettingToggle::ReduceFlashing => settings.reduce_flashing,
+            SettingToggle::Companion => settings.companion,
+            SettingToggle::HealthPips => settings.health_pips,
+        }
+    }
+
+    pub fn set(&self, settings: &mut Settings, value: bool) {
+        match self {
+            SettingToggle::CoinTrail => settings.coin_trail = value,
+            SettingToggle::CoinArrow => settings.coin_arrow = value,
+            SettingToggle::RawDashInput => settings.raw_dash_input = value,
+            SettingToggle::Tips => settings.tips = value,
+            SettingToggle::Victory => settings.victory = value,
+            SettingToggle::PauseOnUnfocus => settings.pause_on_unfocus = value,
+            SettingToggle::Haptics => settings.haptics = value,
+            SettingToggle::Deterministic => settings.deterministic = value,
+            SettingToggle::ReduceFlashing => settings.reduce_flashing = value,
+            SettingToggle::Companion => settings.companion = value,
+            SettingToggle::HealthPips => settings.health_pips = value,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum StorageError {
+    NoSaveDir,
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StorageError::NoSaveDir => write!(f, "no save directory available"),
+            StorageError::Io(error) => write!(f, "{}", error),
+            StorageError::Parse(error) => write!(f, "{}", error),
+            StorageError::Serialize(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+impl From<io::Error> for StorageError {
+    fn from(error: io::Error) -> Self {
+        StorageError::Io(error)
+    }
+}
+
+pub struct Autosave {
+    dirty: bool,
+    since_write: f32,
+}
+
+impl Default for Autosave {
+    fn default() -> Self {
+        Self {
+            dirty: false,
+            since_write: AUTOSAVE_INTERVAL,
+        }
+    }
+}
+
+// Never fails: without a readable save the game starts from defaults. The unreadable file is moved
+// aside first so the first autosave can't write over the player's only copy. If it can't be moved,
+// the defaults are never saved.
+pub fn load_or_default() -> SaveData {
+    let Some(dir) = save_dir() else {
+        warn!("Could not load save data: {}", StorageError::NoSaveDir);
+        return SaveData::default();
+    };
+    load_or_default_from(&dir)
+}
+
+pub fn load_or_default_from(dir: &Path) -> SaveData {
+    let error = match load_from(dir) {
+        Ok(save_data) => return save_data,
+        Err(error) => error,
+    };
+    warn!("Could not load save data: {}", error);
+
+    let primary = dir.join(SAVE_FILE_NAME);
+    if !primary.exists() {
+        return SaveData::default();
+    }
+    let broken = dir.join(BROKEN_FILE_NAME);
+    match fs::rename(&primary, &broken) {
+        Ok(()) => {
+            warn!("Moved {} to {}", primary.display(), broken.display());
+            SaveData::default()
+        }
+        Err(error) => {
+            warn!(
+                "Could not move {} aside: {}, so nothing will be saved",
+                primary.display(),
+                error
+            );
+            SaveData {
+                read_only: true,
+                ..default()
+            }
+        }
+    }
+}
+
+pub fn save(save_data: &SaveData) -> Result<(), StorageError> {
+    save_to(&save_dir().ok_or(StorageError::NoSaveDir)?, save_data)
+}
+
+pub fn load_from(dir: &Path) -> Result<SaveData, StorageError> {
+    let primary = dir.join(SAVE_FILE_NAME);
+    let backup = dir.join(BACKUP_FILE_NAME);
+
+    match read_file(&primary) {
+        Ok(save_data) => Ok(save_data),
+        Err(StorageError::Io(error))
+            if error.kind() == io::ErrorKind::NotFound && !backup.exists() =>
+        {
+            Ok(SaveData::default())
+        }
+        Err(error) => {
+            warn!(
+                "Could not load {}: {}, trying backup",
+                primary.display(),
+                error
+            );
+            read_file(&backup)
+        }
+    }
+}
+
+pub fn save_to(dir: &Path, save_data: &SaveData) -> Result<(), StorageError> {
+    let contents = ron::ser::to_string_pretty(save_data, PrettyConfig::default())
+        .map_err(StorageError::Serialize)?;
+
+    fs::create_dir_all(dir)?;
+
+    let temp = dir.join(TEMP_FILE_NAME);
+    let mut file = File::create(&temp)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()?;
+
+    let primary = dir.join(SAVE_FILE_NAME);
+    if read_file(&primary).is_ok() {
+        fs::rename(&primary, dir.join(BACKUP_FILE_NAME))?;
+    }
+    fs::rename(&temp, &primary)?;
+
+    Ok(())
+}
+
+pub fn save_session(session_stats: &SessionStats) -> Result<(), StorageError> {
+    let dir = save_dir().ok_or(StorageError::NoSaveDir)?;
+    let contents = ron::ser::to_string_pretty(session_stats, PrettyConfig::default())
+        .map_err(StorageError::Serialize)?;
+
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join(SESSION_FILE_NAME), contents)?;
+
+    Ok(())
+}
+
+pub fn autosave(
+    save_data: Res<SaveData>,
+    mut autosave: Local<Autosave>,
+    mut app_exit: EventReader<AppExit>,
+    time: Res<Time<Real>>,
+) {
+    if save_data.read_only {
+        return;
+    }
+
+    if save_data.is_changed() && !save_data.is_added() {
+        autosave.dirty = true;
+    }
+
+    autosave.since_write += time.delta_seconds();
+
+    let exiting = app_exit.read().count() > 0;
+    if !autosave.dirty || (autosave.since_write < AUTOSAVE_INTERVAL && !exiting) {
+        return;
+    }
+
+    if let Err(error) = save(&save_data) {
+        warn!("Could not write save data: {}", error);
+    }
+
+    autosave.dirty = false;
+    autosave.since_write = 0.0;
+}
+
+fn read_file(path: &Path) -> Result<SaveData, StorageError> {
+    let contents = fs::read_to_string(path)?;
+    migrations::parse(&contents)
+}
+
+pub fn save_dir() -> Option<PathBuf> {
+    #[cfg(target_os = "windows")]
+    let base = env::var_os("APPDATA").map(PathBuf::from);
+
+    #[cfg(target_os = "macos")]
+    let base =
+        env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"));
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let base = env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")));
+
+    base.map(|base| base.join("gorbulet"))
+}
+
+// Hand-editable settings, kept apart from the save; see keymap.rs.
+pub fn config_dir() -> Option<PathBuf> {
+    #[cfg(target_os = "windows")]
+    let base = env::var_os("APPDATA").map(PathBuf::from);
+
+    #[cfg(target_os = "macos")]
+    let base = env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Preferences"));
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+
+    base.map(|base| base.join("gorbulet"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A scratch directory under the system temp dir, removed again when the test ends.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = env::temp_dir().join(format!("gorbulet-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            Self(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn with_best(score: i32) -> SaveData {
+        SaveData {
+            best_score: Some(score),
+            ..default()
+        }
+    }
+
+    #[test]
+    fn missing_save_loads_defaults() {
+        let dir = TempDir::new("missing");
+
+        let save_data = load_from(&dir.0).unwrap();
+
+        assert_eq!(save_data.version, SAVE_VERSION);
+        assert_eq!(save_data.best_score, None);
+    }
+
+    #[test]
+    fn save_round_trips() {
+        let dir = TempDir::new("round-trip");
+
+        save_to(&dir.0, &with_best(17)).unwrap();
+
+        assert_eq!(load_from(&dir.0).unwrap().best_score, Some(17));
+        assert!(!dir.0.join(TEMP_FILE_NAME).exists());
+    }
+
+    #[test]
+    fn save_keeps_one_backup_generation() {
+        let dir = TempDir::new("backup");
+
+        save_to(&dir.0, &with_best(1)).unwrap();
+        save_to(&dir.0, &with_best(2)).unwrap();
+        save_to(&dir.0, &with_best(3)).unwrap();
+
+        assert_eq!(
+            read_file(&dir.0.join(SAVE_FILE_NAME)).unwrap().best_score,
+            Some(3)
+        );
+        assert_eq!(
+            read_file(&dir.0.join(BACKUP_FILE_NAME)).unwrap().best_score,
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn corrupted_primary_falls_back_to_backup() {
+        let dir = TempDir::new("corrupted");
+
+        save_to(&dir.0, &with_best(5)).unwrap();
+        save_to(&dir.0, &with_best(6)).unwrap();
+        fs::write(dir.0.join(SAVE_FILE_NAME), "(best_score: Some(").unwrap();
+
+        assert_eq!(load_from(&dir.0).unwrap().best_score, Some(5));
+    }
+
+    #[test]
+    fn corrupted_primary_is_not_rotated_over_the_backup() {
+        let dir = TempDir::new("rotate");
+
+        save_to(&dir.0, &with_best(5)).unwrap();
+        save_to(&dir.0, &with_best(6)).unwrap();
+        fs::write(dir.0.join(SAVE_FILE_NAME), "garbage").unwrap();
+        save_to(&dir.0, &with_best(7)).unwrap();
+
+        assert_eq!(
+            read_file(&dir.0.join(BACKUP_FILE_NAME)).unwrap().best_score,
+            Some(5)
+        );
+        assert_eq!(load_from(&dir.0).unwrap().best_score, Some(7));
+    }
+
+    #[test]
+    fn corrupted_primary_without_backup_is_moved_aside() {
+        let dir = TempDir::new("no-backup");
+
+        fs::create_dir_all(&dir.0).unwrap();
+        fs::write(dir.0.join(SAVE_FILE_NAME), "garbage").unwrap();
+
+        assert!(load_from(&dir.0).is_err());
+        let save_data = load_or_default_from(&dir.0);
+        assert!(!save_data.read_only);
+        assert_eq!(save_data.best_score, None);
+
+        save_to(&dir.0, &with_best(3)).unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.0.join(BROKEN_FILE_NAME)).unwrap(),
+            "garbage"
+        );
+        assert_eq!(load_from(&dir.0).unwrap().best_score, Some(3));
+    }
+
+    fn record(score: i32, time: f32) -> RunRecord {
+        RunRecord {
+            score,
+            time,
+            difficulty: Difficulty::Normal,
+            feel: MovementFeel::default(),
+            arena: Arena::default(),
+            day: 0,
+            score_decay: false,
+        }
+    }
+
+    fn scores(records: &[RunRecord]) -> Vec<(i32, f32)> {
+        records
+            .iter()
+            .map(|record| (record.score, record.time))
+            .collect()
+    }
+
+    #[test]
+    fn records_stay_sorted_best_first() {
+        let mut records = Vec::new();
+
+        assert_eq!(insert_record(&mut records, record(5, 10.0)), Some(0));
+        assert_eq!(insert_record(&mut records, record(9, 10.0)), Some(0));
+        assert_eq!(insert_record(&mut records, record(7, 10.0)), Some(1));
+
+        assert_eq!(scores(&records), [(9, 10.0), (7, 10.0), (5, 10.0)]);
+    }
+
+    #[test]
+    fn score_ties_go_to_the_longer_survival() {
+        let mut records = vec![record(5, 20.0), record(5, 10.0)];
+
+        assert_eq!(insert_record(&mut records, record(5, 15.0)), Some(1));
+        // An exact tie ranks below the record that was there first.
+        assert_eq!(insert_record(&mut records, record(5, 10.0)), Some(3));
+
+        assert_eq!(
+            scores(&records),
+            [(5, 20.0), (5, 15.0), (5, 10.0), (5, 10.0)]
+        );
+    }
+
+    #[test]
+    fn records_are_capped_per_mode() {
+        let mut records: Vec<_> = (0..RECORDS_PER_MODE as i32)
+            .rev()
+            .map(|score| record(score + 1, 0.0))
+            .collect();
+
+        assert_eq!(insert_record(&mut records, record(0, 0.0)), None);
+        assert_eq!(insert_record(&mut records, record(100, 0.0)), Some(0));
+
+        assert_eq!(records.len(), RECORDS_PER_MODE);
+        assert_eq!(records[0].score, 100);
+        assert_eq!(records[RECORDS_PER_MODE - 1].score, 2);
+    }
+}
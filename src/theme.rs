@@ -0,0 +1,223 @@
+use bevy::{
+    prelude::*,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    assets::AssetHandles,
+    config::*,
+    menu_list::{EntryKind, MenuEntry},
+    player::Player,
+    render_z,
+    storage::SaveData,
+    AppState,
+};
+
+pub struct ThemePlugin;
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Arena::default().theme())
+            .add_systems(
+                PreUpdate,
+                apply_theme.run_if(resource_exists::<AssetHandles>()),
+            )
+            .add_systems(
+                Update,
+                (attach_vignette, scale_vignette).run_if(in_state(AppState::Game)),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Arena {
+    #[default]
+    Classic,
+    Night,
+    Inferno,
+}
+
+impl Arena {
+    pub const ALL: [Arena; 3] = [Arena::Classic, Arena::Night, Arena::Inferno];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Arena::Classic => "Classic",
+            Arena::Night => "Night",
+            Arena::Inferno => "Inferno",
+        }
+    }
+
+    pub fn menu_entry(&self) -> MenuEntry {
+        MenuEntry::new(
+            "arena",
+            "Arena",
+            EntryKind::Choice {
+                options: Arena::ALL.iter().map(Arena::name).collect(),
+                selected: Arena::ALL
+                    .iter()
+                    .position(|arena| arena == self)
+                    .unwrap_or(0),
+            },
+        )
+    }
+
+    pub fn theme(self) -> Theme {
+        match self {
+            Arena::Classic => Theme {
+                arena: self,
+                clear_color: ClearColor::default().0,
+                decor_color: None,
+                music_dir: None,
+                vignette: false,
+            },
+            Arena::Night => Theme {
+                arena: self,
+                clear_color: NIGHT_CLEAR_COLOR,
+                decor_color: Some(NIGHT_DECOR_COLOR),
+                music_dir: Some("night"),
+                vignette: true,
+            },
+            Arena::Inferno => Theme {
+                arena: self,
+                clear_color: INFERNO_CLEAR_COLOR,
+                decor_color: Some(INFERNO_DECOR_COLOR),
+                music_dir: Some("inferno"),
+                vignette: false,
+            },
+        }
+    }
+
+    // Inferno's faster enemies are paid back in score, like a mutator.
+    pub fn apply(&self, config: &mut GameConfig, rules: &mut GameRules) {
+        if *self == Arena::Inferno {
+            config.enemy_speed_multiplier *= INFERNO_ENEMY_SPEED_SCALE;
+            rules.score_multiplier *= INFERNO_ENEMY_SPEED_SCALE;
+        }
+    }
+}
+
+// What the selected arena looks and sounds like. Visual systems read this instead of matching on
+// the arena themselves.
+#[derive(Resource, Debug, Clone)]
+pub struct Theme {
+    pub arena: Arena,
+    pub clear_color: Color,
+    // Tints the menu's background chasers, which otherwise wear the enemy colors.
+    pub decor_color: Option<Color>,
+    // A folder under assets holding this arena's music; any file missing there falls back to the
+    // default one.
+    pub music_dir: Option<&'static str>,
+    pub vignette: bool,
+}
+
+// The playfield accent takes the clear color as its base when a run starts, so the arena only
+// ever changes between runs, from the settings screen.
+fn apply_theme(
+    mut theme: ResMut<Theme>,
+    mut clear_color: ResMut<ClearColor>,
+    mut asset_handles: ResMut<AssetHandles>,
+    asset_server: Res<AssetServer>,
+    save_data: Res<SaveData>,
+    mut loaded: Local<bool>,
+) {
+    let arena = save_data.settings.arena;
+    if *loaded && theme.arena == arena {
+        return;
+    }
+    *loaded = true;
+
+    *theme = arena.theme();
+    clear_color.0 = theme.clear_color;
+    asset_handles.load_music(&asset_server, theme.music_dir);
+}
+
+#[derive(Component)]
+struct Vignette;
+
+// A few stacked rings, each darkening everything outside its radius a little more, read as one
+// soft edge around the player.
+fn attach_vignette(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    theme: Res<Theme>,
+    player_query: Query<(Entity, &Transform), Added<Player>>,
+) {
+    if !theme.vignette {
+        return;
+    }
+
+    for (player, player_transform) in player_query.iter() {
+        // Undo the player's own scale, so a tiny player doesn't also see less.
+        let scale = Vec3::ONE / player_transform.scale;
+        let material = materials.add(ColorMaterial::from(
+            NIGHT_VIGNETTE_COLOR.with_a(NIGHT_VIGNETTE_LAYER_ALPHA),
+        ));
+
+        commands.entity(player).with_children(|parent| {
+            for radius in NIGHT_VIGNETTE_RADII {
+                parent.spawn((
+                    Vignette,
+                    ColorMesh2dBundle {
+                        mesh: meshes
+                            .add(annulus(radius, NIGHT_VIGNETTE_OUTER_RADIUS))
+                            .into(),
+                        material: material.clone(),
+                        transform: Transform::from_xyz(
+                            0.0,
+                            0.0,
+                            render_z::EFFECTS - render_z::PLAYER,
+                        )
+                        .with_scale(scale),
+                        ..default()
+                    },
+                ));
+            }
+        });
+    }
+}
+
+// Keeps undoing the player's scale while it changes, as it does while shrunk.
+fn scale_vignette(
+    mut vignette_query: Query<(&Parent, &mut Transform), With<Vignette>>,
+    player_query: Query<&Transform, (With<Player>, Changed<Transform>, Without<Vignette>)>,
+) {
+    for (parent, mut transform) in vignette_query.iter_mut() {
+        if let Ok(player_transform) = player_query.get(parent.get()) {
+            let scale = Vec3::ONE / player_transform.scale;
+            if transform.scale != scale {
+                transform.scale = scale;
+            }
+        }
+    }
+}
+
+fn annulus(inner: f32, outer: f32) -> Mesh {
+    let segments = NIGHT_VIGNETTE_SEGMENTS;
+    let mut positions = Vec::with_capacity(segments as usize * 2);
+    let mut indices = Vec::with_capacity(segments as usize * 6);
+
+    for segment in 0..segments {
+        let angle = segment as f32 / segments as f32 * std::f32::consts::TAU;
+        let direction = Vec2::from_angle(angle);
+        positions.push((direction * inner).extend(0.0).to_array());
+        positions.push((direction * outer).extend(0.0).to_array());
+
+        let [inner_a, outer_a] = [segment * 2, segment * 2 + 1];
+        let [inner_b, outer_b] = [
+            (segment + 1) % segments * 2,
+            (segment + 1) % segments * 2 + 1,
+        ];
+        indices.extend([inner_a, outer_a, outer_b, inner_a, outer_b, inner_b]);
+    }
+
+    let vertices = positions.len();
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 0.0, 1.0]; vertices]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0, 0.0]; vertices]);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
@@ -0,0 +1,140 @@
+use bevy::prelude::*;
+
+use crate::stats::threat_tint;
+
+// The layered color of an entity that owns its material. Sources apply in a fixed order: the base
+// color, then the threat tint, then dimming, and a flash overrides all of them. Alpha is left to
+// whatever else animates it, like Fade.
+#[derive(Component, Debug, Clone)]
+pub struct TintController {
+    pub base: Color,
+    pub threat: f32,
+    pub dim: f32,
+    pub flash: Option<Color>,
+}
+
+impl TintController {
+    pub fn new(base: Color) -> Self {
+        Self {
+            base,
+            threat: 0.0,
+            dim: 0.0,
+            flash: None,
+        }
+    }
+
+    pub fn with_threat(mut self, threat: f32) -> Self {
+        self.threat = threat;
+        self
+    }
+
+    pub fn color(&self) -> Color {
+        if let Some(flash) = self.flash {
+            return flash;
+        }
+
+        let tinted = threat_tint(self.base, self.threat);
+        let lit = 1.0 - self.dim.clamp(0.0, 1.0);
+        Color::rgba(
+            tinted.r() * lit,
+            tinted.g() * lit,
+            tinted.b() * lit,
+            tinted.a(),
+        )
+    }
+}
+
+// A copy of a shared material for one entity to recolor on its own. Handles are reference
+// counted, so the copy is dropped from Assets<ColorMaterial> along with the entity holding it.
+pub fn instance_material(
+    materials: &mut Assets<ColorMaterial>,
+    source: &Handle<ColorMaterial>,
+    tint: &TintController,
+) -> Handle<ColorMaterial> {
+    let mut material = materials.get(source).cloned().unwrap_or_default();
+    material.color = tint.color();
+    materials.add(material)
+}
+
+pub fn apply_tints(
+    query: Query<(&TintController, &Handle<ColorMaterial>), Changed<TintController>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    for (tint, handle) in query.iter() {
+        if let Some(material) = materials.get_mut(handle) {
+            let alpha = material.color.a();
+            material.color = tint.color().with_a(alpha);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn untouched_tint_is_the_base_color() {
+        let base = Color::rgba(0.8, 0.2, 0.4, 0.5);
+
+        assert_eq!(TintController::new(base).color(), base);
+    }
+
+    #[test]
+    fn dimming_darkens_the_threat_tint_and_keeps_alpha() {
+        let base = Color::rgba(0.8, 0.2, 0.4, 0.5);
+        let mut tint = TintController::new(base).with_threat(0.7);
+        let tinted = threat_tint(base, 0.7);
+        tint.dim = 0.25;
+
+        let color = tint.color();
+
+        assert!((color.r() - tinted.r() * 0.75).abs() < 1e-6);
+        assert!((color.g() - tinted.g() * 0.75).abs() < 1e-6);
+        assert!((color.b() - tinted.b() * 0.75).abs() < 1e-6);
+        assert_eq!(color.a(), base.a());
+    }
+
+    #[test]
+    fn dimming_is_clamped() {
+        let mut tint = TintController::new(Color::WHITE);
+        tint.dim = 3.0;
+
+        assert_eq!(tint.color(), Color::rgba(0.0, 0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn flash_overrides_every_other_source() {
+        let mut tint = TintController::new(Color::RED).with_threat(1.0);
+        tint.dim = 0.5;
+        tint.flash = Some(Color::WHITE);
+
+        assert_eq!(tint.color(), Color::WHITE);
+    }
+
+    #[test]
+    fn instanced_materials_go_away_with_their_entities() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<ColorMaterial>();
+        let shared = app
+            .world
+            .resource_mut::<Assets<ColorMaterial>>()
+            .add(ColorMaterial::from(Color::RED));
+        app.update();
+
+        for _ in 0..500 {
+            let tint = TintController::new(Color::RED).with_threat(0.5);
+            let material = instance_material(
+                &mut app.world.resource_mut::<Assets<ColorMaterial>>(),
+                &shared,
+                &tint,
+            );
+            let entity = app.world.spawn((tint, material)).id();
+            app.update();
+            app.world.despawn(entity);
+            app.update();
+        }
+
+        assert_eq!(app.world.resource::<Assets<ColorMaterial>>().len(), 1);
+    }
+}
@@ -0,0 +1,108 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::*,
+    effects::BannerQueue,
+    enemy::{EnemySpawned, EnemyType},
+    game::{GameInfo, GameMode, HitPlayer},
+    storage::SaveData,
+    AppState,
+};
+
+pub struct TipsPlugin;
+
+impl Plugin for TipsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Game), count_tip_run)
+            .add_systems(Update, show_tips.run_if(in_state(AppState::Game)));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tip {
+    FirstEnemy,
+    HealthCoin,
+    FirstPurple,
+    FirstHit,
+}
+
+impl Tip {
+    fn bit(&self) -> u8 {
+        match self {
+            Tip::FirstEnemy => 1 << 0,
+            Tip::HealthCoin => 1 << 1,
+            Tip::FirstPurple => 1 << 2,
+            Tip::FirstHit => 1 << 3,
+        }
+    }
+
+    fn text(&self) -> &'static str {
+        match self {
+            Tip::FirstEnemy => "It chases you",
+            Tip::HealthCoin => "Green coin next \u{2014} heals",
+            Tip::FirstPurple => "Purple ones cut across the edges",
+            Tip::FirstHit => "You're briefly invincible \u{2014} run!",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TipsSeen(u8);
+
+impl TipsSeen {
+    pub fn contains(&self, tip: Tip) -> bool {
+        self.0 & tip.bit() != 0
+    }
+
+    pub fn insert(&mut self, tip: Tip) {
+        self.0 |= tip.bit();
+    }
+}
+
+fn count_tip_run(mut save_data: ResMut<SaveData>, game_mode: Res<GameMode>) {
+    if !matches!(*game_mode, GameMode::Zen | GameMode::Tutorial) {
+        save_data.tip_runs += 1;
+    }
+}
+
+fn show_tips(
+    mut enemy_spawned: EventReader<EnemySpawned>,
+    mut hit_player: EventReader<HitPlayer>,
+    mut save_data: ResMut<SaveData>,
+    mut banners: ResMut<BannerQueue>,
+    game_info: Res<GameInfo>,
+    game_mode: Res<GameMode>,
+    game_rules: Res<GameRules>,
+) {
+    let mut triggered = Vec::new();
+
+    for event in enemy_spawned.read() {
+        triggered.push(Tip::FirstEnemy);
+        if event.enemy_type == EnemyType::Purple {
+            triggered.push(Tip::FirstPurple);
+        }
+    }
+    if hit_player.read().count() > 0 {
+        triggered.push(Tip::FirstHit);
+    }
+    if game_rules.health_coins && game_info.points() >= HEALTH_MULTIPLE - 1 {
+        triggered.push(Tip::HealthCoin);
+    }
+
+    // The tutorial's own banners already cover what the tips would say.
+    if matches!(*game_mode, GameMode::Zen | GameMode::Tutorial)
+        || !save_data.settings.tips
+        || save_data.tip_runs > TIP_RUNS
+    {
+        return;
+    }
+
+    for tip in triggered {
+        if save_data.tips_seen.contains(tip) {
+            continue;
+        }
+        save_data.tips_seen.insert(tip);
+        banners.push(tip.text());
+    }
+}
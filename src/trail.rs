@@ -0,0 +1,72 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{
+    assets::{trail_alpha, AssetHandles},
+    config::*,
+    effects::text_label,
+    render_z,
+    storage::SaveData,
+};
+
+#[derive(Resource, Default)]
+pub struct CoinTrail(VecDeque<Vec3>);
+
+impl CoinTrail {
+    pub fn push(&mut self, position: Vec3) {
+        self.0.push_front(position);
+        self.0.truncate(COIN_TRAIL_LENGTH);
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+#[derive(Component)]
+pub struct CoinTrailMarker;
+
+pub fn update_coin_trail(
+    mut commands: Commands,
+    trail: Res<CoinTrail>,
+    save_data: Res<SaveData>,
+    marker_query: Query<Entity, With<CoinTrailMarker>>,
+    asset_handles: Res<AssetHandles>,
+) {
+    if !trail.is_changed() {
+        return;
+    }
+
+    marker_query.iter().for_each(|entity| {
+        commands.entity(entity).despawn_recursive();
+    });
+
+    if !save_data.settings.coin_trail {
+        return;
+    }
+
+    for (age, position) in trail.0.iter().enumerate() {
+        commands
+            .spawn((
+                CoinTrailMarker,
+                ColorMesh2dBundle {
+                    mesh: asset_handles.coin_mesh.clone().into(),
+                    material: asset_handles.coin_trail_materials[age].clone(),
+                    transform: Transform::from_translation(
+                        position.truncate().extend(render_z::TRAIL),
+                    ),
+                    ..default()
+                },
+            ))
+            .with_children(|parent| {
+                parent.spawn(text_label(
+                    &asset_handles,
+                    (age + 1).to_string(),
+                    14.0,
+                    Color::BLACK.with_a(trail_alpha(age)),
+                    Vec3::new(0.0, 0.0, render_z::CHILD_ABOVE),
+                ));
+            });
+    }
+}
@@ -0,0 +1,279 @@
+use bevy::prelude::*;
+
+use crate::{pause::PauseState, AppState};
+
+// Gameplay asks for state changes here instead of writing NextState itself. One arbiter applies
+// at most one of them per frame, after everything else has run, so a death, a win and a pause
+// landing on the same frame can't trample each other.
+pub struct TransitionPlugin;
+
+impl Plugin for TransitionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RequestedTransition>()
+            .add_systems(Last, arbitrate_transitions);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    App(AppState),
+    Pause(PauseState),
+    // Tears the current run down and sets it up again, without leaving AppState::Game.
+    Restart,
+}
+
+impl Transition {
+    // Of several requests in the same frame, the highest one wins and the rest are dropped.
+    fn priority(self) -> u8 {
+        match self {
+            Transition::App(_) => 5,
+            Transition::Restart => 4,
+            Transition::Pause(PauseState::GameOver) => 3,
+            Transition::Pause(PauseState::Won) => 2,
+            Transition::Pause(PauseState::Paused) => 1,
+            Transition::Pause(PauseState::Running) => 0,
+        }
+    }
+
+    fn is_legal(self, app_state: AppState, pause_state: PauseState) -> bool {
+        match self {
+            Transition::App(to) => to != app_state,
+            Transition::Restart => app_state == AppState::Game,
+            Transition::Pause(to) => {
+                app_state == AppState::Game
+                    && matches!(
+                        (pause_state, to),
+                        (
+                            PauseState::Running,
+                            PauseState::Paused | PauseState::Won | PauseState::GameOver
+                        ) | (PauseState::Paused | PauseState::Won, PauseState::Running)
+                    )
+            }
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct RequestedTransition(Vec<Transition>);
+
+impl RequestedTransition {
+    pub fn request(&mut self, transition: Transition) {
+        self.0.push(transition);
+    }
+}
+
+// The legal request with the highest priority, the earliest one on a tie.
+fn choose_transition(
+    requests: &[Transition],
+    app_state: AppState,
+    pause_state: PauseState,
+) -> Option<Transition> {
+    requests
+        .iter()
+        .copied()
+        .filter(|transition| transition.is_legal(app_state, pause_state))
+        .fold(
+            None,
+            |chosen: Option<Transition>, transition| match chosen {
+                Some(chosen) if chosen.priority() >= transition.priority() => Some(chosen),
+                _ => Some(transition),
+            },
+        )
+}
+
+fn arbitrate_transitions(
+    mut commands: Commands,
+    mut requested: ResMut<RequestedTransition>,
+    app_state: Res<State<AppState>>,
+    pause_state: Res<State<PauseState>>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+) {
+    if requested.0.is_empty() {
+        return;
+    }
+
+    // Requests made while a transition applied this frame, from its OnExit or OnEnter systems
+    // included, or while one set directly is still queued, wait for the next frame. They are
+    // then judged against the state that transition left behind.
+    if app_state.is_changed()
+        || pause_state.is_changed()
+        || next_app_state.0.is_some()
+        || next_pause_state.0.is_some()
+    {
+        return;
+    }
+
+    let chosen = choose_transition(&requested.0, *app_state.get(), *pause_state.get());
+    for dropped in requested
+        .0
+        .iter()
+        .filter(|&&request| Some(request) != chosen)
+    {
+        debug!("Dropped transition request {:?}", dropped);
+    }
+    requested.0.clear();
+
+    match chosen {
+        Some(Transition::App(to)) => next_app_state.set(to),
+        Some(Transition::Pause(to)) => next_pause_state.set(to),
+        Some(Transition::Restart) => commands.add(|world: &mut World| {
+            world.run_schedule(OnExit(AppState::Game));
+            world.run_schedule(OnEnter(AppState::Game));
+        }),
+        None => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transition_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, TransitionPlugin))
+            .add_state::<AppState>()
+            .add_state::<PauseState>();
+        app.update();
+        app
+    }
+
+    fn request(app: &mut App, transition: Transition) {
+        app.world
+            .resource_mut::<RequestedTransition>()
+            .request(transition);
+    }
+
+    fn states(app: &App) -> (AppState, PauseState) {
+        (
+            *app.world.resource::<State<AppState>>().get(),
+            *app.world.resource::<State<PauseState>>().get(),
+        )
+    }
+
+    #[test]
+    fn death_beats_a_win_and_a_pause_on_the_same_frame() {
+        let requests = [
+            Transition::Pause(PauseState::Paused),
+            Transition::Pause(PauseState::GameOver),
+            Transition::Pause(PauseState::Won),
+        ];
+
+        assert_eq!(
+            choose_transition(&requests, AppState::Game, PauseState::Running),
+            Some(Transition::Pause(PauseState::GameOver))
+        );
+    }
+
+    #[test]
+    fn leaving_the_game_beats_everything_in_it() {
+        let requests = [
+            Transition::Pause(PauseState::GameOver),
+            Transition::Restart,
+            Transition::App(AppState::Menu),
+        ];
+
+        assert_eq!(
+            choose_transition(&requests, AppState::Game, PauseState::Running),
+            Some(Transition::App(AppState::Menu))
+        );
+    }
+
+    #[test]
+    fn earliest_request_wins_a_tie() {
+        let requests = [
+            Transition::App(AppState::Settings),
+            Transition::App(AppState::Records),
+        ];
+
+        assert_eq!(
+            choose_transition(&requests, AppState::Menu, PauseState::Running),
+            Some(Transition::App(AppState::Settings))
+        );
+    }
+
+    #[test]
+    fn illegal_requests_are_dropped() {
+        for (request, app_state, pause_state) in [
+            (
+                Transition::Pause(PauseState::GameOver),
+                AppState::Menu,
+                PauseState::Running,
+            ),
+            (Transition::Restart, AppState::Menu, PauseState::Running),
+            (
+                Transition::App(AppState::Game),
+                AppState::Game,
+                PauseState::Running,
+            ),
+            (
+                Transition::Pause(PauseState::Paused),
+                AppState::Game,
+                PauseState::Paused,
+            ),
+            (
+                Transition::Pause(PauseState::Running),
+                AppState::Game,
+                PauseState::GameOver,
+            ),
+            (
+                Transition::Pause(PauseState::Won),
+                AppState::Game,
+                PauseState::GameOver,
+            ),
+        ] {
+            assert_eq!(
+                choose_transition(&[request], app_state, pause_state),
+                None,
+                "{:?} in {:?} / {:?}",
+                request,
+                app_state,
+                pause_state
+            );
+        }
+    }
+
+    #[test]
+    fn illegal_request_does_not_block_a_legal_one() {
+        let requests = [
+            Transition::Pause(PauseState::GameOver),
+            Transition::App(AppState::Menu),
+            Transition::App(AppState::Game),
+        ];
+
+        assert_eq!(
+            choose_transition(&requests, AppState::Menu, PauseState::Running),
+            Some(Transition::App(AppState::Game))
+        );
+    }
+
+    #[test]
+    fn arbiter_applies_one_request_and_drops_the_rest() {
+        let mut app = transition_app();
+        request(&mut app, Transition::App(AppState::Game));
+        request(&mut app, Transition::App(AppState::Settings));
+
+        app.update();
+        app.update();
+
+        assert_eq!(states(&app), (AppState::Game, PauseState::Running));
+        assert!(app.world.resource::<RequestedTransition>().0.is_empty());
+    }
+
+    #[test]
+    fn request_made_during_a_transition_waits_a_frame() {
+        let mut app = transition_app();
+        app.world
+            .resource_mut::<NextState<AppState>>()
+            .set(AppState::Game);
+        request(&mut app, Transition::Pause(PauseState::Paused));
+
+        app.update();
+        assert_eq!(states(&app), (AppState::Game, PauseState::Running));
+        assert_eq!(app.world.resource::<RequestedTransition>().0.len(), 1);
+
+        app.update();
+        app.update();
+        assert_eq!(states(&app), (AppState::Game, PauseState::Paused));
+    }
+}
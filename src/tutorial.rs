@@ -0,0 +1,247 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    assets::AssetHandles,
+    config::*,
+    effects::BannerQueue,
+    enemy::{
+        get_enemy_spawn_position, spawn_enemy_with_stats, EnemySpawned, EnemyStats, EnemyType,
+        SpawnSide,
+    },
+    game::{GameInfo, GameMode, HitPlayer},
+    game_time::GameTime,
+    pause::PauseState,
+    pickup::spawn_pickups,
+    player::Player,
+    render_z,
+    rng::GameRng,
+    storage::SaveData,
+    transition::{RequestedTransition, Transition},
+    window::not_suspended,
+    AppState,
+};
+
+pub struct TutorialPlugin;
+
+impl Plugin for TutorialPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Game), start_tutorial)
+            .add_systems(
+                Update,
+                advance_tutorial
+                    .after(spawn_pickups)
+                    .run_if(resource_exists::<TutorialScript>())
+                    .run_if(in_state(AppState::Game))
+                    .run_if(in_state(PauseState::Running))
+                    .run_if(not_suspended),
+            )
+            .add_systems(OnExit(AppState::Game), end_tutorial);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TutorialGoal {
+    // Reach the marked spot, placed at TUTORIAL_TARGET.
+    Reach,
+    // Collect this many coins, counted from when the step starts.
+    Collect(i32),
+    // Go this long without being hit; a hit starts the count over.
+    Survive(f32),
+    // Play on until the time runs out, hit or not.
+    FreePlay(f32),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TutorialStep {
+    pub banner: &'static str,
+    pub goal: TutorialGoal,
+    // Spawned when the step starts, on the side away from the player.
+    pub enemy: Option<(EnemyType, EnemyStats)>,
+}
+
+// The tutorial's steps and how far the player is through them. Only present during a Tutorial
+// run; advance_tutorial checks the current step's goal every frame and moves on once it's met.
+#[derive(Resource)]
+pub struct TutorialScript {
+    steps: Vec<TutorialStep>,
+    current: usize,
+    started: bool,
+    start_points: i32,
+    elapsed: f32,
+}
+
+impl Default for TutorialScript {
+    fn default() -> Self {
+        Self {
+            steps: tutorial_steps(),
+            current: 0,
+            started: false,
+            start_points: 0,
+            elapsed: 0.0,
+        }
+    }
+}
+
+impl TutorialScript {
+    fn step(&self) -> Option<&TutorialStep> {
+        self.steps.get(self.current)
+    }
+
+    fn next(&mut self) {
+        self.current += 1;
+        self.started = false;
+    }
+}
+
+#[derive(Component)]
+struct TutorialTarget;
+
+fn tutorial_steps() -> Vec<TutorialStep> {
+    let slow = |enemy_type| {
+        Some((
+            enemy_type,
+            EnemyStats {
+                speed: ENEMY_MIN_SPEED * TUTORIAL_ENEMY_SCALE,
+                accel: ENEMY_MIN_ACCEL * TUTORIAL_ENEMY_SCALE,
+                future_prediction: 0.0,
+                coin_pull: 0.0,
+            },
+        ))
+    };
+
+    vec![
+        TutorialStep {
+            banner: "Move to the marked spot",
+            goal: TutorialGoal::Reach,
+            enemy: None,
+        },
+        TutorialStep {
+            banner: "Collect three coins",
+            goal: TutorialGoal::Collect(TUTORIAL_COINS),
+            enemy: None,
+        },
+        TutorialStep {
+            banner: "Enemies chase you. Stay away from this one",
+            goal: TutorialGoal::Survive(TUTORIAL_SURVIVE_TIME),
+            enemy: slow(EnemyType::Red),
+        },
+        TutorialStep {
+            banner: "Purple enemies wrap around the edges. Watch for their ghost",
+            goal: TutorialGoal::Survive(TUTORIAL_SURVIVE_TIME),
+            enemy: slow(EnemyType::Purple),
+        },
+        TutorialStep {
+            banner: "Now play on for a bit",
+            goal: TutorialGoal::FreePlay(TUTORIAL_FREE_PLAY_TIME),
+            enemy: None,
+        },
+    ]
+}
+
+fn start_tutorial(mut commands: Commands, game_mode: Res<GameMode>) {
+    if *game_mode == GameMode::Tutorial {
+        commands.insert_resource(TutorialScript::default());
+    }
+}
+
+fn advance_tutorial(
+    mut commands: Commands,
+    mut script: ResMut<TutorialScript>,
+    mut hit_player: EventReader<HitPlayer>,
+    mut enemy_spawned: EventWriter<EnemySpawned>,
+    mut banners: ResMut<BannerQueue>,
+    mut save_data: ResMut<SaveData>,
+    mut transitions: ResMut<RequestedTransition>,
+    (mut rng, mut materials): (ResMut<GameRng>, ResMut<Assets<ColorMaterial>>),
+    game_info: Res<GameInfo>,
+    game_time: Res<GameTime>,
+    asset_handles: Res<AssetHandles>,
+    player_query: Query<&Transform, With<Player>>,
+    target_query: Query<Entity, With<TutorialTarget>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+) {
+    let (Ok(player_transform), Ok(window)) = (player_query.get_single(), window.get_single())
+    else {
+        return;
+    };
+    let player_position = player_transform.translation.truncate();
+    let half_extent = Vec2::new(window.width(), window.height()) / 2.0;
+    let target = half_extent * TUTORIAL_TARGET;
+    let hit = hit_player.read().count() > 0;
+
+    let Some(step) = script.step().copied() else {
+        return;
+    };
+
+    if !script.started {
+        script.started = true;
+        script.start_points = game_info.points();
+        script.elapsed = 0.0;
+        banners.push(step.banner);
+
+        if step.goal == TutorialGoal::Reach {
+            commands.spawn((
+                TutorialTarget,
+                ColorMesh2dBundle {
+                    mesh: asset_handles.home_zone_mesh.clone().into(),
+                    material: asset_handles.home_zone_material.clone(),
+                    transform: Transform::from_translation(target.extend(render_z::PLAYFIELD)),
+                    ..default()
+                },
+            ));
+        }
+
+        if let Some((enemy_type, stats)) = step.enemy {
+            let side = if player_position.x > 0.0 {
+                SpawnSide::Left
+            } else {
+                SpawnSide::Right
+            };
+            let spawn_position =
+                get_enemy_spawn_position(window.width(), window.height(), side, &mut rng);
+            enemy_spawned.send(spawn_enemy_with_stats(
+                &mut commands,
+                &mut materials,
+                &mut rng,
+                0,
+                &asset_handles,
+                enemy_type,
+                spawn_position,
+                stats,
+                false,
+            ));
+        }
+    }
+
+    script.elapsed += game_time.delta_seconds();
+    let done = match step.goal {
+        TutorialGoal::Reach => player_position.distance(target) < HOME_ZONE_RADIUS,
+        TutorialGoal::Collect(count) => game_info.points() - script.start_points >= count,
+        TutorialGoal::Survive(time) => {
+            if hit {
+                script.elapsed = 0.0;
+                banners.push("Hit! Starting that one over");
+            }
+            script.elapsed >= time
+        }
+        TutorialGoal::FreePlay(time) => script.elapsed >= time,
+    };
+    if !done {
+        return;
+    }
+
+    target_query.iter().for_each(|entity| {
+        commands.entity(entity).despawn();
+    });
+    script.next();
+
+    if script.step().is_none() {
+        save_data.tutorial_done = true;
+        transitions.request(Transition::App(AppState::Menu));
+    }
+}
+
+// Runs however the run ended, so leaving early never carries the script into the next run.
+fn end_tutorial(mut commands: Commands) {
+    commands.remove_resource::<TutorialScript>();
+}
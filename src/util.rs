@@ -0,0 +1,221 @@
+use bevy::{
+    prelude::*,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+};
+
+use crate::config::HUD_PIE_SEGMENTS;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+const CHECKSUM_POSITION_STEP: f32 = 0.1;
+
+pub struct StateSnapshot {
+    pub player_position: Vec2,
+    pub points: i32,
+    pub health: i8,
+    pub enemy_count: usize,
+    pub rng_words: u64,
+}
+
+pub fn quantize(value: f32, step: f32) -> i64 {
+    (value / step).round() as i64
+}
+
+pub fn state_checksum(snapshot: &StateSnapshot) -> u64 {
+    [
+        &quantize(snapshot.player_position.x, CHECKSUM_POSITION_STEP).to_le_bytes()[..],
+        &quantize(snapshot.player_position.y, CHECKSUM_POSITION_STEP).to_le_bytes()[..],
+        &snapshot.points.to_le_bytes()[..],
+        &snapshot.health.to_le_bytes()[..],
+        &(snapshot.enemy_count as u64).to_le_bytes()[..],
+        &snapshot.rng_words.to_le_bytes()[..],
+    ]
+    .iter()
+    .fold(FNV_OFFSET_BASIS, |hash, bytes| fnv1a(hash, bytes))
+}
+
+pub fn first_divergence(recorded: &[u64], expected: &[u64]) -> Option<usize> {
+    recorded
+        .iter()
+        .zip(expected)
+        .position(|(recorded, expected)| recorded != expected)
+}
+
+pub fn bytes_checksum(bytes: &[u8]) -> u64 {
+    fnv1a(FNV_OFFSET_BASIS, bytes)
+}
+
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+// Half the window's extent with `safe_area` percent of each dimension kept clear on every side.
+pub fn safe_half_extent(half_extent: Vec2, safe_area: f32) -> Vec2 {
+    half_extent * (1.0 - safe_area / 50.0)
+}
+
+pub fn vec3_move_toward(from: Vec3, to: Vec3, distance: f32) -> Vec3 {
+    if from.distance_squared(to) <= distance.powf(2.0) {
+        return to;
+    }
+
+    if let Some(direction) = (to - from).try_normalize() {
+        from + direction * distance
+    } else {
+        from
+    }
+}
+
+// Where something heading out over a playfield edge comes back into view on the opposite one, once
+// it is within edge_distance of that edge. Both axes wrap at once near a corner.
+pub fn wraparound_entry_position(
+    position: Vec2,
+    velocity: Vec2,
+    half_extent: Vec2,
+    edge_distance: f32,
+) -> Option<Vec2> {
+    let mut entry = position;
+    let mut wrapping = false;
+    for axis in 0..2 {
+        let outward = velocity[axis] != 0.0 && velocity[axis].signum() == position[axis].signum();
+        if outward && position[axis].abs() > half_extent[axis] - edge_distance {
+            entry[axis] = -position[axis].signum() * half_extent[axis];
+            wrapping = true;
+        }
+    }
+
+    wrapping.then_some(entry)
+}
+
+pub fn wraparound_tracking_position(from: Vec3, to: Vec3, width: f32, height: f32) -> Vec3 {
+    let position_x;
+    let position_y;
+
+    let distance_x = to.x - from.x;
+    let distance_y = to.y - from.y;
+
+    if distance_x.abs() > width / 2.0 {
+        if distance_x < 0.0 {
+            position_x = to.x + width;
+        } else {
+            position_x = to.x - width;
+        }
+    } else {
+        position_x = to.x;
+    }
+    if distance_y.abs() > height / 2.0 {
+        if distance_y < 0.0 {
+            position_y = to.y + height;
+        } else {
+            position_y = to.y - height;
+        }
+    } else {
+        position_y = to.y;
+    }
+
+    Vec3::new(position_x, position_y, 0.0)
+}
+
+pub fn lerp(from: f32, to: f32, float: f32) -> f32 {
+    from + float * (to - from)
+}
+
+// A filled wedge starting at twelve o'clock and running clockwise, `fraction` of a full circle.
+pub fn pie_mesh(fraction: f32, radius: f32) -> Mesh {
+    let segments = ((HUD_PIE_SEGMENTS as f32 * fraction).ceil() as usize).max(1);
+    let sweep = std::f32::consts::TAU * fraction;
+
+    let mut positions = vec![[0.0, 0.0, 0.0]];
+    positions.extend((0..=segments).map(|segment| {
+        let angle = std::f32::consts::FRAC_PI_2 - sweep * segment as f32 / segments as f32;
+        (Vec2::from_angle(angle) * radius).extend(0.0).to_array()
+    }));
+    let indices = (1..=segments as u32)
+        .flat_map(|segment| [0, segment + 1, segment])
+        .collect();
+
+    let vertices = positions.len();
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 0.0, 1.0]; vertices]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0, 0.0]; vertices]);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PINNED_CHECKSUM: u64 = 644_659_126_366_131_922;
+
+    fn snapshot() -> StateSnapshot {
+        StateSnapshot {
+            player_position: Vec2::new(120.3, -56.8),
+            points: 42,
+            health: 2,
+            enemy_count: 17,
+            rng_words: 903,
+        }
+    }
+
+    #[test]
+    fn quantize_rounds_to_the_nearest_step() {
+        assert_eq!(quantize(1.04, 0.1), 10);
+        assert_eq!(quantize(1.06, 0.1), 11);
+        assert_eq!(quantize(-1.06, 0.1), -11);
+        assert_eq!(quantize(-0.04, 0.1), 0);
+    }
+
+    #[test]
+    fn checksum_is_stable_for_the_same_state() {
+        assert_eq!(state_checksum(&snapshot()), state_checksum(&snapshot()));
+        // Pinned so a change to the hashed fields or their encoding can't silently invalidate
+        // recorded repro files.
+        assert_eq!(state_checksum(&snapshot()), PINNED_CHECKSUM);
+    }
+
+    #[test]
+    fn checksum_ignores_movement_below_the_quantization_step() {
+        let mut jittered = snapshot();
+        jittered.player_position += Vec2::splat(0.01);
+
+        assert_eq!(state_checksum(&jittered), state_checksum(&snapshot()));
+    }
+
+    #[test]
+    fn checksum_changes_with_every_field() {
+        let base = state_checksum(&snapshot());
+        let changes: [fn(&mut StateSnapshot); 6] = [
+            |s| s.player_position.x += CHECKSUM_POSITION_STEP,
+            |s| s.player_position.y -= CHECKSUM_POSITION_STEP,
+            |s| s.points += 1,
+            |s| s.health -= 1,
+            |s| s.enemy_count += 1,
+            |s| s.rng_words += 1,
+        ];
+
+        for change in changes {
+            let mut changed = snapshot();
+            change(&mut changed);
+            assert_ne!(state_checksum(&changed), base);
+        }
+    }
+
+    #[test]
+    fn first_divergence_finds_the_first_differing_second() {
+        assert_eq!(first_divergence(&[1, 2, 3], &[1, 2, 3]), None);
+        assert_eq!(first_divergence(&[1, 2, 3, 4], &[1, 2, 9, 8]), Some(2));
+        assert_eq!(first_divergence(&[7], &[1]), Some(0));
+    }
+
+    #[test]
+    fn first_divergence_only_compares_recorded_seconds() {
+        assert_eq!(first_divergence(&[1, 2], &[1, 2, 3, 4]), None);
+        assert_eq!(first_divergence(&[1, 2, 3], &[1, 2]), None);
+        assert_eq!(first_divergence(&[], &[1]), None);
+    }
+}
@@ -0,0 +1,233 @@
+use bevy::prelude::*;
+
+use crate::{
+    assets::AssetHandles,
+    audio::AudioAvailable,
+    config::*,
+    effects::text_label,
+    enemy::{despawn_enemy, Enemy},
+    game::RunStats,
+    menu_list::{spawn_menu_list, EntryKind, MenuEntry, MenuFocus, MenuListEvent},
+    pause::PauseState,
+    player::Player,
+    render_z,
+    storage::SaveData,
+    transition::{RequestedTransition, Transition},
+    window::not_suspended,
+    AppState,
+};
+
+const VICTORY_LIST: &str = "victory";
+
+pub struct VictoryPlugin;
+
+impl Plugin for VictoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<RunWon>()
+            .add_systems(OnEnter(AppState::Game), reset_victory)
+            .add_systems(
+                Update,
+                (start_victory, tick_victory)
+                    .chain()
+                    .run_if(in_state(AppState::Game))
+                    .run_if(in_state(PauseState::Running))
+                    .run_if(not_suspended),
+            )
+            .add_systems(
+                Update,
+                handle_victory_menu.run_if(in_state(PauseState::Won)),
+            )
+            .add_systems(OnEnter(PauseState::Won), show_victory)
+            .add_systems(OnExit(PauseState::Won), hide_victory);
+    }
+}
+
+#[derive(Event, Default)]
+pub struct RunWon;
+
+#[derive(Resource, Default)]
+pub struct Victory {
+    won: bool,
+    elapsed: f32,
+    // Enemies still to be removed, with the time into the sequence each one goes.
+    pending: Vec<(f32, Entity)>,
+}
+
+impl Victory {
+    pub fn is_celebrating(&self) -> bool {
+        self.won && self.elapsed < VICTORY_SEQUENCE_TIME
+    }
+}
+
+#[derive(Component)]
+struct VictoryScreen;
+
+fn reset_victory(mut commands: Commands) {
+    commands.insert_resource(Victory::default());
+}
+
+fn start_victory(
+    mut run_won: EventReader<RunWon>,
+    mut victory: ResMut<Victory>,
+    mut save_data: ResMut<SaveData>,
+    mut commands: Commands,
+    run_stats: Res<RunStats>,
+    asset_handles: Res<AssetHandles>,
+    player_query: Query<&Transform, With<Player>>,
+    enemy_query: Query<(Entity, &Transform), With<Enemy>>,
+    audio: Res<AudioAvailable>,
+) {
+    if run_won.read().count() == 0 || victory.won {
+        return;
+    }
+
+    save_data.completed = true;
+    if save_data
+        .fastest_win
+        .is_none_or(|fastest| run_stats.elapsed < fastest)
+    {
+        save_data.fastest_win = Some(run_stats.elapsed);
+    }
+
+    if audio.0 {
+        commands.spawn(AudioBundle {
+            source: asset_handles.fanfare_sound.clone(),
+            settings: PlaybackSettings::DESPAWN,
+        });
+    }
+
+    // Closest enemies go first, so the threat clears outward from the player.
+    let player_position = player_query
+        .get_single()
+        .map_or(Vec3::ZERO, |transform| transform.translation);
+    let mut enemies: Vec<(f32, Entity)> = enemy_query
+        .iter()
+        .map(|(entity, transform)| (transform.translation.distance(player_position), entity))
+        .collect();
+    enemies.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let count = enemies.len().max(1) as f32;
+    let removal_window = VICTORY_SEQUENCE_TIME - ENEMY_REMOVAL_TIME;
+    *victory = Victory {
+        won: true,
+        elapsed: 0.0,
+        pending: enemies
+            .into_iter()
+            .enumerate()
+            .map(|(index, (_, entity))| (removal_window * index as f32 / count, entity))
+            .rev()
+            .collect(),
+    };
+}
+
+fn tick_victory(
+    mut victory: ResMut<Victory>,
+    mut commands: Commands,
+    mut transitions: ResMut<RequestedTransition>,
+    enemy_query: Query<&Enemy>,
+    time: Res<Time>,
+) {
+    if !victory.is_celebrating() {
+        return;
+    }
+
+    victory.elapsed += time.delta_seconds();
+
+    while let Some(&(at, entity)) = victory.pending.last() {
+        if at > victory.elapsed {
+            break;
+        }
+        victory.pending.pop();
+
+        if let Ok(enemy) = enemy_query.get(entity) {
+            despawn_enemy(&mut commands, entity, enemy.enemy_type);
+        }
+    }
+
+    if victory.elapsed >= VICTORY_SEQUENCE_TIME {
+        transitions.request(Transition::Pause(PauseState::Won));
+    }
+}
+
+fn show_victory(
+    mut commands: Commands,
+    mut time: ResMut<Time<Virtual>>,
+    asset_handles: Res<AssetHandles>,
+    run_stats: Res<RunStats>,
+    save_data: Res<SaveData>,
+    focus: Res<MenuFocus>,
+) {
+    time.pause();
+
+    commands.spawn((
+        VictoryScreen,
+        text_label(
+            &asset_handles,
+            "You win!",
+            120.0,
+            Color::WHITE,
+            Vec3::new(0.0, 120.0, render_z::HUD),
+        ),
+    ));
+
+    let fastest = save_data
+        .fastest_win
+        .map_or("-".to_string(), |fastest| format!("{:.1}s", fastest));
+    commands.spawn((
+        VictoryScreen,
+        text_label(
+            &asset_handles,
+            format!(
+                "Time {:.1}s   Hits {}   Fastest {}",
+                run_stats.elapsed, run_stats.hits, fastest
+            ),
+            32.0,
+            Color::GRAY,
+            Vec3::new(0.0, 20.0, render_z::HUD),
+        ),
+    ));
+
+    let list = spawn_menu_list(
+        &mut commands,
+        &asset_handles,
+        &focus,
+        VICTORY_LIST,
+        vec![
+            MenuEntry::new("continue", "Keep playing", EntryKind::Action),
+            MenuEntry::new("finish", "Finish", EntryKind::Action),
+        ],
+        Vec3::new(0.0, -80.0, render_z::HUD),
+    );
+    commands.entity(list).insert(VictoryScreen);
+}
+
+fn handle_victory_menu(
+    mut events: EventReader<MenuListEvent>,
+    mut transitions: ResMut<RequestedTransition>,
+) {
+    for event in events.read() {
+        match event {
+            MenuListEvent::Activated {
+                list: VICTORY_LIST,
+                entry: "continue",
+            } => transitions.request(Transition::Pause(PauseState::Running)),
+            MenuListEvent::Activated {
+                list: VICTORY_LIST,
+                entry: "finish",
+            } => transitions.request(Transition::App(AppState::Menu)),
+            _ => {}
+        }
+    }
+}
+
+fn hide_victory(
+    mut commands: Commands,
+    mut time: ResMut<Time<Virtual>>,
+    query: Query<Entity, With<VictoryScreen>>,
+) {
+    time.unpause();
+
+    query.iter().for_each(|entity| {
+        commands.entity(entity).despawn_recursive();
+    });
+}
@@ -0,0 +1,217 @@
+use std::time::Duration;
+
+use bevy::{
+    prelude::*,
+    time::TimeSystem,
+    window::PrimaryWindow,
+    winit::{UpdateMode, WinitSettings},
+};
+
+use crate::{
+    config::*,
+    game::GameInfo,
+    inhibit::{self, SleepInhibitor},
+    pause::PauseState,
+    AppState,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+const ICON: &[u8] = include_bytes!("../assets/icon.png");
+
+pub const WINDOW_TITLE: &str = "Gorbulet";
+
+const WINDOW_TITLE_INTERVAL: f32 = 1.0;
+
+pub struct WindowIntegrationPlugin;
+
+impl Plugin for WindowIntegrationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_non_send_resource::<SleepInhibitor>()
+            .init_resource::<Suspended>()
+            .add_systems(Startup, set_window_icon)
+            // After the time update, so the frame that suspends still steps and the one that
+            // restores doesn't.
+            .add_systems(
+                First,
+                (detect_minimized, apply_suspended)
+                    .chain()
+                    .after(TimeSystem),
+            )
+            .add_systems(Update, update_window_title.run_if(in_state(AppState::Game)))
+            .add_systems(OnEnter(AppState::Game), inhibit::acquire_sleep_inhibitor)
+            .add_systems(
+                OnExit(AppState::Game),
+                (reset_window_title, inhibit::release_sleep_inhibitor),
+            );
+    }
+}
+
+// Set while the window is minimized. Virtual time stops, so everything timed by it freezes where
+// it is, playing sounds are paused rather than despawned, and the app only wakes for window
+// events until it is restored.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Suspended(pub bool);
+
+pub fn not_suspended(suspended: Res<Suspended>) -> bool {
+    !suspended.0
+}
+
+// Marks sinks paused by the suspension, so ones that were already paused stay that way after.
+#[derive(Component)]
+struct HeldBySuspend;
+
+#[cfg(not(target_arch = "wasm32"))]
+fn detect_minimized(
+    windows: Option<NonSend<bevy::winit::WinitWindows>>,
+    primary_window: Query<(Entity, &Window), With<PrimaryWindow>>,
+    mut suspended: ResMut<Suspended>,
+) {
+    let Ok((entity, window)) = primary_window.get_single() else {
+        return;
+    };
+    // Some platforms don't report minimizing, but shrink the window to nothing instead.
+    let minimized = windows
+        .as_ref()
+        .and_then(|windows| windows.get_window(entity))
+        .and_then(|window| window.is_minimized())
+        .unwrap_or(false)
+        || window.physical_width() == 0
+        || window.physical_height() == 0;
+    suspended.set_if_neq(Suspended(minimized));
+}
+
+#[cfg(target_arch = "wasm32")]
+fn detect_minimized(
+    primary_window: Query<&Window, With<PrimaryWindow>>,
+    mut suspended: ResMut<Suspended>,
+) {
+    let Ok(window) = primary_window.get_single() else {
+        return;
+    };
+    let minimized = window.physical_width() == 0 || window.physical_height() == 0;
+    suspended.set_if_neq(Suspended(minimized));
+}
+
+// Time<Virtual> is only resumed here if suspending paused it and no pause or victory screen has
+// taken it over since. On restore, the first step is held to the usual frame delta cap however
+// long the window was away.
+fn apply_suspended(
+    mut commands: Commands,
+    suspended: Res<Suspended>,
+    mut paused_time: Local<bool>,
+    mut previous_modes: Local<Option<(UpdateMode, UpdateMode)>>,
+    mut time: ResMut<Time<Virtual>>,
+    pause_state: Res<State<PauseState>>,
+    winit_settings: Option<ResMut<WinitSettings>>,
+    audio_sinks: Query<(Entity, &AudioSink), Without<HeldBySuspend>>,
+    spatial_sinks: Query<(Entity, &SpatialAudioSink), Without<HeldBySuspend>>,
+    held_audio: Query<(Entity, &AudioSink), With<HeldBySuspend>>,
+    held_spatial: Query<(Entity, &SpatialAudioSink), With<HeldBySuspend>>,
+) {
+    if !suspended.is_changed() || suspended.is_added() {
+        return;
+    }
+
+    if suspended.0 {
+        *paused_time = !time.is_paused();
+        time.pause();
+
+        for (entity, sink) in audio_sinks.iter().filter(|(_, sink)| !sink.is_paused()) {
+            sink.pause();
+            commands.entity(entity).insert(HeldBySuspend);
+        }
+        for (entity, sink) in spatial_sinks.iter().filter(|(_, sink)| !sink.is_paused()) {
+            sink.pause();
+            commands.entity(entity).insert(HeldBySuspend);
+        }
+
+        if let Some(mut settings) = winit_settings {
+            *previous_modes = Some((settings.focused_mode, settings.unfocused_mode));
+            let idle = UpdateMode::ReactiveLowPower {
+                wait: Duration::from_secs_f32(SUSPENDED_WAKE_INTERVAL),
+            };
+            settings.focused_mode = idle;
+            settings.unfocused_mode = idle;
+        }
+    } else {
+        if std::mem::take(&mut *paused_time)
+            && !matches!(pause_state.get(), PauseState::Paused | PauseState::Won)
+        {
+            time.unpause();
+        }
+
+        for (entity, sink) in held_audio.iter() {
+            sink.play();
+            commands.entity(entity).remove::<HeldBySuspend>();
+        }
+        for (entity, sink) in held_spatial.iter() {
+            sink.play();
+            commands.entity(entity).remove::<HeldBySuspend>();
+        }
+
+        if let (Some(mut settings), Some((focused, unfocused))) =
+            (winit_settings, previous_modes.take())
+        {
+            settings.focused_mode = focused;
+            settings.unfocused_mode = unfocused;
+        }
+    }
+}
+
+fn update_window_title(
+    game_info: Res<GameInfo>,
+    mut window: Query<&mut Window, With<PrimaryWindow>>,
+    mut since_update: Local<f32>,
+    time: Res<Time>,
+) {
+    *since_update += time.delta_seconds();
+    if *since_update < WINDOW_TITLE_INTERVAL {
+        return;
+    }
+
+    let title = format!("{} — {}", WINDOW_TITLE, game_info.score);
+    let mut window = window.single_mut();
+    if window.title != title {
+        window.title = title;
+        *since_update = 0.0;
+    }
+}
+
+fn reset_window_title(mut window: Query<&mut Window, With<PrimaryWindow>>) {
+    window.single_mut().title = WINDOW_TITLE.to_string();
+}
+
+// Headless runs like the smoke test have no winit windows at all.
+#[cfg(not(target_arch = "wasm32"))]
+fn set_window_icon(
+    windows: Option<NonSend<bevy::winit::WinitWindows>>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+) {
+    let Some(window) = windows
+        .as_ref()
+        .zip(primary_window.get_single().ok())
+        .and_then(|(windows, entity)| windows.get_window(entity))
+    else {
+        return;
+    };
+
+    match decode_icon() {
+        Ok(icon) => window.set_window_icon(Some(icon)),
+        Err(error) => warn!("Could not load window icon: {}", error),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn set_window_icon() {}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn decode_icon() -> Result<winit::window::Icon, Box<dyn std::error::Error>> {
+    let image = image::load_from_memory(ICON)?.into_rgba8();
+    let (width, height) = image.dimensions();
+
+    Ok(winit::window::Icon::from_rgba(
+        image.into_raw(),
+        width,
+        height,
+    )?)
+}
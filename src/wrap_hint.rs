@@ -0,0 +1,82 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    assets::AssetHandles, config::*, enemy::Enemy, movement::Velocity, quality::EffectsLevel,
+    render_z, util::wraparound_entry_position,
+};
+
+// A faint copy of an enemy about to wrap, shown where it will come back in on the opposite edge.
+// Each enemy gets one the first time it needs it and keeps it, hidden, until it dies.
+#[derive(Component)]
+pub struct WrapHint {
+    owner: Entity,
+}
+
+pub fn update_wrap_hints(
+    mut commands: Commands,
+    enemies: Query<(Entity, &Transform, &Velocity), With<Enemy>>,
+    mut hints: Query<(Entity, &WrapHint, &mut Transform, &mut Visibility), Without<Enemy>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    effects_level: Res<EffectsLevel>,
+    asset_handles: Res<AssetHandles>,
+) {
+    let window = window.single();
+    let half_extent = Vec2::new(window.width(), window.height()) / 2.0;
+
+    // Only the enemies closest to wrapping get a hint, at most WRAP_HINT_MAX of them.
+    let mut entries: Vec<(Entity, Vec2, f32)> = Vec::new();
+    if effects_level.0.wrap_hints() {
+        entries = enemies
+            .iter()
+            .filter_map(|(entity, transform, velocity)| {
+                let position = transform.translation.truncate();
+                wraparound_entry_position(
+                    position,
+                    velocity.0.truncate(),
+                    half_extent - ENEMY_RADIUS,
+                    WRAP_HINT_EDGE_DISTANCE,
+                )
+                .map(|entry| (entity, entry, (position / half_extent).abs().max_element()))
+            })
+            .collect();
+        entries.sort_by(|a, b| b.2.total_cmp(&a.2));
+        entries.truncate(WRAP_HINT_MAX);
+    }
+
+    let hinted: Vec<Entity> = hints.iter().map(|(_, hint, ..)| hint.owner).collect();
+    for (entity, hint, mut transform, mut visibility) in hints.iter_mut() {
+        if !enemies.contains(hint.owner) {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let next = match entries.iter().find(|(owner, ..)| *owner == hint.owner) {
+            Some((_, entry, _)) => {
+                transform.translation = entry.extend(transform.translation.z);
+                Visibility::Inherited
+            }
+            None => Visibility::Hidden,
+        };
+        if *visibility != next {
+            *visibility = next;
+        }
+    }
+
+    for (owner, entry, _) in entries {
+        if hinted.contains(&owner) {
+            continue;
+        }
+
+        commands.spawn((
+            WrapHint { owner },
+            ColorMesh2dBundle {
+                mesh: asset_handles.enemy_mesh.clone().into(),
+                material: asset_handles.wrap_hint_material.clone(),
+                transform: Transform::from_translation(
+                    entry.extend(render_z::ENEMY + render_z::CHILD_BELOW),
+                ),
+                ..default()
+            },
+        ));
+    }
+}
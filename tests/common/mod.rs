@@ -0,0 +1,194 @@
+//! Boots the whole game without a window or GPU, for the tests that play it end to end.
+
+// Each test file uses its own share of these.
+#![allow(dead_code)]
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use bevy::{
+    app::{PluginGroupBuilder, PluginsState},
+    input::{keyboard::KeyboardInput, ButtonState},
+    prelude::*,
+    render::{settings::WgpuSettings, RenderPlugin},
+    time::TimeUpdateStrategy,
+    window::{ExitCondition, PrimaryWindow},
+    winit::WinitPlugin,
+};
+use gorbulet::{
+    AppState, Coin, EnemyType, GorbuletPlugin, HitPlayer, HitSource, PauseState, Player,
+    RequestedTransition, Transition,
+};
+
+const MAX_WAIT_FRAMES: u32 = 60;
+const MAX_HITS: u32 = 20;
+
+pub struct Headless {
+    pub app: App,
+    pub window: Entity,
+    save_root: PathBuf,
+}
+
+impl Headless {
+    // The save goes to a fresh directory under `name`, so the run never reads or writes the
+    // real one. The save location comes from the environment, which is why every file that
+    // boots the game holds a single test.
+    pub fn new(name: &str, frame_time: f32) -> Self {
+        Self::with_plugins(name, frame_time, DefaultPlugins.build())
+    }
+
+    pub fn with_plugins(name: &str, frame_time: f32, plugins: PluginGroupBuilder) -> Self {
+        Self::boot(fresh_save_root(name), frame_time, plugins)
+    }
+
+    // Starts from a save directory that already holds `contents` under `file_name`.
+    pub fn with_save_file(name: &str, frame_time: f32, file_name: &str, contents: &str) -> Self {
+        let save_root = fresh_save_root(name);
+        let dir = save_dir(&save_root);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(file_name), contents).unwrap();
+        Self::boot(save_root, frame_time, DefaultPlugins.build())
+    }
+
+    fn boot(save_root: PathBuf, frame_time: f32, plugins: PluginGroupBuilder) -> Self {
+        let mut app = App::new();
+        app.add_plugins((
+            plugins
+                .set(WindowPlugin {
+                    primary_window: Some(Window::default()),
+                    exit_condition: ExitCondition::DontExit,
+                    close_when_requested: false,
+                })
+                .set(RenderPlugin {
+                    render_creation: WgpuSettings {
+                        backends: None,
+                        ..default()
+                    }
+                    .into(),
+                })
+                .disable::<WinitPlugin>(),
+            GorbuletPlugin,
+        ));
+        set_step(&mut app, frame_time);
+
+        while app.plugins_state() == PluginsState::Adding {
+            bevy::tasks::tick_global_task_pools_on_main_thread();
+        }
+        app.finish();
+        app.cleanup();
+
+        let window = app
+            .world
+            .query_filtered::<Entity, With<PrimaryWindow>>()
+            .single(&app.world);
+
+        Self {
+            app,
+            window,
+            save_root,
+        }
+    }
+
+    pub fn save_dir(&self) -> PathBuf {
+        save_dir(&self.save_root)
+    }
+
+    pub fn send_key(&mut self, key_code: KeyCode, state: ButtonState) {
+        let window = self.window;
+        self.app.world.send_event(KeyboardInput {
+            scan_code: 0,
+            key_code: Some(key_code),
+            state,
+            window,
+        });
+    }
+
+    // Requests the state and steps until it takes over. False if it never does.
+    pub fn enter(&mut self, state: AppState) -> bool {
+        self.app
+            .world
+            .resource_mut::<RequestedTransition>()
+            .request(Transition::App(state));
+
+        let mut waited = 0;
+        while *self.app.world.resource::<State<AppState>>().get() != state {
+            self.app.update();
+            waited += 1;
+            if waited > MAX_WAIT_FRAMES {
+                return false;
+            }
+        }
+        true
+    }
+
+    pub fn update(&mut self, frames: u32) {
+        for _ in 0..frames {
+            self.app.update();
+        }
+    }
+
+    // One hit a frame until the run is over, so no hit is lost to another in the same frame.
+    // False if the run never ends.
+    pub fn die(&mut self) -> bool {
+        for _ in 0..MAX_HITS {
+            self.app.world.send_event(HitPlayer {
+                source: HitSource::Enemy(EnemyType::Red),
+                direction: Vec3::X,
+                attacker_position: Vec3::ZERO,
+            });
+            self.app.update();
+            if *self.app.world.resource::<State<PauseState>>().get() == PauseState::GameOver {
+                return true;
+            }
+        }
+        false
+    }
+
+    // Moves every coin onto the player, so the next frame collects them.
+    pub fn collect_coin(&mut self) {
+        let world = &mut self.app.world;
+        let player = world
+            .query_filtered::<&Transform, With<Player>>()
+            .get_single(world)
+            .map_or(Vec2::ZERO, |transform| transform.translation.truncate());
+        for mut transform in world
+            .query_filtered::<&mut Transform, With<Coin>>()
+            .iter_mut(world)
+        {
+            transform.translation = player.extend(transform.translation.z);
+        }
+    }
+}
+
+impl Drop for Headless {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.save_root);
+    }
+}
+
+fn fresh_save_root(name: &str) -> PathBuf {
+    let save_root = env::temp_dir().join(name);
+    let _ = fs::remove_dir_all(&save_root);
+    for var in ["XDG_DATA_HOME", "APPDATA", "HOME"] {
+        env::set_var(var, &save_root);
+    }
+    save_root
+}
+
+// Where the game keeps its save when every base directory it looks at is `save_root`.
+fn save_dir(save_root: &Path) -> PathBuf {
+    if cfg!(target_os = "macos") {
+        save_root.join("Library/Application Support/gorbulet")
+    } else {
+        save_root.join("gorbulet")
+    }
+}
+
+pub fn set_step(app: &mut App, seconds: f32) {
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+        seconds,
+    )));
+}
@@ -0,0 +1,43 @@
+//! Boots on a save that can't be read and has no backup, plays a run to its end and quits. The
+//! run's record has to land in a fresh save without costing the player the unreadable one.
+
+mod common;
+
+use std::fs;
+
+use bevy::app::AppExit;
+use gorbulet::AppState;
+
+use common::Headless;
+
+const FRAME_TIME: f32 = 1.0 / 30.0;
+const GAME_FRAMES: u32 = 60;
+const CORRUPT_SAVE: &str = "(version: 3, best_score: Some(";
+
+#[test]
+fn unreadable_save_survives_the_first_autosave() {
+    let mut game = Headless::with_save_file(
+        "gorbulet-corrupt-save",
+        FRAME_TIME,
+        "save.ron",
+        CORRUPT_SAVE,
+    );
+
+    assert!(game.enter(AppState::Game), "the run never started");
+    game.update(GAME_FRAMES);
+    assert!(game.die(), "the run never ended");
+    assert!(game.enter(AppState::Menu), "the run never left");
+
+    // Quitting writes whatever changed straight away.
+    game.app.world.send_event(AppExit);
+    game.update(1);
+
+    let dir = game.save_dir();
+    assert_eq!(
+        fs::read_to_string(dir.join("save.ron.broken")).unwrap(),
+        CORRUPT_SAVE
+    );
+    let saved = fs::read_to_string(dir.join("save.ron")).unwrap();
+    assert_ne!(saved, CORRUPT_SAVE);
+    assert!(saved.contains("records"));
+}
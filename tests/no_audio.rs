@@ -0,0 +1,76 @@
+//! Plays the game with the audio plugin left out, as on a machine with no sound at all. Nothing may
+//! panic, and the health pips have to stand in for the music.
+
+mod common;
+
+use bevy::{audio::AudioPlugin, input::ButtonState, prelude::*};
+use gorbulet::{AppState, AudioAvailable, HudElement, HudKey};
+
+use common::Headless;
+
+const FRAME_TIME: f32 = 1.0 / 30.0;
+const MENU_FRAMES: u32 = 30;
+const GAME_FRAMES: u32 = (10.0 / FRAME_TIME) as u32;
+const RUNS: usize = 3;
+
+#[test]
+fn silent_game_shows_the_health_pips() {
+    let mut game = Headless::with_plugins(
+        "gorbulet-no-audio",
+        FRAME_TIME,
+        DefaultPlugins.build().disable::<AudioPlugin>(),
+    );
+    game.update(MENU_FRAMES);
+
+    assert!(!game.app.world.resource::<AudioAvailable>().0);
+    assert!(
+        texts(&mut game)
+            .iter()
+            .any(|text| text.starts_with("Audio unavailable")),
+        "the menu never said the audio is unavailable"
+    );
+
+    // Held movement keeps the idle pause away; the runs end however they end.
+    game.send_key(KeyCode::D, ButtonState::Pressed);
+    for run in 0..RUNS {
+        assert!(game.enter(AppState::Game), "run {} never started", run);
+        game.update(1);
+        assert_eq!(
+            health_pips(&mut game),
+            Some(Visibility::Inherited),
+            "run {} hid the health pips",
+            run
+        );
+
+        game.update(GAME_FRAMES);
+        assert_eq!(sounds(&mut game), 0, "run {} spawned sounds", run);
+        assert!(game.enter(AppState::Menu), "run {} never ended", run);
+        game.update(MENU_FRAMES);
+    }
+}
+
+fn texts(game: &mut Headless) -> Vec<String> {
+    game.app
+        .world
+        .query::<&Text>()
+        .iter(&game.app.world)
+        .flat_map(|text| text.sections.iter().map(|section| section.value.clone()))
+        .collect()
+}
+
+fn sounds(game: &mut Headless) -> usize {
+    game.app
+        .world
+        .query_filtered::<(), With<Handle<AudioSource>>>()
+        .iter(&game.app.world)
+        .count()
+}
+
+fn health_pips(game: &mut Headless) -> Option<Visibility> {
+    game.app
+        .world
+        .query::<(&HudElement, &Visibility)>()
+        .iter(&game.app.world)
+        .find(|(element, _)| element.key == HudKey::Health)
+        .map(|(_, visibility)| *visibility)
+}
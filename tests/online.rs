@@ -0,0 +1,152 @@
+//! Loopback runs of the race protocol: two peers on one machine, talking over a real socket.
+
+#![cfg(feature = "online")]
+
+use std::{
+    io::Write,
+    net::{Shutdown, TcpListener, TcpStream},
+    thread,
+};
+
+use gorbulet::{
+    handshake_host, handshake_join, read_frame, write_frame, Difficulty, Message, OnlineError,
+    PROTOCOL_VERSION,
+};
+
+const SEED: u64 = 0x5eed_cafe;
+
+// A connected pair of sockets: the host's end and the joiner's end.
+fn loopback() -> (TcpStream, TcpStream) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let joiner = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (host, _) = listener.accept().unwrap();
+    (host, joiner)
+}
+
+#[test]
+fn handshake_agrees_on_the_hosts_run() {
+    let (mut host, mut joiner) = loopback();
+
+    let joined =
+        thread::spawn(move || handshake_join(&mut joiner).map_err(|error| error.to_string()));
+    handshake_host(&mut host, SEED, Difficulty::Hard).unwrap();
+
+    assert_eq!(joined.join().unwrap(), Ok((SEED, Difficulty::Hard)));
+}
+
+#[test]
+fn peers_exchange_state_after_the_handshake() {
+    let (mut host, mut joiner) = loopback();
+
+    let joined = thread::spawn(move || {
+        handshake_join(&mut joiner).unwrap();
+        let heard = read_frame(&mut joiner).unwrap();
+        write_frame(
+            &mut joiner,
+            &Message::State {
+                points: 3,
+                health: 2,
+                pos: (-10.0, 42.5),
+            },
+        )
+        .unwrap();
+        write_frame(&mut joiner, &Message::Finished { score: 17 }).unwrap();
+        heard
+    });
+
+    handshake_host(&mut host, SEED, Difficulty::Normal).unwrap();
+    let sent = Message::State {
+        points: 5,
+        health: 1,
+        pos: (120.25, -7.0),
+    };
+    write_frame(&mut host, &sent).unwrap();
+
+    assert_eq!(
+        read_frame(&mut host).unwrap(),
+        Message::State {
+            points: 3,
+            health: 2,
+            pos: (-10.0, 42.5),
+        }
+    );
+    assert_eq!(
+        read_frame(&mut host).unwrap(),
+        Message::Finished { score: 17 }
+    );
+    assert_eq!(joined.join().unwrap(), sent);
+}
+
+#[test]
+fn host_turns_away_another_protocol_version() {
+    let (mut host, mut joiner) = loopback();
+
+    let joined = thread::spawn(move || {
+        read_frame(&mut joiner).unwrap();
+        write_frame(
+            &mut joiner,
+            &Message::Hello {
+                version: PROTOCOL_VERSION + 1,
+                seed: SEED,
+                difficulty: Difficulty::Normal,
+            },
+        )
+        .unwrap();
+    });
+
+    let result = handshake_host(&mut host, SEED, Difficulty::Normal);
+    joined.join().unwrap();
+
+    assert!(
+        matches!(result, Err(OnlineError::Version(version)) if version == PROTOCOL_VERSION + 1)
+    );
+}
+
+#[test]
+fn host_rejects_a_joiner_that_echoes_another_seed() {
+    let (mut host, mut joiner) = loopback();
+
+    let joined = thread::spawn(move || {
+        read_frame(&mut joiner).unwrap();
+        write_frame(
+            &mut joiner,
+            &Message::Hello {
+                version: PROTOCOL_VERSION,
+                seed: SEED + 1,
+                difficulty: Difficulty::Normal,
+            },
+        )
+        .unwrap();
+    });
+
+    let result = handshake_host(&mut host, SEED, Difficulty::Normal);
+    joined.join().unwrap();
+
+    assert!(matches!(result, Err(OnlineError::Handshake)));
+}
+
+#[test]
+fn oversized_frame_is_not_read() {
+    let (mut host, mut joiner) = loopback();
+
+    joiner.write_all(&u32::MAX.to_be_bytes()).unwrap();
+
+    assert!(matches!(
+        read_frame(&mut host),
+        Err(OnlineError::FrameTooLarge(len)) if len == u32::MAX as usize
+    ));
+}
+
+#[test]
+fn disconnect_mid_race_is_an_error_not_a_hang() {
+    let (mut host, mut joiner) = loopback();
+
+    let joined = thread::spawn(move || {
+        handshake_join(&mut joiner).unwrap();
+        joiner.shutdown(Shutdown::Both).unwrap();
+    });
+    handshake_host(&mut host, SEED, Difficulty::Easy).unwrap();
+    joined.join().unwrap();
+
+    assert!(matches!(read_frame(&mut host), Err(OnlineError::Io(_))));
+}
@@ -0,0 +1,80 @@
+//! Starts a Zen run and drops the coin and a shrink pickup onto the player in the same frame,
+//! PICKUP_TRIALS times. Each time, both must be collected on that frame: the coin scores and
+//! moves on, and the shrink pickup goes away and shrinks the player.
+
+mod common;
+
+use bevy::{input::ButtonState, prelude::*};
+use gorbulet::{AppState, Coin, GameInfo, GameMode, Pickup, PickupKind, Player};
+
+use common::Headless;
+
+const FRAME_TIME: f32 = 1.0 / 30.0;
+const PICKUP_TRIALS: usize = 5;
+const WARMUP_FRAMES: u32 = 60;
+// Long enough for the previous trial's shrink to wear off.
+const FRAMES_BETWEEN_TRIALS: u32 = 300;
+// Matches COIN_MAX_BONUS.
+const MAX_COIN_POINTS: i32 = 1 + 3;
+
+#[test]
+fn coin_and_shrink_on_one_frame_are_both_collected() {
+    let mut game = Headless::new("gorbulet-pickups", FRAME_TIME);
+
+    // Zen, so enemies and health coins stay out of it. Held movement keeps the idle pause away.
+    *game.app.world.resource_mut::<GameMode>() = GameMode::Zen;
+    game.send_key(KeyCode::D, ButtonState::Pressed);
+    assert!(game.enter(AppState::Game), "the run never started");
+    game.update(WARMUP_FRAMES);
+
+    for trial in 0..PICKUP_TRIALS {
+        let world = &mut game.app.world;
+        let player = world.query_filtered::<Entity, With<Player>>().single(world);
+        let position = world.get::<Transform>(player).unwrap().translation;
+        let scale = world.get::<Transform>(player).unwrap().scale;
+        let points = world.resource::<GameInfo>().points();
+
+        let coin = world.query_filtered::<Entity, With<Coin>>().single(world);
+        let mut coin_transform = world.get_mut::<Transform>(coin).unwrap();
+        coin_transform.translation = position.truncate().extend(coin_transform.translation.z);
+        let shrink = world
+            .spawn((
+                Pickup::new(PickupKind::Shrink),
+                TransformBundle::from_transform(Transform::from_translation(position)),
+            ))
+            .id();
+
+        game.app.update();
+        let world = &mut game.app.world;
+
+        let gained = world.resource::<GameInfo>().points() - points;
+        assert!(
+            (1..=MAX_COIN_POINTS).contains(&gained),
+            "trial {} gained {} points from one coin",
+            trial,
+            gained
+        );
+        let coin_position = world.get::<Transform>(coin).unwrap().translation;
+        assert!(
+            coin_position.truncate().distance(position.truncate()) >= 1.0,
+            "trial {} left the coin in place",
+            trial
+        );
+        assert!(
+            world.get_entity(shrink).is_none(),
+            "trial {} left the shrink pickup",
+            trial
+        );
+
+        // The new size is applied the frame after the pickup.
+        game.app.update();
+        let shrunk = game.app.world.get::<Transform>(player).unwrap().scale;
+        assert!(
+            shrunk.x < scale.x,
+            "trial {} never shrank the player",
+            trial
+        );
+
+        game.update(FRAMES_BETWEEN_TRIALS);
+    }
+}
@@ -0,0 +1,73 @@
+//! Cycles between the menu and a Zen run SOAK_CYCLES times, collecting a few coins in every run,
+//! and checks that no count the SoakGuard tracks ends higher than it was after warming up.
+
+mod common;
+
+use bevy::{input::ButtonState, prelude::*};
+use gorbulet::{AppState, GameMode, SoakGuard, SoakSnapshot};
+
+use common::Headless;
+
+const FRAME_TIME: f32 = 1.0 / 30.0;
+const SOAK_CYCLES: usize = 100;
+const WARMUP_CYCLES: usize = 10;
+// Long enough for every sound of the run to finish or be swept before it ends.
+const GAME_FRAMES: u32 = (8.0 / FRAME_TIME) as u32;
+const MENU_FRAMES: u32 = 30;
+const PICKUP_FRAMES: [u32; 3] = [30, 60, 90];
+
+#[test]
+fn menu_and_game_cycles_do_not_grow() {
+    let mut game = Headless::new("gorbulet-soak", FRAME_TIME);
+
+    // Zen, so enemies never end the run early. Held movement keeps the idle pause away.
+    *game.app.world.resource_mut::<GameMode>() = GameMode::Zen;
+    game.send_key(KeyCode::D, ButtonState::Pressed);
+
+    let mut menu_snapshots = Vec::new();
+    let mut game_snapshots = Vec::new();
+    for cycle in 0..SOAK_CYCLES {
+        assert!(game.enter(AppState::Game), "cycle {} never started", cycle);
+        for frame in 0..GAME_FRAMES {
+            if PICKUP_FRAMES.contains(&frame) {
+                game.collect_coin();
+            }
+            game.update(1);
+        }
+        game_snapshots.push(snapshot(&game, AppState::Game));
+
+        assert!(game.enter(AppState::Menu), "cycle {} never ended", cycle);
+        game.update(MENU_FRAMES);
+        menu_snapshots.push(snapshot(&game, AppState::Menu));
+    }
+
+    let failures: Vec<String> = [&menu_snapshots, &game_snapshots]
+        .into_iter()
+        .flat_map(|snapshots| growth(&snapshots[WARMUP_CYCLES], &snapshots[SOAK_CYCLES - 1]))
+        .collect();
+    assert!(failures.is_empty(), "{}", failures.join("; "));
+}
+
+fn snapshot(game: &Headless, state: AppState) -> SoakSnapshot {
+    game.app
+        .world
+        .resource::<SoakGuard>()
+        .latest(state)
+        .cloned()
+        .unwrap_or_else(|| panic!("no snapshot was taken in {:?}", state))
+}
+
+fn growth(baseline: &SoakSnapshot, last: &SoakSnapshot) -> Vec<String> {
+    baseline
+        .counts
+        .iter()
+        .zip(&last.counts)
+        .filter(|((_, before), (_, after))| after > before)
+        .map(|((name, before), (_, after))| {
+            format!(
+                "{} in {:?} went from {} to {}",
+                name, last.state, before, after
+            )
+        })
+        .collect()
+}
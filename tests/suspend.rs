@@ -0,0 +1,115 @@
+//! Starts a Zen run and minimizes and restores the window SUSPEND_CYCLES times. Each time, the run
+//! must come back exactly where it was left, and the first step after a long minimize must stay
+//! within the frame delta cap.
+
+mod common;
+
+use std::time::Duration;
+
+use bevy::{input::ButtonState, prelude::*};
+use gorbulet::{AppState, Coin, Enemy, GameInfo, GameMode, Player, Suspended};
+
+use common::{set_step, Headless};
+
+const FRAME_TIME: f32 = 1.0 / 30.0;
+const SUSPEND_CYCLES: usize = 50;
+const WARMUP_FRAMES: u32 = 60;
+const RUNNING_FRAMES: u32 = 10;
+const SUSPENDED_FRAMES: u32 = 20;
+// The real time that passes on the frame the window comes back.
+const RESTORE_STEP: f32 = 600.0;
+// Matches MAX_FRAME_DELTA.
+const MAX_STEP: f32 = 0.05;
+
+// What has to survive a suspension untouched.
+#[derive(Debug, Clone, PartialEq)]
+struct RunState {
+    elapsed: Duration,
+    player: Vec3,
+    coins: Vec<Vec3>,
+    enemies: Vec<Vec3>,
+    points: i32,
+    health: i8,
+}
+
+#[test]
+fn minimized_run_resumes_where_it_was_left() {
+    let mut game = Headless::new("gorbulet-suspend", FRAME_TIME);
+
+    // Zen, so enemies never end the run early. Held movement keeps the idle pause away.
+    *game.app.world.resource_mut::<GameMode>() = GameMode::Zen;
+    game.send_key(KeyCode::D, ButtonState::Pressed);
+    assert!(game.enter(AppState::Game), "the run never started");
+    game.update(WARMUP_FRAMES);
+
+    for cycle in 0..SUSPEND_CYCLES {
+        game.update(RUNNING_FRAMES);
+
+        // The frame that minimizes still steps; everything after it must stand still.
+        set_minimized(&mut game, true);
+        game.update(1);
+        let before = run_state(&mut game.app);
+        assert!(
+            game.app.world.resource::<Suspended>().0,
+            "cycle {} never suspended",
+            cycle
+        );
+
+        game.update(SUSPENDED_FRAMES);
+        set_minimized(&mut game, false);
+        set_step(&mut game.app, RESTORE_STEP);
+        game.update(1);
+        set_step(&mut game.app, FRAME_TIME);
+        assert_eq!(run_state(&mut game.app), before, "cycle {}", cycle);
+
+        game.update(1);
+        let step = game.app.world.resource::<Time<Virtual>>().delta_seconds();
+        assert!(
+            step <= MAX_STEP + f32::EPSILON,
+            "cycle {} resumed with a {}s step",
+            cycle,
+            step
+        );
+    }
+}
+
+fn set_minimized(game: &mut Headless, minimized: bool) {
+    let mut window = game.app.world.get_mut::<Window>(game.window).unwrap();
+    if minimized {
+        window.resolution.set_physical_resolution(0, 0);
+    } else {
+        window.resolution.set_physical_resolution(1280, 720);
+    }
+}
+
+fn run_state(app: &mut App) -> RunState {
+    let player = app
+        .world
+        .query_filtered::<&Transform, With<Player>>()
+        .single(&app.world)
+        .translation;
+    let mut coins: Vec<Vec3> = app
+        .world
+        .query_filtered::<&Transform, With<Coin>>()
+        .iter(&app.world)
+        .map(|transform| transform.translation)
+        .collect();
+    let mut enemies: Vec<Vec3> = app
+        .world
+        .query_filtered::<&Transform, With<Enemy>>()
+        .iter(&app.world)
+        .map(|transform| transform.translation)
+        .collect();
+    coins.sort_by(|a, b| a.to_array().partial_cmp(&b.to_array()).unwrap());
+    enemies.sort_by(|a, b| a.to_array().partial_cmp(&b.to_array()).unwrap());
+    let game_info = app.world.resource::<GameInfo>();
+
+    RunState {
+        elapsed: app.world.resource::<Time<Virtual>>().elapsed(),
+        player,
+        coins,
+        enemies,
+        points: game_info.points(),
+        health: game_info.health(),
+    }
+}
@@ -0,0 +1,41 @@
+//! Dies, goes back to the menu and starts again, RUNS times. Whatever a run leaves behind when it
+//! ends shows up as a menu that holds more entities every time.
+
+mod common;
+
+use gorbulet::AppState;
+
+use common::Headless;
+
+const FRAME_TIME: f32 = 1.0 / 30.0;
+const RUNS: usize = 10;
+const GAME_FRAMES: u32 = 60;
+// Long enough for the death to play out before leaving.
+const GAME_OVER_FRAMES: u32 = 60;
+const MENU_FRAMES: u32 = 30;
+
+#[test]
+fn dying_and_restarting_does_not_grow_the_menu() {
+    let mut game = Headless::new("gorbulet-teardown", FRAME_TIME);
+
+    let mut menu_entities = Vec::new();
+    for run in 0..RUNS {
+        assert!(game.enter(AppState::Game), "run {} never started", run);
+        game.update(GAME_FRAMES);
+        assert!(game.die(), "run {} never ended", run);
+        game.update(GAME_OVER_FRAMES);
+
+        assert!(game.enter(AppState::Menu), "run {} never left", run);
+        game.update(MENU_FRAMES);
+        menu_entities.push(game.app.world.entities().len());
+    }
+
+    // The first run is the warmup: it loads what every later run reuses.
+    assert!(
+        menu_entities[RUNS - 1] <= menu_entities[1],
+        "the menu went from {} to {} entities: {:?}",
+        menu_entities[1],
+        menu_entities[RUNS - 1],
+        menu_entities
+    );
+}